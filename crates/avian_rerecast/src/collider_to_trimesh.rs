@@ -86,6 +86,7 @@ fn shape_to_trimesh(shape: &TypedShape, subdivisions: u32) -> Option<TriMesh> {
         vertices: vertices.into_iter().map(|v| v.into()).collect(),
         indices: indices.into_iter().map(|i| i.into()).collect(),
         area_types: vec![AreaType::NOT_WALKABLE; indices_len],
+        materials: Vec::new(),
     })
 }
 
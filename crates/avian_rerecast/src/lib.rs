@@ -2,7 +2,9 @@
 
 use avian3d::prelude::*;
 use bevy::prelude::*;
-use bevy_rerecast_core::{NavmeshApp as _, rerecast::TriMesh};
+use bevy_rerecast_core::{
+    NavmeshAffectorFilter, NavmeshApp as _, NavmeshLayers, NavmeshTileQuery, rerecast::TriMesh,
+};
 
 mod collider_to_trimesh;
 use crate::collider_to_trimesh::ToTriMesh;
@@ -13,30 +15,83 @@ pub mod prelude {
 }
 
 /// The plugin of the crate. Will make all entities with [`Collider`] a collider belonging to a static [`RigidBody`] available for navmesh generation.
+///
+/// Colliders are matched against the [`NavmeshAffectorFilter`] using their [`NavmeshLayers`] if present,
+/// or the memberships of their [`CollisionLayers`] otherwise.
+///
+/// Tiled builds only gather the colliders intersecting each tile by querying the physics broad-phase.
 #[non_exhaustive]
 #[derive(Debug, Default)]
 pub struct AvianRerecastPlugin;
 
 impl Plugin for AvianRerecastPlugin {
     fn build(&self, app: &mut App) {
-        app.set_navmesh_affector_backend(collider_backend);
+        app.set_navmesh_affector_backend(collider_backend)
+            .set_navmesh_tile_affector_backend(collider_tile_backend);
     }
 }
 
+type ColliderData = (
+    &'static GlobalTransform,
+    &'static Collider,
+    &'static ColliderOf,
+    Option<&'static NavmeshLayers>,
+    Option<&'static CollisionLayers>,
+);
+
 fn collider_backend(
-    colliders: Query<(&GlobalTransform, &Collider, &ColliderOf)>,
+    In(filter): In<NavmeshAffectorFilter>,
+    colliders: Query<ColliderData>,
     bodies: Query<&RigidBody>,
 ) -> Vec<(GlobalTransform, TriMesh)> {
     colliders
         .iter()
-        .filter_map(|(transform, collider, collider_of)| {
-            let body = bodies.get(collider_of.body).ok()?;
-            if !body.is_static() {
-                return None;
-            }
-            let subdivisions = 10;
-            let mesh = collider.to_trimesh(subdivisions)?;
-            Some((*transform, mesh))
-        })
+        .filter_map(|collider| collider_to_affector(collider, filter, &bodies))
+        .collect::<Vec<_>>()
+}
+
+fn collider_tile_backend(
+    In(query): In<NavmeshTileQuery>,
+    spatial_query: SpatialQuery,
+    colliders: Query<ColliderData>,
+    bodies: Query<&RigidBody>,
+) -> Vec<(GlobalTransform, TriMesh)> {
+    let aabb = ColliderAabb {
+        min: query.aabb.min,
+        max: query.aabb.max,
+    };
+    spatial_query
+        .aabb_intersections_with_aabb(aabb)
+        .into_iter()
+        .filter_map(|entity| colliders.get(entity).ok())
+        .filter_map(|collider| collider_to_affector(collider, query.filter, &bodies))
         .collect::<Vec<_>>()
 }
+
+fn collider_to_affector(
+    (transform, collider, collider_of, navmesh_layers, collision_layers): (
+        &GlobalTransform,
+        &Collider,
+        &ColliderOf,
+        Option<&NavmeshLayers>,
+        Option<&CollisionLayers>,
+    ),
+    filter: NavmeshAffectorFilter,
+    bodies: &Query<&RigidBody>,
+) -> Option<(GlobalTransform, TriMesh)> {
+    let layers = navmesh_layers.copied().unwrap_or_else(|| {
+        collision_layers.map_or(NavmeshLayers::DEFAULT, |collision_layers| {
+            NavmeshLayers(collision_layers.memberships.0)
+        })
+    });
+    if !filter.allows(layers) {
+        return None;
+    }
+    let body = bodies.get(collider_of.body).ok()?;
+    if !body.is_static() {
+        return None;
+    }
+    let subdivisions = 10;
+    let mesh = collider.to_trimesh(subdivisions)?;
+    Some((*transform, mesh))
+}
@@ -2,11 +2,69 @@ use bevy_app::prelude::*;
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::{prelude::*, system::SystemId};
 use bevy_transform::prelude::*;
-use rerecast::TriMesh;
+use glam::IVec2;
+use rerecast::{Aabb3d, TriMesh};
+
+use crate::NavmeshAffectorFilter;
 
 /// The current backend registered through [`NavmeshApp::set_navmesh_affector_backend`]
+///
+/// The backend receives the [`NavmeshAffectorFilter`] of the navmesh being generated
+/// and should only return affectors that pass it.
+#[derive(Resource, Clone, Deref, DerefMut)]
+pub struct NavmeshAffectorBackend(
+    SystemId<In<NavmeshAffectorFilter>, Vec<(GlobalTransform, TriMesh)>>,
+);
+
+/// The request passed to a [`NavmeshTileAffectorBackend`] when gathering the geometry of a single tile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavmeshTileQuery {
+    /// The coordinates of the tile being built.
+    pub tile: IVec2,
+    /// The world space bounds of the tile, including its border.
+    /// Only affectors intersecting these bounds need to be returned.
+    pub aabb: Aabb3d,
+    /// The filter of the navmesh being generated.
+    pub filter: NavmeshAffectorFilter,
+}
+
+/// The current backend registered through [`NavmeshApp::set_navmesh_tile_affector_backend`]
+///
+/// Unlike the [`NavmeshAffectorBackend`], this backend only returns the affectors intersecting a single tile,
+/// e.g. by running a physics broad-phase query, so that tiled builds like [`crate::tile_rebuild`] never need the geometry of the whole world at once.
 #[derive(Resource, Clone, Deref, DerefMut)]
-pub struct NavmeshAffectorBackend(SystemId<(), Vec<(GlobalTransform, TriMesh)>>);
+pub struct NavmeshTileAffectorBackend(
+    SystemId<In<NavmeshTileQuery>, Vec<(GlobalTransform, TriMesh)>>,
+);
+
+/// Gathers the affectors of a single tile.
+///
+/// Uses the [`NavmeshTileAffectorBackend`] if one is set. Otherwise, falls back to the [`NavmeshAffectorBackend`]
+/// and discards all affectors not intersecting [`NavmeshTileQuery::aabb`], which requires
+/// collecting the geometry of the whole world.
+///
+/// Returns `None` if no backend is set or the backend system could not be run.
+pub fn tile_affectors(
+    world: &mut World,
+    query: NavmeshTileQuery,
+) -> Option<Vec<(GlobalTransform, TriMesh)>> {
+    if let Some(backend) = world.get_resource::<NavmeshTileAffectorBackend>().cloned() {
+        return world.run_system_with(*backend, query).ok();
+    }
+    let backend = world.get_resource::<NavmeshAffectorBackend>().cloned()?;
+    let affectors = world.run_system_with(*backend, query.filter).ok()?;
+    Some(
+        affectors
+            .into_iter()
+            .filter(|(transform, mesh)| {
+                let mut mesh = mesh.clone();
+                mesh.transform(&transform.affine());
+                mesh.compute_aabb()
+                    .is_some_and(|aabb| aabb.intersects(&query.aabb))
+            })
+            .collect(),
+    )
+}
 
 /// Extension used to implement [`NavmeshApp::set_navmesh_affector_backend`] on [`App`]
 pub trait NavmeshApp {
@@ -14,17 +72,35 @@ pub trait NavmeshApp {
     /// Setting a backend will replace any existing backend. By default, no backend is set.
     fn set_navmesh_affector_backend<M>(
         &mut self,
-        system: impl IntoSystem<(), Vec<(GlobalTransform, TriMesh)>, M> + 'static,
+        system: impl IntoSystem<In<NavmeshAffectorFilter>, Vec<(GlobalTransform, TriMesh)>, M> + 'static,
+    ) -> &mut App;
+
+    /// Set the backend for gathering the navmesh affectors of a single tile. Only one tile backend can be set at a time.
+    /// Setting a backend will replace any existing tile backend. By default, no tile backend is set
+    /// and tiled builds fall back to the backend set through [`NavmeshApp::set_navmesh_affector_backend`].
+    fn set_navmesh_tile_affector_backend<M>(
+        &mut self,
+        system: impl IntoSystem<In<NavmeshTileQuery>, Vec<(GlobalTransform, TriMesh)>, M> + 'static,
     ) -> &mut App;
 }
 
 impl NavmeshApp for App {
     fn set_navmesh_affector_backend<M>(
         &mut self,
-        system: impl IntoSystem<(), Vec<(GlobalTransform, TriMesh)>, M> + 'static,
+        system: impl IntoSystem<In<NavmeshAffectorFilter>, Vec<(GlobalTransform, TriMesh)>, M> + 'static,
     ) -> &mut App {
         let id = self.register_system(system);
         self.world_mut().insert_resource(NavmeshAffectorBackend(id));
         self
     }
+
+    fn set_navmesh_tile_affector_backend<M>(
+        &mut self,
+        system: impl IntoSystem<In<NavmeshTileQuery>, Vec<(GlobalTransform, TriMesh)>, M> + 'static,
+    ) -> &mut App {
+        let id = self.register_system(system);
+        self.world_mut()
+            .insert_resource(NavmeshTileAffectorBackend(id));
+        self
+    }
 }
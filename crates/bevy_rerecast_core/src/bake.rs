@@ -0,0 +1,188 @@
+//! Baking navmeshes in the asset pipeline.
+//!
+//! A bake file with the extension [`NavmeshBakeLoader::EXTENSION`] lists the mesh assets to build a navmesh from:
+//!
+//! ```json
+//! {
+//!   "meshes": [
+//!     "models/level.glb#Mesh0/Primitive0",
+//!     {
+//!       "path": "models/crate.glb#Mesh0/Primitive0",
+//!       "transform": { "translation": [4.0, 0.0, 2.0], "rotation": [0.0, 0.0, 0.0, 1.0], "scale": [1.0, 1.0, 1.0] }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! The [`NavmeshConfig`] used for the build is stored in the `.meta` file of the bake file as [`NavmeshBakeSettings`].
+//! When the asset processor is enabled, bake files are processed into baked navmeshes ahead of time
+//! by [`NavmeshBakeProcessor`], so loading them at runtime only deserializes the result.
+//! Without it, bake files are built when they are loaded.
+//!
+//! Meshes listed by path alone are used in their local space, i.e. without the transforms of the scenes they belong to.
+//! To place a mesh, list it together with a [`Transform`], see [`NavmeshBakeMesh`].
+//!
+//! Only available with the `serialize` and `bevy_mesh` features.
+
+use bevy_app::prelude::*;
+use bevy_asset::{
+    AssetLoader, AssetPath, LoadContext, io::Reader, prelude::*, processor::LoadTransformAndSave,
+    transformer::IdentityAssetTransformer,
+};
+use bevy_mesh::Mesh;
+use bevy_reflect::TypePath;
+use bevy_transform::components::Transform;
+use rerecast::{NavmeshConfig, SoloNavmeshError, TriMesh, build_solo_navmesh};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Navmesh, TriMeshFromBevyMesh as _, loader::NavmeshSaver};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset_loader::<NavmeshBakeLoader>()
+        .register_asset_processor(NavmeshBakeProcessor::new(
+            IdentityAssetTransformer::new(),
+            NavmeshSaver,
+        ))
+        .set_default_asset_processor::<NavmeshBakeProcessor>(NavmeshBakeLoader::EXTENSION);
+}
+
+/// Processes bake files into baked navmeshes, see the [module docs](self).
+pub type NavmeshBakeProcessor =
+    LoadTransformAndSave<NavmeshBakeLoader, IdentityAssetTransformer<Navmesh>, NavmeshSaver>;
+
+/// The content of a bake file, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct NavmeshBake {
+    /// The mesh assets to build the navmesh from.
+    pub meshes: Vec<NavmeshBakeMesh>,
+}
+
+/// A mesh asset listed in a [`NavmeshBake`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NavmeshBakeMesh {
+    /// The path of a mesh asset including its label, used in its local space.
+    Path(String),
+    /// A mesh asset placed with a transform.
+    Transformed {
+        /// The path of the mesh asset including its label.
+        path: String,
+        /// The transform applied to the mesh before building.
+        transform: Transform,
+    },
+}
+
+impl NavmeshBakeMesh {
+    /// The path of the mesh asset including its label.
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Path(path) | Self::Transformed { path, .. } => path,
+        }
+    }
+
+    /// The transform applied to the mesh before building.
+    pub fn transform(&self) -> Transform {
+        match self {
+            Self::Path(_) => Transform::IDENTITY,
+            Self::Transformed { transform, .. } => *transform,
+        }
+    }
+}
+
+/// The settings of [`NavmeshBakeLoader`], configured in the `.meta` file of a bake file.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct NavmeshBakeSettings {
+    /// The config to build the navmesh with.
+    pub config: NavmeshConfig,
+}
+
+/// The [`AssetLoader`] for bake files, which builds a navmesh from the meshes they list.
+#[derive(Debug, Default, TypePath)]
+#[non_exhaustive]
+pub struct NavmeshBakeLoader;
+
+impl NavmeshBakeLoader {
+    /// The file extension of bake files.
+    pub const EXTENSION: &'static str = "navmesh.json";
+}
+
+impl AssetLoader for NavmeshBakeLoader {
+    type Asset = Navmesh;
+    type Settings = NavmeshBakeSettings;
+    type Error = NavmeshBakeError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let bake: NavmeshBake = serde_json::from_slice(&bytes)?;
+
+        let mut trimesh = TriMesh::default();
+        for entry in &bake.meshes {
+            let path = entry.path();
+            // Immediate loads can't request labeled assets directly, so load the whole file and pick the label from it.
+            let asset_path = AssetPath::parse(path).into_owned();
+            let loaded = load_context
+                .loader()
+                .immediate()
+                .with_unknown_type()
+                .load(asset_path.without_label())
+                .await
+                .map_err(|error| NavmeshBakeError::LoadMesh {
+                    path: path.to_string(),
+                    error: Box::new(error),
+                })?;
+            let loaded = match asset_path.label() {
+                Some(label) => loaded.get_labeled(label.to_string()),
+                None => Some(&loaded),
+            };
+            let mesh = loaded
+                .and_then(|loaded| loaded.get::<Mesh>())
+                .ok_or_else(|| NavmeshBakeError::NotAMesh(path.to_string()))?;
+            let mut mesh = TriMesh::from_mesh(mesh)
+                .ok_or_else(|| NavmeshBakeError::UnsupportedMesh(path.to_string()))?;
+            mesh.transform(&entry.transform().compute_affine());
+            trimesh.extend(mesh);
+        }
+
+        let (polygon, detail) = build_solo_navmesh(&trimesh, &settings.config)?;
+        Ok(Navmesh { polygon, detail })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[Self::EXTENSION]
+    }
+}
+
+/// Errors that can occur when building a navmesh from a bake file.
+#[derive(Error, Debug)]
+pub enum NavmeshBakeError {
+    /// The bake file could not be read.
+    #[error("Failed to read bake file: {0}")]
+    Io(#[from] std::io::Error),
+    /// The bake file is not valid JSON or does not match [`NavmeshBake`].
+    #[error("Failed to parse bake file: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A mesh listed in the bake file could not be loaded.
+    #[error("Failed to load mesh `{path}`: {error}")]
+    LoadMesh {
+        /// The path of the mesh.
+        path: String,
+        /// The reason the mesh could not be loaded.
+        error: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// An asset listed in the bake file is not a [`Mesh`].
+    #[error("Asset `{0}` is not a mesh")]
+    NotAMesh(String),
+    /// A mesh listed in the bake file is not an indexed triangle list.
+    #[error("Mesh `{0}` is not an indexed triangle list")]
+    UnsupportedMesh(String),
+    /// The navmesh could not be built.
+    #[error("Failed to build navmesh: {0}")]
+    Build(#[from] SoloNavmeshError),
+}
@@ -0,0 +1,81 @@
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+
+/// The navmesh layers an entity belongs to, stored as a bitmask of up to 32 layers.
+///
+/// Backends use this together with a [`NavmeshAffectorFilter`] to decide which entities contribute to a navmesh,
+/// e.g. to ignore foliage. When an entity has no [`NavmeshLayers`], backends may derive its layers from other
+/// sources, like render layers or physics collision layers, and fall back to [`NavmeshLayers::DEFAULT`] otherwise.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct NavmeshLayers(pub u32);
+
+impl NavmeshLayers {
+    /// Belongs to no layer.
+    pub const NONE: Self = Self(0);
+    /// Belongs to all layers.
+    pub const ALL: Self = Self(u32::MAX);
+    /// Belongs to layer 0 only.
+    pub const DEFAULT: Self = Self(1);
+
+    /// Creates a mask containing only the given layer. `[Limit: < 32]`
+    #[inline]
+    pub const fn layer(layer: u32) -> Self {
+        Self(1 << layer)
+    }
+
+    /// Adds the given layer to the mask. `[Limit: < 32]`
+    #[inline]
+    pub const fn with(self, layer: u32) -> Self {
+        Self(self.0 | (1 << layer))
+    }
+
+    /// Removes the given layer from the mask. `[Limit: < 32]`
+    #[inline]
+    pub const fn without(self, layer: u32) -> Self {
+        Self(self.0 & !(1 << layer))
+    }
+
+    /// Returns whether the masks share at least one layer.
+    #[inline]
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl Default for NavmeshLayers {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Decides which entities a navmesh affector backend should use for a navmesh,
+/// based on the [`NavmeshLayers`] they belong to.
+///
+/// An entity is used if it belongs to at least one layer in [`Self::include`] and to no layer in [`Self::exclude`].
+/// The default filter uses all entities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct NavmeshAffectorFilter {
+    /// The layers an entity must belong to at least one of.
+    pub include: NavmeshLayers,
+    /// The layers an entity must not belong to any of. Takes precedence over [`Self::include`].
+    pub exclude: NavmeshLayers,
+}
+
+impl Default for NavmeshAffectorFilter {
+    fn default() -> Self {
+        Self {
+            include: NavmeshLayers::ALL,
+            exclude: NavmeshLayers::NONE,
+        }
+    }
+}
+
+impl NavmeshAffectorFilter {
+    /// Returns whether an entity belonging to the given layers should affect the navmesh.
+    #[inline]
+    pub const fn allows(&self, layers: NavmeshLayers) -> bool {
+        layers.intersects(self.include) && !layers.intersects(self.exclude)
+    }
+}
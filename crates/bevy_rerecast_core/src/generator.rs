@@ -8,7 +8,7 @@ use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::{prelude::*, system::SystemParam};
 use rerecast::NavmeshConfig;
 
-use crate::Navmesh;
+use crate::{Navmesh, NavmeshAffectorFilter};
 
 pub(super) fn plugin(app: &mut App) {
     app.init_resource::<NavmeshQueue>();
@@ -27,14 +27,25 @@ pub struct NavmeshGenerator<'w, Marker: 'static> {
 
 impl<'w, Marker: 'static> NavmeshGenerator<'w, Marker> {
     /// Queue a navmesh generation task.
-    /// When you call this method, a new navmesh will be generated asynchronously.
+    /// When you call this method, a new navmesh will be generated over the next frames, see [`crate::stepped_build`].
     /// Calling it multiple times will queue multiple navmeshes to be generated in a FIFO order.
     pub fn generate(&mut self, config: NavmeshConfig) -> Handle<Navmesh> {
+        self.generate_filtered(config, NavmeshAffectorFilter::default())
+    }
+
+    /// Queue a navmesh generation task that only uses the affectors passing the given filter.
+    /// See [`Self::generate`] for details.
+    pub fn generate_filtered(
+        &mut self,
+        config: NavmeshConfig,
+        filter: NavmeshAffectorFilter,
+    ) -> Handle<Navmesh> {
         let handle = self.navmeshes.reserve_handle();
-        self.queue.push_back((handle.clone(), config));
+        self.queue.push_back((handle.clone(), config, filter));
         handle
     }
 }
 
+/// The navmeshes waiting to be built, in the order they were queued. Consumed by [`crate::stepped_build`].
 #[derive(Resource, Default, Deref, DerefMut)]
-struct NavmeshQueue(VecDeque<(Handle<Navmesh>, NavmeshConfig)>);
+pub(crate) struct NavmeshQueue(VecDeque<(Handle<Navmesh>, NavmeshConfig, NavmeshAffectorFilter)>);
@@ -0,0 +1,36 @@
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+
+use crate::{
+    Navmesh,
+    tile_rebuild::{NavmeshTileRebuild, RebuiltNavmeshTiles},
+    tile_streaming::{NavmeshTileStreaming, StreamedNavmeshTiles},
+    tiles::{DirtyNavmeshTiles, NavmeshTileSettings},
+};
+
+/// Makes an entity an independent navmesh instance, e.g. for a sub-scene, a dungeon or a parallel simulation.
+///
+/// Every instance has its own [`NavmeshTileSettings`], [`DirtyNavmeshTiles`], [`NavmeshTileRebuild`], [`RebuiltNavmeshTiles`],
+/// [`NavmeshTileStreaming`] and [`StreamedNavmeshTiles`] as components on the same entity. Entities are assigned to an instance with [`NavmeshInstanceOf`].
+/// Entities without it belong to the global instance, which is made up of the resources of the same names.
+#[derive(Component, Debug, Clone, Default, PartialEq, Reflect)]
+#[require(
+    NavmeshTileSettings,
+    DirtyNavmeshTiles,
+    NavmeshTileRebuild,
+    RebuiltNavmeshTiles,
+    NavmeshTileStreaming,
+    StreamedNavmeshTiles
+)]
+pub struct NavmeshInstance {
+    /// The navmesh of this instance, e.g. as returned by [`NavmeshGenerator::generate`](crate::generator::NavmeshGenerator::generate).
+    pub navmesh: Handle<Navmesh>,
+}
+
+/// Assigns an entity to the [`NavmeshInstance`] on the given entity,
+/// e.g. a [`NavmeshAffector`](crate::tiles::NavmeshAffector) or a camera with a [`NavmeshTilePrefetch`](crate::tile_streaming::NavmeshTilePrefetch).
+///
+/// Entities assigned to an entity without a [`NavmeshInstance`] are ignored.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct NavmeshInstanceOf(pub Entity);
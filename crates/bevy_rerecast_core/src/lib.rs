@@ -8,8 +8,20 @@ use bevy_reflect::Reflect;
 #[cfg(feature = "bevy_mesh")]
 pub use mesh::{Mesh3dNavmeshPlugin, TriMeshFromBevyMesh};
 mod backend;
+#[cfg(all(feature = "serialize", feature = "bevy_mesh"))]
+pub mod bake;
+mod filter;
 pub mod generator;
+mod instance;
+#[cfg(feature = "serialize")]
+pub mod loader;
+pub mod stepped_build;
+pub mod tile_rebuild;
+pub mod tile_streaming;
+pub mod tiles;
 pub use backend::*;
+pub use filter::*;
+pub use instance::*;
 
 pub use rerecast;
 use rerecast::{DetailNavmesh, PolygonNavmesh};
@@ -22,7 +34,17 @@ pub struct RerecastPlugin;
 impl Plugin for RerecastPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<Navmesh>();
-        app.add_plugins(generator::plugin);
+        app.add_plugins((
+            generator::plugin,
+            stepped_build::plugin,
+            tiles::plugin,
+            tile_rebuild::plugin,
+            tile_streaming::plugin,
+        ));
+        #[cfg(feature = "serialize")]
+        app.add_plugins(loader::plugin);
+        #[cfg(all(feature = "serialize", feature = "bevy_mesh"))]
+        app.add_plugins(bake::plugin);
     }
 }
 
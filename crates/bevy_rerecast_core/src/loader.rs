@@ -0,0 +1,105 @@
+//! Loading of baked navmeshes as assets.
+//!
+//! Navmeshes saved with [`Navmesh::to_bytes`] into files with the [`Navmesh::FILE_EXTENSION`] extension
+//! can be loaded through the [`AssetServer`] like any other asset, e.g. `asset_server.load("level.navmesh")`.
+//! With the `file_watcher` feature, changed files are reloaded automatically and
+//! the change is announced through [`AssetEvent::Modified`].
+//!
+//! Only available with the `serialize` feature.
+
+use bevy_app::prelude::*;
+use bevy_asset::{
+    AssetLoader, AsyncWriteExt as _, LoadContext,
+    io::{Reader, Writer},
+    prelude::*,
+    saver::{AssetSaver, SavedAsset},
+};
+use bevy_reflect::TypePath;
+use thiserror::Error;
+
+use crate::Navmesh;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset_loader::<NavmeshLoader>();
+}
+
+impl Navmesh {
+    /// The file extension of baked navmeshes.
+    pub const FILE_EXTENSION: &'static str = "navmesh";
+
+    /// Serializes the navmesh into the format loaded by [`NavmeshLoader`].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, NavmeshFileError> {
+        Ok(bincode::serde::encode_to_vec(
+            self,
+            bincode::config::standard(),
+        )?)
+    }
+
+    /// Deserializes a navmesh written by [`Navmesh::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NavmeshFileError> {
+        let (navmesh, _) = bincode::serde::decode_from_slice(bytes, bincode::config::standard())?;
+        Ok(navmesh)
+    }
+}
+
+/// The [`AssetLoader`] for baked navmesh files, see the [module docs](self).
+#[derive(Debug, Default, TypePath)]
+#[non_exhaustive]
+pub struct NavmeshLoader;
+
+impl AssetLoader for NavmeshLoader {
+    type Asset = Navmesh;
+    type Settings = ();
+    type Error = NavmeshFileError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Navmesh::from_bytes(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[Navmesh::FILE_EXTENSION]
+    }
+}
+
+/// The [`AssetSaver`] for baked navmesh files, used to write the output of asset processors.
+#[derive(Debug, Default, TypePath)]
+#[non_exhaustive]
+pub struct NavmeshSaver;
+
+impl AssetSaver for NavmeshSaver {
+    type Asset = Navmesh;
+    type Settings = ();
+    type OutputLoader = NavmeshLoader;
+    type Error = NavmeshFileError;
+
+    async fn save(
+        &self,
+        writer: &mut Writer,
+        asset: SavedAsset<'_, Self::Asset>,
+        _settings: &Self::Settings,
+    ) -> Result<(), Self::Error> {
+        writer.write_all(&asset.to_bytes()?).await?;
+        Ok(())
+    }
+}
+
+/// Errors that can occur when reading or writing a baked navmesh.
+#[derive(Error, Debug)]
+pub enum NavmeshFileError {
+    /// The file could not be read or written.
+    #[error("Failed to access navmesh file: {0}")]
+    Io(#[from] std::io::Error),
+    /// The navmesh could not be serialized.
+    #[error("Failed to encode navmesh: {0}")]
+    Encode(#[from] bincode::error::EncodeError),
+    /// The file does not contain a valid navmesh.
+    #[error("Failed to decode navmesh: {0}")]
+    Decode(#[from] bincode::error::DecodeError),
+}
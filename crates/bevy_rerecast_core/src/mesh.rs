@@ -2,15 +2,18 @@ use bevy_app::prelude::*;
 use bevy_asset::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_mesh::{Mesh, PrimitiveTopology};
-use bevy_render::prelude::*;
+use bevy_render::{prelude::*, view::RenderLayers};
 use bevy_transform::components::GlobalTransform;
 use glam::{UVec3, Vec3A};
 use rerecast::{AreaType, TriMesh};
 
-use crate::NavmeshApp as _;
+use crate::{NavmeshAffectorFilter, NavmeshApp as _, NavmeshLayers};
 
 /// A backend for navmesh generation.
 /// Uses all entities with a [`Mesh3d`] component as navmesh affectors.
+///
+/// Entities are matched against the [`NavmeshAffectorFilter`] using their [`NavmeshLayers`] if present,
+/// or their first 32 [`RenderLayers`] otherwise.
 #[derive(Debug, Default)]
 #[non_exhaustive]
 pub struct Mesh3dNavmeshPlugin;
@@ -22,12 +25,31 @@ impl Plugin for Mesh3dNavmeshPlugin {
 }
 
 fn mesh3d_backend(
+    In(filter): In<NavmeshAffectorFilter>,
     meshes: Res<Assets<Mesh>>,
-    affectors: Query<(&GlobalTransform, &Mesh3d)>,
+    affectors: Query<(
+        &GlobalTransform,
+        &Mesh3d,
+        Option<&NavmeshLayers>,
+        Option<&RenderLayers>,
+    )>,
 ) -> Vec<(GlobalTransform, TriMesh)> {
     affectors
         .iter()
-        .filter_map(|(transform, mesh)| {
+        .filter_map(|(transform, mesh, navmesh_layers, render_layers)| {
+            let layers = match (navmesh_layers, render_layers) {
+                (Some(layers), _) => *layers,
+                (None, Some(render_layers)) => render_layers
+                    .iter()
+                    .filter(|layer| *layer < 32)
+                    .fold(NavmeshLayers::NONE, |layers, layer| {
+                        layers.with(layer as u32)
+                    }),
+                (None, None) => NavmeshLayers::DEFAULT,
+            };
+            if !filter.allows(layers) {
+                return None;
+            }
             let transform = *transform;
             let mesh = meshes.get(mesh)?;
             let proxy_mesh = TriMesh::from_mesh(mesh)?;
@@ -0,0 +1,135 @@
+//! Building the navmeshes queued with [`NavmeshGenerator`](crate::generator::NavmeshGenerator) on the main thread,
+//! a few milliseconds per frame.
+//!
+//! Every frame, the oldest queued navmesh is advanced with [`SoloNavmeshBuild::step`] until [`NavmeshBuildBudget::per_frame`]
+//! is used up, so builds also work on platforms without threads, e.g. the web, without freezing the game.
+//! The geometry of a navmesh is gathered from the [`NavmeshAffectorBackend`] when its build starts.
+//!
+//! The build in progress can be inspected and canceled through the [`ActiveNavmeshBuild`] resource.
+
+use core::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_platform::time::Instant;
+use bevy_reflect::prelude::*;
+use rerecast::{SoloNavmeshBuild, SoloNavmeshBuildStage, TriMesh};
+
+use crate::{Navmesh, NavmeshAffectorBackend, generator::NavmeshQueue};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<NavmeshBuildBudget>();
+    app.init_resource::<ActiveNavmeshBuild>();
+    app.add_systems(Update, advance_navmesh_builds);
+}
+
+/// How much work is spent on building navmeshes every frame, see the [module docs](self).
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+pub struct NavmeshBuildBudget {
+    /// The time spent on building navmeshes per frame.
+    ///
+    /// The budget is checked between steps, so a frame can take longer by the duration of a single step.
+    pub per_frame: Duration,
+    /// See [`SoloNavmeshBuild::triangles_per_step`]. `[Limit: > 0]`
+    pub triangles_per_step: usize,
+}
+
+impl Default for NavmeshBuildBudget {
+    fn default() -> Self {
+        Self {
+            per_frame: Duration::from_millis(4),
+            triangles_per_step: SoloNavmeshBuild::DEFAULT_TRIANGLES_PER_STEP,
+        }
+    }
+}
+
+/// The navmesh currently being built, see the [module docs](self).
+#[derive(Resource, Debug, Default)]
+pub struct ActiveNavmeshBuild(Option<(Handle<Navmesh>, SoloNavmeshBuild)>);
+
+impl ActiveNavmeshBuild {
+    /// Returns the handle of the navmesh being built, or `None` if no build is in progress.
+    pub fn handle(&self) -> Option<&Handle<Navmesh>> {
+        self.0.as_ref().map(|(handle, _)| handle)
+    }
+
+    /// Returns the stage the build runs next, or `None` if no build is in progress.
+    pub fn stage(&self) -> Option<SoloNavmeshBuildStage> {
+        self.0.as_ref().map(|(_, build)| build.stage())
+    }
+
+    /// Returns a rough estimate of the share of the build that is done, see [`SoloNavmeshBuild::progress`],
+    /// or `None` if no build is in progress. `[Limit: 0..=1]`
+    pub fn progress(&self) -> Option<f32> {
+        self.0.as_ref().map(|(_, build)| build.progress())
+    }
+
+    /// Cancels the build in progress and returns the handle of its navmesh, which will never be loaded.
+    /// The next queued navmesh starts building in the next frame.
+    pub fn cancel(&mut self) -> Option<Handle<Navmesh>> {
+        self.0.take().map(|(handle, _)| handle)
+    }
+}
+
+fn advance_navmesh_builds(world: &mut World) {
+    let start = Instant::now();
+    let budget = world.resource::<NavmeshBuildBudget>().clone();
+    world.resource_scope(|world, mut active: Mut<ActiveNavmeshBuild>| {
+        while start.elapsed() < budget.per_frame {
+            if active.0.is_none() {
+                let Some(next) = start_next_build(world, &budget) else {
+                    return;
+                };
+                active.0 = Some(next);
+            }
+            let Some((handle, build)) = active.0.as_mut() else {
+                return;
+            };
+            match build.step() {
+                Ok(None) => {}
+                Ok(Some((polygon, detail))) => {
+                    world
+                        .resource_mut::<Assets<Navmesh>>()
+                        .insert(handle.id(), Navmesh { polygon, detail });
+                    active.0 = None;
+                }
+                Err(error) => {
+                    tracing::error!("Failed to build navmesh: {error}");
+                    active.0 = None;
+                }
+            }
+        }
+    });
+}
+
+/// Takes the oldest queued navmesh and gathers its geometry.
+/// Returns `None` if nothing is queued or no backend is set.
+fn start_next_build(
+    world: &mut World,
+    budget: &NavmeshBuildBudget,
+) -> Option<(Handle<Navmesh>, SoloNavmeshBuild)> {
+    let backend = world.get_resource::<NavmeshAffectorBackend>().cloned()?;
+    loop {
+        let (handle, config, filter) = world.resource_mut::<NavmeshQueue>().pop_front()?;
+        let affectors = match world.run_system_with(*backend, filter) {
+            Ok(affectors) => affectors,
+            Err(error) => {
+                tracing::error!("Failed to gather navmesh affectors: {error}");
+                continue;
+            }
+        };
+        let mut trimesh = TriMesh::default();
+        for (transform, mut mesh) in affectors {
+            mesh.transform(&transform.affine());
+            trimesh.extend(mesh);
+        }
+        match SoloNavmeshBuild::new(trimesh, config) {
+            Ok(mut build) => {
+                build.triangles_per_step = budget.triangles_per_step;
+                return Some((handle, build));
+            }
+            Err(error) => tracing::error!("Failed to build navmesh: {error}"),
+        }
+    }
+}
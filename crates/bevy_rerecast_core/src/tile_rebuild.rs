@@ -0,0 +1,240 @@
+//! Rebuilding the navmesh tiles queued in [`DirtyNavmeshTiles`] when the geometry affecting them changed.
+//!
+//! Every frame, up to [`NavmeshTileRebuild::max_tiles_per_frame`] dirty tiles of the global navmesh and of every [`NavmeshInstance`]
+//! are drained and rebuilt one by one from the geometry [`tile_affectors`] gathers for the bounds of each tile,
+//! so that a [`NavmeshTileAffectorBackend`] only needs to return the affectors near the tile.
+//! Rebuilt tiles are stored in [`RebuiltNavmeshTiles`] and announced through [`NavmeshTileBuilt`].
+//! Tiles that no longer contain any polygons are removed and announced through [`NavmeshTileRemoved`].
+//!
+//! Nothing is rebuilt, and dirty tiles stay queued, until [`NavmeshTileRebuild::config`] is set and a backend is registered.
+
+use std::collections::HashMap;
+
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use glam::{IVec2, UVec2};
+use rerecast::{NavmeshConfig, TiledNavmeshBuilder, TiledNavmeshError, TriMesh};
+
+use crate::{
+    Navmesh, NavmeshAffectorBackend, NavmeshAffectorFilter, NavmeshInstance,
+    NavmeshTileAffectorBackend, NavmeshTileQuery, tile_affectors,
+    tile_streaming::{NavmeshTileBuilt, NavmeshTileRemoved},
+    tiles::{DirtyNavmeshTiles, NavmeshTileSettings, mark_dirty_tiles},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<NavmeshTileRebuild>();
+    app.init_resource::<RebuiltNavmeshTiles>();
+    app.add_systems(PostUpdate, rebuild_dirty_tiles.after(mark_dirty_tiles));
+}
+
+/// Settings for rebuilding dirty navmesh tiles, see the [module docs](self).
+///
+/// Used as a resource for the global navmesh and as a component for each [`NavmeshInstance`].
+#[derive(Resource, Component, Debug, Clone, PartialEq)]
+pub struct NavmeshTileRebuild {
+    /// The config the tiles are built with, or `None` to leave the [`DirtyNavmeshTiles`] untouched.
+    ///
+    /// Only the vertical extent of [`NavmeshConfig::aabb`] is used, the horizontal bounds of each tile
+    /// come from [`NavmeshTileSettings`]. [`NavmeshConfig::tile_size`] is derived from [`NavmeshTileSettings::tile_size`],
+    /// which should thus be a multiple of [`NavmeshConfig::cell_size`].
+    pub config: Option<NavmeshConfig>,
+    /// The filter passed to the backend as [`NavmeshTileQuery::filter`] when gathering the geometry of the tiles.
+    pub filter: NavmeshAffectorFilter,
+    /// The number of tiles rebuilt per frame. Remaining tiles stay in [`DirtyNavmeshTiles`] until the next frame. `[Limit: > 0]`
+    pub max_tiles_per_frame: usize,
+}
+
+impl Default for NavmeshTileRebuild {
+    fn default() -> Self {
+        Self {
+            config: None,
+            filter: NavmeshAffectorFilter::default(),
+            max_tiles_per_frame: 4,
+        }
+    }
+}
+
+/// The navmesh tiles rebuilt from [`DirtyNavmeshTiles`], keyed by their coordinates.
+///
+/// Used as a resource for the global navmesh and as a component for each [`NavmeshInstance`].
+#[derive(Resource, Component, Debug, Default)]
+pub struct RebuiltNavmeshTiles(HashMap<IVec2, Handle<Navmesh>>);
+
+impl RebuiltNavmeshTiles {
+    /// Returns the handle of the given tile, or `None` if it was not rebuilt or contains no polygons.
+    pub fn get(&self, tile: IVec2) -> Option<&Handle<Navmesh>> {
+        self.0.get(&tile)
+    }
+
+    /// Iterates over the coordinates and handles of all rebuilt tiles.
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &Handle<Navmesh>)> {
+        self.0.iter().map(|(tile, handle)| (*tile, handle))
+    }
+
+    /// Returns the number of rebuilt tiles.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether no tiles were rebuilt.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// The tiles of one navmesh drained from its [`DirtyNavmeshTiles`] in the current frame.
+struct TileRebuildJob {
+    instance: Option<Entity>,
+    settings: NavmeshTileSettings,
+    config: NavmeshConfig,
+    filter: NavmeshAffectorFilter,
+    tiles: Vec<IVec2>,
+}
+
+fn rebuild_dirty_tiles(world: &mut World) {
+    if !world.contains_resource::<NavmeshAffectorBackend>()
+        && !world.contains_resource::<NavmeshTileAffectorBackend>()
+    {
+        return;
+    }
+    let mut jobs = Vec::new();
+    let settings = *world.resource::<NavmeshTileSettings>();
+    let rebuild = world.resource::<NavmeshTileRebuild>().clone();
+    if let Some(config) = rebuild.config {
+        let tiles = drain_tiles(
+            &mut world.resource_mut::<DirtyNavmeshTiles>(),
+            rebuild.max_tiles_per_frame,
+        );
+        jobs.push(TileRebuildJob {
+            instance: None,
+            settings,
+            config,
+            filter: rebuild.filter,
+            tiles,
+        });
+    }
+    let mut instances = world.query_filtered::<(
+        Entity,
+        &NavmeshTileSettings,
+        &NavmeshTileRebuild,
+        &mut DirtyNavmeshTiles,
+    ), With<NavmeshInstance>>();
+    for (entity, settings, rebuild, mut dirty) in instances.iter_mut(world) {
+        let Some(config) = rebuild.config.clone() else {
+            continue;
+        };
+        jobs.push(TileRebuildJob {
+            instance: Some(entity),
+            settings: *settings,
+            config,
+            filter: rebuild.filter,
+            tiles: drain_tiles(&mut dirty, rebuild.max_tiles_per_frame),
+        });
+    }
+
+    for job in jobs {
+        let border = job.config.border_size as f32 * job.config.cell_size;
+        for tile in job.tiles {
+            let query = NavmeshTileQuery {
+                tile,
+                aabb: job.settings.tile_aabb(
+                    tile,
+                    border,
+                    job.config.aabb.min.y,
+                    job.config.aabb.max.y,
+                ),
+                filter: job.filter,
+            };
+            let Some(affectors) = tile_affectors(world, query) else {
+                tracing::error!("Failed to gather the navmesh affectors of tile {tile}");
+                continue;
+            };
+            let mut trimesh = TriMesh::default();
+            for (transform, mut mesh) in affectors {
+                mesh.transform(&transform.affine());
+                trimesh.extend(mesh);
+            }
+            match build_tile(&job.settings, &job.config, tile, &trimesh) {
+                Ok(navmesh) => store_tile(world, job.instance, tile, navmesh),
+                Err(error) => tracing::error!("Failed to rebuild navmesh tile: {error}"),
+            }
+        }
+    }
+}
+
+/// Removes up to `max_tiles` tiles from `dirty`, in a stable order so that runs are reproducible.
+fn drain_tiles(dirty: &mut DirtyNavmeshTiles, max_tiles: usize) -> Vec<IVec2> {
+    let mut tiles: Vec<IVec2> = dirty.iter().copied().collect();
+    tiles.sort_unstable_by_key(|tile| (tile.y, tile.x));
+    tiles.truncate(max_tiles);
+    for tile in &tiles {
+        dirty.remove(tile);
+    }
+    tiles
+}
+
+/// Builds a single tile from `geometry`, including the geometry within [`NavmeshConfig::border_size`] around it.
+/// Returns `None` if the tile contains no polygons.
+fn build_tile(
+    settings: &NavmeshTileSettings,
+    config: &NavmeshConfig,
+    tile: IVec2,
+    geometry: &TriMesh,
+) -> Result<Option<Navmesh>, TiledNavmeshError> {
+    let config = NavmeshConfig {
+        aabb: settings.tile_aabb(tile, 0.0, config.aabb.min.y, config.aabb.max.y),
+        tile_size: (settings.tile_size / config.cell_size).round().max(1.0) as u16,
+        ..config.clone()
+    };
+    let tile = TiledNavmeshBuilder::new(config)?.build_tile(UVec2::ZERO, geometry)?;
+    Ok(tile.map(|tile| Navmesh {
+        polygon: tile.polygon,
+        detail: tile.detail,
+    }))
+}
+
+/// Stores the rebuilt `navmesh` of `tile`, reusing the handle of its previous navmesh, and announces the change.
+fn store_tile(world: &mut World, instance: Option<Entity>, tile: IVec2, navmesh: Option<Navmesh>) {
+    let Some(handle) = rebuilt_tiles(world, instance).map(|rebuilt| rebuilt.get(tile).cloned())
+    else {
+        // The instance was despawned while its tiles were being rebuilt.
+        return;
+    };
+    let Some(navmesh) = navmesh else {
+        if let Some(mut rebuilt) = rebuilt_tiles(world, instance)
+            && rebuilt.0.remove(&tile).is_some()
+        {
+            world.send_event(NavmeshTileRemoved { instance, tile });
+        }
+        return;
+    };
+    let mut assets = world.resource_mut::<Assets<Navmesh>>();
+    let handle = match handle {
+        Some(handle) => {
+            assets.insert(handle.id(), navmesh);
+            handle
+        }
+        None => assets.add(navmesh),
+    };
+    let id = handle.id();
+    if let Some(mut rebuilt) = rebuilt_tiles(world, instance) {
+        rebuilt.0.insert(tile, handle);
+    }
+    world.send_event(NavmeshTileBuilt {
+        instance,
+        tile,
+        navmesh: id,
+    });
+}
+
+fn rebuilt_tiles(
+    world: &mut World,
+    instance: Option<Entity>,
+) -> Option<Mut<'_, RebuiltNavmeshTiles>> {
+    match instance {
+        None => world.get_resource_mut::<RebuiltNavmeshTiles>(),
+        Some(entity) => world.get_mut::<RebuiltNavmeshTiles>(entity),
+    }
+}
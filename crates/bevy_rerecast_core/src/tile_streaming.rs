@@ -0,0 +1,371 @@
+//! Streaming of baked navmesh tiles from the asset store around the camera.
+//!
+//! Every frame, the tiles in view of each camera with a [`NavmeshTilePrefetch`] are requested from the [`AssetServer`],
+//! including the tiles the camera is about to see given its current velocity, so they are loaded before agents query them.
+//! Tiles that were not needed for a while are unloaded again once more than [`NavmeshTileStreaming::max_loaded_tiles`] are loaded.
+//!
+//! Cameras with a [`NavmeshInstanceOf`] stream the tiles of that [`NavmeshInstance`] into its own [`StreamedNavmeshTiles`],
+//! all other cameras stream the tiles of the global navmesh into the [`StreamedNavmeshTiles`] resource.
+//!
+//! Tiles that finished loading are announced through [`NavmeshTileBuilt`], unloaded tiles through [`NavmeshTileRemoved`].
+
+use std::collections::{HashMap, HashSet};
+
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_time::prelude::*;
+use bevy_transform::{TransformSystem, prelude::*};
+use glam::{IVec2, Vec2, Vec3, Vec3Swizzles as _};
+use rerecast::Aabb3d;
+
+use crate::{Navmesh, NavmeshInstance, NavmeshInstanceOf, tiles::NavmeshTileSettings};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<NavmeshTileStreaming>();
+    app.init_resource::<StreamedNavmeshTiles>();
+    app.add_event::<NavmeshTileBuilt>();
+    app.add_event::<NavmeshTileRemoved>();
+    app.add_systems(
+        PostUpdate,
+        prefetch_navmesh_tiles.after(TransformSystem::TransformPropagate),
+    );
+    app.add_systems(PostUpdate, announce_built_tiles);
+}
+
+/// Marks a camera whose view decides which navmesh tiles are streamed in, see the [module docs](self).
+///
+/// The view is approximated on the xz-plane by a cone around the camera's forward direction,
+/// which works for the top-down and third-person cameras navmeshes are usually streamed for.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct NavmeshTilePrefetch {
+    /// How far in front of the camera tiles are loaded. `[Limit: >= 0] [Units: wu]`
+    pub view_distance: f32,
+    /// The horizontal field of view of the camera. `[Limit: >= 0] [Units: radians]`
+    pub field_of_view: f32,
+    /// How far into the future the camera's movement is extrapolated to load the tiles it is about to see. `[Limit: >= 0] [Units: s]`
+    pub lookahead: f32,
+}
+
+impl Default for NavmeshTilePrefetch {
+    fn default() -> Self {
+        Self {
+            view_distance: 100.0,
+            field_of_view: 90_f32.to_radians(),
+            lookahead: 1.0,
+        }
+    }
+}
+
+impl NavmeshTilePrefetch {
+    /// Returns the tiles in view of a camera at `position` looking along `forward`,
+    /// as well as the tiles in view of where it will be after [`Self::lookahead`] seconds of moving at `velocity`.
+    ///
+    /// The tile containing the camera and its direct neighbors are always included.
+    pub fn tiles_in_view(
+        &self,
+        settings: &NavmeshTileSettings,
+        position: Vec3,
+        forward: Vec3,
+        velocity: Vec3,
+    ) -> HashSet<IVec2> {
+        let mut tiles = HashSet::new();
+        let predicted = position + velocity * self.lookahead;
+        for eye in [position, predicted] {
+            self.extend_with_view(settings, eye, forward.xz().normalize_or_zero(), &mut tiles);
+        }
+        tiles
+    }
+
+    fn extend_with_view(
+        &self,
+        settings: &NavmeshTileSettings,
+        eye: Vec3,
+        forward: Vec2,
+        tiles: &mut HashSet<IVec2>,
+    ) {
+        // The radius of the circle enclosing a tile
+        let tile_radius = settings.tile_size * core::f32::consts::FRAC_1_SQRT_2;
+        let half_fov = self.field_of_view * 0.5;
+        let reach = Vec3::new(self.view_distance, 0.0, self.view_distance);
+        let candidates = settings.tiles_overlapping(&Aabb3d {
+            min: eye - reach,
+            max: eye + reach,
+        });
+        for tile in candidates {
+            let center = settings.origin.xz() + (tile.as_vec2() + 0.5) * settings.tile_size;
+            let offset = center - eye.xz();
+            let distance = offset.length();
+            if distance <= settings.tile_size * 2.0 {
+                // The tiles around the camera are needed no matter where it looks.
+                tiles.insert(tile);
+                continue;
+            }
+            if distance - tile_radius > self.view_distance || forward == Vec2::ZERO {
+                continue;
+            }
+            // Widen the cone by the angle the tile covers, so that tiles partially in view are included.
+            let angle = forward.angle_to(offset).abs();
+            let slack = (tile_radius / distance).min(1.0).asin();
+            if angle <= half_fov + slack {
+                tiles.insert(tile);
+            }
+        }
+    }
+}
+
+/// Sent when a streamed tile finished loading, or was reloaded because it changed on disk,
+/// or when a tile was rebuilt by [`crate::tile_rebuild`], so its navmesh is available in [`Assets<Navmesh>`].
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct NavmeshTileBuilt {
+    /// The [`NavmeshInstance`] the tile was streamed into, or `None` for the global navmesh.
+    pub instance: Option<Entity>,
+    /// The coordinates of the tile, see [`NavmeshTileSettings::tile_at`].
+    pub tile: IVec2,
+    /// The navmesh of the tile.
+    pub navmesh: AssetId<Navmesh>,
+}
+
+/// Sent when a tile was evicted from [`StreamedNavmeshTiles`] because it was not needed for a while,
+/// or removed from [`RebuiltNavmeshTiles`](crate::tile_rebuild::RebuiltNavmeshTiles) because it no longer contains any polygons.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NavmeshTileRemoved {
+    /// The [`NavmeshInstance`] the tile was streamed into, or `None` for the global navmesh.
+    pub instance: Option<Entity>,
+    /// The coordinates of the tile, see [`NavmeshTileSettings::tile_at`].
+    pub tile: IVec2,
+}
+
+/// Settings for streaming navmesh tiles from the asset store, see the [module docs](self).
+///
+/// Used as a resource for the global navmesh and as a component for each [`NavmeshInstance`].
+#[derive(Resource, Component, Debug, Clone, PartialEq, Reflect)]
+pub struct NavmeshTileStreaming {
+    /// The asset path of each tile, where `{x}` and `{z}` are replaced with the coordinates of the tile,
+    /// see [`NavmeshTileSettings::tile_at`].
+    pub path_template: String,
+    /// The number of loaded tiles above which the least recently needed tiles are unloaded.
+    /// Tiles that are currently in view are never unloaded. `[Limit: >= 0]`
+    pub max_loaded_tiles: usize,
+}
+
+impl Default for NavmeshTileStreaming {
+    fn default() -> Self {
+        Self {
+            path_template: "navmesh/tile_{x}_{z}.navmesh".to_string(),
+            max_loaded_tiles: 256,
+        }
+    }
+}
+
+impl NavmeshTileStreaming {
+    /// Returns the asset path of the given tile, see [`Self::path_template`].
+    pub fn tile_path(&self, tile: IVec2) -> String {
+        self.path_template
+            .replace("{x}", &tile.x.to_string())
+            .replace("{z}", &tile.y.to_string())
+    }
+}
+
+/// The navmesh tiles currently streamed in, keyed by their coordinates.
+///
+/// Holds a strong [`Handle`] to every tile, so a tile is unloaded when it is evicted from here,
+/// unless someone else holds on to its handle as well.
+///
+/// Used as a resource for the global navmesh and as a component for each [`NavmeshInstance`].
+#[derive(Resource, Component, Debug, Default)]
+pub struct StreamedNavmeshTiles {
+    tiles: HashMap<IVec2, StreamedTile>,
+    frame: u64,
+}
+
+#[derive(Debug)]
+struct StreamedTile {
+    handle: Handle<Navmesh>,
+    /// The last frame in which the tile was in view.
+    last_needed: u64,
+}
+
+impl StreamedNavmeshTiles {
+    /// Returns the handle of the given tile, or `None` if it is not streamed in.
+    /// The tile may still be loading, check the [`AssetServer`] for its load state.
+    pub fn get(&self, tile: IVec2) -> Option<&Handle<Navmesh>> {
+        self.tiles.get(&tile).map(|tile| &tile.handle)
+    }
+
+    /// Iterates over the coordinates and handles of all tiles that are streamed in.
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &Handle<Navmesh>)> {
+        self.tiles
+            .iter()
+            .map(|(tile, streamed)| (*tile, &streamed.handle))
+    }
+
+    /// Returns the number of tiles that are streamed in.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns whether no tiles are streamed in.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Returns the coordinates of the streamed tile with the given navmesh, or `None` if it is not streamed in.
+    fn tile_of(&self, id: AssetId<Navmesh>) -> Option<IVec2> {
+        self.tiles
+            .iter()
+            .find(|(_, streamed)| streamed.handle.id() == id)
+            .map(|(tile, _)| *tile)
+    }
+
+    /// Starts a new frame in which `needed` are in view, loading the ones that are not streamed in yet
+    /// and unloading the least recently needed ones above [`NavmeshTileStreaming::max_loaded_tiles`].
+    /// Returns the unloaded tiles.
+    fn stream(
+        &mut self,
+        needed: HashSet<IVec2>,
+        streaming: &NavmeshTileStreaming,
+        asset_server: &AssetServer,
+    ) -> Vec<IVec2> {
+        self.frame += 1;
+        let frame = self.frame;
+        for tile in needed {
+            self.tiles
+                .entry(tile)
+                .and_modify(|streamed| streamed.last_needed = frame)
+                .or_insert_with(|| StreamedTile {
+                    handle: asset_server.load(streaming.tile_path(tile)),
+                    last_needed: frame,
+                });
+        }
+        self.evict(streaming.max_loaded_tiles)
+    }
+
+    /// Unloads the least recently needed tiles until at most `max_tiles` are left, never unloading tiles needed in the current frame.
+    /// Returns the unloaded tiles.
+    fn evict(&mut self, max_tiles: usize) -> Vec<IVec2> {
+        let excess = self.tiles.len().saturating_sub(max_tiles);
+        if excess == 0 {
+            return Vec::new();
+        }
+        let mut candidates: Vec<(u64, IVec2)> = self
+            .tiles
+            .iter()
+            .filter(|(_, streamed)| streamed.last_needed != self.frame)
+            .map(|(tile, streamed)| (streamed.last_needed, *tile))
+            .collect();
+        // Sort by coordinates as well, so that the same tiles are evicted on every run.
+        candidates.sort_unstable_by_key(|(last_needed, tile)| (*last_needed, tile.y, tile.x));
+        candidates
+            .into_iter()
+            .take(excess)
+            .map(|(_, tile)| {
+                self.tiles.remove(&tile);
+                tile
+            })
+            .collect()
+    }
+}
+
+fn prefetch_navmesh_tiles(
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    settings: Res<NavmeshTileSettings>,
+    streaming: Res<NavmeshTileStreaming>,
+    mut streamed: ResMut<StreamedNavmeshTiles>,
+    mut instances: Query<
+        (
+            Entity,
+            &NavmeshTileSettings,
+            &NavmeshTileStreaming,
+            &mut StreamedNavmeshTiles,
+        ),
+        With<NavmeshInstance>,
+    >,
+    mut previous_positions: Local<HashMap<Entity, Vec3>>,
+    cameras: Query<(
+        Entity,
+        &GlobalTransform,
+        &NavmeshTilePrefetch,
+        Option<&NavmeshInstanceOf>,
+    )>,
+    mut removed: EventWriter<NavmeshTileRemoved>,
+) {
+    let delta = time.delta_secs();
+    previous_positions.retain(|entity, _| cameras.contains(*entity));
+    let mut needed: HashMap<Option<Entity>, HashSet<IVec2>> = HashMap::new();
+    for (entity, transform, prefetch, instance_of) in &cameras {
+        let instance = instance_of.map(|instance_of| instance_of.0);
+        let settings = match instance {
+            None => &*settings,
+            Some(instance) => match instances.get(instance) {
+                Ok((_, settings, ..)) => settings,
+                Err(_) => continue,
+            },
+        };
+        let position = transform.translation();
+        let velocity = match previous_positions.insert(entity, position) {
+            Some(previous) if delta > 0.0 => (position - previous) / delta,
+            _ => Vec3::ZERO,
+        };
+        needed
+            .entry(instance)
+            .or_default()
+            .extend(prefetch.tiles_in_view(
+                settings,
+                position,
+                transform.forward().into(),
+                velocity,
+            ));
+    }
+
+    let evicted = streamed.stream(
+        needed.remove(&None).unwrap_or_default(),
+        &streaming,
+        &asset_server,
+    );
+    removed.write_batch(evicted.into_iter().map(|tile| NavmeshTileRemoved {
+        instance: None,
+        tile,
+    }));
+    for (entity, _, streaming, mut streamed) in &mut instances {
+        let evicted = streamed.stream(
+            needed.remove(&Some(entity)).unwrap_or_default(),
+            streaming,
+            &asset_server,
+        );
+        removed.write_batch(evicted.into_iter().map(|tile| NavmeshTileRemoved {
+            instance: Some(entity),
+            tile,
+        }));
+    }
+}
+
+fn announce_built_tiles(
+    mut asset_events: EventReader<AssetEvent<Navmesh>>,
+    streamed: Res<StreamedNavmeshTiles>,
+    instances: Query<(Entity, &StreamedNavmeshTiles), With<NavmeshInstance>>,
+    mut built: EventWriter<NavmeshTileBuilt>,
+) {
+    for event in asset_events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = *event
+        else {
+            continue;
+        };
+        let streamed = core::iter::once((None, &*streamed)).chain(
+            instances
+                .iter()
+                .map(|(entity, streamed)| (Some(entity), streamed)),
+        );
+        for (instance, streamed) in streamed {
+            if let Some(tile) = streamed.tile_of(id) {
+                built.write(NavmeshTileBuilt {
+                    instance,
+                    tile,
+                    navmesh: id,
+                });
+            }
+        }
+    }
+}
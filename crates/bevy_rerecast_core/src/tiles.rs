@@ -0,0 +1,222 @@
+//! Utilities for tracking which navmesh tiles need to be rebuilt because the geometry affecting them changed.
+//!
+//! Every change is also announced through the [`NavmeshObstacleApplied`] and [`NavmeshInvalidated`] events,
+//! so that systems such as audio, minimaps or AI can react to it without polling [`DirtyNavmeshTiles`].
+
+use std::collections::{HashMap, HashSet};
+
+use bevy_app::prelude::*;
+use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_transform::{TransformSystem, prelude::*};
+use glam::{IVec2, Vec3, Vec3A};
+use rerecast::Aabb3d;
+
+use crate::instance::{NavmeshInstance, NavmeshInstanceOf};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<NavmeshTileSettings>();
+    app.init_resource::<DirtyNavmeshTiles>();
+    app.init_resource::<AffectorBounds>();
+    app.add_event::<NavmeshObstacleApplied>();
+    app.add_event::<NavmeshInvalidated>();
+    app.add_systems(
+        PostUpdate,
+        mark_dirty_tiles.after(TransformSystem::TransformPropagate),
+    );
+}
+
+/// Marks an entity as contributing geometry to the navmesh.
+///
+/// Whenever the [`GlobalTransform`] of the entity or this component changes, all navmesh tiles
+/// the affector overlapped before and after the change are queued in [`DirtyNavmeshTiles`].
+/// The same happens when the component is removed.
+///
+/// The tiles are queued in the [`DirtyNavmeshTiles`] of the [`NavmeshInstance`] given by [`NavmeshInstanceOf`],
+/// or in the [`DirtyNavmeshTiles`] resource if the affector does not belong to an instance.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct NavmeshAffector {
+    /// The bounds of the affector's geometry in local space.
+    pub aabb: Aabb3d,
+}
+
+/// Settings describing how the world is divided into navmesh tiles on the xz-plane.
+///
+/// Used as a resource for the global navmesh and as a component for each [`NavmeshInstance`].
+#[derive(Resource, Component, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct NavmeshTileSettings {
+    /// The world space position of the minimum corner of tile `(0, 0)`.
+    pub origin: Vec3,
+    /// The width and depth of a single tile. `[Limit: > 0] [Units: wu]`
+    pub tile_size: f32,
+}
+
+impl Default for NavmeshTileSettings {
+    fn default() -> Self {
+        Self {
+            origin: Vec3::ZERO,
+            // 48 voxels per tile at the default cell size
+            tile_size: 48.0 * 0.3,
+        }
+    }
+}
+
+impl NavmeshTileSettings {
+    /// Returns the coordinates of the tile containing the given world space position.
+    #[inline]
+    pub fn tile_at(&self, position: Vec3) -> IVec2 {
+        let tile = (position - self.origin) / self.tile_size;
+        IVec2::new(tile.x.floor() as i32, tile.z.floor() as i32)
+    }
+
+    /// Returns the world space bounds of the given tile, spanning `min_y..=max_y` vertically.
+    ///
+    /// The bounds are grown by `border` on the xz-plane so that geometry just outside the tile,
+    /// which still influences the tile's edges, is included as well. `[Units: wu]`
+    pub fn tile_aabb(&self, tile: IVec2, border: f32, min_y: f32, max_y: f32) -> Aabb3d {
+        let min = self.origin + Vec3::new(tile.x as f32, 0.0, tile.y as f32) * self.tile_size;
+        let max = min + Vec3::new(self.tile_size, 0.0, self.tile_size);
+        Aabb3d {
+            min: Vec3::new(min.x - border, min_y, min.z - border),
+            max: Vec3::new(max.x + border, max_y, max.z + border),
+        }
+    }
+
+    /// Iterates over the coordinates of all tiles overlapping the given world space AABB.
+    pub fn tiles_overlapping(&self, aabb: &Aabb3d) -> impl Iterator<Item = IVec2> + use<> {
+        let min = self.tile_at(aabb.min);
+        let max = self.tile_at(aabb.max);
+        (min.y..=max.y).flat_map(move |z| (min.x..=max.x).map(move |x| IVec2::new(x, z)))
+    }
+}
+
+/// The coordinates of all tiles that need to be rebuilt because at least one of their [`NavmeshAffector`]s changed.
+///
+/// All changes happening in the same frame are batched into this set, so every tile is only queued once.
+/// The set is drained by [`crate::tile_rebuild`] once [`NavmeshTileRebuild::config`](crate::tile_rebuild::NavmeshTileRebuild::config) is set,
+/// otherwise whoever rebuilds the tiles is expected to drain it.
+///
+/// Used as a resource for the global navmesh and as a component for each [`NavmeshInstance`].
+#[derive(Resource, Component, Debug, Default, Clone, PartialEq, Eq, Deref, DerefMut)]
+pub struct DirtyNavmeshTiles(HashSet<IVec2>);
+
+/// Sent when a [`NavmeshAffector`] was added, moved, changed or removed and the tiles it overlapped
+/// before and after the change were queued in [`DirtyNavmeshTiles`].
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NavmeshObstacleApplied {
+    /// The entity of the affector. May no longer exist if the affector was despawned.
+    pub affector: Entity,
+    /// The [`NavmeshInstance`] the affector belongs to, or `None` for the global navmesh.
+    pub instance: Option<Entity>,
+}
+
+/// Sent for every world space region of a navmesh that is out of date because of a changed [`NavmeshAffector`].
+///
+/// Paths and other data derived from the navmesh within [`Self::aabb`] should be considered stale
+/// until the [`DirtyNavmeshTiles`] overlapping it are rebuilt.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct NavmeshInvalidated {
+    /// The [`NavmeshInstance`] whose navmesh is out of date, or `None` for the global navmesh.
+    pub instance: Option<Entity>,
+    /// The world space bounds of the region that is out of date.
+    pub aabb: Aabb3d,
+}
+
+/// The instance and world space bounds of each affector when it was last seen,
+/// so that the tiles an affector moved away from are rebuilt as well.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub(crate) struct AffectorBounds(HashMap<Entity, (Option<Entity>, Aabb3d)>);
+
+pub(crate) fn mark_dirty_tiles(
+    settings: Res<NavmeshTileSettings>,
+    mut dirty_tiles: ResMut<DirtyNavmeshTiles>,
+    mut instances: Query<(&NavmeshTileSettings, &mut DirtyNavmeshTiles), With<NavmeshInstance>>,
+    mut affector_bounds: ResMut<AffectorBounds>,
+    changed_affectors: Query<
+        Entity,
+        (
+            With<NavmeshAffector>,
+            Or<(
+                Changed<GlobalTransform>,
+                Changed<NavmeshAffector>,
+                Changed<NavmeshInstanceOf>,
+            )>,
+        ),
+    >,
+    affectors: Query<(
+        &GlobalTransform,
+        &NavmeshAffector,
+        Option<&NavmeshInstanceOf>,
+    )>,
+    mut removed_affectors: RemovedComponents<NavmeshAffector>,
+    mut removed_instance_of: RemovedComponents<NavmeshInstanceOf>,
+    mut applied: EventWriter<NavmeshObstacleApplied>,
+    mut invalidated: EventWriter<NavmeshInvalidated>,
+) {
+    let mut mark = |instance: Option<Entity>, bounds: &Aabb3d| {
+        match instance {
+            None => dirty_tiles.extend(settings.tiles_overlapping(bounds)),
+            Some(instance) => {
+                let Ok((settings, mut dirty_tiles)) = instances.get_mut(instance) else {
+                    return;
+                };
+                dirty_tiles.extend(settings.tiles_overlapping(bounds));
+            }
+        }
+        invalidated.write(NavmeshInvalidated {
+            instance,
+            aabb: *bounds,
+        });
+    };
+    for entity in removed_affectors.read() {
+        if let Some((instance, bounds)) = affector_bounds.remove(&entity) {
+            mark(instance, &bounds);
+            applied.write(NavmeshObstacleApplied {
+                affector: entity,
+                instance,
+            });
+        }
+    }
+    // Affectors that left their instance move back to the global navmesh.
+    let changed: HashSet<Entity> = changed_affectors
+        .iter()
+        .chain(removed_instance_of.read())
+        .collect();
+    for entity in changed {
+        let Ok((transform, affector, instance_of)) = affectors.get(entity) else {
+            continue;
+        };
+        let instance = instance_of.map(|instance_of| instance_of.0);
+        let bounds = transform_aabb(&affector.aabb, transform);
+        if let Some((previous_instance, previous_bounds)) =
+            affector_bounds.insert(entity, (instance, bounds))
+        {
+            if (previous_instance, previous_bounds) == (instance, bounds) {
+                continue;
+            }
+            mark(previous_instance, &previous_bounds);
+        }
+        mark(instance, &bounds);
+        applied.write(NavmeshObstacleApplied {
+            affector: entity,
+            instance,
+        });
+    }
+}
+
+/// Computes the world space AABB enclosing the local space `aabb` after applying `transform`.
+fn transform_aabb(aabb: &Aabb3d, transform: &GlobalTransform) -> Aabb3d {
+    let affine = transform.affine();
+    let min = Vec3A::from(aabb.min);
+    let max = Vec3A::from(aabb.max);
+    let center = affine.transform_point3a((min + max) * 0.5);
+    let half_size = (max - min) * 0.5;
+    let half_size = affine.matrix3.x_axis.abs() * half_size.x
+        + affine.matrix3.y_axis.abs() * half_size.y
+        + affine.matrix3.z_axis.abs() * half_size.z;
+    Aabb3d {
+        min: (center - half_size).into(),
+        max: (center + half_size).into(),
+    }
+}
@@ -60,7 +60,10 @@ fn fetch_navmesh_input(
         gizmo.clear();
     }
 
-    for affector in response.affector_meshes {
+    for mut affector in response.affector_meshes {
+        // Bake the transform into the mesh, as decomposing it into a `Transform` would lose
+        // the shear produced by non-uniformly scaled hierarchies.
+        affector.mesh.transform(&affector.transform.affine());
         let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::all())
             .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, affector.mesh.vertices)
             .with_inserted_indices(Indices::U32(
@@ -73,7 +76,7 @@ fn fetch_navmesh_input(
             ));
 
         commands.spawn((
-            affector.transform.compute_transform(),
+            Transform::IDENTITY,
             Mesh3d(meshes.add(mesh)),
             NavmeshAffector,
             Visibility::Hidden,
@@ -7,11 +7,18 @@ use crate::{
         palette::BEVY_GRAY,
         widget::{button, checkbox},
     },
-    visualization::{AvailableGizmos, GizmosToDraw},
+    visualization::{AreaNames, AvailableGizmos, GizmosToDraw, Navmesh, area_color},
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(Startup, spawn_ui);
+    app.add_systems(
+        Update,
+        update_area_legend.run_if(
+            resource_exists::<Navmesh>
+                .and(resource_changed::<Navmesh>.or(resource_changed::<AreaNames>)),
+        ),
+    );
     app.add_observer(close_modal);
 }
 
@@ -63,6 +70,16 @@ fn spawn_ui(mut commands: Commands) {
                     checkbox(
                         "Show Detail Mesh",
                         toggle_gizmo(AvailableGizmos::DetailMesh)
+                    ),
+                    (
+                        Name::new("Area Legend"),
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Px(5.0),
+                            margin: UiRect::top(Px(20.0)),
+                            ..default()
+                        },
+                        AreaLegend,
                     )
                 ],
                 BackgroundColor(BEVY_GRAY.with_alpha(0.6)),
@@ -88,6 +105,46 @@ fn spawn_ui(mut commands: Commands) {
 #[derive(Component)]
 struct LoadSceneModal;
 
+/// Lists the areas of the polygon mesh by their names in [`AreaNames`], next to the colors they are drawn in.
+#[derive(Component)]
+struct AreaLegend;
+
+fn update_area_legend(
+    legend: Single<Entity, With<AreaLegend>>,
+    navmesh: Res<Navmesh>,
+    names: Res<AreaNames>,
+    mut commands: Commands,
+) {
+    let mut areas = navmesh.poly_mesh.areas.clone();
+    areas.sort_unstable();
+    areas.dedup();
+    let mut legend = commands.entity(*legend);
+    legend.despawn_related::<Children>();
+    legend.with_children(|legend| {
+        for area in areas {
+            legend.spawn((
+                Name::new("Area Legend Entry"),
+                Node {
+                    column_gap: Px(8.0),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                children![
+                    (
+                        Node {
+                            width: Px(14.0),
+                            height: Px(14.0),
+                            ..default()
+                        },
+                        BackgroundColor(area_color(area).into()),
+                    ),
+                    status_bar_text(names.display(area).to_string()),
+                ],
+            ));
+        }
+    });
+}
+
 fn build_navmesh(_: Trigger<Pointer<Click>>, mut commands: Commands) {
     commands.trigger(BuildNavmesh);
 }
@@ -8,7 +8,7 @@ use bevy::{
 };
 use bevy_rerecast::{
     TriMeshFromBevyMesh as _,
-    rerecast::{DetailNavmesh, PolygonNavmesh, TriMesh},
+    rerecast::{AreaRegistry, AreaType, DetailNavmesh, PolygonNavmesh, TriMesh},
 };
 
 use crate::build::NavmeshAffector;
@@ -16,6 +16,7 @@ use crate::build::NavmeshAffector;
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(Startup, spawn_gizmos);
     app.init_resource::<GizmosToDraw>();
+    app.init_resource::<AreaNames>();
     app.add_systems(
         Update,
         (
@@ -45,6 +46,27 @@ pub(crate) struct Navmesh {
     pub(crate) detail_mesh: DetailNavmesh,
 }
 
+/// The names shown for area types in the legend of the polygon mesh.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub(crate) struct AreaNames(AreaRegistry);
+
+/// The color the outlines of polygons with the given area are drawn in.
+pub(crate) fn area_color(area: AreaType) -> Srgba {
+    const PALETTE: [Srgba; 6] = [
+        tailwind::TEAL_500,
+        tailwind::VIOLET_500,
+        tailwind::ROSE_500,
+        tailwind::LIME_500,
+        tailwind::ORANGE_500,
+        tailwind::FUCHSIA_500,
+    ];
+    if area == AreaType::DEFAULT_WALKABLE {
+        tailwind::SKY_700
+    } else {
+        PALETTE[area.id() as usize % PALETTE.len()]
+    }
+}
+
 #[derive(Resource, Deref, DerefMut)]
 pub(crate) struct GizmosToDraw(HashSet<AvailableGizmos>);
 
@@ -163,7 +185,13 @@ fn draw_poly_mesh(
         // Connect back to first vertex to finish the polygon
         verts.push(verts[0]);
 
-        gizmo.linestrip(verts, tailwind::SKY_700);
+        // Highlight polygons marked by flag volumes, and color the others by their area
+        let color = if mesh.flags[i] == 0 {
+            area_color(mesh.areas[i])
+        } else {
+            tailwind::AMBER_500
+        };
+        gizmo.linestrip(verts, color);
     }
 
     let mut visual_mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::all());
@@ -8,7 +8,7 @@ use bevy_pbr::{MeshMaterial3d, StandardMaterial};
 use bevy_platform::collections::HashMap;
 use bevy_remote::{BrpError, BrpResult, RemoteMethodSystemId, RemoteMethods};
 use bevy_render::prelude::*;
-use bevy_rerecast_core::NavmeshAffectorBackend;
+use bevy_rerecast_core::{NavmeshAffectorBackend, NavmeshAffectorFilter};
 use bevy_transform::prelude::*;
 use rerecast::TriMesh;
 use serde::{Deserialize, Serialize};
@@ -51,7 +51,7 @@ fn get_navmesh_input(In(params): In<Option<Value>>, world: &mut World) -> BrpRes
             data: None,
         });
     };
-    let affectors = match world.run_system(*backend_id) {
+    let affectors = match world.run_system_with(*backend_id, NavmeshAffectorFilter::default()) {
         Ok(result) => result,
         Err(err) => {
             return Err(BrpError {
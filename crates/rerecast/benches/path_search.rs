@@ -0,0 +1,110 @@
+//! Benchmarks the open list of path searches.
+//!
+//! The open list is selected at compile time, so compare the binary heap and the bucket queue by running
+//! `cargo bench --bench path_search --features test_utils` with and without the `bucket_queue` feature.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use rerecast::{
+    AreaType, BvTree, NavmeshQuery, NavmeshQueryBuffers, NearestPolygon, QueryFilter,
+    test_utils::GridNavmesh,
+};
+
+/// The number of cells along each side of the benchmark maps.
+const SIZE: usize = 96;
+/// How long each benchmark runs.
+const DURATION: Duration = Duration::from_secs(3);
+
+fn main() {
+    let open_list = if cfg!(feature = "bucket_queue") {
+        "bucket queue"
+    } else {
+        "binary heap"
+    };
+    println!("open list: {open_list}");
+    bench("open field", &open_field());
+    bench("walls with gaps", &walls_with_gaps());
+    bench("mixed area costs", &mixed_areas());
+}
+
+/// Searches the path from `a` to `b` on `map` repeatedly and prints the average time per search.
+fn bench(name: &str, map: &str) {
+    let grid = GridNavmesh::parse(map);
+    let tree = BvTree::new(&grid.navmesh);
+    let mut query = NavmeshQuery::new(&grid.navmesh, &tree);
+    query.max_nodes = SIZE * SIZE;
+    let nearest = |marker: char| NearestPolygon {
+        polygon: grid.polygon(marker),
+        point: grid.position(marker),
+    };
+    let (start, end) = (nearest('a'), nearest('b'));
+    let mut filter = QueryFilter::default();
+    filter.set_area_cost(AreaType::new(2), 4.0);
+    let mut buffers = NavmeshQueryBuffers::default();
+    let mut polygons = vec![0; SIZE * SIZE];
+
+    let path = query.find_path_into(start, end, &filter, &mut buffers, &mut polygons);
+    assert!(path.complete, "{name}: the benchmark map has no path");
+
+    let started = Instant::now();
+    let mut searches = 0_u32;
+    while started.elapsed() < DURATION {
+        black_box(query.find_path_into(
+            black_box(start),
+            black_box(end),
+            &filter,
+            &mut buffers,
+            &mut polygons,
+        ));
+        searches += 1;
+    }
+    let average = started.elapsed() / searches;
+    println!(
+        "{name}: {average:?} per search ({searches} searches, {} polygons on the path)",
+        path.count
+    );
+}
+
+/// A map without obstacles, from one corner to the opposite one.
+fn open_field() -> String {
+    map(|_, _| '.')
+}
+
+/// A map of vertical walls with alternating gaps at the top and bottom, forcing the path to zigzag.
+fn walls_with_gaps() -> String {
+    map(|x, z| {
+        if x % 6 != 3 {
+            '.'
+        } else if (x / 6) % 2 == 0 {
+            if z + 1 == SIZE { '.' } else { '#' }
+        } else if z == 0 {
+            '.'
+        } else {
+            '#'
+        }
+    })
+}
+
+/// A map with patches of an expensive area, so the costs of the search spread further.
+fn mixed_areas() -> String {
+    map(|x, z| if (x / 8 + z / 8) % 3 == 0 { '2' } else { '.' })
+}
+
+/// Builds a `SIZE`x`SIZE` map from `cell`, with the markers `a` and `b` in opposite corners.
+fn map(cell: impl Fn(usize, usize) -> char) -> String {
+    let mut map = String::new();
+    for z in 0..SIZE {
+        for x in 0..SIZE {
+            map.push(match (x, z) {
+                (0, 0) => 'a',
+                (x, z) if x + 1 == SIZE && z + 1 == SIZE => 'b',
+                (x, z) => cell(x, z),
+            });
+        }
+        map.push('\n');
+    }
+    map
+}
@@ -0,0 +1,187 @@
+use glam::{Vec2, Vec3, Vec3Swizzles as _};
+
+use crate::{Aabb3d, BvTree, LayerConstraint, NearestPolygon, PolygonNavmesh, math::next};
+
+/// Settings for [`PolygonNavmesh::place_agent`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct AgentPlacement {
+    /// The half extents of the box around the desired position in which the navmesh is searched. `[Limit: >= 0] [Units: wu]`
+    pub search_extents: Vec3,
+    /// How far the position may move up or down while it is pushed away from the boundary,
+    /// so that agents are not pushed onto the floors above or below. `[Limit: >= 0] [Units: wu]`
+    pub max_climb: f32,
+    /// The maximum number of times the position is pushed away from the boundary edges overlapping the agent. `[Limit: > 0]`
+    pub max_iterations: usize,
+}
+
+impl Default for AgentPlacement {
+    fn default() -> Self {
+        Self {
+            search_extents: Vec3::new(2.0, 4.0, 2.0),
+            max_climb: 0.5,
+            max_iterations: 8,
+        }
+    }
+}
+
+impl PolygonNavmesh {
+    /// How far an agent may still overlap the boundary for its placement to count as valid. `[Units: wu]`
+    const PLACEMENT_TOLERANCE: f32 = 1.0e-4;
+
+    /// Finds the valid position closest to `desired` for an agent of the given `radius`, e.g. to spawn or teleport it. `[Limit: >= 0] [Units: wu]`
+    ///
+    /// Unlike [`Self::find_nearest_polygon`], the position keeps at least `radius` of horizontal distance to the boundary edges
+    /// of the navmesh, so the agent does not start out overlapping a wall. Starting at the point on the navmesh nearest to `desired`,
+    /// the position is repeatedly pushed out of all boundary edges it overlaps and projected back onto the navmesh.
+    ///
+    /// Returns `None` if no navmesh lies within [`AgentPlacement::search_extents`], or if the agent still overlaps the boundary
+    /// after [`AgentPlacement::max_iterations`], e.g. because the navmesh around `desired` is narrower than the agent.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn place_agent(
+        &self,
+        tree: &BvTree,
+        desired: Vec3,
+        radius: f32,
+        settings: &AgentPlacement,
+    ) -> Option<NearestPolygon> {
+        let mut placement = self.find_nearest_polygon(
+            tree,
+            desired,
+            settings.search_extents,
+            LayerConstraint::Any,
+        )?;
+        let mut edges = Vec::new();
+        for _ in 0..settings.max_iterations {
+            self.boundary_edges_near(
+                tree,
+                placement.point,
+                radius,
+                settings.max_climb,
+                &mut edges,
+            );
+            let mut push = Vec2::ZERO;
+            let mut overlaps = false;
+            for (a, b, inward) in &edges {
+                let point = placement.point.xz();
+                let direction = b - a;
+                let t = (point - a).dot(direction) / direction.length_squared().max(f32::EPSILON);
+                let offset = point - a.lerp(*b, t.clamp(0.0, 1.0));
+                let distance = offset.length();
+                if distance < radius - Self::PLACEMENT_TOLERANCE {
+                    // The position always lies on the navmesh, so positions on the edge itself are pushed inwards.
+                    push += offset.try_normalize().unwrap_or(*inward) * (radius - distance);
+                    overlaps = true;
+                }
+            }
+            if !overlaps {
+                return Some(placement);
+            }
+            let pushed = placement.point + Vec3::new(push.x, 0.0, push.y);
+            placement = self.find_nearest_polygon(
+                tree,
+                pushed,
+                Vec3::new(radius, settings.max_climb, radius),
+                LayerConstraint::SameFloor {
+                    max_climb: settings.max_climb,
+                },
+            )?;
+        }
+        None
+    }
+
+    /// Collects the boundary edges of the polygons within `radius` of `point` into `edges`,
+    /// together with their unit normal pointing into the polygon, on the xz-plane.
+    /// Collinear edges sharing a vertex are merged into one.
+    fn boundary_edges_near(
+        &self,
+        tree: &BvTree,
+        point: Vec3,
+        radius: f32,
+        max_climb: f32,
+        edges: &mut Vec<(Vec2, Vec2, Vec2)>,
+    ) {
+        edges.clear();
+        let extent = Vec3::new(radius, max_climb, radius);
+        let search_aabb = Aabb3d {
+            min: point - extent,
+            max: point + extent,
+        };
+        for polygon in tree.query_aabb(&search_aabb) {
+            let vertices = self.polygon_vertices(polygon);
+            let centroid = self.polygon_world_vertices(polygon).sum::<Vec3>().xz()
+                / vertices.len().max(1) as f32;
+            for edge in 0..vertices.len() {
+                if self.internal_neighbor(polygon, edge).is_some() {
+                    continue;
+                }
+                let a = self.world_vertex(vertices[edge]).xz();
+                let b = self.world_vertex(vertices[next(edge, vertices.len())]).xz();
+                let mut inward = (b - a).perp().normalize_or_zero();
+                if inward.dot(centroid - a) < 0.0 {
+                    inward = -inward;
+                }
+                edges.push((a, b, inward));
+            }
+        }
+
+        // Merge collinear edges meeting at a vertex, e.g. of neighboring grid cells,
+        // so that agents overlapping both are pushed once, and not sideways by the vertex between them.
+        'merge: loop {
+            for i in 0..edges.len() {
+                let (_, end, inward) = edges[i];
+                let continuation = edges
+                    .iter()
+                    .position(|(start, _, other)| *start == end && *other == inward);
+                if let Some(j) = continuation {
+                    edges[i].1 = edges[j].1;
+                    edges.swap_remove(j);
+                    continue 'merge;
+                }
+            }
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    #[test]
+    fn pushes_agents_away_from_boundary() {
+        let grid = GridNavmesh::parse(
+            "
+            ....
+            ....
+            ....
+            #..#
+            ",
+        );
+        let tree = BvTree::new(&grid.navmesh);
+        let settings = AgentPlacement::default();
+        let place = |x: f32, z: f32, radius: f32| {
+            grid.navmesh
+                .place_agent(&tree, Vec3::new(x, 0.0, z), radius, &settings)
+                .map(|placement| placement.point)
+        };
+        let assert_near = |actual: Option<Vec3>, expected: Vec3| {
+            let actual = actual.unwrap();
+            assert!(actual.distance(expected) < 1.0e-3, "{actual} != {expected}");
+        };
+
+        // Far enough from the boundary already.
+        assert_near(place(2.0, 1.5, 0.4), Vec3::new(2.0, 0.0, 1.5));
+        // Pushed out of a corner.
+        assert_near(place(0.1, 0.1, 0.4), Vec3::new(0.4, 0.0, 0.4));
+        // Snapped onto the navmesh and away from its edge.
+        assert_near(place(-1.0, 1.5, 0.4), Vec3::new(0.4, 0.0, 1.5));
+        // The corridor at the bottom is 2 units wide.
+        assert_near(place(2.0, 3.9, 0.6), Vec3::new(2.0, 0.0, 3.4));
+        // Pushed out of the corridor's wall first, then away from its end.
+        assert_near(place(1.0, 3.5, 0.9), Vec3::new(1.9, 0.0, 3.1));
+        assert_eq!(place(2.0, 1.5, 2.0), None);
+        assert_eq!(place(20.0, 1.5, 0.4), None);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{PolygonNavmesh, QueryFilter};
+
+impl PolygonNavmesh {
+    /// Returns the total area of all polygons passing `filter` on the xz-plane, e.g. to compare it with
+    /// [`Self::reachable_area_from`] or with the area a faction controls. `[Units: wu²]`
+    pub fn walkable_area_total(&self, filter: &QueryFilter) -> f32 {
+        (0..self.polygon_count())
+            .filter(|polygon| filter.passes(self, *polygon))
+            .map(|polygon| self.polygon_area(polygon))
+            .sum()
+    }
+
+    /// Returns the total area on the xz-plane of all polygons an agent starting on the polygon at index `start_polygon`
+    /// can reach through polygons passing `filter`, including the start polygon itself. `[Units: wu²]`
+    ///
+    /// Returns 0 if the start polygon does not pass `filter`. See [`Self::reachable_polygons_from`].
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn reachable_area_from(&self, start_polygon: usize, filter: &QueryFilter) -> f32 {
+        self.reachable_polygons_from(start_polygon, filter)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, reachable)| *reachable)
+            .map(|(polygon, _)| self.polygon_area(polygon))
+            .sum()
+    }
+
+    /// Floods the polygons passing `filter` from the polygon at index `start_polygon` and returns whether each polygon was reached,
+    /// indexed by polygon.
+    ///
+    /// Only edges shared by polygons are followed, like in [`Self::islands`], so off-mesh connections and portals
+    /// to other tiles are not taken. No polygon is reached if the start polygon does not pass `filter`.
+    pub fn reachable_polygons_from(&self, start_polygon: usize, filter: &QueryFilter) -> Vec<bool> {
+        let mut reachable = vec![false; self.polygon_count()];
+        if !filter.passes(self, start_polygon) {
+            return reachable;
+        }
+        reachable[start_polygon] = true;
+        let mut stack = vec![start_polygon];
+        while let Some(polygon) = stack.pop() {
+            for edge in 0..self.polygon_vertices(polygon).len() {
+                let Some(neighbor) = self.internal_neighbor(polygon, edge) else {
+                    continue;
+                };
+                if !reachable[neighbor] && filter.passes(self, neighbor) {
+                    reachable[neighbor] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AreaType, test_utils::GridNavmesh};
+
+    #[test]
+    fn measures_walkable_and_reachable_area() {
+        let mut grid = GridNavmesh::parse(
+            "
+            a..1..b
+            ...1...
+            ###1###
+            c......
+            ",
+        );
+        let mut filter = QueryFilter::default();
+        assert_eq!(grid.navmesh.walkable_area_total(&filter), 22.0);
+        assert_eq!(
+            grid.navmesh.reachable_area_from(grid.polygon('a'), &filter),
+            22.0
+        );
+
+        // The river splits the map, and the unit can not cross it.
+        filter.set_area_cost(AreaType::new(1), f32::INFINITY);
+        assert_eq!(grid.navmesh.walkable_area_total(&filter), 19.0);
+        assert_eq!(
+            grid.navmesh.reachable_area_from(grid.polygon('a'), &filter),
+            6.0
+        );
+        assert_eq!(
+            grid.navmesh.reachable_area_from(grid.polygon('b'), &filter),
+            6.0
+        );
+        assert_eq!(
+            grid.navmesh.reachable_area_from(grid.polygon('c'), &filter),
+            7.0
+        );
+        let reachable = grid
+            .navmesh
+            .reachable_polygons_from(grid.polygon('a'), &filter);
+        assert!(reachable[grid.polygon('a') + 1]);
+        assert!(!reachable[grid.polygon('b')]);
+
+        let c = grid.polygon('c');
+        filter.exclude_flags = 1;
+        grid.navmesh.flags[c] = 1;
+        assert_eq!(grid.navmesh.reachable_area_from(c, &filter), 0.0);
+    }
+}
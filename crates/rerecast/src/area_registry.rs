@@ -0,0 +1,123 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::AreaType;
+
+/// Human-readable names for [`AreaType`]s, so that logs, errors and debug output can say `'water'` instead of `area 3`.
+///
+/// [`AreaType::NOT_WALKABLE`] and [`AreaType::DEFAULT_WALKABLE`] are registered as `"not walkable"` and `"walkable"` by default.
+///
+/// ```rust
+/// # use rerecast::{AreaRegistry, AreaType};
+/// const WATER: AreaType = AreaType::new(3);
+/// let mut registry = AreaRegistry::default();
+/// registry.register(WATER, "water");
+/// assert_eq!(registry.display(WATER).to_string(), "'water'");
+/// assert_eq!(registry.display(AreaType::new(4)).to_string(), "area 4");
+/// assert_eq!(registry.area_by_name("walkable"), Some(AreaType::DEFAULT_WALKABLE));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct AreaRegistry {
+    names: HashMap<AreaType, String>,
+}
+
+impl Default for AreaRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(AreaType::NOT_WALKABLE, "not walkable");
+        registry.register(AreaType::DEFAULT_WALKABLE, "walkable");
+        registry
+    }
+}
+
+impl AreaRegistry {
+    /// Creates a registry without any names, not even for the built-in area types.
+    pub fn empty() -> Self {
+        Self {
+            names: HashMap::new(),
+        }
+    }
+
+    /// Names the area type `area`, replacing its previous name. Returns the previous name, if any.
+    pub fn register(&mut self, area: AreaType, name: impl Into<String>) -> Option<String> {
+        self.names.insert(area, name.into())
+    }
+
+    /// Removes the name of `area`. Returns the removed name, if any.
+    pub fn unregister(&mut self, area: AreaType) -> Option<String> {
+        self.names.remove(&area)
+    }
+
+    /// Returns the name of `area`, or `None` if it has no name.
+    #[inline]
+    pub fn name(&self, area: AreaType) -> Option<&str> {
+        self.names.get(&area).map(String::as_str)
+    }
+
+    /// Returns the area type with the given name, or `None` if no area type has this name.
+    pub fn area_by_name(&self, name: &str) -> Option<AreaType> {
+        self.names
+            .iter()
+            .filter(|(_, area_name)| *area_name == name)
+            .map(|(area, _)| *area)
+            .min()
+    }
+
+    /// Returns a [`Display`](fmt::Display) for `area`, which prints the quoted name if it has one and the id otherwise,
+    /// e.g. `'water'` or `area 3`.
+    #[inline]
+    pub fn display(&self, area: AreaType) -> AreaName<'_> {
+        AreaName {
+            area,
+            name: self.name(area),
+        }
+    }
+}
+
+/// Displays an [`AreaType`] by its name, see [`AreaRegistry::display`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AreaName<'a> {
+    area: AreaType,
+    name: Option<&'a str>,
+}
+
+impl fmt::Display for AreaName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "'{name}'"),
+            None => write!(f, "area {}", self.area.id()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_areas() {
+        let water = AreaType::new(3);
+        let mut registry = AreaRegistry::default();
+        assert_eq!(registry.name(AreaType::NOT_WALKABLE), Some("not walkable"));
+        assert_eq!(registry.register(water, "water"), None);
+        assert_eq!(
+            registry.register(water, "deep water"),
+            Some("water".to_string())
+        );
+        assert_eq!(registry.area_by_name("deep water"), Some(water));
+        assert_eq!(registry.area_by_name("water"), None);
+        assert_eq!(
+            format!(
+                "{} overwritten by {}",
+                registry.display(water),
+                registry.display(AreaType::DEFAULT_WALKABLE)
+            ),
+            "'deep water' overwritten by 'walkable'"
+        );
+
+        assert_eq!(registry.unregister(water), Some("deep water".to_string()));
+        assert_eq!(registry.display(water).to_string(), "area 3");
+        assert_eq!(AreaRegistry::empty().name(AreaType::DEFAULT_WALKABLE), None);
+    }
+}
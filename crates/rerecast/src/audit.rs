@@ -0,0 +1,416 @@
+//! Statistics and integrity checks for baked navmeshes, e.g. to reject bad bakes in CI.
+
+use std::{collections::HashMap, fmt};
+
+use glam::{Vec2, Vec3, Vec3Swizzles as _};
+
+use crate::{
+    Aabb3d, AreaRegistry, AreaType, OffMeshConnection, PolygonNavmesh,
+    bv_tree::BvTree,
+    math::{distance_squared_between_point_and_line_vec2, next, point_in_poly},
+};
+
+/// The report produced by [`PolygonNavmesh::audit`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NavmeshAudit {
+    /// General statistics about the navmesh.
+    pub statistics: NavmeshStatistics,
+    /// The area of the polygons of each area type on the xz-plane, ordered by area type. `[Units: wu²]`
+    pub area_by_type: Vec<(AreaType, f32)>,
+    /// Edges without a neighbor that share both vertices with an edge of another polygon,
+    /// given as `(polygon, edge)` pairs. Agents cannot walk over these edges even though they should.
+    pub unlinked_edges: Vec<(usize, usize)>,
+    /// Edges whose neighbor does not link back to them, or whose neighbor index is out of range,
+    /// given as `(polygon, edge)` pairs.
+    pub asymmetric_links: Vec<(usize, usize)>,
+    /// Polygons with fewer than 3 vertices, out of range vertex indices, or no area on the xz-plane.
+    pub degenerate_polygons: Vec<usize>,
+    /// Polygons using the same vertex more than once. Their edges are skipped by the link checks,
+    /// as a zero length edge shifts the neighbors of all following edges.
+    pub repeated_vertex_polygons: Vec<usize>,
+    /// Pairs of polygons that overlap on the xz-plane at roughly the same height.
+    pub overlapping_polygons: Vec<(usize, usize)>,
+    /// Indices of off-mesh connections whose start or end point is not attached to any polygon.
+    pub orphan_off_mesh_connections: Vec<usize>,
+}
+
+/// Statistics about a navmesh, part of a [`NavmeshAudit`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NavmeshStatistics {
+    /// The number of polygons.
+    pub polygon_count: usize,
+    /// The number of vertices.
+    pub vertex_count: usize,
+    /// The number of islands, see [`PolygonNavmesh::islands`].
+    pub island_count: usize,
+    /// The total area of all polygons on the xz-plane. `[Units: wu²]`
+    pub area: f32,
+}
+
+impl NavmeshAudit {
+    /// Returns whether no problems were found.
+    pub fn is_ok(&self) -> bool {
+        self.unlinked_edges.is_empty()
+            && self.asymmetric_links.is_empty()
+            && self.degenerate_polygons.is_empty()
+            && self.repeated_vertex_polygons.is_empty()
+            && self.overlapping_polygons.is_empty()
+            && self.orphan_off_mesh_connections.is_empty()
+    }
+}
+
+impl NavmeshAudit {
+    /// Returns a [`Display`](fmt::Display) for the report that prints area types by their names in `registry`.
+    pub fn display_with<'a>(&'a self, registry: &'a AreaRegistry) -> NavmeshAuditDisplay<'a> {
+        NavmeshAuditDisplay {
+            audit: self,
+            registry,
+        }
+    }
+}
+
+impl fmt::Display for NavmeshAudit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display_with(&AreaRegistry::empty()).fmt(f)
+    }
+}
+
+/// Displays a [`NavmeshAudit`] with named area types, see [`NavmeshAudit::display_with`].
+#[derive(Debug, Clone, Copy)]
+pub struct NavmeshAuditDisplay<'a> {
+    audit: &'a NavmeshAudit,
+    registry: &'a AreaRegistry,
+}
+
+impl fmt::Display for NavmeshAuditDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let audit = self.audit;
+        let statistics = &audit.statistics;
+        writeln!(
+            f,
+            "{} polygons, {} vertices, {} islands, {:.2} wu² walkable area",
+            statistics.polygon_count,
+            statistics.vertex_count,
+            statistics.island_count,
+            statistics.area
+        )?;
+        write!(f, "area by type:")?;
+        for (i, (area, size)) in audit.area_by_type.iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            write!(
+                f,
+                "{separator}{} {size:.2} wu²",
+                self.registry.display(*area)
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "unlinked edges: {:?}", audit.unlinked_edges)?;
+        writeln!(f, "asymmetric links: {:?}", audit.asymmetric_links)?;
+        writeln!(f, "degenerate polygons: {:?}", audit.degenerate_polygons)?;
+        writeln!(
+            f,
+            "polygons with repeated vertices: {:?}",
+            audit.repeated_vertex_polygons
+        )?;
+        writeln!(f, "overlapping polygons: {:?}", audit.overlapping_polygons)?;
+        write!(
+            f,
+            "orphan off-mesh connections: {:?}",
+            audit.orphan_off_mesh_connections
+        )
+    }
+}
+
+impl PolygonNavmesh {
+    /// Collects statistics about the navmesh and checks it for common integrity problems.
+    ///
+    /// `off_mesh_connections` are the connections that will be used together with this navmesh.
+    /// Use [`NavmeshAudit::is_ok`] to gate bad bakes, e.g. in CI.
+    ///
+    /// If any polygon references a vertex or neighbor that does not exist, the island count,
+    /// overlap and off-mesh connection checks are skipped, as they cannot be computed reliably.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn audit(&self, off_mesh_connections: &[OffMeshConnection]) -> NavmeshAudit {
+        let polygon_count = self.polygon_count();
+        let mut audit = NavmeshAudit {
+            statistics: NavmeshStatistics {
+                polygon_count,
+                vertex_count: self.vertices.len(),
+                island_count: 0,
+                area: 0.0,
+            },
+            ..Default::default()
+        };
+
+        // Degenerate polygons and area
+        let mut valid = Vec::with_capacity(polygon_count);
+        let mut repeats_vertex = Vec::with_capacity(polygon_count);
+        let mut has_dangling_indices = false;
+        for polygon in 0..polygon_count {
+            let vertices = self.polygon_vertices(polygon);
+            let dangling = vertices.iter().any(|i| *i as usize >= self.vertices.len());
+            has_dangling_indices |= dangling
+                || (0..vertices.len()).any(|edge| {
+                    self.internal_neighbor(polygon, edge)
+                        .is_some_and(|neighbor| neighbor >= polygon_count)
+                });
+            let repeated = vertices
+                .iter()
+                .enumerate()
+                .any(|(i, vertex)| vertices[i + 1..].contains(vertex));
+            repeats_vertex.push(repeated);
+            if repeated {
+                audit.repeated_vertex_polygons.push(polygon);
+            }
+            if vertices.len() < 3 || dangling {
+                valid.push(false);
+                audit.degenerate_polygons.push(polygon);
+                continue;
+            }
+            let area = self.polygon_area(polygon);
+            valid.push(area > f32::EPSILON);
+            if area <= f32::EPSILON {
+                audit.degenerate_polygons.push(polygon);
+            }
+            audit.statistics.area += area;
+            match audit
+                .area_by_type
+                .binary_search_by_key(&self.areas[polygon], |(area, _)| *area)
+            {
+                Ok(index) => audit.area_by_type[index].1 += area,
+                Err(index) => audit
+                    .area_by_type
+                    .insert(index, (self.areas[polygon], area)),
+            }
+        }
+
+        // Links
+        let nvp = self.max_vertices_per_polygon as usize;
+        let mut edges: HashMap<(u16, u16), Vec<(usize, usize)>> = HashMap::new();
+        for polygon in (0..polygon_count).filter(|polygon| valid[*polygon]) {
+            let vertices = self.polygon_vertices(polygon);
+            for edge in 0..vertices.len() {
+                let a = vertices[edge];
+                let b = vertices[next(edge, vertices.len())];
+                edges
+                    .entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push((polygon, edge));
+            }
+        }
+        for polygon in
+            (0..polygon_count).filter(|polygon| valid[*polygon] && !repeats_vertex[*polygon])
+        {
+            let vertices = self.polygon_vertices(polygon);
+            for edge in 0..vertices.len() {
+                let a = vertices[edge];
+                let b = vertices[next(edge, vertices.len())];
+                let twins = &edges[&(a.min(b), a.max(b))];
+                let neighbor = self.polygon_neighbors[polygon * nvp + edge];
+                if neighbor == Self::NO_CONNECTION {
+                    if twins
+                        .iter()
+                        .any(|(other, _)| *other != polygon && !repeats_vertex[*other])
+                    {
+                        audit.unlinked_edges.push((polygon, edge));
+                    }
+                    continue;
+                }
+                let Some(neighbor) = self.internal_neighbor(polygon, edge) else {
+                    // Portal to a neighboring tile
+                    continue;
+                };
+                let links_back = twins.iter().any(|(other, other_edge)| {
+                    *other == neighbor
+                        && self.internal_neighbor(*other, *other_edge) == Some(polygon)
+                });
+                if neighbor >= polygon_count || (!links_back && !repeats_vertex[neighbor]) {
+                    audit.asymmetric_links.push((polygon, edge));
+                }
+            }
+        }
+        if has_dangling_indices {
+            return audit;
+        }
+        audit.statistics.island_count = self
+            .islands()
+            .into_iter()
+            .max()
+            .map_or(0, |max| max as usize + 1);
+
+        // Overlaps
+        let tree = BvTree::new(self);
+        for polygon in (0..polygon_count).filter(|polygon| valid[*polygon]) {
+            let Some(aabb) = self.polygon_aabb(polygon) else {
+                continue;
+            };
+            let aabb = Aabb3d {
+                min: aabb.min - Vec3::Y * self.cell_height,
+                max: aabb.max + Vec3::Y * self.cell_height,
+            };
+            let vertices = self.polygon_xz(polygon);
+            for other in tree.query_aabb(&aabb) {
+                if other <= polygon || !valid[other] {
+                    continue;
+                }
+                let other_vertices = self.polygon_xz(other);
+                if convex_polygons_overlap(&vertices, &other_vertices, self.cell_size * 0.01) {
+                    audit.overlapping_polygons.push((polygon, other));
+                }
+            }
+        }
+
+        // Off-mesh connections
+        for (index, connection) in off_mesh_connections.iter().enumerate() {
+            let attached = [connection.start, connection.end]
+                .into_iter()
+                .all(|point| self.is_attached(&tree, point, connection.radius.max(self.cell_size)));
+            if !attached {
+                audit.orphan_off_mesh_connections.push(index);
+            }
+        }
+
+        audit
+    }
+
+    /// Returns the area of the polygon at index `polygon` on the xz-plane.
+    pub(crate) fn polygon_area(&self, polygon: usize) -> f32 {
+        let vertices = self.polygon_xz(polygon);
+        let mut doubled_area = 0.0;
+        for (i, a) in vertices.iter().enumerate() {
+            let b = vertices[next(i, vertices.len())];
+            doubled_area += a.perp_dot(b);
+        }
+        doubled_area.abs() * 0.5
+    }
+
+    fn polygon_xz(&self, polygon: usize) -> Vec<Vec2> {
+        self.polygon_world_vertices(polygon)
+            .map(|vertex| vertex.xz())
+            .collect()
+    }
+
+    /// Returns whether a polygon lies within `radius` of `point`.
+    fn is_attached(&self, tree: &BvTree, point: Vec3, radius: f32) -> bool {
+        let search_aabb = Aabb3d {
+            min: point - Vec3::splat(radius),
+            max: point + Vec3::splat(radius),
+        };
+        tree.query_aabb(&search_aabb).any(|polygon| {
+            let vertices = self.polygon_xz(polygon);
+            point_in_poly(&point.xz(), &vertices)
+                || (0..vertices.len()).any(|i| {
+                    let edge = (vertices[i], vertices[next(i, vertices.len())]);
+                    distance_squared_between_point_and_line_vec2(point.xz(), edge)
+                        <= radius * radius
+                })
+        })
+    }
+}
+
+/// Checks whether two convex polygons overlap by more than `tolerance` using the separating axis theorem.
+/// Polygons that merely touch do not overlap.
+fn convex_polygons_overlap(a: &[Vec2], b: &[Vec2], tolerance: f32) -> bool {
+    for polygon in [a, b] {
+        for (i, start) in polygon.iter().enumerate() {
+            let end = polygon[next(i, polygon.len())];
+            let Some(axis) = (end - *start).perp().try_normalize() else {
+                continue;
+            };
+            let project = |vertices: &[Vec2]| {
+                vertices
+                    .iter()
+                    .map(|vertex| vertex.dot(axis))
+                    .fold((f32::MAX, f32::MIN), |(min, max), value| {
+                        (min.min(value), max.max(value))
+                    })
+            };
+            let (a_min, a_max) = project(a);
+            let (b_min, b_max) = project(b);
+            if a_max <= b_min + tolerance || b_max <= a_min + tolerance {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::U16Vec3;
+
+    use super::*;
+    use crate::{AreaType, test_utils::two_quads};
+
+    #[test]
+    fn clean_navmesh_passes_audit() {
+        let mesh = two_quads();
+        let audit = mesh.audit(&[OffMeshConnection::new(
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(7.0, 0.0, 3.0),
+        )]);
+        assert!(audit.is_ok(), "{audit}");
+        assert_eq!(audit.statistics.polygon_count, 2);
+        assert_eq!(audit.statistics.island_count, 1);
+        assert_eq!(audit.statistics.area, 32.0);
+    }
+
+    #[test]
+    fn audit_names_area_types() {
+        let water = AreaType::new(3);
+        let mut mesh = two_quads();
+        mesh.areas[1] = water;
+        let audit = mesh.audit(&[]);
+        assert_eq!(
+            audit.area_by_type,
+            vec![(water, 16.0), (AreaType::DEFAULT_WALKABLE, 16.0)]
+        );
+
+        let mut registry = AreaRegistry::default();
+        registry.register(water, "water");
+        let report = audit.display_with(&registry).to_string();
+        assert!(
+            report.contains("area by type: 'water' 16.00 wu², 'walkable' 16.00 wu²"),
+            "{report}"
+        );
+        assert!(
+            audit
+                .to_string()
+                .contains("area by type: area 3 16.00 wu², area 63 16.00 wu²")
+        );
+    }
+
+    #[test]
+    fn audit_finds_problems() {
+        let mut mesh = two_quads();
+        // Unlink the shared edge on one side only
+        mesh.polygon_neighbors[4] = PolygonNavmesh::NO_CONNECTION;
+        // Make the second polygon overlap the first
+        mesh.vertices[4] = U16Vec3::new(2, 0, 4);
+        mesh.vertices[5] = U16Vec3::new(2, 0, 0);
+
+        let audit = mesh.audit(&[OffMeshConnection::new(
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(20.0, 0.0, 3.0),
+        )]);
+        assert!(!audit.is_ok());
+        assert_eq!(audit.unlinked_edges, vec![(1, 0)]);
+        assert_eq!(audit.asymmetric_links, vec![(0, 2)]);
+        assert_eq!(audit.overlapping_polygons, vec![(0, 1)]);
+        assert_eq!(audit.orphan_off_mesh_connections, vec![0]);
+    }
+
+    #[test]
+    fn audit_reports_repeated_vertices_separately() {
+        let mut mesh = two_quads();
+        // A triangle with a zero length first edge, still linked like the quad it was
+        mesh.polygons[4..8].copy_from_slice(&[3, 3, 2, 4]);
+
+        let audit = mesh.audit(&[]);
+        assert!(!audit.is_ok());
+        assert_eq!(audit.repeated_vertex_polygons, vec![1]);
+        assert!(audit.degenerate_polygons.is_empty());
+        assert!(audit.asymmetric_links.is_empty(), "{audit}");
+        assert!(audit.unlinked_edges.is_empty(), "{audit}");
+    }
+}
@@ -0,0 +1,739 @@
+use std::ops::Range;
+
+use glam::{U16Vec3, Vec3};
+use thiserror::Error;
+use zerocopy::{
+    FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned,
+    little_endian::{F32, U16, U32, U64},
+};
+
+use crate::{Aabb3d, AreaType, DetailNavmesh, PolygonNavmesh, RegionId, SubMesh, math::Fnv1a};
+
+const HEADER_SIZE: usize = size_of::<NavmeshBlobHeader>();
+const CHECKSUM_RANGE: Range<usize> = 8..16;
+const SECTION_ALIGNMENT: usize = 4;
+
+const VERTICES: usize = 0;
+const POLYGONS: usize = 1;
+const POLYGON_NEIGHBORS: usize = 2;
+const FLAGS: usize = 3;
+const REGIONS: usize = 4;
+const AREAS: usize = 5;
+const DETAIL_MESHES: usize = 6;
+const DETAIL_VERTICES: usize = 7;
+const DETAIL_TRIANGLES: usize = 8;
+const DETAIL_TRIANGLE_FLAGS: usize = 9;
+const SECTION_COUNT: usize = 10;
+
+impl PolygonNavmesh {
+    /// Writes the navmesh together with its `detail` mesh into a single buffer using the layout described in [`NavmeshBlob`].
+    ///
+    /// Read it back with [`NavmeshBlob::new`]. [`DetailNavmesh::triangle_flags`] is stored with one entry per triangle.
+    pub fn to_blob(&self, detail: &DetailNavmesh) -> Vec<u8> {
+        let section_sizes = [
+            self.vertices.len() * 6,
+            self.polygons.len() * 2,
+            self.polygon_neighbors.len() * 2,
+            self.flags.len() * 2,
+            self.regions.len() * 2,
+            self.areas.len(),
+            detail.meshes.len() * 16,
+            detail.vertices.len() * 12,
+            detail.triangles.len() * 3,
+            detail.triangles.len(),
+        ];
+        let mut section_offsets = [U32::ZERO; SECTION_COUNT];
+        let mut end = HEADER_SIZE;
+        for (offset, size) in section_offsets.iter_mut().zip(section_sizes) {
+            *offset = U32::new(end as u32);
+            end = (end + size).next_multiple_of(SECTION_ALIGNMENT);
+        }
+
+        let header = NavmeshBlobHeader {
+            magic: NavmeshBlob::MAGIC,
+            version: U32::new(NavmeshBlob::VERSION),
+            // The checksum is filled in once the sections are written.
+            checksum: U64::ZERO,
+            vertex_count: U32::new(self.vertices.len() as u32),
+            polygon_count: U32::new(self.polygon_count() as u32),
+            detail_mesh_count: U32::new(detail.meshes.len() as u32),
+            detail_vertex_count: U32::new(detail.vertices.len() as u32),
+            detail_triangle_count: U32::new(detail.triangles.len() as u32),
+            max_vertices_per_polygon: U16::new(self.max_vertices_per_polygon),
+            border_size: U16::new(self.border_size),
+            cell_size: F32::new(self.cell_size),
+            cell_height: F32::new(self.cell_height),
+            max_edge_error: F32::new(self.max_edge_error),
+            aabb_min: self.aabb.min.to_array().map(F32::new),
+            aabb_max: self.aabb.max.to_array().map(F32::new),
+            section_offsets,
+        };
+        let mut bytes = Vec::with_capacity(end);
+        bytes.extend_from_slice(header.as_bytes());
+
+        let mut write_u16s = |section: usize, values: &mut dyn Iterator<Item = u16>| {
+            bytes.resize(section_offsets[section].get() as usize, 0);
+            for value in values {
+                bytes.extend_from_slice(U16::new(value).as_bytes());
+            }
+        };
+        write_u16s(
+            VERTICES,
+            &mut self.vertices.iter().flat_map(|vertex| vertex.to_array()),
+        );
+        write_u16s(POLYGONS, &mut self.polygons.iter().copied());
+        write_u16s(
+            POLYGON_NEIGHBORS,
+            &mut self.polygon_neighbors.iter().copied(),
+        );
+        write_u16s(FLAGS, &mut self.flags.iter().copied());
+        write_u16s(
+            REGIONS,
+            &mut self.regions.iter().map(|region| region.bits()),
+        );
+        bytes.resize(section_offsets[AREAS].get() as usize, 0);
+        bytes.extend(self.areas.iter().map(|area| area.id()));
+
+        bytes.resize(section_offsets[DETAIL_MESHES].get() as usize, 0);
+        for mesh in &detail.meshes {
+            let fields = [
+                mesh.base_vertex_index,
+                mesh.vertex_count,
+                mesh.base_triangle_index,
+                mesh.triangle_count,
+            ];
+            bytes.extend_from_slice(fields.map(U32::new).as_bytes());
+        }
+        bytes.resize(section_offsets[DETAIL_VERTICES].get() as usize, 0);
+        for vertex in &detail.vertices {
+            bytes.extend_from_slice(vertex.to_array().map(F32::new).as_bytes());
+        }
+        bytes.resize(section_offsets[DETAIL_TRIANGLES].get() as usize, 0);
+        bytes.extend(detail.triangles.iter().flatten());
+        let flags_offset = section_offsets[DETAIL_TRIANGLE_FLAGS].get() as usize;
+        bytes.resize(flags_offset, 0);
+        bytes.extend(&detail.triangle_flags);
+        bytes.resize(flags_offset + detail.triangles.len(), 0);
+        bytes.resize(end, 0);
+
+        let checksum = U64::new(checksum(&bytes));
+        bytes[CHECKSUM_RANGE].copy_from_slice(checksum.as_bytes());
+        bytes
+    }
+}
+
+/// The header of a navmesh blob, see [`NavmeshBlob`].
+///
+/// All fields are stored as little-endian and have an alignment of 1,
+/// so the header can be read from any position in any buffer on any target.
+#[derive(
+    Debug, Clone, Copy, PartialEq, FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned,
+)]
+#[repr(C)]
+pub struct NavmeshBlobHeader {
+    /// [`NavmeshBlob::MAGIC`]
+    pub magic: [u8; 4],
+    /// [`NavmeshBlob::VERSION`]
+    pub version: U32,
+    /// The FNV-1a hash of the whole blob, including the header with this field set to zero.
+    pub checksum: U64,
+    /// The number of vertices.
+    pub vertex_count: U32,
+    /// The number of polygons.
+    pub polygon_count: U32,
+    /// The number of entries in [`DetailNavmesh::meshes`].
+    pub detail_mesh_count: U32,
+    /// The number of entries in [`DetailNavmesh::vertices`].
+    pub detail_vertex_count: U32,
+    /// The number of entries in [`DetailNavmesh::triangles`] and [`DetailNavmesh::triangle_flags`].
+    pub detail_triangle_count: U32,
+    /// See [`PolygonNavmesh::max_vertices_per_polygon`].
+    pub max_vertices_per_polygon: U16,
+    /// See [`PolygonNavmesh::border_size`].
+    pub border_size: U16,
+    /// See [`PolygonNavmesh::cell_size`].
+    pub cell_size: F32,
+    /// See [`PolygonNavmesh::cell_height`].
+    pub cell_height: F32,
+    /// See [`PolygonNavmesh::max_edge_error`].
+    pub max_edge_error: F32,
+    /// The minimum corner of [`PolygonNavmesh::aabb`].
+    pub aabb_min: [F32; 3],
+    /// The maximum corner of [`PolygonNavmesh::aabb`].
+    pub aabb_max: [F32; 3],
+    /// The offsets of the vertex, polygon, neighbor, flag, region and area sections,
+    /// followed by the detail mesh, detail vertex, detail triangle and detail triangle flag sections, from the start of the blob.
+    pub section_offsets: [U32; SECTION_COUNT],
+}
+
+/// A read-only view of a navmesh stored in a single contiguous byte buffer, as written by [`PolygonNavmesh::to_blob`].
+///
+/// All sections of the navmesh are addressed by offsets relative to the start of the blob,
+/// so a blob can be written to disk as is, loaded or memory-mapped again, and shared between processes
+/// without deserializing it first. Creating a view is cheap and does not allocate, no matter how large the navmesh is.
+///
+/// The blob starts with a [`NavmeshBlobHeader`], followed by the sections, each starting at a multiple of 4 bytes.
+/// All values are stored as little-endian, and the raw sections are exposed as slices of
+/// alignment-free little-endian types, so baked blobs can be shared between targets of any endianness
+/// and read from buffers of any alignment.
+#[derive(Debug, Clone, Copy)]
+pub struct NavmeshBlob<'a> {
+    bytes: &'a [u8],
+    header: &'a NavmeshBlobHeader,
+    vertices: &'a [[U16; 3]],
+    polygons: &'a [U16],
+    polygon_neighbors: &'a [U16],
+    flags: &'a [U16],
+    regions: &'a [U16],
+    areas: &'a [u8],
+    detail_meshes: &'a [[U32; 4]],
+    detail_vertices: &'a [[F32; 3]],
+    detail_triangles: &'a [[u8; 3]],
+    detail_triangle_flags: &'a [u8],
+}
+
+impl<'a> NavmeshBlob<'a> {
+    /// The bytes every navmesh blob starts with.
+    pub const MAGIC: [u8; 4] = *b"RRNM";
+
+    /// The current version of the layout.
+    pub const VERSION: u32 = 2;
+
+    /// Validates the header, section bounds, checksum and content of `bytes` and creates a view of them.
+    ///
+    /// The content is checked even if the checksum matches, as it only guards against accidental damage,
+    /// so the accessors of the view never panic for blobs built by other tools or crafted on purpose.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, NavmeshBlobError> {
+        Self::parse(bytes, true)
+    }
+
+    /// Like [`Self::new`], but skips the checksum, for bytes that were already validated once.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn new_unverified(bytes: &'a [u8]) -> Result<Self, NavmeshBlobError> {
+        Self::parse(bytes, false)
+    }
+
+    fn parse(bytes: &'a [u8], verify_checksum: bool) -> Result<Self, NavmeshBlobError> {
+        let Ok((header, _)) = NavmeshBlobHeader::ref_from_prefix(bytes) else {
+            return Err(NavmeshBlobError::TooShort);
+        };
+        if header.magic != Self::MAGIC {
+            return Err(NavmeshBlobError::InvalidMagic);
+        }
+        if header.version.get() != Self::VERSION {
+            return Err(NavmeshBlobError::UnsupportedVersion(header.version.get()));
+        }
+
+        // The counts are untrusted, so their section sizes may overflow on 32-bit targets.
+        let section_size = |count: usize, size: usize| {
+            count
+                .checked_mul(size)
+                .ok_or(NavmeshBlobError::SectionOutOfBounds)
+        };
+        let polygon_count = header.polygon_count.get() as usize;
+        let indices = section_size(
+            polygon_count,
+            header.max_vertices_per_polygon.get() as usize,
+        )?;
+        let section = |index: usize, size: usize| {
+            let offset = header.section_offsets[index].get() as usize;
+            if offset < HEADER_SIZE || !offset.is_multiple_of(SECTION_ALIGNMENT) {
+                return Err(NavmeshBlobError::SectionOutOfBounds);
+            }
+            offset
+                .checked_add(size)
+                .and_then(|end| bytes.get(offset..end))
+                .ok_or(NavmeshBlobError::SectionOutOfBounds)
+        };
+        let u16s = |index: usize, count: usize| {
+            section(index, section_size(count, 2)?).map(|bytes| {
+                <[U16]>::ref_from_bytes(bytes).expect("U16 has no alignment and the size is exact")
+            })
+        };
+        let vertex_count = header.vertex_count.get() as usize;
+        let vertices = section(VERTICES, section_size(vertex_count, 6)?).map(|bytes| {
+            <[[U16; 3]]>::ref_from_bytes(bytes).expect("U16 has no alignment and the size is exact")
+        })?;
+        let detail_mesh_count = header.detail_mesh_count.get() as usize;
+        let detail_meshes =
+            section(DETAIL_MESHES, section_size(detail_mesh_count, 16)?).map(|bytes| {
+                <[[U32; 4]]>::ref_from_bytes(bytes)
+                    .expect("U32 has no alignment and the size is exact")
+            })?;
+        let detail_vertex_count = header.detail_vertex_count.get() as usize;
+        let detail_vertices = section(DETAIL_VERTICES, section_size(detail_vertex_count, 12)?)
+            .map(|bytes| {
+                <[[F32; 3]]>::ref_from_bytes(bytes)
+                    .expect("F32 has no alignment and the size is exact")
+            })?;
+        let detail_triangle_count = header.detail_triangle_count.get() as usize;
+        let detail_triangles =
+            section(DETAIL_TRIANGLES, section_size(detail_triangle_count, 3)?)
+                .map(|bytes| <[[u8; 3]]>::ref_from_bytes(bytes).expect("The size is exact"))?;
+        let blob = Self {
+            bytes,
+            header,
+            vertices,
+            polygons: u16s(POLYGONS, indices)?,
+            polygon_neighbors: u16s(POLYGON_NEIGHBORS, indices)?,
+            flags: u16s(FLAGS, polygon_count)?,
+            regions: u16s(REGIONS, polygon_count)?,
+            areas: section(AREAS, polygon_count)?,
+            detail_meshes,
+            detail_vertices,
+            detail_triangles,
+            detail_triangle_flags: section(DETAIL_TRIANGLE_FLAGS, detail_triangle_count)?,
+        };
+
+        if verify_checksum && header.checksum.get() != checksum(bytes) {
+            return Err(NavmeshBlobError::ChecksumMismatch);
+        }
+        blob.validate()?;
+        Ok(blob)
+    }
+
+    /// Checks that all areas are valid and all indices refer to existing vertices, polygons and triangles.
+    fn validate(&self) -> Result<(), NavmeshBlobError> {
+        let nvp = self.max_vertices_per_polygon() as usize;
+        let vertex_count = self.vertex_count();
+        let polygon_count = self.polygon_count();
+        for polygon in 0..polygon_count {
+            if AreaType::from_id(self.areas[polygon]).is_none() {
+                return Err(NavmeshBlobError::InvalidArea(polygon));
+            }
+            let edges = polygon * nvp..(polygon + 1) * nvp;
+            let vertices_valid = self.polygons[edges.clone()].iter().all(|vertex| {
+                vertex.get() == PolygonNavmesh::NO_INDEX || (vertex.get() as usize) < vertex_count
+            });
+            // Neighbors with the border bit set are portals into neighboring tiles, see `PolygonNavmesh::polygon_neighbors`.
+            let neighbors_valid = self.polygon_neighbors[edges].iter().all(|neighbor| {
+                neighbor.get() & RegionId::BORDER_REGION.bits() != 0
+                    || (neighbor.get() as usize) < polygon_count
+            });
+            if !vertices_valid || !neighbors_valid {
+                return Err(NavmeshBlobError::IndexOutOfBounds(polygon));
+            }
+        }
+        for index in 0..self.detail_meshes.len() {
+            let mesh = self.detail_mesh(index);
+            let in_bounds = |base: u32, count: u32, len: usize| {
+                base.checked_add(count)
+                    .is_some_and(|end| end as usize <= len)
+            };
+            if !in_bounds(
+                mesh.base_vertex_index,
+                mesh.vertex_count,
+                self.detail_vertices.len(),
+            ) || !in_bounds(
+                mesh.base_triangle_index,
+                mesh.triangle_count,
+                self.detail_triangles.len(),
+            ) {
+                return Err(NavmeshBlobError::IndexOutOfBounds(index));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the underlying bytes.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the header of the blob.
+    #[inline]
+    pub fn header(&self) -> &'a NavmeshBlobHeader {
+        self.header
+    }
+
+    /// Returns the number of vertices.
+    #[inline]
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Returns the number of polygons.
+    #[inline]
+    pub fn polygon_count(&self) -> usize {
+        self.flags.len()
+    }
+
+    /// See [`PolygonNavmesh::max_vertices_per_polygon`].
+    #[inline]
+    pub fn max_vertices_per_polygon(&self) -> u16 {
+        self.header.max_vertices_per_polygon.get()
+    }
+
+    /// See [`PolygonNavmesh::border_size`].
+    #[inline]
+    pub fn border_size(&self) -> u16 {
+        self.header.border_size.get()
+    }
+
+    /// See [`PolygonNavmesh::cell_size`].
+    #[inline]
+    pub fn cell_size(&self) -> f32 {
+        self.header.cell_size.get()
+    }
+
+    /// See [`PolygonNavmesh::cell_height`].
+    #[inline]
+    pub fn cell_height(&self) -> f32 {
+        self.header.cell_height.get()
+    }
+
+    /// See [`PolygonNavmesh::max_edge_error`].
+    #[inline]
+    pub fn max_edge_error(&self) -> f32 {
+        self.header.max_edge_error.get()
+    }
+
+    /// See [`PolygonNavmesh::aabb`].
+    pub fn aabb(&self) -> Aabb3d {
+        Aabb3d {
+            min: Vec3::from_array(self.header.aabb_min.map(F32::get)),
+            max: Vec3::from_array(self.header.aabb_max.map(F32::get)),
+        }
+    }
+
+    /// Returns the raw vertex section. See [`PolygonNavmesh::vertices`].
+    #[inline]
+    pub fn raw_vertices(&self) -> &'a [[U16; 3]] {
+        self.vertices
+    }
+
+    /// Returns the raw polygon section. See [`PolygonNavmesh::polygons`].
+    #[inline]
+    pub fn raw_polygons(&self) -> &'a [U16] {
+        self.polygons
+    }
+
+    /// Returns the raw polygon neighbor section. See [`PolygonNavmesh::polygon_neighbors`].
+    #[inline]
+    pub fn raw_polygon_neighbors(&self) -> &'a [U16] {
+        self.polygon_neighbors
+    }
+
+    /// Returns the raw flag section. See [`PolygonNavmesh::flags`].
+    #[inline]
+    pub fn raw_flags(&self) -> &'a [U16] {
+        self.flags
+    }
+
+    /// Returns the raw region section. See [`PolygonNavmesh::regions`].
+    #[inline]
+    pub fn raw_regions(&self) -> &'a [U16] {
+        self.regions
+    }
+
+    /// Returns the raw area section. See [`PolygonNavmesh::areas`].
+    #[inline]
+    pub fn raw_areas(&self) -> &'a [u8] {
+        self.areas
+    }
+
+    /// Returns the vertex at `index`. See [`PolygonNavmesh::vertices`].
+    #[inline]
+    pub fn vertex(&self, index: usize) -> U16Vec3 {
+        U16Vec3::from_array(self.vertices[index].map(U16::get))
+    }
+
+    /// Iterates over the vertex indices of the polygon at index `polygon`,
+    /// without the trailing [`PolygonNavmesh::NO_INDEX`] entries.
+    pub fn polygon_vertices(&self, polygon: usize) -> impl Iterator<Item = u16> + 'a {
+        let nvp = self.max_vertices_per_polygon() as usize;
+        self.polygons[polygon * nvp..(polygon + 1) * nvp]
+            .iter()
+            .map(|i| i.get())
+            .take_while(|i| *i != PolygonNavmesh::NO_INDEX)
+    }
+
+    /// Returns the polygon on the other side of the edge starting at vertex `edge` of the polygon at index `polygon`.
+    /// See [`PolygonNavmesh::polygon_neighbors`].
+    #[inline]
+    pub fn polygon_neighbor(&self, polygon: usize, edge: usize) -> u16 {
+        let nvp = self.max_vertices_per_polygon() as usize;
+        self.polygon_neighbors[polygon * nvp + edge].get()
+    }
+
+    /// Returns the flags of the polygon at index `polygon`. See [`PolygonNavmesh::flags`].
+    #[inline]
+    pub fn flags(&self, polygon: usize) -> u16 {
+        self.flags[polygon].get()
+    }
+
+    /// Returns the region of the polygon at index `polygon`. See [`PolygonNavmesh::regions`].
+    #[inline]
+    pub fn region(&self, polygon: usize) -> RegionId {
+        RegionId::from(self.regions[polygon].get())
+    }
+
+    /// Returns the area of the polygon at index `polygon`. See [`PolygonNavmesh::areas`].
+    #[inline]
+    pub fn area(&self, polygon: usize) -> AreaType {
+        AreaType::new(self.areas[polygon])
+    }
+
+    /// Returns the raw detail mesh section, with the fields of each [`SubMesh`] in declaration order.
+    /// See [`DetailNavmesh::meshes`].
+    #[inline]
+    pub fn raw_detail_meshes(&self) -> &'a [[U32; 4]] {
+        self.detail_meshes
+    }
+
+    /// Returns the raw detail vertex section. See [`DetailNavmesh::vertices`].
+    #[inline]
+    pub fn raw_detail_vertices(&self) -> &'a [[F32; 3]] {
+        self.detail_vertices
+    }
+
+    /// Returns the raw detail triangle section. See [`DetailNavmesh::triangles`].
+    #[inline]
+    pub fn raw_detail_triangles(&self) -> &'a [[u8; 3]] {
+        self.detail_triangles
+    }
+
+    /// Returns the raw detail triangle flag section. See [`DetailNavmesh::triangle_flags`].
+    #[inline]
+    pub fn raw_detail_triangle_flags(&self) -> &'a [u8] {
+        self.detail_triangle_flags
+    }
+
+    /// Returns the detail sub-mesh at `index`, usually of the polygon with the same index. See [`DetailNavmesh::meshes`].
+    #[inline]
+    pub fn detail_mesh(&self, index: usize) -> SubMesh {
+        let [
+            base_vertex_index,
+            vertex_count,
+            base_triangle_index,
+            triangle_count,
+        ] = self.detail_meshes[index].map(U32::get);
+        SubMesh {
+            base_vertex_index,
+            vertex_count,
+            base_triangle_index,
+            triangle_count,
+        }
+    }
+
+    /// Returns the detail vertex at `index`. See [`DetailNavmesh::vertices`].
+    #[inline]
+    pub fn detail_vertex(&self, index: usize) -> Vec3 {
+        Vec3::from_array(self.detail_vertices[index].map(F32::get))
+    }
+
+    /// Copies the blob into an owned [`PolygonNavmesh`].
+    pub fn to_navmesh(&self) -> PolygonNavmesh {
+        let u16s = |values: &[U16]| values.iter().map(|value| value.get()).collect();
+        PolygonNavmesh {
+            vertices: (0..self.vertex_count()).map(|i| self.vertex(i)).collect(),
+            polygons: u16s(self.polygons),
+            polygon_neighbors: u16s(self.polygon_neighbors),
+            flags: u16s(self.flags),
+            regions: (0..self.polygon_count()).map(|i| self.region(i)).collect(),
+            areas: (0..self.polygon_count()).map(|i| self.area(i)).collect(),
+            edge_flags: Vec::new(),
+            max_vertices_per_polygon: self.max_vertices_per_polygon(),
+            aabb: self.aabb(),
+            cell_size: self.cell_size(),
+            cell_height: self.cell_height(),
+            border_size: self.border_size(),
+            max_edge_error: self.max_edge_error(),
+        }
+    }
+
+    /// Copies the detail mesh of the blob into an owned [`DetailNavmesh`].
+    pub fn to_detail_navmesh(&self) -> DetailNavmesh {
+        DetailNavmesh {
+            meshes: (0..self.detail_meshes.len())
+                .map(|i| self.detail_mesh(i))
+                .collect(),
+            vertices: (0..self.detail_vertices.len())
+                .map(|i| self.detail_vertex(i))
+                .collect(),
+            triangles: self.detail_triangles.to_vec(),
+            triangle_flags: self.detail_triangle_flags.to_vec(),
+        }
+    }
+}
+
+/// Errors that can occur when reading a blob with [`NavmeshBlob::new`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum NavmeshBlobError {
+    /// The blob is shorter than its header.
+    #[error("Navmesh blob is shorter than its header")]
+    TooShort,
+    /// The blob does not start with [`NavmeshBlob::MAGIC`].
+    #[error("Data is not a navmesh blob")]
+    InvalidMagic,
+    /// The blob was written with an incompatible version of the layout.
+    #[error("Unsupported navmesh blob version {0}, expected {expected}", expected = NavmeshBlob::VERSION)]
+    UnsupportedVersion(u32),
+    /// A section of the blob is misaligned or extends past its end.
+    #[error("Navmesh blob section is misaligned or out of bounds")]
+    SectionOutOfBounds,
+    /// The content of the blob does not match its checksum.
+    #[error("Navmesh blob does not match its checksum")]
+    ChecksumMismatch,
+    /// The polygon at this index has an area id greater than [`AreaType::MAX_ID`].
+    #[error("Navmesh blob polygon {0} has an invalid area id")]
+    InvalidArea(usize),
+    /// The polygon or detail sub-mesh at this index refers to a vertex, polygon or triangle that does not exist.
+    #[error("Navmesh blob polygon {0} refers to a vertex, polygon or triangle out of bounds")]
+    IndexOutOfBounds(usize),
+}
+
+/// Hashes the whole blob, taking the checksum stored in the header as zero.
+fn checksum(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write(&bytes[..CHECKSUM_RANGE.start]);
+    hasher.write(&[0; CHECKSUM_RANGE.end - CHECKSUM_RANGE.start]);
+    hasher.write(&bytes[CHECKSUM_RANGE.end..]);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    #[test]
+    fn round_trips_navmesh() {
+        let mut navmesh = GridNavmesh::parse(
+            "
+            ..#
+            .1.
+            ",
+        )
+        .navmesh;
+        navmesh.flags[2] = 0b101;
+        let detail = DetailNavmesh {
+            meshes: vec![SubMesh {
+                base_vertex_index: 0,
+                vertex_count: 4,
+                base_triangle_index: 0,
+                triangle_count: 2,
+            }],
+            vertices: vec![
+                Vec3::new(0.0, 0.5, 0.0),
+                Vec3::new(0.0, 0.5, 1.0),
+                Vec3::new(1.0, 0.25, 1.0),
+                Vec3::new(1.0, 0.5, 0.0),
+            ],
+            triangles: vec![[0, 1, 2], [0, 2, 3]],
+            triangle_flags: vec![0b01, 0b100],
+        };
+        let bytes = navmesh.to_blob(&detail);
+        assert!(bytes.len().is_multiple_of(SECTION_ALIGNMENT));
+
+        let blob = NavmeshBlob::new(&bytes).unwrap();
+        assert_eq!(blob.polygon_count(), 5);
+        assert_eq!(blob.area(3), AreaType::new(1));
+        assert_eq!(
+            blob.polygon_vertices(0).collect::<Vec<_>>(),
+            navmesh.polygon_vertices(0)
+        );
+        assert_eq!(blob.to_navmesh(), navmesh);
+        assert_eq!(blob.detail_vertex(2), Vec3::new(1.0, 0.25, 1.0));
+        assert_eq!(blob.to_detail_navmesh(), detail);
+    }
+
+    #[test]
+    fn reads_unaligned_buffers() {
+        let navmesh = GridNavmesh::parse("..").navmesh;
+        let mut bytes = vec![0];
+        bytes.extend(navmesh.to_blob(&DetailNavmesh::default()));
+
+        let blob = NavmeshBlob::new(&bytes[1..]).unwrap();
+        assert_eq!(blob.header().polygon_count.get(), 2);
+        assert_eq!(blob.raw_vertices()[1].map(U16::get), [1, 0, 0]);
+        assert_eq!(blob.to_navmesh(), navmesh);
+    }
+
+    #[test]
+    fn rejects_damaged_blobs() {
+        let navmesh = GridNavmesh::parse("..").navmesh;
+        let mut bytes = navmesh.to_blob(&DetailNavmesh::default());
+        assert_eq!(
+            NavmeshBlob::new(&bytes[..HEADER_SIZE - 1]).unwrap_err(),
+            NavmeshBlobError::TooShort
+        );
+        assert_eq!(
+            NavmeshBlob::new(&bytes[..bytes.len() - 4]).unwrap_err(),
+            NavmeshBlobError::SectionOutOfBounds
+        );
+
+        *bytes.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            NavmeshBlob::new(&bytes).unwrap_err(),
+            NavmeshBlobError::ChecksumMismatch
+        );
+        *bytes.last_mut().unwrap() ^= 1;
+        // The header is covered by the checksum as well.
+        let mut moved = bytes.clone();
+        NavmeshBlobHeader::mut_from_prefix(&mut moved)
+            .unwrap()
+            .0
+            .aabb_min[0] = F32::new(1.0);
+        assert_eq!(
+            NavmeshBlob::new(&moved).unwrap_err(),
+            NavmeshBlobError::ChecksumMismatch
+        );
+        let mut huge = bytes.clone();
+        NavmeshBlobHeader::mut_from_prefix(&mut huge)
+            .unwrap()
+            .0
+            .polygon_count = U32::new(u32::MAX);
+        assert_eq!(
+            NavmeshBlob::new(&huge).unwrap_err(),
+            NavmeshBlobError::SectionOutOfBounds
+        );
+        bytes[0] = b'X';
+        assert_eq!(
+            NavmeshBlob::new(&bytes).unwrap_err(),
+            NavmeshBlobError::InvalidMagic
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_content_with_valid_checksum() {
+        let navmesh = GridNavmesh::parse("..").navmesh;
+        let bytes = navmesh.to_blob(&DetailNavmesh::default());
+        let offset = |section: usize| {
+            NavmeshBlobHeader::ref_from_prefix(&bytes)
+                .unwrap()
+                .0
+                .section_offsets[section]
+                .get() as usize
+        };
+        let rechecksummed = |mut bytes: Vec<u8>| {
+            let checksum = U64::new(checksum(&bytes));
+            bytes[CHECKSUM_RANGE].copy_from_slice(checksum.as_bytes());
+            bytes
+        };
+
+        let mut bad_area = bytes.clone();
+        bad_area[offset(AREAS) + 1] = 255;
+        assert_eq!(
+            NavmeshBlob::new(&rechecksummed(bad_area)).unwrap_err(),
+            NavmeshBlobError::InvalidArea(1)
+        );
+
+        let mut bad_vertex = bytes.clone();
+        let vertex = offset(POLYGONS);
+        bad_vertex[vertex..vertex + 2].copy_from_slice(U16::new(100).as_bytes());
+        assert_eq!(
+            NavmeshBlob::new(&rechecksummed(bad_vertex)).unwrap_err(),
+            NavmeshBlobError::IndexOutOfBounds(0)
+        );
+
+        let mut bad_neighbor = bytes.clone();
+        let neighbor = offset(POLYGON_NEIGHBORS) + 4 * 2;
+        bad_neighbor[neighbor..neighbor + 2].copy_from_slice(U16::new(2).as_bytes());
+        assert_eq!(
+            NavmeshBlob::new(&rechecksummed(bad_neighbor)).unwrap_err(),
+            NavmeshBlobError::IndexOutOfBounds(1)
+        );
+    }
+}
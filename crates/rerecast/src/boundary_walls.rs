@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use glam::{UVec3, Vec3, Vec3A};
+
+use crate::{AreaType, EdgeNeighbor, PolygonNavmesh, TriMesh};
+
+impl PolygonNavmesh {
+    /// Extrudes the solid borders of the navmesh upwards into walls of the given height, e.g. to build containment colliders
+    /// that guarantee agents can not leave the navigable area. `[Limit: > 0] [Units: wu]`
+    ///
+    /// Every edge not shared with another polygon becomes a quad standing on the navmesh surface.
+    /// Portals to neighboring tiles are left open, so the walls of adjacent tiles line up.
+    /// Walls of edges meeting at a vertex share their vertices, so there are no cracks between them.
+    /// The triangles face towards the navmesh, so one-sided colliders block agents from inside the navigable area.
+    ///
+    /// All triangles have the area [`AreaType::NOT_WALKABLE`].
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn extrude_boundary(&self, height: f32) -> TriMesh {
+        let mut trimesh = TriMesh::default();
+        // The indices of the bottom and top vertex extruded from each navmesh vertex.
+        let mut extruded: HashMap<u16, (u32, u32)> = HashMap::new();
+        let mut extrude = |vertex: u16, trimesh: &mut TriMesh| {
+            *extruded.entry(vertex).or_insert_with(|| {
+                let bottom = self.world_vertex(vertex);
+                let index = trimesh.vertices.len() as u32;
+                trimesh.vertices.push(Vec3A::from(bottom));
+                trimesh
+                    .vertices
+                    .push(Vec3A::from(bottom + Vec3::Y * height));
+                (index, index + 1)
+            })
+        };
+
+        for polygon in 0..self.polygon_count() {
+            for edge in self.polygon_edges(polygon) {
+                if edge.neighbor != EdgeNeighbor::Border {
+                    continue;
+                }
+                let (a, top_a) = extrude(edge.vertices[0], &mut trimesh);
+                let (b, top_b) = extrude(edge.vertices[1], &mut trimesh);
+                trimesh.indices.push(UVec3::new(a, top_b, b));
+                trimesh.indices.push(UVec3::new(a, top_a, top_b));
+            }
+        }
+        trimesh
+            .area_types
+            .resize(trimesh.indices.len(), AreaType::NOT_WALKABLE);
+        trimesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RegionId, test_utils::GridNavmesh};
+
+    #[test]
+    fn extrudes_borders_into_walls_facing_the_navmesh() {
+        let mut grid = GridNavmesh::parse("ab");
+        let walls = grid.navmesh.extrude_boundary(2.0);
+        // The 6 border edges around both cells, sharing the 6 corners.
+        assert_eq!(walls.indices.len(), 12);
+        assert_eq!(walls.vertices.len(), 12);
+        assert_eq!(walls.area_types, vec![AreaType::NOT_WALKABLE; 12]);
+
+        let center = Vec3A::new(1.0, 0.0, 0.5);
+        for triangle in &walls.indices {
+            let [a, b, c] = triangle.to_array().map(|i| walls.vertices[i as usize]);
+            let normal = (b - a).cross(c - a);
+            let to_center = (center - (a + b + c) / 3.0) * Vec3A::new(1.0, 0.0, 1.0);
+            assert!(normal.dot(to_center) > 0.0);
+        }
+        assert!(
+            walls
+                .vertices
+                .iter()
+                .all(|vertex| vertex.y == 0.0 || vertex.y == 2.0)
+        );
+
+        // Portals to a neighboring tile stay open.
+        let b = grid.polygon('b');
+        let nvp = grid.navmesh.max_vertices_per_polygon as usize;
+        // The +x edge of `b`.
+        grid.navmesh.polygon_neighbors[b * nvp + 2] = RegionId::BORDER_REGION.bits() | 2;
+        assert_eq!(grid.navmesh.extrude_boundary(2.0).indices.len(), 10);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{Contour, RegionId};
+
+/// A snapshot of [`CompactHeightfield::build_regions_with_progress`](crate::CompactHeightfield::build_regions_with_progress),
+/// taken after the watershed flooded another band of distance levels.
+///
+/// The watershed starts at the spans farthest from any border and works its way down to the borders,
+/// so regions first appear in the middle of open areas and grow outwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegionProgress<'a> {
+    /// The region of every span so far, in the order of [`CompactHeightfield::spans`](crate::CompactHeightfield::spans).
+    /// [`RegionId::NONE`] for spans that have not been reached yet.
+    ///
+    /// These are the regions before small regions are merged and filtered, so they may still change afterwards.
+    pub regions: &'a [RegionId],
+    /// The number of distance levels flooded so far. `[Limit: <= total_levels]`
+    pub completed_levels: u16,
+    /// The number of distance levels to flood in total.
+    pub total_levels: u16,
+}
+
+impl RegionProgress<'_> {
+    /// Returns the share of the distance levels flooded so far. `[Limit: 0..=1]`
+    #[inline]
+    pub fn fraction(&self) -> f32 {
+        fraction(self.completed_levels, self.total_levels)
+    }
+}
+
+/// A snapshot of [`CompactHeightfield::build_contours_with_progress`](crate::CompactHeightfield::build_contours_with_progress),
+/// taken after another row of the heightfield was traced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContourProgress<'a> {
+    /// The contours traced so far. Every contour is complete, even if its region extends into rows that were not traced yet.
+    pub contours: &'a [Contour],
+    /// The number of rows along the z-axis traced so far. `[Limit: <= total_rows]`
+    pub completed_rows: u16,
+    /// The number of rows along the z-axis to trace in total, including the border.
+    pub total_rows: u16,
+}
+
+impl ContourProgress<'_> {
+    /// Returns the share of the rows traced so far. `[Limit: 0..=1]`
+    #[inline]
+    pub fn fraction(&self) -> f32 {
+        fraction(self.completed_rows, self.total_rows)
+    }
+}
+
+fn fraction(completed: u16, total: u16) -> f32 {
+    if total == 0 {
+        1.0
+    } else {
+        completed as f32 / total as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec3, Vec3A};
+
+    use crate::{Aabb3d, AreaType, BuildContoursFlags, HeightfieldBuilder, TriMesh};
+
+    use super::*;
+
+    #[test]
+    fn reports_partial_regions_and_contours() {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 1.0, 5.0]),
+            cell_size: 0.25,
+            cell_height: 0.25,
+        }
+        .build()
+        .unwrap();
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(-4.0, 0.0, -4.0),
+                Vec3A::new(-4.0, 0.0, 4.0),
+                Vec3A::new(4.0, 0.0, 4.0),
+                Vec3A::new(4.0, 0.0, -4.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2), UVec3::new(0, 2, 3)],
+            area_types: vec![AreaType::DEFAULT_WALKABLE; 2],
+            materials: Vec::new(),
+        };
+        heightfield.rasterize_triangles(&trimesh, 1).unwrap();
+        let mut compact = heightfield.into_compact(2, 1).unwrap();
+        compact.build_distance_field();
+
+        let mut region_fractions = Vec::new();
+        let mut assigned = Vec::new();
+        compact
+            .build_regions_with_progress(0, 0, 0, |progress| {
+                region_fractions.push(progress.fraction());
+                assigned.push(
+                    progress
+                        .regions
+                        .iter()
+                        .filter(|region| **region != RegionId::NONE)
+                        .count(),
+                );
+            })
+            .unwrap();
+        assert!(region_fractions.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(region_fractions.last(), Some(&1.0));
+        // The regions grow with every band of levels.
+        assert!(assigned.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(assigned[0] < assigned[assigned.len() - 1]);
+
+        let mut contour_counts = Vec::new();
+        let contours = compact.build_contours_with_progress(
+            1.3,
+            12,
+            BuildContoursFlags::DEFAULT,
+            |progress| contour_counts.push((progress.completed_rows, progress.contours.len())),
+        );
+        assert_eq!(contour_counts.len(), compact.height as usize);
+        assert_eq!(
+            contour_counts.last(),
+            Some(&(compact.height, contours.contours.len()))
+        );
+        assert_eq!(
+            contours,
+            compact.build_contours(1.3, 12, BuildContoursFlags::DEFAULT)
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! Recording of build inputs, so that bugs can be reproduced exactly from user submissions.
+
+use glam::Vec3;
+
+use crate::{
+    Aabb3d, AreaType, CompactHeightfield, ConvexVolume, DetailNavmesh, NavmeshConfig,
+    PolygonNavmesh, SoloNavmeshError, TileBuildPool, TriMesh, math::Fnv1a,
+    solo_navmesh::build_marked_navmesh_in,
+};
+
+/// All inputs of a single navmesh build: the config, the rasterized geometry and the volumes marked on it.
+///
+/// Records only contain the triangles that were fed into the rasterizer, not the level assets they came from,
+/// so they can be attached to bug reports. With the `serialize` feature, records are stored as JSON lines
+/// in `.rrbuild` files, so a rolling log of builds can be kept by appending to the same file with [`BuildRecord::append_to`].
+/// Use [`BuildRecord::replay`] to build the recorded navmesh again.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct BuildRecord {
+    /// The version of the record format, see [`BuildRecord::FORMAT_VERSION`].
+    pub version: u32,
+    /// The config the navmesh was built with.
+    pub config: NavmeshConfig,
+    /// The geometry passed to [`Heightfield::rasterize_triangles`](crate::Heightfield::rasterize_triangles).
+    pub geometry: TriMesh,
+    /// The hash of [`Self::geometry`] as computed by [`BuildRecord::hash_geometry`] when the record was created.
+    pub geometry_hash: u64,
+    /// The volumes marked on the compact heightfield in addition to [`NavmeshConfig::area_volumes`] and [`NavmeshConfig::area_paths`], in order.
+    pub volumes: Vec<RecordedVolume>,
+}
+
+impl BuildRecord {
+    /// The current version of the record format.
+    pub const FORMAT_VERSION: u32 = 3;
+
+    /// The file extension used for build records.
+    pub const FILE_EXTENSION: &'static str = "rrbuild";
+
+    /// Records the inputs of a build.
+    pub fn new(config: NavmeshConfig, geometry: TriMesh, volumes: Vec<RecordedVolume>) -> Self {
+        Self {
+            version: Self::FORMAT_VERSION,
+            config,
+            geometry_hash: Self::hash_geometry(&geometry),
+            geometry,
+            volumes,
+        }
+    }
+
+    /// Computes a hash of the geometry that is stable across platforms and builds,
+    /// so that two parties can check whether they are looking at the same input without comparing the triangles.
+    pub fn hash_geometry(geometry: &TriMesh) -> u64 {
+        let mut hasher = Fnv1a::new();
+        for vertex in &geometry.vertices {
+            for component in vertex.to_array() {
+                hasher.write(&component.to_bits().to_le_bytes());
+            }
+        }
+        for indices in &geometry.indices {
+            for index in indices.to_array() {
+                hasher.write(&index.to_le_bytes());
+            }
+        }
+        for area in &geometry.area_types {
+            hasher.write(&(area.id() as u32).to_le_bytes());
+        }
+        hasher.write(&geometry.materials);
+        hasher.finish()
+    }
+
+    /// Returns whether the geometry still matches [`Self::geometry_hash`], i.e. the record was not corrupted or edited.
+    pub fn is_intact(&self) -> bool {
+        Self::hash_geometry(&self.geometry) == self.geometry_hash
+    }
+
+    /// Builds the recorded navmesh again like [`build_solo_navmesh`](crate::build_solo_navmesh),
+    /// marking [`Self::volumes`] after the areas of [`Self::config`].
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn replay(&self) -> Result<(PolygonNavmesh, DetailNavmesh), SoloNavmeshError> {
+        let aabb = self
+            .geometry
+            .compute_aabb()
+            .ok_or(SoloNavmeshError::EmptyGeometry)?;
+        build_marked_navmesh_in(
+            aabb,
+            &self.geometry,
+            &self.config,
+            |_| true,
+            |compact_heightfield| {
+                for volume in &self.volumes {
+                    volume.mark(compact_heightfield);
+                }
+            },
+            &mut TileBuildPool::new(0),
+        )
+    }
+
+    /// Appends the record to a build log as a single line of JSON.
+    #[cfg(feature = "serialize")]
+    pub fn append_to(&self, mut writer: impl std::io::Write) -> Result<(), BuildRecordError> {
+        serde_json::to_writer(&mut writer, self)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Reads all records of a build log, oldest first.
+    ///
+    /// Fails if a record was written by an incompatible version or its geometry does not match its hash.
+    #[cfg(feature = "serialize")]
+    pub fn read_all(reader: impl std::io::BufRead) -> Result<Vec<Self>, BuildRecordError> {
+        let mut records = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Self = serde_json::from_str(&line)?;
+            if record.version != Self::FORMAT_VERSION {
+                return Err(BuildRecordError::UnsupportedVersion(record.version));
+            }
+            if !record.is_intact() {
+                return Err(BuildRecordError::GeometryHashMismatch);
+            }
+            records.push(record);
+        }
+        Ok(records)
+    }
+}
+
+/// A volume marked on the compact heightfield of a recorded build, see [`BuildRecord::volumes`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecordedVolume {
+    /// Marked with [`CompactHeightfield::mark_convex_poly_area`].
+    Convex(ConvexVolume),
+    /// Marked with [`CompactHeightfield::mark_box_area`].
+    Box {
+        /// The box whose spans are marked.
+        aabb: Aabb3d,
+        /// The area type to mark.
+        area: AreaType,
+    },
+    /// Marked with [`CompactHeightfield::mark_cylinder_area`].
+    Cylinder {
+        /// The center of the bottom of the cylinder.
+        position: Vec3,
+        /// The radius of the cylinder. `[Units: wu]`
+        radius: f32,
+        /// The height of the cylinder. `[Units: wu]`
+        height: f32,
+        /// The area type to mark.
+        area: AreaType,
+    },
+}
+
+impl RecordedVolume {
+    /// Marks the volume on `compact_heightfield` with the method it was recorded for.
+    pub fn mark(&self, compact_heightfield: &mut CompactHeightfield) {
+        match self {
+            Self::Convex(volume) => compact_heightfield.mark_convex_poly_area(volume.clone()),
+            Self::Box { aabb, area } => compact_heightfield.mark_box_area(aabb, *area),
+            Self::Cylinder {
+                position,
+                radius,
+                height,
+                area,
+            } => compact_heightfield.mark_cylinder_area(*position, *radius, *height, *area),
+        }
+    }
+}
+
+impl From<ConvexVolume> for RecordedVolume {
+    fn from(volume: ConvexVolume) -> Self {
+        Self::Convex(volume)
+    }
+}
+
+/// Errors that can occur when reading or writing a [`BuildRecord`].
+#[cfg(feature = "serialize")]
+#[derive(Debug, thiserror::Error)]
+pub enum BuildRecordError {
+    /// Reading from or writing to the build log failed.
+    #[error("Failed to access build log: {0}")]
+    Io(#[from] std::io::Error),
+    /// A record could not be (de)serialized.
+    #[error("Failed to (de)serialize build record: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A record was written by an incompatible version of the format.
+    #[error("Unsupported build record version {0}, expected {expected}", expected = BuildRecord::FORMAT_VERSION)]
+    UnsupportedVersion(u32),
+    /// The geometry of a record does not match its hash.
+    #[error("Build record geometry does not match its hash")]
+    GeometryHashMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec3, Vec2, Vec3A};
+
+    use super::*;
+    use crate::build_solo_navmesh;
+
+    fn triangle() -> TriMesh {
+        TriMesh {
+            vertices: vec![Vec3A::ZERO, Vec3A::Z, Vec3A::X],
+            indices: vec![UVec3::new(0, 1, 2)],
+            area_types: vec![AreaType::DEFAULT_WALKABLE],
+            materials: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn detects_changed_geometry() {
+        let mut record = BuildRecord::new(NavmeshConfig::default(), triangle(), Vec::new());
+        assert!(record.is_intact());
+
+        record.geometry.vertices[0].y = 1.0;
+        assert!(!record.is_intact());
+
+        let mut record = BuildRecord::new(NavmeshConfig::default(), triangle(), Vec::new());
+        record.geometry.materials = vec![1];
+        assert!(!record.is_intact());
+    }
+
+    fn plane() -> TriMesh {
+        TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 0.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2), UVec3::new(0, 2, 3)],
+            area_types: vec![AreaType::NOT_WALKABLE; 2],
+            materials: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn replays_recorded_build() {
+        let volume = ConvexVolume {
+            vertices: vec![
+                Vec2::new(2.0, 2.0),
+                Vec2::new(2.0, 5.0),
+                Vec2::new(5.0, 5.0),
+                Vec2::new(5.0, 2.0),
+            ],
+            min_y: -1.0,
+            max_y: 1.0,
+            area: AreaType::from(3),
+        };
+        let config = NavmeshConfig {
+            border_size: 0,
+            ..Default::default()
+        };
+        let expected = build_solo_navmesh(
+            &plane(),
+            &NavmeshConfig {
+                area_volumes: vec![volume.clone()],
+                ..config.clone()
+            },
+        )
+        .unwrap();
+
+        let record = BuildRecord::new(config, plane(), vec![volume.into()]);
+        let (polygon, detail) = record.replay().unwrap();
+        assert_eq!(polygon, expected.0);
+        assert_eq!(detail, expected.1);
+        assert!(polygon.areas.contains(&AreaType::from(3)));
+
+        let mut record = record;
+        record.volumes.push(RecordedVolume::Cylinder {
+            position: Vec3::new(7.5, -1.0, 7.5),
+            radius: 1.5,
+            height: 2.0,
+            area: AreaType::from(4),
+        });
+        let (polygon, _) = record.replay().unwrap();
+        assert!(polygon.areas.contains(&AreaType::from(4)));
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn replays_rolling_log() {
+        let first = BuildRecord::new(NavmeshConfig::default(), triangle(), Vec::new());
+        let second = BuildRecord::new(
+            NavmeshConfig {
+                cell_size: 0.5,
+                ..Default::default()
+            },
+            triangle(),
+            Vec::new(),
+        );
+        let mut log = Vec::new();
+        first.append_to(&mut log).unwrap();
+        second.append_to(&mut log).unwrap();
+
+        let records = BuildRecord::read_all(log.as_slice()).unwrap();
+        assert_eq!(records, vec![first, second]);
+    }
+}
@@ -0,0 +1,207 @@
+use core::fmt;
+
+use crate::{AreaRegistry, AreaType};
+
+/// A build limit that was hit, together with a suggestion on how to avoid it.
+///
+/// Recast silently clamps or aborts in these cases, which makes it hard to find out which parameter to change.
+/// Instead, every warning is emitted as a `tracing` event with the target `rerecast::build_warning`,
+/// carrying the warning itself and [`BuildWarning::suggestion`] as structured fields,
+/// so users can surface them in their own tooling.
+///
+/// Area types are printed by their names in [`NavmeshConfig::area_registry`](crate::NavmeshConfig::area_registry)
+/// if one is set, see [`BuildWarning::display_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildWarning {
+    /// The heightfield is taller than the number of cells a span can address,
+    /// so spans above that height were clamped.
+    SpanHeightClamped {
+        /// The number of cells needed to cover the heightfield's height.
+        cells: usize,
+        /// The maximum number of cells a span can address.
+        max: u16,
+    },
+    /// The heightfield contains more regions than fit into a [`RegionId`](crate::RegionId).
+    TooManyRegions {
+        /// The maximum number of regions.
+        max: u16,
+    },
+    /// The contours contain more vertices than a polygon mesh can index.
+    TooManyVertices {
+        /// The number of vertices needed.
+        actual: usize,
+        /// The maximum number of vertices.
+        max: usize,
+    },
+    /// The polygon mesh contains more polygons than were allocated for it.
+    TooManyPolygons {
+        /// The number of polygons needed.
+        actual: usize,
+        /// The maximum number of polygons.
+        max: usize,
+    },
+    /// Polygons are allowed to have more vertices than Detour supports.
+    TooManyVerticesPerPolygon {
+        /// The requested maximum number of vertices per polygon.
+        actual: u16,
+        /// The maximum number of vertices per polygon Detour supports.
+        max: u16,
+    },
+    /// Ear clipping got stuck on a near-degenerate contour, so its triangulation was repaired
+    /// instead of dropping the contour. The polygons of the region may overlap slightly.
+    TriangulationRepaired {
+        /// The region whose contour was repaired.
+        region: u16,
+        /// The number of ears clipped even though the contour crosses them.
+        forced_ears: usize,
+        /// The number of collinear vertices removed.
+        dropped_vertices: usize,
+    },
+    /// The watershed stopped growing regions at an iteration cap while they were still growing,
+    /// so more regions were seeded than necessary, or spans were left without a region after the last level.
+    RegionExpansionCapped {
+        /// The number of bands of distance levels whose expansion was capped.
+        expansions: usize,
+    },
+    /// Spans of two different walkable areas were merged while rasterizing, so the lower area was overwritten by the higher one,
+    /// see [`Heightfield::rasterize_triangle`](crate::Heightfield::rasterize_triangle).
+    /// Emitted once per pair of areas after every rasterized stream of triangles.
+    AreaOverwritten {
+        /// The area that was overwritten.
+        overwritten: AreaType,
+        /// The area that took its place.
+        by: AreaType,
+        /// The x-coordinate of the first overwritten span in row-major order. `[Units: vx]`
+        x: u16,
+        /// The z-coordinate of the first overwritten span in row-major order. `[Units: vx]`
+        z: u16,
+        /// The number of overwritten spans.
+        count: usize,
+    },
+}
+
+impl BuildWarning {
+    /// Returns which parameters to change to avoid this warning.
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            BuildWarning::SpanHeightClamped { .. } => {
+                "Increase the cell height or reduce the height of the input geometry's bounding box."
+            }
+            BuildWarning::TooManyRegions { .. } => {
+                "Reduce the tile size, or increase the cell size, the minimum region area or the merge region area."
+            }
+            BuildWarning::TooManyVertices { .. } | BuildWarning::TooManyPolygons { .. } => {
+                "Reduce the tile size, or increase the cell size or the maximum simplification error."
+            }
+            BuildWarning::TooManyVerticesPerPolygon { .. } => {
+                "Reduce the maximum vertices per polygon if the navmesh is consumed by Detour."
+            }
+            BuildWarning::TriangulationRepaired { .. } => {
+                "Reduce the maximum simplification error or the maximum edge length, or decrease the cell size."
+            }
+            BuildWarning::RegionExpansionCapped { .. } => {
+                "Increase the expansion iterations of the watershed settings, at the cost of build time."
+            }
+            BuildWarning::AreaOverwritten { .. } => {
+                "Give the area that should win the higher id, or move the surfaces further apart than the walkable climb."
+            }
+        }
+    }
+
+    /// Returns a [`Display`](fmt::Display) for the warning that prints area types by their names in `registry`.
+    pub fn display_with<'a>(&'a self, registry: &'a AreaRegistry) -> BuildWarningDisplay<'a> {
+        BuildWarningDisplay {
+            warning: self,
+            registry,
+        }
+    }
+
+    /// Emits the warning as a `tracing` event.
+    pub(crate) fn emit(&self) {
+        self.emit_with(&AreaRegistry::empty());
+    }
+
+    /// Emits the warning as a `tracing` event, printing area types by their names in `registry`.
+    pub(crate) fn emit_with(&self, registry: &AreaRegistry) {
+        tracing::warn!(
+            target: "rerecast::build_warning",
+            warning = ?self,
+            suggestion = self.suggestion(),
+            "{} {}",
+            self.display_with(registry),
+            self.suggestion()
+        );
+    }
+}
+
+impl fmt::Display for BuildWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display_with(&AreaRegistry::empty()).fmt(f)
+    }
+}
+
+/// Displays a [`BuildWarning`] with named area types, see [`BuildWarning::display_with`].
+#[derive(Debug, Clone, Copy)]
+pub struct BuildWarningDisplay<'a> {
+    warning: &'a BuildWarning,
+    registry: &'a AreaRegistry,
+}
+
+impl fmt::Display for BuildWarningDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.warning {
+            BuildWarning::SpanHeightClamped { cells, max } => {
+                write!(
+                    f,
+                    "Spans were clamped: the heightfield is {cells} cells tall, but spans can only address {max}."
+                )
+            }
+            BuildWarning::TooManyRegions { max } => {
+                write!(
+                    f,
+                    "Too many regions: a heightfield can contain at most {max} regions."
+                )
+            }
+            BuildWarning::TooManyVertices { actual, max } => {
+                write!(f, "Too many vertices: {actual} > {max}.")
+            }
+            BuildWarning::TooManyPolygons { actual, max } => {
+                write!(f, "Too many polygons: {actual} > {max}.")
+            }
+            BuildWarning::TooManyVerticesPerPolygon { actual, max } => {
+                write!(f, "Too many vertices per polygon: {actual} > {max}.")
+            }
+            BuildWarning::TriangulationRepaired {
+                region,
+                forced_ears,
+                dropped_vertices,
+            } => {
+                write!(
+                    f,
+                    "Repaired the triangulation of region {region}: forced {forced_ears} ears and dropped {dropped_vertices} collinear vertices."
+                )
+            }
+            BuildWarning::RegionExpansionCapped { expansions } => {
+                write!(
+                    f,
+                    "Region expansion was capped in {expansions} bands of distance levels."
+                )
+            }
+            BuildWarning::AreaOverwritten {
+                overwritten,
+                by,
+                x,
+                z,
+                count,
+            } => {
+                write!(
+                    f,
+                    "{} overwritten by {} in {count} spans, first at ({x}, {z}).",
+                    self.registry.display(*overwritten),
+                    self.registry.display(*by),
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! A bounding volume tree over the polygons of a [`PolygonNavmesh`], used to accelerate spatial queries.
+//!
+//! The layout follows Detour's `dtBVNode` tree: the nodes are stored in a flat array in depth-first order,
+//! and internal nodes store how many nodes to skip to get past their subtree.
+
+use glam::{U16Vec3, Vec3};
+
+use crate::{Aabb3d, AreaType, PolygonNavmesh};
+
+/// A bounding volume tree over the polygons of a [`PolygonNavmesh`].
+///
+/// Polygon bounds are quantized to integer coordinates relative to the minimum corner of the mesh,
+/// see [`BvTree::quantization_factor`]. Polygons with [`AreaType::NOT_WALKABLE`] are left out of the tree,
+/// so after changing polygon areas at runtime, call [`BvTree::rebuild`] to update it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BvTree {
+    nodes: Vec<BvNode>,
+    /// The world space position that corresponds to the quantized coordinate `(0, 0, 0)`.
+    origin: Vec3,
+    /// The factor used to convert world space distances into quantized coordinates.
+    quantization_factor: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct BvNode {
+    /// The quantized minimum point of the node's bounds
+    min: U16Vec3,
+    /// The quantized maximum point of the node's bounds
+    max: U16Vec3,
+    /// The index of the polygon if this is a leaf node,
+    /// or the negated number of nodes in the subtree if this is an internal node.
+    index: i32,
+}
+
+impl BvTree {
+    /// Builds a tree over the walkable polygons of the mesh, using a quantization factor of `1 / cell_size`.
+    pub fn new(mesh: &PolygonNavmesh) -> Self {
+        Self::with_quantization_factor(mesh, 1.0 / mesh.cell_size)
+    }
+
+    /// Builds a tree over the walkable polygons of the mesh, using the given quantization factor.
+    ///
+    /// Larger factors produce tighter bounds and thus fewer false positives in queries,
+    /// but the quantized size of the mesh, `mesh_size * quantization_factor`, must fit into a [`u16`].
+    pub fn with_quantization_factor(mesh: &PolygonNavmesh, quantization_factor: f32) -> Self {
+        let mut tree = Self {
+            nodes: Vec::new(),
+            origin: mesh.aabb.min,
+            quantization_factor,
+        };
+        tree.rebuild(mesh);
+        tree
+    }
+
+    /// Rebuilds the tree from the current state of the mesh, keeping the quantization factor and reusing the allocation.
+    ///
+    /// Call this after polygons were changed at runtime, e.g. after marking some of them as [`AreaType::NOT_WALKABLE`].
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn rebuild(&mut self, mesh: &PolygonNavmesh) {
+        self.origin = mesh.aabb.min;
+        self.nodes.clear();
+        let mut items = (0..mesh.polygon_count())
+            .filter(|polygon| mesh.areas[*polygon] != AreaType::NOT_WALKABLE)
+            .filter_map(|polygon| {
+                let aabb = mesh.polygon_aabb(polygon)?;
+                Some(BvNode {
+                    min: self.quantize_floor(aabb.min),
+                    max: self.quantize_ceil(aabb.max),
+                    index: polygon as i32,
+                })
+            })
+            .collect::<Vec<_>>();
+        self.nodes.reserve(items.len() * 2);
+        if !items.is_empty() {
+            subdivide(&mut items, &mut self.nodes);
+        }
+    }
+
+    /// The factor used to convert world space distances into the quantized coordinates of the tree.
+    #[inline]
+    pub fn quantization_factor(&self) -> f32 {
+        self.quantization_factor
+    }
+
+    /// Returns the number of nodes in the tree.
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Iterates over the indices of all polygons whose quantized bounds overlap the given world space AABB.
+    ///
+    /// Since the bounds are quantized conservatively, this can yield polygons that lie slightly outside of the AABB.
+    pub fn query_aabb(&self, aabb: &Aabb3d) -> BvTreeQuery<'_> {
+        BvTreeQuery {
+            nodes: &self.nodes,
+            min: self.quantize_floor(aabb.min),
+            max: self.quantize_ceil(aabb.max),
+            cursor: 0,
+            nodes_visited: 0,
+        }
+    }
+
+    #[inline]
+    fn quantize_floor(&self, point: Vec3) -> U16Vec3 {
+        ((point - self.origin) * self.quantization_factor)
+            .floor()
+            .clamp(Vec3::ZERO, Vec3::splat(u16::MAX as f32))
+            .as_u16vec3()
+    }
+
+    #[inline]
+    fn quantize_ceil(&self, point: Vec3) -> U16Vec3 {
+        ((point - self.origin) * self.quantization_factor)
+            .ceil()
+            .clamp(Vec3::ZERO, Vec3::splat(u16::MAX as f32))
+            .as_u16vec3()
+    }
+}
+
+/// Iterator returned by [`BvTree::query_aabb`].
+#[derive(Debug, Clone)]
+pub struct BvTreeQuery<'a> {
+    nodes: &'a [BvNode],
+    min: U16Vec3,
+    max: U16Vec3,
+    cursor: usize,
+    nodes_visited: u32,
+}
+
+impl BvTreeQuery<'_> {
+    /// Returns the number of tree nodes the query has visited so far.
+    #[inline]
+    pub fn nodes_visited(&self) -> u32 {
+        self.nodes_visited
+    }
+}
+
+impl Iterator for BvTreeQuery<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.nodes.get(self.cursor) {
+            self.nodes_visited += 1;
+            let overlaps = self.min.cmple(node.max).all() && self.max.cmpge(node.min).all();
+            let is_leaf = node.index >= 0;
+            if overlaps || is_leaf {
+                self.cursor += 1;
+            } else {
+                // Skip the whole subtree
+                self.cursor += node.index.unsigned_abs() as usize;
+            }
+            if overlaps && is_leaf {
+                return Some(node.index as usize);
+            }
+        }
+        None
+    }
+}
+
+fn subdivide(items: &mut [BvNode], nodes: &mut Vec<BvNode>) {
+    if let [item] = items {
+        // Leaf
+        nodes.push(*item);
+        return;
+    }
+
+    // Split
+    let current = nodes.len();
+    let (min, max) = items
+        .iter()
+        .fold((U16Vec3::MAX, U16Vec3::MIN), |(min, max), item| {
+            (min.min(item.min), max.max(item.max))
+        });
+    nodes.push(BvNode { min, max, index: 0 });
+
+    let axis = longest_axis(max - min);
+    items.sort_unstable_by_key(|item| item.min[axis]);
+
+    let split = items.len() / 2;
+    let (left, right) = items.split_at_mut(split);
+    subdivide(left, nodes);
+    subdivide(right, nodes);
+
+    let escape_index = nodes.len() - current;
+    nodes[current].index = -(escape_index as i32);
+}
+
+fn longest_axis(extent: U16Vec3) -> usize {
+    let mut axis = 0;
+    let mut max = extent.x;
+    if extent.y > max {
+        axis = 1;
+        max = extent.y;
+    }
+    if extent.z > max {
+        axis = 2;
+    }
+    axis
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::U16Vec3;
+
+    use super::*;
+    use crate::test_utils::quad_navmesh;
+
+    #[test]
+    fn rebuild_skips_unwalkable_polygons() {
+        // Two disconnected 4x4 quads along the x-axis
+        let mut mesh = quad_navmesh(
+            vec![
+                U16Vec3::new(0, 0, 0),
+                U16Vec3::new(0, 0, 4),
+                U16Vec3::new(4, 0, 4),
+                U16Vec3::new(4, 0, 0),
+                U16Vec3::new(6, 0, 0),
+                U16Vec3::new(6, 0, 4),
+                U16Vec3::new(10, 0, 4),
+                U16Vec3::new(10, 0, 0),
+            ],
+            &[[0, 1, 2, 3], [4, 5, 6, 7]],
+        );
+        let everything = Aabb3d {
+            min: Vec3::ZERO,
+            max: Vec3::new(10.0, 1.0, 4.0),
+        };
+        let right = Aabb3d {
+            min: Vec3::new(7.0, 0.0, 1.0),
+            max: Vec3::new(8.0, 0.0, 2.0),
+        };
+
+        let mut tree = BvTree::with_quantization_factor(&mesh, 4.0);
+        assert_eq!(tree.quantization_factor(), 4.0);
+        assert_eq!(tree.query_aabb(&everything).count(), 2);
+        assert_eq!(tree.query_aabb(&right).collect::<Vec<_>>(), vec![1]);
+
+        mesh.areas[1] = AreaType::NOT_WALKABLE;
+        tree.rebuild(&mesh);
+        assert_eq!(tree.query_aabb(&everything).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(tree.node_count(), 1);
+    }
+}
@@ -0,0 +1,372 @@
+use core::ops::Range;
+
+use glam::{UVec2, Vec2, Vec3, Vec3Swizzles as _};
+
+use crate::{
+    Aabb2d, Aabb3d, CompactHeightfield, CompressedHeightfield, ContourSet, Heightfield,
+    PolygonNavmesh,
+};
+
+/// The cell grid of a [`Heightfield`], [`CompactHeightfield`] or one of the structures built from them,
+/// converting between world positions and cell coordinates.
+///
+/// Cell `(x, z)` covers the world space square from `origin.xz() + (x, z) * cell_size` to `origin.xz() + (x + 1, z + 1) * cell_size`,
+/// so its center lies half a cell further. Heights in cell units count [`Self::cell_height`]s from `origin.y`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CellGrid {
+    /// The world space position of the lower corner of cell `(0, 0)` at height 0, i.e. the minimum of the heightfield's AABB.
+    pub origin: Vec3,
+    /// The number of cells along the x-axis.
+    pub width: u16,
+    /// The number of cells along the z-axis.
+    pub height: u16,
+    /// The size of each cell on the xz-plane. `[Units: wu]`
+    pub cell_size: f32,
+    /// The size of each cell along the y-axis. `[Units: wu]`
+    pub cell_height: f32,
+}
+
+impl CellGrid {
+    /// Returns the number of cells along the x- and z-axes.
+    #[inline]
+    pub fn cell_counts(&self) -> UVec2 {
+        UVec2::new(self.width as u32, self.height as u32)
+    }
+
+    /// Returns the world space bounds of all cells on the xz-plane.
+    #[inline]
+    pub fn bounds(&self) -> Aabb2d {
+        let min = self.origin.xz();
+        Aabb2d {
+            min,
+            max: min + self.cell_counts().as_vec2() * self.cell_size,
+        }
+    }
+
+    /// Returns the world space bounds of the cell at `(x, z)` on the xz-plane.
+    #[inline]
+    pub fn cell_bounds(&self, x: u16, z: u16) -> Aabb2d {
+        let min = self.origin.xz() + Vec2::new(x as f32, z as f32) * self.cell_size;
+        Aabb2d {
+            min,
+            max: min + self.cell_size,
+        }
+    }
+
+    /// Iterates over the coordinates of all cells, row by row, i.e. in the order of [`Self::column_index`].
+    pub fn cells(&self) -> impl Iterator<Item = (u16, u16)> + use<> {
+        let (width, height) = (self.width, self.height);
+        (0..height).flat_map(move |z| (0..width).map(move |x| (x, z)))
+    }
+
+    /// Returns the cell containing the world space `position`, ignoring its height,
+    /// or `None` if it lies outside the grid.
+    pub fn world_to_cell(&self, position: Vec3) -> Option<(u16, u16)> {
+        let cell = ((position.xz() - self.origin.xz()) / self.cell_size).floor();
+        if cell.x < 0.0 || cell.y < 0.0 {
+            return None;
+        }
+        let (x, z) = (cell.x as u16, cell.y as u16);
+        self.contains_cell(x as i32, z as i32).then_some((x, z))
+    }
+
+    /// Returns the world space center of the cell at `(x, z)` on the xz-plane.
+    #[inline]
+    pub fn cell_to_world_center(&self, x: u16, z: u16) -> Vec2 {
+        self.origin.xz() + (Vec2::new(x as f32, z as f32) + 0.5) * self.cell_size
+    }
+
+    /// Returns the world space y-coordinate of a height in cell units, e.g. [`CompactSpan::y`](crate::CompactSpan::y)
+    /// or [`Span::max`](crate::Span::max).
+    #[inline]
+    pub fn cell_y_to_world(&self, y: u16) -> f32 {
+        self.origin.y + y as f32 * self.cell_height
+    }
+
+    /// Returns the height in cell units of the cell layer containing the world space y-coordinate `y`.
+    /// Negative if `y` lies below the grid.
+    #[inline]
+    pub fn world_to_cell_y(&self, y: f32) -> i32 {
+        ((y - self.origin.y) / self.cell_height).floor() as i32
+    }
+
+    /// Returns the world space position at the center of the cell at `(x, z)` at the height `y` in cell units,
+    /// e.g. the point on top of a span agents stand on.
+    #[inline]
+    pub fn cell_to_world(&self, x: u16, z: u16, y: u16) -> Vec3 {
+        let center = self.cell_to_world_center(x, z);
+        Vec3::new(center.x, self.cell_y_to_world(y), center.y)
+    }
+
+    /// Returns whether `(x, z)` are the coordinates of a cell in the grid.
+    /// Takes signed coordinates, so that neighbors of border cells can be checked directly.
+    #[inline]
+    pub fn contains_cell(&self, x: i32, z: i32) -> bool {
+        x >= 0 && x < self.width as i32 && z >= 0 && z < self.height as i32
+    }
+
+    /// Returns the index of the column at `(x, z)`, e.g. into [`Heightfield::spans`] or [`CompactHeightfield::cells`].
+    #[inline]
+    pub fn column_index(&self, x: u16, z: u16) -> usize {
+        x as usize + z as usize * self.width as usize
+    }
+
+    /// Returns the coordinates of the column at `index`, the inverse of [`Self::column_index`].
+    #[inline]
+    pub fn column_coordinates(&self, index: usize) -> (u16, u16) {
+        let width = self.width.max(1) as usize;
+        ((index % width) as u16, (index / width) as u16)
+    }
+}
+
+impl Heightfield {
+    /// Returns the cell grid of this heightfield, see [`CellGrid`].
+    #[inline]
+    pub fn grid(&self) -> CellGrid {
+        CellGrid {
+            origin: self.aabb.min,
+            width: self.width,
+            height: self.height,
+            cell_size: self.cell_size,
+            cell_height: self.cell_height,
+        }
+    }
+
+    /// Returns the world space bounds of this heightfield.
+    #[inline]
+    pub fn bounds(&self) -> Aabb3d {
+        self.aabb
+    }
+
+    /// Returns the number of cells along the x- and z-axes.
+    #[inline]
+    pub fn cell_counts(&self) -> UVec2 {
+        self.grid().cell_counts()
+    }
+}
+
+impl CompressedHeightfield {
+    /// Returns the cell grid of this heightfield, see [`CellGrid`].
+    #[inline]
+    pub fn grid(&self) -> CellGrid {
+        CellGrid {
+            origin: self.aabb.min,
+            width: self.width,
+            height: self.height,
+            cell_size: self.cell_size,
+            cell_height: self.cell_height,
+        }
+    }
+
+    /// Returns the world space bounds of this heightfield.
+    #[inline]
+    pub fn bounds(&self) -> Aabb3d {
+        self.aabb
+    }
+
+    /// Returns the number of cells along the x- and z-axes.
+    #[inline]
+    pub fn cell_counts(&self) -> UVec2 {
+        self.grid().cell_counts()
+    }
+}
+
+impl ContourSet {
+    /// Returns the cell grid of this contour set, see [`CellGrid`].
+    #[inline]
+    pub fn grid(&self) -> CellGrid {
+        CellGrid {
+            origin: self.aabb.min,
+            width: self.width,
+            height: self.height,
+            cell_size: self.cell_size,
+            cell_height: self.cell_height,
+        }
+    }
+
+    /// Returns the world space bounds of this contour set.
+    #[inline]
+    pub fn bounds(&self) -> Aabb3d {
+        self.aabb
+    }
+
+    /// Returns the number of cells along the x- and z-axes.
+    #[inline]
+    pub fn cell_counts(&self) -> UVec2 {
+        self.grid().cell_counts()
+    }
+}
+
+impl CompactHeightfield {
+    /// Returns the cell grid of this heightfield, see [`CellGrid`].
+    #[inline]
+    pub fn grid(&self) -> CellGrid {
+        CellGrid {
+            origin: self.aabb.min,
+            width: self.width,
+            height: self.height,
+            cell_size: self.cell_size,
+            cell_height: self.cell_height,
+        }
+    }
+
+    /// Returns the world space bounds of this heightfield.
+    #[inline]
+    pub fn bounds(&self) -> Aabb3d {
+        self.aabb
+    }
+
+    /// Returns the number of cells along the x- and z-axes.
+    #[inline]
+    pub fn cell_counts(&self) -> UVec2 {
+        self.grid().cell_counts()
+    }
+
+    /// Returns the indices into [`Self::spans`] of the spans in the column at `(x, z)`, from bottom to top.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates lie outside the grid.
+    #[inline]
+    pub fn column_spans(&self, x: u16, z: u16) -> Range<usize> {
+        let cell = self.cell_at(x, z);
+        let start = cell.index() as usize;
+        start..start + cell.count() as usize
+    }
+
+    /// Returns the world space position on top of the span at `index` into [`Self::spans`] in the column at `(x, z)`.
+    #[inline]
+    pub fn span_position(&self, x: u16, z: u16, index: usize) -> Vec3 {
+        self.grid().cell_to_world(x, z, self.spans[index].y)
+    }
+}
+
+impl PolygonNavmesh {
+    /// Returns the cell grid the vertices of this navmesh are stored in, see [`CellGrid`].
+    ///
+    /// The navmesh does not store the size of the heightfield it was built from,
+    /// so the cell counts are derived from [`Self::aabb`].
+    pub fn grid(&self) -> CellGrid {
+        let cells = ((self.aabb.max.xz() - self.aabb.min.xz()) / self.cell_size)
+            .round()
+            .clamp(Vec2::ZERO, Vec2::splat(u16::MAX as f32));
+        CellGrid {
+            origin: self.aabb.min,
+            width: cells.x as u16,
+            height: cells.y as u16,
+            cell_size: self.cell_size,
+            cell_height: self.cell_height,
+        }
+    }
+
+    /// Returns the world space bounds of this navmesh.
+    #[inline]
+    pub fn bounds(&self) -> Aabb3d {
+        self.aabb
+    }
+
+    /// Returns the number of cells along the x- and z-axes, see [`Self::grid`].
+    #[inline]
+    pub fn cell_counts(&self) -> UVec2 {
+        self.grid().cell_counts()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aabb3d, HeightfieldBuilder};
+
+    #[test]
+    fn converts_between_world_and_cells() {
+        let heightfield = HeightfieldBuilder {
+            aabb: Aabb3d {
+                min: Vec3::new(-2.0, -1.0, 4.0),
+                max: Vec3::new(2.0, 3.0, 6.0),
+            },
+            cell_size: 0.5,
+            cell_height: 0.25,
+        }
+        .build()
+        .unwrap();
+        let grid = heightfield.grid();
+        assert_eq!((grid.width, grid.height), (8, 4));
+
+        assert_eq!(grid.world_to_cell(Vec3::new(-2.0, 0.0, 4.0)), Some((0, 0)));
+        assert_eq!(grid.world_to_cell(Vec3::new(0.3, 0.0, 5.9)), Some((4, 3)));
+        assert_eq!(grid.world_to_cell(Vec3::new(-2.1, 0.0, 5.0)), None);
+        assert_eq!(grid.world_to_cell(Vec3::new(0.0, 0.0, 6.0)), None);
+
+        // The center is half a cell away from the corner.
+        assert_eq!(grid.cell_to_world_center(4, 3), Vec2::new(0.25, 5.75));
+        assert_eq!(
+            grid.world_to_cell(grid.cell_to_world_center(4, 3).extend(0.0).xzy()),
+            Some((4, 3))
+        );
+
+        assert_eq!(grid.world_to_cell_y(-1.0), 0);
+        assert_eq!(grid.world_to_cell_y(-0.6), 1);
+        assert_eq!(grid.world_to_cell_y(-1.1), -1);
+        assert_eq!(grid.cell_y_to_world(4), 0.0);
+        assert_eq!(grid.cell_to_world(4, 3, 4), Vec3::new(0.25, 0.0, 5.75));
+
+        let index = grid.column_index(5, 2);
+        assert_eq!(index, 21);
+        assert_eq!(grid.column_coordinates(index), (5, 2));
+        assert!(grid.contains_cell(7, 3));
+        assert!(!grid.contains_cell(-1, 0));
+        assert!(!grid.contains_cell(8, 0));
+    }
+
+    #[test]
+    fn reports_bounds_and_cell_counts() {
+        let aabb = Aabb3d {
+            min: Vec3::new(-2.0, -1.0, 4.0),
+            max: Vec3::new(2.0, 3.0, 6.0),
+        };
+        let heightfield = HeightfieldBuilder {
+            aabb,
+            cell_size: 0.5,
+            cell_height: 0.25,
+        }
+        .build()
+        .unwrap();
+        assert_eq!(heightfield.bounds(), aabb);
+        assert_eq!(heightfield.cell_counts(), UVec2::new(8, 4));
+
+        let grid = heightfield.grid();
+        assert_eq!(
+            grid.bounds(),
+            Aabb2d {
+                min: Vec2::new(-2.0, 4.0),
+                max: Vec2::new(2.0, 6.0),
+            }
+        );
+        assert_eq!(
+            grid.cell_bounds(4, 3),
+            Aabb2d {
+                min: Vec2::new(0.0, 5.5),
+                max: Vec2::new(0.5, 6.0),
+            }
+        );
+        let cells: Vec<(u16, u16)> = grid.cells().collect();
+        assert_eq!(cells.len(), 32);
+        assert_eq!(cells[..2], [(0, 0), (1, 0)]);
+        assert!(
+            cells
+                .iter()
+                .enumerate()
+                .all(|(index, (x, z))| grid.column_index(*x, *z) == index)
+        );
+
+        let navmesh = PolygonNavmesh {
+            aabb,
+            cell_size: 0.5,
+            cell_height: 0.25,
+            ..Default::default()
+        };
+        assert_eq!(navmesh.grid(), grid);
+        assert_eq!(navmesh.cell_counts(), UVec2::new(8, 4));
+    }
+}
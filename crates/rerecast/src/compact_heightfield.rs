@@ -110,6 +110,11 @@ pub struct CompactHeightfield {
     pub dist: Vec<u16>,
     /// Vector containing area type data. [Size: `spans.len()`]
     pub areas: Vec<AreaType>,
+    /// Vector containing how far the surface of each span lies below its [`CompactSpan::y`],
+    /// in 1/256ths of the cell height. [Size: `spans.len()`]
+    ///
+    /// All zero unless the source heightfield recorded [`Heightfield::sub_voxel_heights`].
+    pub height_offsets: Vec<u8>,
 }
 
 impl Heightfield {
@@ -120,6 +125,7 @@ impl Heightfield {
     /// # Errors
     ///
     /// Returns an error if the heightfield has too many layers.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn into_compact(
         self,
         walkable_height: u16,
@@ -146,6 +152,7 @@ impl Heightfield {
             spans: vec![CompactSpan::default(); walkable_span_count],
             dist: vec![],
             areas: vec![AreaType::NOT_WALKABLE; walkable_span_count],
+            height_offsets: vec![0; walkable_span_count],
         };
         compact_heightfield.aabb.max.y += walkable_height as f32 * compact_heightfield.cell_height;
 
@@ -179,6 +186,7 @@ impl Heightfield {
                     let height = (top.saturating_sub(bot)).min(u8::MAX.into()) as u8;
                     compact_heightfield.spans[cell_index].set_height(height);
                     compact_heightfield.areas[cell_index] = span.area;
+                    compact_heightfield.height_offsets[cell_index] = span.top_offset;
                     cell_index += 1;
                     cell.inc_count();
                 }
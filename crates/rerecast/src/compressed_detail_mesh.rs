@@ -0,0 +1,393 @@
+//! A compact storage format for [`DetailNavmesh`]es, which usually make up most of a navmesh's memory on large terrains.
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::prelude::*;
+use glam::{U16Vec2, Vec2, Vec3, Vec3Swizzles as _};
+use thiserror::Error;
+
+use crate::{Aabb3d, DetailNavmesh, PolygonNavmesh, SubMesh};
+
+/// A lossy, compressed version of a [`DetailNavmesh`], created with [`DetailNavmesh::compress`].
+///
+/// The compression relies on two observations:
+/// - The first vertices of every sub-mesh lie on the vertices of its polygon in the [`PolygonNavmesh`] on the xz-plane.
+///   Only their quantized heights relative to the polygon vertices are stored in [`Self::corner_heights`],
+///   the rest is taken from the shared vertex pool of the polygon mesh when decompressing.
+/// - The remaining detail vertices lie close to the plane of their polygon.
+///   They are stored as quantized offsets from the polygon's bounds on the xz-plane and quantized heights relative to the plane.
+///
+/// Use [`Self::decompress`] to get a regular [`DetailNavmesh`] back. The error introduced by the quantization
+/// is at most half of [`Self::horizontal_quantum`] on the xz-plane and half of [`Self::vertical_quantum`] along the y-axis.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct CompressedDetailNavmesh {
+    /// The sub-mesh data, in the same order as the polygons of the [`PolygonNavmesh`].
+    ///
+    /// [`SubMesh::base_vertex_index`] and [`SubMesh::vertex_count`] only refer to the additional detail vertices in [`Self::vertices`].
+    pub meshes: Vec<SubMesh>,
+    /// The additional detail vertices of all sub-meshes, excluding the polygon vertices.
+    pub vertices: Vec<CompressedDetailVertex>,
+    /// The heights of the polygon vertices of all sub-meshes in the same order as the polygons,
+    /// relative to one cell above the polygon vertices in units of [`Self::vertical_quantum`].
+    ///
+    /// These are zero unless the detail mesh was changed after building,
+    /// e.g. by [`DetailNavmesh::stitch_detail_seams`] or [`DetailNavmesh::offset_areas`].
+    pub corner_heights: Vec<i16>,
+    /// The mesh triangles, see [`DetailNavmesh::triangles`].
+    ///
+    /// Indices smaller than the vertex count of the sub-mesh's polygon refer to the polygon's vertices,
+    /// the rest refer to the sub-mesh's additional vertices in [`Self::vertices`].
+    pub triangles: Vec<[u8; 3]>,
+    /// Flags corresponding to [`Self::triangles`], see [`DetailNavmesh::triangle_flags`].
+    pub triangle_flags: Vec<u8>,
+    /// The size of one quantization step on the xz-plane. `[Limit: > 0] [Units: wu]`
+    pub horizontal_quantum: f32,
+    /// The size of one quantization step along the y-axis. `[Limit: > 0] [Units: wu]`
+    pub vertical_quantum: f32,
+}
+
+/// A detail vertex in [`CompressedDetailNavmesh::vertices`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct CompressedDetailVertex {
+    /// The position on the xz-plane relative to the minimum corner of the polygon's bounds,
+    /// in units of [`CompressedDetailNavmesh::horizontal_quantum`].
+    pub offset: U16Vec2,
+    /// The height relative to the plane of the polygon, in units of [`CompressedDetailNavmesh::vertical_quantum`].
+    pub height: i16,
+}
+
+/// Settings for [`DetailNavmesh::compress`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetailCompressionSettings {
+    /// The size of one quantization step on the xz-plane. `[Limit: > 0] [Units: wu]`
+    ///
+    /// Polygons can be at most `u16::MAX` steps wide.
+    pub horizontal_quantum: f32,
+    /// The size of one quantization step along the y-axis. `[Limit: > 0] [Units: wu]`
+    ///
+    /// Detail vertices can be at most `i16::MAX` steps above or below the plane of their polygon.
+    pub vertical_quantum: f32,
+}
+
+impl Default for DetailCompressionSettings {
+    fn default() -> Self {
+        Self {
+            horizontal_quantum: 0.01,
+            vertical_quantum: 0.01,
+        }
+    }
+}
+
+impl DetailNavmesh {
+    /// Compresses the detail mesh for storage, see [`CompressedDetailNavmesh`].
+    ///
+    /// `mesh` must be the polygon mesh this detail mesh was built from.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn compress(
+        &self,
+        mesh: &PolygonNavmesh,
+        settings: &DetailCompressionSettings,
+    ) -> Result<CompressedDetailNavmesh, DetailCompressionError> {
+        if self.meshes.len() != mesh.polygon_count() {
+            return Err(DetailCompressionError::PolygonCountMismatch {
+                detail: self.meshes.len(),
+                polygon: mesh.polygon_count(),
+            });
+        }
+        let mut compressed = CompressedDetailNavmesh {
+            meshes: Vec::with_capacity(self.meshes.len()),
+            vertices: Vec::new(),
+            corner_heights: Vec::new(),
+            triangles: self.triangles.clone(),
+            triangle_flags: self.triangle_flags.clone(),
+            horizontal_quantum: settings.horizontal_quantum,
+            vertical_quantum: settings.vertical_quantum,
+        };
+        for (polygon, submesh) in self.meshes.iter().enumerate() {
+            let polygon_vertex_count = mesh.polygon_vertices(polygon).len();
+            if (submesh.vertex_count as usize) < polygon_vertex_count {
+                return Err(DetailCompressionError::MissingPolygonVertices { polygon });
+            }
+            let plane = PolygonPlane::new(mesh, polygon);
+            let vertices = &self.vertices[submesh.base_vertex_index as usize..]
+                [..submesh.vertex_count as usize];
+
+            for (corner, vertex) in mesh
+                .polygon_world_vertices(polygon)
+                .zip(&vertices[..polygon_vertex_count])
+            {
+                // The detail mesh builder places the polygon vertices one cell above the polygon mesh.
+                let corner = corner + Vec3::Y * mesh.cell_height;
+                if corner.xz().distance(vertex.xz()) > settings.horizontal_quantum * 0.5 {
+                    return Err(DetailCompressionError::CornerMoved { polygon });
+                }
+                let height = ((vertex.y - corner.y) / settings.vertical_quantum).round();
+                if height.abs() > i16::MAX as f32 {
+                    return Err(DetailCompressionError::VertexOutOfRange { polygon });
+                }
+                compressed.corner_heights.push(height as i16);
+            }
+
+            let base_vertex_index = compressed.vertices.len() as u32;
+            for vertex in &vertices[polygon_vertex_count..] {
+                let offset = (vertex.xz() - plane.min) / settings.horizontal_quantum;
+                let height = (vertex.y - plane.height_at(vertex.xz())) / settings.vertical_quantum;
+                let offset = offset.round();
+                let height = height.round();
+                if offset.min_element() < 0.0
+                    || offset.max_element() > u16::MAX as f32
+                    || height.abs() > i16::MAX as f32
+                {
+                    return Err(DetailCompressionError::VertexOutOfRange { polygon });
+                }
+                compressed.vertices.push(CompressedDetailVertex {
+                    offset: offset.as_u16vec2(),
+                    height: height as i16,
+                });
+            }
+            compressed.meshes.push(SubMesh {
+                base_vertex_index,
+                vertex_count: compressed.vertices.len() as u32 - base_vertex_index,
+                base_triangle_index: submesh.base_triangle_index,
+                triangle_count: submesh.triangle_count,
+            });
+        }
+        Ok(compressed)
+    }
+
+    /// Returns the number of bytes used by the detail mesh, excluding the size of the struct itself.
+    pub fn size_in_bytes(&self) -> usize {
+        self.meshes.len() * size_of::<SubMesh>()
+            + self.vertices.len() * size_of::<Vec3>()
+            + self.triangles.len() * size_of::<[u8; 3]>()
+            + self.triangle_flags.len() * size_of::<u8>()
+    }
+}
+
+impl CompressedDetailNavmesh {
+    /// Restores a regular [`DetailNavmesh`].
+    ///
+    /// `mesh` must be the polygon mesh that was passed to [`DetailNavmesh::compress`].
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn decompress(&self, mesh: &PolygonNavmesh) -> DetailNavmesh {
+        let mut detail = DetailNavmesh {
+            meshes: Vec::with_capacity(self.meshes.len()),
+            vertices: Vec::with_capacity(
+                self.vertices.len() + mesh.polygon_count() * mesh.max_vertices_per_polygon as usize,
+            ),
+            triangles: self.triangles.clone(),
+            triangle_flags: self.triangle_flags.clone(),
+        };
+        let mut corner_heights = self.corner_heights.iter();
+        for (polygon, submesh) in self.meshes.iter().enumerate() {
+            let base_vertex_index = detail.vertices.len() as u32;
+            detail
+                .vertices
+                .extend(mesh.polygon_world_vertices(polygon).map(|vertex| {
+                    let height = corner_heights.next().copied().unwrap_or_default();
+                    vertex + Vec3::Y * (mesh.cell_height + height as f32 * self.vertical_quantum)
+                }));
+            let plane = PolygonPlane::new(mesh, polygon);
+            let vertices = &self.vertices[submesh.base_vertex_index as usize..]
+                [..submesh.vertex_count as usize];
+            detail.vertices.extend(vertices.iter().map(|vertex| {
+                let xz = plane.min + vertex.offset.as_vec2() * self.horizontal_quantum;
+                let y = plane.height_at(xz) + vertex.height as f32 * self.vertical_quantum;
+                Vec3::new(xz.x, y, xz.y)
+            }));
+            detail.meshes.push(SubMesh {
+                base_vertex_index,
+                vertex_count: detail.vertices.len() as u32 - base_vertex_index,
+                base_triangle_index: submesh.base_triangle_index,
+                triangle_count: submesh.triangle_count,
+            });
+        }
+        detail
+    }
+
+    /// Returns the number of bytes used by the compressed detail mesh, excluding the size of the struct itself.
+    pub fn size_in_bytes(&self) -> usize {
+        self.meshes.len() * size_of::<SubMesh>()
+            + self.vertices.len() * size_of::<CompressedDetailVertex>()
+            + self.corner_heights.len() * size_of::<i16>()
+            + self.triangles.len() * size_of::<[u8; 3]>()
+            + self.triangle_flags.len() * size_of::<u8>()
+    }
+}
+
+/// The plane through a polygon of a [`PolygonNavmesh`], fitted with Newell's method.
+struct PolygonPlane {
+    /// The minimum corner of the polygon's bounds on the xz-plane.
+    min: Vec2,
+    centroid: Vec3,
+    normal: Vec3,
+}
+
+impl PolygonPlane {
+    fn new(mesh: &PolygonNavmesh, polygon: usize) -> Self {
+        let aabb = mesh.polygon_aabb(polygon).unwrap_or(Aabb3d {
+            min: mesh.aabb.min,
+            max: mesh.aabb.min,
+        });
+        let vertices = mesh.polygon_world_vertices(polygon).collect::<Vec<_>>();
+        let mut normal = Vec3::ZERO;
+        let mut centroid = Vec3::ZERO;
+        for (i, current) in vertices.iter().enumerate() {
+            let next = vertices[(i + 1) % vertices.len()];
+            normal.x += (current.y - next.y) * (current.z + next.z);
+            normal.y += (current.z - next.z) * (current.x + next.x);
+            normal.z += (current.x - next.x) * (current.y + next.y);
+            centroid += *current;
+        }
+        if !vertices.is_empty() {
+            centroid /= vertices.len() as f32;
+        }
+        Self {
+            min: aabb.min.xz(),
+            centroid,
+            normal,
+        }
+    }
+
+    fn height_at(&self, point: Vec2) -> f32 {
+        if self.normal.y.abs() <= f32::EPSILON {
+            return self.centroid.y;
+        }
+        self.centroid.y
+            - (self.normal.x * (point.x - self.centroid.x)
+                + self.normal.z * (point.y - self.centroid.z))
+                / self.normal.y
+    }
+}
+
+/// Errors that can occur when compressing a [`DetailNavmesh`] with [`DetailNavmesh::compress`].
+#[derive(Error, Debug)]
+pub enum DetailCompressionError {
+    /// Happens when the detail mesh was not built from the given polygon mesh.
+    #[error(
+        "Detail mesh has {detail} sub-meshes, but the polygon mesh has {polygon} polygons. Was the detail mesh built from this polygon mesh?"
+    )]
+    PolygonCountMismatch {
+        /// The number of sub-meshes in the detail mesh
+        detail: usize,
+        /// The number of polygons in the polygon mesh
+        polygon: usize,
+    },
+    /// Happens when a sub-mesh does not start with the vertices of its polygon.
+    #[error("The sub-mesh of polygon {polygon} has fewer vertices than the polygon itself")]
+    MissingPolygonVertices {
+        /// The index of the polygon
+        polygon: usize,
+    },
+    /// Happens when the first vertices of a sub-mesh do not lie on the vertices of its polygon on the xz-plane.
+    #[error(
+        "The sub-mesh of polygon {polygon} does not start with the vertices of the polygon. Was the detail mesh built from this polygon mesh?"
+    )]
+    CornerMoved {
+        /// The index of the polygon
+        polygon: usize,
+    },
+    /// Happens when a detail vertex cannot be represented with the chosen quantization.
+    #[error(
+        "A detail vertex of polygon {polygon} lies too far from the polygon to be quantized. Try a larger quantum."
+    )]
+    VertexOutOfRange {
+        /// The index of the polygon
+        polygon: usize,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{U16Vec3, UVec3, Vec3A};
+
+    use super::*;
+    use crate::{AreaType, NavmeshConfig, TriMesh, build_solo_navmesh, test_utils::quad_navmesh};
+
+    #[test]
+    fn compression_roundtrip() {
+        // A single 4x4 quad sloping up along the x-axis
+        let mesh = quad_navmesh(
+            vec![
+                U16Vec3::new(0, 0, 0),
+                U16Vec3::new(0, 0, 4),
+                U16Vec3::new(4, 2, 4),
+                U16Vec3::new(4, 2, 0),
+            ],
+            &[[0, 1, 2, 3]],
+        );
+        let detail = DetailNavmesh {
+            meshes: vec![SubMesh {
+                base_vertex_index: 0,
+                vertex_count: 5,
+                base_triangle_index: 0,
+                triangle_count: 4,
+            }],
+            vertices: vec![
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec3::new(0.0, 1.0, 4.0),
+                Vec3::new(4.0, 3.0, 4.0),
+                Vec3::new(4.0, 3.0, 0.0),
+                // A bump in the middle
+                Vec3::new(2.0, 2.5, 2.0),
+            ],
+            triangles: vec![[0, 1, 4], [1, 2, 4], [2, 3, 4], [3, 0, 4]],
+            triangle_flags: vec![0; 4],
+        };
+
+        let compressed = detail
+            .compress(&mesh, &DetailCompressionSettings::default())
+            .unwrap();
+        assert_eq!(compressed.vertices.len(), 1);
+        assert!(compressed.size_in_bytes() < detail.size_in_bytes());
+
+        let decompressed = compressed.decompress(&mesh);
+        assert_eq!(decompressed.meshes, detail.meshes);
+        assert_eq!(decompressed.triangles, detail.triangles);
+        for (actual, expected) in decompressed.vertices.iter().zip(&detail.vertices) {
+            assert!(actual.distance(*expected) < 0.01);
+        }
+    }
+
+    #[test]
+    fn compression_roundtrip_keeps_area_offsets() {
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 0.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2), UVec3::new(0, 2, 3)],
+            area_types: vec![AreaType::NOT_WALKABLE; 2],
+            materials: Vec::new(),
+        };
+        let config = NavmeshConfig {
+            border_size: 0,
+            area_height_offsets: vec![(AreaType::DEFAULT_WALKABLE, 0.5)],
+            ..Default::default()
+        };
+        let (mesh, detail) = build_solo_navmesh(&trimesh, &config).unwrap();
+        let settings = DetailCompressionSettings::default();
+        let compressed = detail.compress(&mesh, &settings).unwrap();
+        assert!(compressed.corner_heights.iter().all(|&height| height != 0));
+
+        let decompressed = compressed.decompress(&mesh);
+        assert_eq!(decompressed.meshes.len(), detail.meshes.len());
+        assert_eq!(decompressed.vertices.len(), detail.vertices.len());
+        for (actual, expected) in decompressed.vertices.iter().zip(&detail.vertices) {
+            assert!(actual.distance(*expected) <= settings.vertical_quantum);
+        }
+    }
+}
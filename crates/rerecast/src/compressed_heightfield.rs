@@ -0,0 +1,295 @@
+//! A compact storage format for [`Heightfield`]s, e.g. to cache the voxelized geometry of a tile between rebuilds.
+
+use thiserror::Error;
+
+use crate::{
+    Aabb3d, AreaType, Heightfield,
+    span::{Span, SpanKey, Spans},
+};
+
+/// A lossless, compressed version of a [`Heightfield`], created with [`Heightfield::compress`].
+///
+/// The compression relies on two observations:
+/// - Neighboring columns are usually identical, e.g. across a flat floor or empty space.
+///   Consecutive identical columns in row-major order are stored once as a [`HeightfieldColumnRun`].
+/// - The spans of a column are sorted from bottom to top and do not overlap.
+///   Every span is stored relative to the span below it in a [`CompressedSpan`], which makes more columns identical.
+///
+/// Only the voxel data and [`Heightfield::sub_voxel_heights`] are kept. The rasterization settings,
+/// i.e. [`Heightfield::boundary`], [`Heightfield::exclusion_volumes`] and [`Heightfield::record_sources`],
+/// as well as the recorded [`Heightfield::sources`], are reset to their defaults by [`Self::decompress`].
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompressedHeightfield {
+    /// See [`Heightfield::width`].
+    pub width: u16,
+    /// See [`Heightfield::height`].
+    pub height: u16,
+    /// See [`Heightfield::aabb`].
+    pub aabb: Aabb3d,
+    /// See [`Heightfield::cell_size`].
+    pub cell_size: f32,
+    /// See [`Heightfield::cell_height`].
+    pub cell_height: f32,
+    /// See [`Heightfield::sub_voxel_heights`].
+    pub sub_voxel_heights: bool,
+    /// The runs of identical columns, covering all columns of the heightfield in row-major order.
+    pub runs: Vec<HeightfieldColumnRun>,
+    /// The spans of the columns of all runs, in the order of [`Self::runs`].
+    pub spans: Vec<CompressedSpan>,
+}
+
+/// A run of consecutive identical columns in [`CompressedHeightfield::runs`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeightfieldColumnRun {
+    /// The number of columns in the run.
+    pub column_count: u32,
+    /// The number of spans in each column of the run, stored once in [`CompressedHeightfield::spans`].
+    pub span_count: u32,
+}
+
+/// A span in [`CompressedHeightfield::spans`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompressedSpan {
+    /// The distance from the ceiling of the span below to the floor of this span,
+    /// or the floor itself for the lowest span of a column. `[Units: vx]`
+    pub gap: u16,
+    /// The distance from the floor to the ceiling of the span. `[Units: vx]`
+    pub thickness: u16,
+    /// See [`Span::area`].
+    pub area: AreaType,
+    /// See [`Span::top_offset`].
+    pub top_offset: u8,
+}
+
+impl Heightfield {
+    /// Compresses the heightfield for storage, see [`CompressedHeightfield`].
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn compress(&self) -> CompressedHeightfield {
+        let mut compressed = CompressedHeightfield {
+            width: self.width,
+            height: self.height,
+            aabb: self.aabb,
+            cell_size: self.cell_size,
+            cell_height: self.cell_height,
+            sub_voxel_heights: self.sub_voxel_heights,
+            runs: Vec::new(),
+            spans: Vec::new(),
+        };
+        let mut column = Vec::new();
+        for column_index in 0..self.spans.len() {
+            column.clear();
+            let mut ceiling = 0;
+            for span in self.column_spans_at_index(column_index) {
+                // Wrapping, so that even overlapping spans survive the round trip.
+                column.push(CompressedSpan {
+                    gap: span.min.wrapping_sub(ceiling),
+                    thickness: span.max.wrapping_sub(span.min),
+                    area: span.area,
+                    top_offset: span.top_offset,
+                });
+                ceiling = span.max;
+            }
+            match compressed.runs.last_mut() {
+                Some(run)
+                    if compressed.spans[compressed.spans.len() - run.span_count as usize..]
+                        == column[..] =>
+                {
+                    run.column_count += 1;
+                }
+                _ => {
+                    compressed.runs.push(HeightfieldColumnRun {
+                        column_count: 1,
+                        span_count: column.len() as u32,
+                    });
+                    compressed.spans.extend_from_slice(&column);
+                }
+            }
+        }
+        compressed
+    }
+
+    /// Returns the number of bytes used by the spans of the heightfield, excluding the size of the struct itself
+    /// and the rasterization settings.
+    pub fn size_in_bytes(&self) -> usize {
+        self.spans.len() * size_of::<Option<SpanKey>>()
+            + self.allocated_spans.len() * size_of::<Span>()
+    }
+}
+
+impl CompressedHeightfield {
+    /// Restores a regular [`Heightfield`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the runs do not match the size of the heightfield or the number of spans,
+    /// e.g. because the data was corrupted.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn decompress(&self) -> Result<Heightfield, HeightfieldDecompressionError> {
+        let expected_columns = self.width as usize * self.height as usize;
+        let columns: usize = self.runs.iter().map(|run| run.column_count as usize).sum();
+        if columns != expected_columns {
+            return Err(HeightfieldDecompressionError::ColumnCountMismatch {
+                expected: expected_columns,
+                actual: columns,
+            });
+        }
+        let spans: usize = self.runs.iter().map(|run| run.span_count as usize).sum();
+        if spans != self.spans.len() {
+            return Err(HeightfieldDecompressionError::SpanCountMismatch {
+                expected: self.spans.len(),
+                actual: spans,
+            });
+        }
+        let span_count = self
+            .runs
+            .iter()
+            .map(|run| run.column_count as usize * run.span_count as usize)
+            .sum();
+
+        let mut heightfield = Heightfield {
+            width: self.width,
+            height: self.height,
+            aabb: self.aabb,
+            cell_size: self.cell_size,
+            cell_height: self.cell_height,
+            spans: vec![None; expected_columns],
+            allocated_spans: Spans::with_min_capacity(span_count),
+            sub_voxel_heights: self.sub_voxel_heights,
+            ..Default::default()
+        };
+        let mut column_index = 0;
+        let mut first_span = 0;
+        for run in &self.runs {
+            let compressed_spans = &self.spans[first_span..][..run.span_count as usize];
+            first_span += run.span_count as usize;
+            for _ in 0..run.column_count {
+                let mut ceiling = 0_u16;
+                let mut previous_span_key: Option<SpanKey> = None;
+                for compressed_span in compressed_spans {
+                    let min = ceiling.wrapping_add(compressed_span.gap);
+                    let max = min.wrapping_add(compressed_span.thickness);
+                    ceiling = max;
+                    let span_key = heightfield.allocated_spans.insert(Span {
+                        min,
+                        max,
+                        area: compressed_span.area,
+                        top_offset: compressed_span.top_offset,
+                        next: None,
+                    });
+                    match previous_span_key {
+                        Some(previous_span_key) => {
+                            heightfield.span_mut(previous_span_key).next = Some(span_key);
+                        }
+                        None => heightfield.spans[column_index] = Some(span_key),
+                    }
+                    previous_span_key = Some(span_key);
+                }
+                column_index += 1;
+            }
+        }
+        Ok(heightfield)
+    }
+
+    /// Returns the number of bytes used by the compressed heightfield, excluding the size of the struct itself.
+    pub fn size_in_bytes(&self) -> usize {
+        self.runs.len() * size_of::<HeightfieldColumnRun>()
+            + self.spans.len() * size_of::<CompressedSpan>()
+    }
+}
+
+/// Errors that can occur when decompressing a [`CompressedHeightfield`] with [`CompressedHeightfield::decompress`].
+#[derive(Error, Debug)]
+pub enum HeightfieldDecompressionError {
+    /// Happens when the runs do not cover exactly the columns of the heightfield.
+    #[error("The runs cover {actual} columns, but the heightfield has {expected} columns")]
+    ColumnCountMismatch {
+        /// The number of columns of the heightfield, i.e. width * height
+        expected: usize,
+        /// The number of columns covered by the runs
+        actual: usize,
+    },
+    /// Happens when the runs do not refer to exactly the stored spans.
+    #[error("The runs refer to {actual} spans, but {expected} spans are stored")]
+    SpanCountMismatch {
+        /// The number of stored spans
+        expected: usize,
+        /// The number of spans the runs refer to
+        actual: usize,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec3, Vec3A};
+
+    use super::*;
+    use crate::{HeightfieldBuilder, TriMesh};
+
+    fn columns(heightfield: &Heightfield) -> Vec<Vec<Span>> {
+        (0..heightfield.spans.len())
+            .map(|column| {
+                heightfield
+                    .column_spans_at_index(column)
+                    .map(|span| Span {
+                        next: None,
+                        ..span.clone()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_heightfield() {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 5.0, 5.0]),
+            cell_size: 0.5,
+            cell_height: 0.25,
+        }
+        .build()
+        .unwrap();
+        heightfield.sub_voxel_heights = true;
+        // A floor covering the whole heightfield and a ramp above part of it.
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(-5.0, -1.0, -5.0),
+                Vec3A::new(-5.0, -1.0, 5.0),
+                Vec3A::new(5.0, -1.0, 5.0),
+                Vec3A::new(5.0, -1.0, -5.0),
+                Vec3A::new(-2.0, 0.0, -2.0),
+                Vec3A::new(-2.0, 0.0, 2.0),
+                Vec3A::new(2.0, 1.5, 2.0),
+            ],
+            indices: vec![
+                UVec3::new(0, 1, 2),
+                UVec3::new(0, 2, 3),
+                UVec3::new(4, 5, 6),
+            ],
+            area_types: vec![
+                AreaType::DEFAULT_WALKABLE,
+                AreaType::DEFAULT_WALKABLE,
+                AreaType::new(3),
+            ],
+            materials: Vec::new(),
+        };
+        heightfield.rasterize_triangles(&trimesh, 1).unwrap();
+
+        let compressed = heightfield.compress();
+        assert!(compressed.size_in_bytes() * 4 < heightfield.size_in_bytes());
+        let decompressed = compressed.decompress().unwrap();
+        assert_eq!(decompressed.width, heightfield.width);
+        assert_eq!(decompressed.height, heightfield.height);
+        assert!(decompressed.sub_voxel_heights);
+        assert_eq!(columns(&decompressed), columns(&heightfield));
+
+        let mut corrupted = compressed;
+        corrupted.runs[0].column_count += 1;
+        assert!(matches!(
+            corrupted.decompress(),
+            Err(HeightfieldDecompressionError::ColumnCountMismatch { .. })
+        ));
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{Aabb3d, BuildContoursFlags};
+use glam::Vec2;
+
+use crate::{
+    Aabb3d, AreaRegistry, AreaType, BuildContoursFlags, ConvexVolume, ExclusionVolume, FlagVolume,
+    PathVolume, WatershedSettings,
+};
 
 /// Specifies a configuration to use when performing Recast builds.
 ///
@@ -20,6 +25,8 @@ use crate::{Aabb3d, BuildContoursFlags};
 /// > If your game world uses meters as units, a reasonable starting point for a human-sized agent
 /// > might be a radius of 0.4 and a height of 2.0.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(default))]
 pub struct NavmeshConfig {
     /// The width of the field along the x-axis. `[Limit: >= 0] [Units: vx]`
     pub width: u16,
@@ -29,7 +36,7 @@ pub struct NavmeshConfig {
 
     /// The width/height size of tiles on the xz-plane. `[Limit: >= 0] [Units: vx]`
     ///
-    /// This field is only used when building multi-tile meshes.
+    /// This field is only used when building multi-tile meshes with [`TiledNavmeshBuilder`](crate::TiledNavmeshBuilder).
     pub tile_size: u16,
 
     /// The size of the non-navigable border around the heightfield. `[Limit: >=0] [Units: vx]`
@@ -84,6 +91,12 @@ pub struct NavmeshConfig {
     /// The practical upper limit for this parameter is usually around `85.0.to_radians()`.
     pub walkable_slope_angle: f32,
 
+    /// Walkable slope angles for specific [triangle materials](crate::TriMesh::materials), overriding
+    /// [`Self::walkable_slope_angle`] for them. `[Limits: 0 <= value < 0.5*π] [Units: Radians]`
+    ///
+    /// See [`TriMesh::mark_walkable_triangles_by_material`](crate::TriMesh::mark_walkable_triangles_by_material).
+    pub material_slope_angles: Vec<(u8, f32)>,
+
     /// Minimum floor to 'ceiling' height that will still allow the floor area to
     /// be considered walkable. `[Limit: >= 3] [Units: vx]`
     ///
@@ -95,6 +108,17 @@ pub struct NavmeshConfig {
     /// The value is usually set to the maximum agent height
     pub walkable_height: u16,
 
+    /// The minimum vertical clearance between a walkable layer and the layer above it,
+    /// e.g. between a road and a bridge crossing over it. `[Limit: >= walkable_height] [Units: vx]`
+    ///
+    /// Spans of the lower layer with less clearance are discarded
+    /// by [`Heightfield::filter_walkable_low_height_spans`](crate::Heightfield::filter_walkable_low_height_spans).
+    /// Both layers are kept as separate regions, contours, and polygons otherwise.
+    /// Increase this above [`Self::walkable_height`] to keep agents from squeezing under low overpasses.
+    ///
+    /// If `None`, [`Self::walkable_height`] is used.
+    pub min_layer_separation: Option<u16>,
+
     /// Maximum ledge height that is considered to still be traversable. `[Limit: >=0] [Units: vx]`
     ///
     /// The walkable_climb value defines the maximum height of ledges and steps that the agent can walk up.
@@ -177,18 +201,82 @@ pub struct NavmeshConfig {
     /// If you see small patches missing here and there, you could lower the [`Self::min_region_area`] value.
     pub merge_region_area: u16,
 
+    /// Whether regions, and with them contours and polygons, are split wherever the area type changes.
+    ///
+    /// When enabled, no polygon ever spans two area types, so area costs apply exactly where the areas were marked.
+    /// When disabled, neighboring areas are merged into the same regions, which results in fewer polygons.
+    /// Every polygon then gets the area covering most of its region, see
+    /// [`CompactHeightfield::assign_region_areas`](crate::CompactHeightfield::assign_region_areas).
+    pub split_area_boundaries: bool,
+
+    /// How the watershed floods the distance field when building regions, trading region quality for build time.
+    pub watershed: WatershedSettings,
+
     /// The maximum number of vertices allowed for polygons generated during the
     /// contour to polygon conversion process. `[Limit: >= 3]`
     pub max_vertices_per_polygon: u16,
 
+    /// Whether to record the height of span tops more precisely than [`Self::cell_height`] during rasterization.
+    ///
+    /// The detail mesh then follows ramps and slopes smoothly instead of stair-stepping along the voxel grid,
+    /// which allows using a coarser cell height. See [`Heightfield::sub_voxel_heights`](crate::Heightfield::sub_voxel_heights).
+    pub sub_voxel_heights: bool,
+
+    /// An optional boundary polygon on the xz-plane outside of which no navmesh is generated. `[Units: wu]`
+    ///
+    /// See [`Heightfield::boundary`](crate::Heightfield::boundary).
+    pub boundary: Option<Vec<Vec2>>,
+
+    /// Volumes in which no navmesh is generated. See [`ExclusionVolume`].
+    pub exclusion_volumes: Vec<ExclusionVolume>,
+
+    /// Volumes that add flags to the polygons inside them, e.g. capability or faction masks. See [`FlagVolume`].
+    pub flag_volumes: Vec<FlagVolume>,
+
+    /// Volumes that set the area of the walkable spans inside them after erosion, e.g. water or roads.
+    ///
+    /// Marked in order with [`CompactHeightfield::mark_convex_poly_area`](crate::CompactHeightfield::mark_convex_poly_area),
+    /// so later volumes win where they overlap.
+    pub area_volumes: Vec<ConvexVolume>,
+
+    /// Paths that set the area of the walkable spans they cover after the [`Self::area_volumes`], e.g. road networks.
+    ///
+    /// Marked in order with [`CompactHeightfield::mark_path_area`](crate::CompactHeightfield::mark_path_area),
+    /// so later paths win where they overlap.
+    pub area_paths: Vec<PathVolume>,
+
+    /// Whether to generate a [`DetailNavmesh`](crate::DetailNavmesh) at all.
+    ///
+    /// Detail meshes usually make up most of a navmesh's memory. Projects that are memory-constrained and have mostly flat
+    /// walkable surfaces can skip them, in which case height queries fall back to interpolating the polygons,
+    /// see [`PolygonNavmesh::surface_height`](crate::PolygonNavmesh::surface_height).
+    pub build_detail_mesh: bool,
+
     /// Sets the sampling distance to use when generating the detail mesh.
     /// (For height detail only.) `[Limits: 0 or >= 0.9] [Units: wu]`
     pub detail_sample_dist: f32,
 
+    /// Sets the sampling distance to use along polygon edges when generating the detail mesh,
+    /// separately from the interior sampling distance. (For height detail only.) `[Limits: 0 or >= 0.9] [Units: wu]`
+    ///
+    /// If `None`, [`Self::detail_sample_dist`] is used for the edges as well.
+    pub detail_edge_sample_dist: Option<f32>,
+
     /// The maximum distance the detail mesh surface should deviate from heightfield
     /// data. (For height detail only.) `[Limit: >=0] [Units: wu]`
     pub detail_sample_max_error: f32,
 
+    /// Vertical offsets applied to the detail mesh of polygons with the given area. `[Units: wu]`
+    ///
+    /// Lets queries return the height agents actually travel at on a surface,
+    /// e.g. raising the navmesh of a water area to swimming height.
+    /// See [`DetailNavmesh::offset_areas`](crate::DetailNavmesh::offset_areas).
+    pub area_height_offsets: Vec<(AreaType, f32)>,
+
+    /// The names used for area types in the [`BuildWarning`](crate::BuildWarning)s emitted during the build.
+    /// If `None`, area types are printed by their ids.
+    pub area_registry: Option<AreaRegistry>,
+
     /// Flags controlling the [`ContourSet`](crate::ContourSet) generation process.
     pub contour_flags: BuildContoursFlags,
 }
@@ -199,18 +287,32 @@ impl Default for NavmeshConfig {
             cell_size: 0.3,
             cell_height: 0.2,
             walkable_slope_angle: 45.0_f32.to_radians(),
+            material_slope_angles: Vec::new(),
             walkable_height: 10,
+            min_layer_separation: None,
             walkable_climb: 4,
             walkable_radius: 2,
             min_region_area: 64,
             merge_region_area: 400,
+            split_area_boundaries: true,
+            watershed: WatershedSettings::default(),
             border_size: 5,
             max_simplification_error: 1.3,
             max_edge_len: 40,
             max_vertices_per_polygon: 6,
             contour_flags: BuildContoursFlags::TESSELLATE_SOLID_WALL_EDGES,
+            sub_voxel_heights: false,
+            boundary: None,
+            exclusion_volumes: Vec::new(),
+            flag_volumes: Vec::new(),
+            area_volumes: Vec::new(),
+            area_paths: Vec::new(),
+            build_detail_mesh: true,
             detail_sample_dist: 1.8,
+            detail_edge_sample_dist: None,
             detail_sample_max_error: 0.2,
+            area_height_offsets: Vec::new(),
+            area_registry: None,
             width: 0,
             height: 0,
             tile_size: 0,
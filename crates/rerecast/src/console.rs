@@ -0,0 +1,295 @@
+//! Navmesh queries as text commands, for integration into in-game developer consoles.
+//!
+//! Only available with the `console` feature.
+
+use std::fmt::Write as _;
+
+use glam::{Vec3, Vec3Swizzles as _};
+use thiserror::Error;
+
+use crate::{
+    AreaRegistry, BvTree, LayerConstraint, NavmeshQuery, NearestPolygon, PolygonNavmesh,
+    QueryFilter,
+};
+
+/// Executes text commands against a navmesh. See [`NavmeshConsole::HELP`] for the available commands.
+///
+/// Commands operate on the xz-plane. Points are snapped to the nearest polygon among all heights,
+/// so `nearest 3 4` finds the navmesh below or above `(3, 4)` regardless of the floor it is on.
+#[derive(Debug, Clone, Copy)]
+pub struct NavmeshConsole<'a> {
+    navmesh: &'a PolygonNavmesh,
+    tree: &'a BvTree,
+    /// How far points may be moved on the xz-plane to snap them to the navmesh. `[Limit: >= 0] [Units: wu]`
+    pub snap_distance: f32,
+    /// The names printed for area types. If `None`, only the built-in area types are named, see [`AreaRegistry::default`].
+    pub area_registry: Option<&'a AreaRegistry>,
+}
+
+impl<'a> NavmeshConsole<'a> {
+    /// The help text listing all commands.
+    pub const HELP: &'static str = "\
+help                         list all commands
+nearest <x> <z>              find the nearest polygon to a point
+area <x> <z>                 name the area type of the nearest polygon to a point
+raycast <x1> <z1> <x2> <z2>  cast a ray along the navmesh between two points
+path <x1> <z1> <x2> <z2>     find the polygons along the cheapest path between two points
+islands                      count the connected parts of the navmesh
+coverage                     report the walkable area and polygon count";
+
+    /// Creates a console for the navmesh, using `tree` to find polygons.
+    pub fn new(navmesh: &'a PolygonNavmesh, tree: &'a BvTree) -> Self {
+        Self {
+            navmesh,
+            tree,
+            snap_distance: 1.0,
+            area_registry: None,
+        }
+    }
+
+    /// Parses and executes a single command, returning its output.
+    pub fn execute(&self, command: &str) -> Result<String, ConsoleError> {
+        let mut words = command.split_whitespace();
+        let Some(name) = words.next() else {
+            return Ok(String::new());
+        };
+        let arguments: Vec<&str> = words.collect();
+        match name {
+            "help" => {
+                expect_arguments(name, &arguments, 0)?;
+                Ok(Self::HELP.to_string())
+            }
+            "nearest" => {
+                let [x, z] = parse_arguments(name, &arguments)?;
+                Ok(match self.snap(x, z) {
+                    Some((polygon, point)) => {
+                        format!("polygon {polygon} at {}", format_point(point))
+                    }
+                    None => "no polygon found".to_string(),
+                })
+            }
+            "area" => {
+                let [x, z] = parse_arguments(name, &arguments)?;
+                let Some((polygon, _)) = self.snap(x, z) else {
+                    return Ok("no polygon found".to_string());
+                };
+                let area = self.navmesh.areas[polygon];
+                let default_registry;
+                let registry = match self.area_registry {
+                    Some(registry) => registry,
+                    None => {
+                        default_registry = AreaRegistry::default();
+                        &default_registry
+                    }
+                };
+                Ok(format!("polygon {polygon} is {}", registry.display(area)))
+            }
+            "raycast" => {
+                let [x1, z1, x2, z2] = parse_arguments(name, &arguments)?;
+                let Some((polygon, start)) = self.snap(x1, z1) else {
+                    return Ok("start is not on the navmesh".to_string());
+                };
+                let raycast = self
+                    .navmesh
+                    .raycast(polygon, start, Vec3::new(x2, start.y, z2));
+                let mut output = format!("path {:?}", raycast.path);
+                match raycast.hit {
+                    Some(hit) => {
+                        let point = start.xz().lerp(Vec3::new(x2, 0.0, z2).xz(), hit.t);
+                        let _ = write!(
+                            output,
+                            ", hit polygon {} edge {} at t = {:.2} ({:.2}, {:.2})",
+                            hit.polygon, hit.edge, hit.t, point.x, point.y
+                        );
+                    }
+                    None => output.push_str(", reached end"),
+                }
+                Ok(output)
+            }
+            "path" => {
+                let [x1, z1, x2, z2] = parse_arguments(name, &arguments)?;
+                let Some((start_polygon, start)) = self.snap(x1, z1) else {
+                    return Ok("start is not on the navmesh".to_string());
+                };
+                let Some((end_polygon, end)) = self.snap(x2, z2) else {
+                    return Ok("end is not on the navmesh".to_string());
+                };
+                let path = NavmeshQuery::new(self.navmesh, self.tree).find_path(
+                    NearestPolygon {
+                        polygon: start_polygon,
+                        point: start,
+                    },
+                    NearestPolygon {
+                        polygon: end_polygon,
+                        point: end,
+                    },
+                    &QueryFilter::default(),
+                );
+                Ok(format!(
+                    "path {:?}, {} at {}, cost {:.2}",
+                    path.polygons,
+                    if path.complete {
+                        "reached end"
+                    } else {
+                        "stopped"
+                    },
+                    format_point(path.end),
+                    path.cost
+                ))
+            }
+            "islands" => {
+                expect_arguments(name, &arguments, 0)?;
+                let islands = self.navmesh.islands();
+                let count = islands.iter().max().map_or(0, |max| max + 1);
+                Ok(format!("{count} islands"))
+            }
+            "coverage" => {
+                expect_arguments(name, &arguments, 0)?;
+                let coverage = self.navmesh.coverage();
+                Ok(format!(
+                    "{} walkable polygons, {:.2} wu² walkable area, {:.2} wu² per polygon",
+                    coverage.polygon_count,
+                    coverage.walkable_area,
+                    coverage.average_polygon_area()
+                ))
+            }
+            _ => Err(ConsoleError::UnknownCommand(name.to_string())),
+        }
+    }
+
+    /// Finds the polygon nearest to `(x, z)` at any height.
+    fn snap(&self, x: f32, z: f32) -> Option<(usize, Vec3)> {
+        let aabb = &self.navmesh.aabb;
+        let center = Vec3::new(x, (aabb.min.y + aabb.max.y) * 0.5, z);
+        let half_extents = Vec3::new(
+            self.snap_distance,
+            (aabb.max.y - aabb.min.y) * 0.5 + 1.0,
+            self.snap_distance,
+        );
+        self.navmesh
+            .find_nearest_polygon(self.tree, center, half_extents, LayerConstraint::Any)
+            .map(|nearest| (nearest.polygon, nearest.point))
+    }
+}
+
+fn expect_arguments(
+    command: &str,
+    arguments: &[&str],
+    expected: usize,
+) -> Result<(), ConsoleError> {
+    if arguments.len() != expected {
+        return Err(ConsoleError::WrongArgumentCount {
+            command: command.to_string(),
+            expected,
+            actual: arguments.len(),
+        });
+    }
+    Ok(())
+}
+
+fn parse_arguments<const N: usize>(
+    command: &str,
+    arguments: &[&str],
+) -> Result<[f32; N], ConsoleError> {
+    expect_arguments(command, arguments, N)?;
+    let mut values = [0.0; N];
+    for (value, argument) in values.iter_mut().zip(arguments) {
+        *value = argument
+            .parse()
+            .map_err(|_| ConsoleError::InvalidNumber(argument.to_string()))?;
+    }
+    Ok(values)
+}
+
+fn format_point(point: Vec3) -> String {
+    format!("({:.2}, {:.2}, {:.2})", point.x, point.y, point.z)
+}
+
+/// Errors that can occur when executing a command with [`NavmeshConsole::execute`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleError {
+    /// The command does not exist.
+    #[error("Unknown command `{0}`, type `help` for a list of commands")]
+    UnknownCommand(String),
+    /// The command was given the wrong number of arguments.
+    #[error("`{command}` expects {expected} arguments, but got {actual}")]
+    WrongArgumentCount {
+        /// The name of the command.
+        command: String,
+        /// The number of arguments the command expects.
+        expected: usize,
+        /// The number of arguments that were given.
+        actual: usize,
+    },
+    /// An argument is not a number.
+    #[error("`{0}` is not a number")]
+    InvalidNumber(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AreaType, test_utils::GridNavmesh};
+
+    #[test]
+    fn executes_commands() {
+        let grid = GridNavmesh::parse(
+            "
+            ...#.
+            ...#.
+            ",
+        );
+        let tree = BvTree::new(&grid.navmesh);
+        let mut console = NavmeshConsole::new(&grid.navmesh, &tree);
+
+        assert_eq!(
+            console.execute("nearest 1.5 0.5").unwrap(),
+            "polygon 1 at (1.50, 0.00, 0.50)"
+        );
+        assert_eq!(
+            console.execute("area 1.5 0.5").unwrap(),
+            "polygon 1 is 'walkable'"
+        );
+        let mut registry = AreaRegistry::empty();
+        registry.register(AreaType::DEFAULT_WALKABLE, "grass");
+        console.area_registry = Some(&registry);
+        assert_eq!(
+            console.execute("area 1.5 0.5").unwrap(),
+            "polygon 1 is 'grass'"
+        );
+        assert_eq!(console.execute("islands").unwrap(), "2 islands");
+        assert_eq!(
+            console.execute("raycast 0.5 0.5 2.5 0.5").unwrap(),
+            "path [0, 1, 2], reached end"
+        );
+        assert!(
+            console
+                .execute("raycast 0.5 0.5 4.5 0.5")
+                .unwrap()
+                .contains("hit polygon 2 edge 2")
+        );
+        assert_eq!(
+            console.execute("path 0.5 0.5 2.5 1.5").unwrap(),
+            "path [0, 1, 5, 6], reached end at (2.50, 0.00, 1.50), cost 2.41"
+        );
+        assert!(
+            console
+                .execute("path 0.5 0.5 4.5 0.5")
+                .unwrap()
+                .contains("stopped at")
+        );
+        assert_eq!(console.execute("").unwrap(), "");
+        assert!(matches!(
+            console.execute("teleport"),
+            Err(ConsoleError::UnknownCommand(_))
+        ));
+        assert!(matches!(
+            console.execute("nearest 1"),
+            Err(ConsoleError::WrongArgumentCount { expected: 2, .. })
+        ));
+        assert_eq!(
+            console.execute("nearest a 1"),
+            Err(ConsoleError::InvalidNumber("a".to_string()))
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use glam::{IVec2, IVec3, U16Vec3, Vec3, Vec3Swizzles as _};
+
+use crate::{AreaType, ContourSet};
+
+/// A loop of contour edges in world space, merged from the contours of neighboring tiles by [`merge_tile_contours`].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContourOutline {
+    /// The world space points of the loop, in the order of the contours they were taken from.
+    /// The last point connects back to the first one if [`Self::closed`] is `true`.
+    pub points: Vec<Vec3>,
+    /// The area of the contour the loop starts in.
+    pub area: AreaType,
+    /// Whether the loop returns to its first point. Only `false` if the seams of the tiles did not line up.
+    pub closed: bool,
+}
+
+/// Merges the contours of adjacent tiles into world space loops, e.g. for editor overlays
+/// that would otherwise draw every seam between two tiles twice.
+///
+/// Edges of a contour lying on the border of its tile, which the navmesh turns into portals,
+/// are removed together with the edges running the other way along the same seam in the neighboring tile.
+/// Seam edges are first split at the seam vertices of the neighboring tile, so they do not need to line up exactly,
+/// and vertices of both tiles are welded if their heights differ by at most `walkable_climb`. `[Units: vx]`
+/// The remaining edges are stitched into loops, which may now span several tiles.
+///
+/// All sets must be built with the same cell size and cell height, from tiles of the same grid like those of a
+/// [`TiledNavmeshBuilder`](crate::TiledNavmeshBuilder). Seam edges without a counterpart, e.g. towards tiles without geometry, are kept.
+#[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+pub fn merge_tile_contours(sets: &[ContourSet], walkable_climb: u16) -> Vec<ContourOutline> {
+    let Some(first) = sets.first() else {
+        return Vec::new();
+    };
+    let origin = sets
+        .iter()
+        .fold(first.aabb.min, |origin, set| origin.min(set.aabb.min));
+    let (cell_size, cell_height) = (first.cell_size, first.cell_height);
+
+    // Collect the edges of all contours in cell units relative to `origin`.
+    let mut edges = Vec::new();
+    for (set_index, set) in sets.iter().enumerate() {
+        let offset = (set.aabb.min - origin) / Vec3::new(cell_size, cell_height, cell_size);
+        let offset = offset.round().as_ivec3();
+        let size = IVec2::new(set.width as i32, set.height as i32);
+        for contour in &set.contours {
+            let vertices = &contour.vertices;
+            for (i, (start, _)) in vertices.iter().enumerate() {
+                let end = vertices[(i + 1) % vertices.len()].0;
+                edges.push(OutlineEdge {
+                    set: set_index,
+                    start: offset + start.as_ivec3(),
+                    end: offset + end.as_ivec3(),
+                    area: contour.area,
+                    seam: seam_line(*start, end, size).map(|line| match line {
+                        SeamLine::X(x) => SeamLine::X(x + offset.x),
+                        SeamLine::Z(z) => SeamLine::Z(z + offset.z),
+                    }),
+                });
+            }
+        }
+    }
+
+    let edges = split_seam_edges(edges);
+    let edges = remove_shared_seams(edges, walkable_climb as i32);
+    stitch_loops(&edges, walkable_climb as i32)
+        .into_iter()
+        .map(|(loop_edges, closed)| ContourOutline {
+            points: loop_edges
+                .iter()
+                .map(|edge| {
+                    origin + edge.start.as_vec3() * Vec3::new(cell_size, cell_height, cell_size)
+                })
+                .collect(),
+            area: loop_edges
+                .first()
+                .map_or(AreaType::NOT_WALKABLE, |edge| edge.area),
+            closed,
+        })
+        .collect()
+}
+
+/// A directed contour edge in cell units relative to the origin of all tiles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OutlineEdge {
+    /// The index of the contour set the edge comes from.
+    set: usize,
+    start: IVec3,
+    end: IVec3,
+    area: AreaType,
+    /// The tile border the edge lies on, if any.
+    seam: Option<SeamLine>,
+}
+
+/// A tile border, given by its constant coordinate in cell units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum SeamLine {
+    X(i32),
+    Z(i32),
+}
+
+impl SeamLine {
+    /// Returns the coordinate of `point` along the line.
+    #[inline]
+    fn along(self, point: IVec3) -> i32 {
+        match self {
+            SeamLine::X(_) => point.z,
+            SeamLine::Z(_) => point.x,
+        }
+    }
+}
+
+/// Returns the border of a tile of `size` cells both `start` and `end` lie on, like the portal edges of
+/// [`ContourSet::into_polygon_mesh`].
+fn seam_line(start: U16Vec3, end: U16Vec3, size: IVec2) -> Option<SeamLine> {
+    let (start, end) = (start.xz().as_ivec2(), end.xz().as_ivec2());
+    if start.x == end.x && (start.x == 0 || start.x == size.x) {
+        Some(SeamLine::X(start.x))
+    } else if start.y == end.y && (start.y == 0 || start.y == size.y) {
+        Some(SeamLine::Z(start.y))
+    } else {
+        None
+    }
+}
+
+/// Splits every seam edge at the seam vertices of all other edges on the same line lying inside of it,
+/// so that the edges of neighboring tiles can be matched one to one.
+fn split_seam_edges(edges: Vec<OutlineEdge>) -> Vec<OutlineEdge> {
+    let mut seam_vertices: HashMap<SeamLine, Vec<i32>> = HashMap::new();
+    for edge in &edges {
+        if let Some(line) = edge.seam {
+            let vertices = seam_vertices.entry(line).or_default();
+            vertices.push(line.along(edge.start));
+            vertices.push(line.along(edge.end));
+        }
+    }
+    for vertices in seam_vertices.values_mut() {
+        vertices.sort_unstable();
+        vertices.dedup();
+    }
+
+    let mut split = Vec::with_capacity(edges.len());
+    for edge in edges {
+        let Some(line) = edge.seam else {
+            split.push(edge);
+            continue;
+        };
+        let (from, to) = (line.along(edge.start), line.along(edge.end));
+        let mut cuts: Vec<i32> = seam_vertices[&line]
+            .iter()
+            .copied()
+            .filter(|cut| (*cut > from.min(to)) && (*cut < from.max(to)))
+            .collect();
+        if to < from {
+            cuts.reverse();
+        }
+        let mut start = edge.start;
+        for cut in cuts {
+            let t = (cut - from) as f32 / (to - from) as f32;
+            let mut end = start;
+            match line {
+                SeamLine::X(_) => end.z = cut,
+                SeamLine::Z(_) => end.x = cut,
+            }
+            end.y = (edge.start.y as f32 + (edge.end.y - edge.start.y) as f32 * t).round() as i32;
+            split.push(OutlineEdge { start, end, ..edge });
+            start = end;
+        }
+        split.push(OutlineEdge { start, ..edge });
+    }
+    split
+}
+
+/// Removes every seam edge that runs the other way along the same seam as an edge of another tile, together with that edge.
+fn remove_shared_seams(edges: Vec<OutlineEdge>, walkable_climb: i32) -> Vec<OutlineEdge> {
+    let mut seams: HashMap<(IVec2, IVec2), Vec<usize>> = HashMap::new();
+    for (index, edge) in edges.iter().enumerate() {
+        if edge.seam.is_some() {
+            seams
+                .entry((edge.start.xz(), edge.end.xz()))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let mut removed = vec![false; edges.len()];
+    for (index, edge) in edges.iter().enumerate() {
+        if edge.seam.is_none() || removed[index] {
+            continue;
+        }
+        let Some(candidates) = seams.get(&(edge.end.xz(), edge.start.xz())) else {
+            continue;
+        };
+        let opposite = candidates.iter().copied().find(|other| {
+            let other_edge = &edges[*other];
+            !removed[*other]
+                && other_edge.set != edge.set
+                && (other_edge.start.y - edge.end.y).abs() <= walkable_climb
+                && (other_edge.end.y - edge.start.y).abs() <= walkable_climb
+        });
+        if let Some(opposite) = opposite {
+            removed[index] = true;
+            removed[opposite] = true;
+        }
+    }
+    edges
+        .into_iter()
+        .zip(removed)
+        .filter_map(|(edge, removed)| (!removed).then_some(edge))
+        .collect()
+}
+
+/// Follows the edges from one to the next until every edge belongs to a loop.
+///
+/// An edge continues with an unused edge starting where it ends, preferring edges of its own tile,
+/// so that loops only cross into other tiles where their shared seams were removed.
+/// Returns the edges of every loop and whether it returned to its first edge.
+fn stitch_loops(edges: &[OutlineEdge], walkable_climb: i32) -> Vec<(Vec<OutlineEdge>, bool)> {
+    let mut starting_at: HashMap<IVec2, Vec<usize>> = HashMap::new();
+    for (index, edge) in edges.iter().enumerate() {
+        starting_at.entry(edge.start.xz()).or_default().push(index);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut loops = Vec::new();
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut loop_edges = vec![edges[first]];
+        let mut current = edges[first];
+        let closed = loop {
+            let welds = |edge: &OutlineEdge| (edge.start.y - current.end.y).abs() <= walkable_climb;
+            if current.end.xz() == edges[first].start.xz() && welds(&edges[first]) {
+                break true;
+            }
+            let next = starting_at.get(&current.end.xz()).and_then(|candidates| {
+                let unused = || {
+                    candidates
+                        .iter()
+                        .copied()
+                        .filter(|index| !used[*index] && welds(&edges[*index]))
+                };
+                unused()
+                    .find(|index| edges[*index].set == current.set)
+                    .or_else(|| unused().next())
+            });
+            let Some(next) = next else {
+                break false;
+            };
+            used[next] = true;
+            current = edges[next];
+            loop_edges.push(current);
+        };
+        loops.push((loop_edges, closed));
+    }
+    loops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aabb3d, Contour, RegionId};
+
+    /// A tile of 4x4 cells at `x` containing a single contour through `vertices`.
+    fn tile(x: f32, vertices: &[[u16; 3]]) -> ContourSet {
+        ContourSet {
+            contours: vec![Contour {
+                vertices: vertices
+                    .iter()
+                    .map(|vertex| (U16Vec3::from_array(*vertex), 0))
+                    .collect(),
+                region: RegionId::from(1_u16),
+                area: AreaType::DEFAULT_WALKABLE,
+                ..Default::default()
+            }],
+            aabb: Aabb3d {
+                min: Vec3::new(x, 0.0, 0.0),
+                max: Vec3::new(x + 4.0, 4.0, 4.0),
+            },
+            cell_size: 1.0,
+            cell_height: 1.0,
+            width: 4,
+            height: 4,
+            border_size: 2,
+            max_error: 1.0,
+        }
+    }
+
+    #[test]
+    fn merges_contours_across_seams() {
+        let left = tile(0.0, &[[0, 0, 0], [0, 0, 4], [4, 0, 4], [4, 0, 0]]);
+        // The right tile has an extra vertex on the seam and lies one cell higher there.
+        let right = tile(
+            4.0,
+            &[[0, 0, 0], [0, 1, 2], [0, 1, 4], [4, 1, 4], [4, 1, 0]],
+        );
+        let island = tile(8.0, &[[1, 0, 1], [1, 0, 3], [3, 0, 3], [3, 0, 1]]);
+
+        let outlines = merge_tile_contours(&[left.clone(), right.clone(), island], 1);
+        assert_eq!(outlines.len(), 2);
+        assert!(outlines.iter().all(|outline| outline.closed));
+        let points: Vec<[f32; 2]> = outlines[0]
+            .points
+            .iter()
+            .map(|point| point.xz().to_array())
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                [0.0, 0.0],
+                [0.0, 4.0],
+                [4.0, 4.0],
+                [8.0, 4.0],
+                [8.0, 0.0],
+                [4.0, 0.0],
+            ]
+        );
+        assert_eq!(outlines[1].points.len(), 4);
+
+        // Seams are only welded within the walkable climb.
+        let outlines = merge_tile_contours(&[left, right], 0);
+        assert_eq!(outlines.len(), 2);
+        assert!(outlines.iter().all(|outline| outline.points.len() >= 4));
+    }
+}
@@ -1,8 +1,9 @@
 use glam::{U16Vec3, Vec3Swizzles};
 
 use crate::{
-    Aabb3d, AreaType, CompactHeightfield, RegionId,
-    math::{dir_offset_x, dir_offset_z, distance_squared_between_point_and_line_u16vec2},
+    Aabb3d, AreaType, CompactHeightfield, ContourProgress, RegionId,
+    math::{dir_offset_x, dir_offset_z},
+    predicates::segment_deviation_squared,
 };
 
 impl CompactHeightfield {
@@ -13,14 +14,55 @@ impl CompactHeightfield {
     /// (They are considered mandatory vertices.)
     ///
     /// Setting `max_edge_length` to zero will disabled the edge length feature.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn build_contours(
         &self,
         max_error: f32,
         max_edge_len: u16,
         build_flags: BuildContoursFlags,
+    ) -> ContourSet {
+        self.build_contours_reusing(
+            max_error,
+            max_edge_len,
+            build_flags,
+            ContourSet::default(),
+            |_| {},
+        )
+    }
+
+    /// Same as [`Self::build_contours`], but calls `on_progress` with the contours traced so far
+    /// after every completed row of the heightfield, see [`ContourProgress`].
+    ///
+    /// Lets editors show the contours appearing during long bakes.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn build_contours_with_progress(
+        &self,
+        max_error: f32,
+        max_edge_len: u16,
+        build_flags: BuildContoursFlags,
+        on_progress: impl FnMut(ContourProgress),
+    ) -> ContourSet {
+        self.build_contours_reusing(
+            max_error,
+            max_edge_len,
+            build_flags,
+            ContourSet::default(),
+            on_progress,
+        )
+    }
+
+    /// Like [`Self::build_contours_with_progress`], but writes the contours into the buffers of `recycled`,
+    /// so that rebuilding a tile does not allocate them again. See [`TileBuildPool`](crate::TileBuildPool).
+    pub(crate) fn build_contours_reusing(
+        &self,
+        max_error: f32,
+        max_edge_len: u16,
+        build_flags: BuildContoursFlags,
+        recycled: ContourSet,
+        mut on_progress: impl FnMut(ContourProgress),
     ) -> ContourSet {
         let mut cset = ContourSet {
-            contours: Vec::new(),
+            contours: recycled.contours,
             aabb: self.aabb,
             cell_size: self.cell_size,
             cell_height: self.cell_height,
@@ -40,7 +82,9 @@ impl CompactHeightfield {
 
         let mut max_contours = self.max_region.bits().max(8);
 
-        cset.contours = vec![Contour::default(); max_contours as usize];
+        // Recycled contours are overwritten below, so they keep the allocations of their vertices.
+        cset.contours
+            .resize_with(max_contours as usize, Contour::default);
         // We will shrink contours to this value later
         let mut contour_count = 0;
         let mut flags = vec![0_u8; self.spans.len()];
@@ -125,7 +169,7 @@ impl CompactHeightfield {
                         let cont = &mut cset.contours[contour_count];
                         contour_count += 1;
 
-                        cont.vertices = simplified.clone();
+                        cont.vertices.clone_from(&simplified);
                         if self.border_size > 0 {
                             // If the heightfield was build with bordersize, remove the offset.
                             for (vert, _) in &mut cont.vertices {
@@ -133,7 +177,7 @@ impl CompactHeightfield {
                                 vert.z = vert.z.saturating_sub(self.border_size);
                             }
                         }
-                        cont.raw_vertices = verts.clone();
+                        cont.raw_vertices.clone_from(&verts);
                         if self.border_size > 0 {
                             // If the heightfield was build with bordersize, remove the offset.
                             for (vert, _) in &mut cont.raw_vertices {
@@ -146,6 +190,11 @@ impl CompactHeightfield {
                     }
                 }
             }
+            on_progress(ContourProgress {
+                contours: &cset.contours[..contour_count],
+                completed_rows: z + 1,
+                total_rows: self.height,
+            });
         }
         cset.contours.resize_with(contour_count, Contour::default);
         cset
@@ -249,7 +298,7 @@ impl CompactHeightfield {
         // But eh, this was fast to implement.
         let get_reg = |i: usize| {
             RegionVertexId::from(
-                self.spans[i].region.bits() as u32 | ((self.areas[i].0 as u32) << 16),
+                self.spans[i].region.bits() as u32 | ((self.areas[i].id() as u32) << 16),
             )
         };
         regs[0] = get_reg(i);
@@ -392,8 +441,7 @@ fn simplify_contour(
         {
             while ci != endi {
                 let point = points[ci].0;
-                let d =
-                    distance_squared_between_point_and_line_u16vec2(point.xz(), (a.xz(), b.xz()));
+                let d = segment_deviation_squared(point.xz(), (a.xz(), b.xz()));
                 if d > maxd {
                     maxd = d;
                     maxi = Some(ci);
@@ -405,7 +453,7 @@ fn simplify_contour(
         // If the max deviation is larger than accepted error,
         // add new point, else continue to next segment.
         if let Some(maxi) = maxi
-            && maxd > max_error * max_error
+            && maxd > (max_error * max_error) as f64
         {
             // Add space for the new point.
             simplified.resize(simplified.len() + 1, Default::default());
@@ -644,6 +692,7 @@ pub struct Contour {
 bitflags::bitflags! {
     /// Contour build flags used in [`CompactHeightfield::build_contours`]
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
     #[repr(transparent)]
     pub struct BuildContoursFlags: u8 {
         /// Tessellate solid (impassable) edges during contour simplification.
@@ -0,0 +1,104 @@
+use crate::{AreaType, heightfield::Heightfield};
+
+impl Heightfield {
+    /// Sets the [`AreaType`] of walkable spans that are covered by geometry above them to `area`,
+    /// e.g. so that agents can prefer or avoid paths under roofs, or so that weather systems can query for shelter.
+    ///
+    /// A span is covered if the next span in its column starts between `min_cover_height` and `max_cover_height` above its top.
+    /// Call this after all geometry has been rasterized, but before the heightfield is compacted.
+    ///
+    /// # Arguments
+    ///
+    /// - `min_cover_height` - The minimum distance from the floor to the geometry above it. [Limit: >= 0] [Units: vx]
+    /// - `max_cover_height` - The maximum distance from the floor to the geometry above it. Geometry higher than this, e.g. a high ceiling or a tree canopy, does not count as cover. [Limit: >= min_cover_height] [Units: vx]
+    /// - `area` - The area type to assign to covered spans.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn mark_covered_spans(
+        &mut self,
+        min_cover_height: u16,
+        max_cover_height: u16,
+        area: AreaType,
+    ) {
+        for z in 0..self.height {
+            for x in 0..self.width {
+                let mut span_key = self.span_key_at(x, z);
+                while let Some(current_span_key) = span_key {
+                    let span = self.span(current_span_key);
+                    span_key = span.next;
+
+                    // Skip non-walkable spans.
+                    if !span.area.is_walkable() {
+                        continue;
+                    }
+                    let Some(next_key) = span.next else {
+                        // Nothing above this span
+                        continue;
+                    };
+                    let clearance = self.span(next_key).min.saturating_sub(span.max);
+                    if (min_cover_height..=max_cover_height).contains(&clearance) {
+                        self.span_mut(current_span_key).area = area;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3A;
+
+    use super::*;
+    use crate::{
+        Aabb3d, HeightfieldBuilder,
+        heightfield::{SpanInsertion, SpanOverlap},
+        span::SpanBuilder,
+    };
+
+    #[test]
+    fn marks_only_spans_under_low_cover() {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 5.0, 5.0]),
+            cell_size: 1.0,
+            cell_height: 1.0,
+        }
+        .build()
+        .unwrap();
+        // A floor under a roof, a floor under a high canopy, and an open floor.
+        for (x, min, max) in [(0, 0, 2), (0, 6, 7), (1, 0, 2), (1, 9, 10), (2, 0, 2)] {
+            heightfield
+                .add_span(SpanInsertion {
+                    x,
+                    z: 0,
+                    flag_merge_threshold: 0,
+                    overlap: SpanOverlap::Merge,
+                    span: SpanBuilder {
+                        min,
+                        max,
+                        area: AreaType::DEFAULT_WALKABLE,
+                        next: None,
+                    }
+                    .build(),
+                })
+                .unwrap();
+        }
+
+        heightfield.mark_covered_spans(2, 5, AreaType::new(3));
+
+        let roofed = heightfield.span_at(0, 0).unwrap();
+        assert_eq!(roofed.area, AreaType::new(3));
+        // The roof itself is not covered
+        assert_eq!(
+            heightfield.span(roofed.next.unwrap()).area,
+            AreaType::DEFAULT_WALKABLE
+        );
+        assert_eq!(
+            heightfield.span_at(1, 0).unwrap().area,
+            AreaType::DEFAULT_WALKABLE
+        );
+        assert_eq!(
+            heightfield.span_at(2, 0).unwrap().area,
+            AreaType::DEFAULT_WALKABLE
+        );
+    }
+}
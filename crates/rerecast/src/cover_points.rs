@@ -0,0 +1,220 @@
+use glam::{Vec2, Vec3, Vec3Swizzles as _};
+
+use crate::{Heightfield, PolygonNavmesh};
+
+/// Settings for [`PolygonNavmesh::cover_points`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoverPointSettings {
+    /// The distance between cover points along a boundary edge. `[Limit: > 0] [Units: wu]`
+    pub spacing: f32,
+    /// How far beyond the boundary edge the heightfield is probed for an obstacle. `[Limit: > 0] [Units: wu]`
+    pub probe_distance: f32,
+    /// The minimum height of an obstacle above the floor to provide [`CoverKind::Low`] cover,
+    /// e.g. the height of a crouching agent. `[Limit: > 0] [Units: wu]`
+    pub low_cover_height: f32,
+    /// The minimum height of an obstacle above the floor to provide [`CoverKind::High`] cover,
+    /// e.g. the height of a standing agent. `[Limit: >= low_cover_height] [Units: wu]`
+    pub high_cover_height: f32,
+}
+
+impl Default for CoverPointSettings {
+    fn default() -> Self {
+        Self {
+            spacing: 1.0,
+            probe_distance: 0.5,
+            low_cover_height: 1.0,
+            high_cover_height: 1.8,
+        }
+    }
+}
+
+/// How much of an agent an obstacle hides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum CoverKind {
+    /// The obstacle hides a crouching agent, which can still shoot over it.
+    Low,
+    /// The obstacle hides a standing agent, which has to lean out to shoot.
+    High,
+}
+
+/// A position on the navmesh next to an obstacle that agents can take cover behind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoverPoint {
+    /// The position on the boundary edge of the navmesh.
+    pub position: Vec3,
+    /// The horizontal unit direction from [`Self::position`] towards the obstacle.
+    pub direction: Vec3,
+    /// The height of the obstacle above [`Self::position`]. `[Units: wu]`
+    pub height: f32,
+    /// How much cover the obstacle provides.
+    pub kind: CoverKind,
+    /// The index of the polygon the cover point lies on.
+    pub polygon: usize,
+}
+
+impl PolygonNavmesh {
+    /// Generates cover points along the solid boundary edges of the walkable polygons.
+    ///
+    /// Every boundary edge is sampled every [`CoverPointSettings::spacing`],
+    /// and the columns of `heightfield` just beyond the edge are checked for an obstacle rising from the floor.
+    /// Samples next to obstacles lower than [`CoverPointSettings::low_cover_height`], such as steps or curbs,
+    /// and samples where the ground simply ends, such as ledges, are discarded.
+    ///
+    /// `heightfield` should be the heightfield the navmesh was built from, i.e. a clone taken before
+    /// [`Heightfield::into_compact`] and ideally before the spans were filtered.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn cover_points(
+        &self,
+        heightfield: &Heightfield,
+        settings: &CoverPointSettings,
+    ) -> Vec<CoverPoint> {
+        let mut cover_points = Vec::new();
+        for polygon in 0..self.polygon_count() {
+            if !self.areas[polygon].is_walkable() {
+                continue;
+            }
+            let vertices: Vec<Vec3> = self.polygon_world_vertices(polygon).collect();
+            let centroid = vertices.iter().sum::<Vec3>() / vertices.len() as f32;
+            let nvp = self.max_vertices_per_polygon as usize;
+            for edge in 0..vertices.len() {
+                if self.polygon_neighbors[polygon * nvp + edge] != Self::NO_CONNECTION {
+                    continue;
+                }
+                let a = vertices[edge];
+                let b = vertices[(edge + 1) % vertices.len()];
+                let along = (b - a).xz();
+                let length = along.length();
+                if length <= f32::EPSILON {
+                    continue;
+                }
+                // Point away from the polygon regardless of its winding.
+                let mut outward = along.perp() / length;
+                if outward.dot(((a + b) * 0.5 - centroid).xz()) < 0.0 {
+                    outward = -outward;
+                }
+
+                let samples = (length / settings.spacing).floor().max(1.0) as usize;
+                for sample in 0..samples {
+                    let position = a.lerp(b, (sample as f32 + 0.5) / samples as f32);
+                    let probe = position.xz() + outward * settings.probe_distance;
+                    let Some(height) = obstacle_height(heightfield, probe, position.y) else {
+                        continue;
+                    };
+                    let kind = if height >= settings.high_cover_height {
+                        CoverKind::High
+                    } else if height >= settings.low_cover_height {
+                        CoverKind::Low
+                    } else {
+                        continue;
+                    };
+                    cover_points.push(CoverPoint {
+                        position,
+                        direction: Vec3::new(outward.x, 0.0, outward.y),
+                        height,
+                        kind,
+                        polygon,
+                    });
+                }
+            }
+        }
+        cover_points
+    }
+}
+
+/// Returns how far the solid span at the xz-coordinates `probe` rises above `floor`,
+/// or `None` if there is no solid span at the height of the floor.
+fn obstacle_height(heightfield: &Heightfield, probe: Vec2, floor: f32) -> Option<f32> {
+    let cell = ((probe - heightfield.aabb.min.xz()) / heightfield.cell_size).floor();
+    if !heightfield.contains(cell.x as i32, cell.y as i32) {
+        return None;
+    }
+    // Ignore the floor itself, which may be slightly higher than the polygon.
+    let sample_height = floor + heightfield.cell_height * 0.5;
+    let mut span_key = heightfield.span_key_at(cell.x as u16, cell.y as u16);
+    while let Some(key) = span_key {
+        let span = heightfield.span(key);
+        span_key = span.next;
+        let bottom = heightfield.aabb.min.y + span.min as f32 * heightfield.cell_height;
+        let top = heightfield.aabb.min.y + span.max as f32 * heightfield.cell_height;
+        if (bottom..=top).contains(&sample_height) {
+            return Some(top - floor);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Aabb3d, AreaType, HeightfieldBuilder, SpanBuilder, SpanInsertion, SpanOverlap,
+        test_utils::GridNavmesh,
+    };
+
+    #[test]
+    fn finds_cover_next_to_tall_obstacles() {
+        let grid = GridNavmesh::parse(
+            "
+            ..#
+            ..#
+            ",
+        );
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d {
+                min: Vec3::new(0.0, -1.0, 0.0),
+                max: Vec3::new(3.0, 4.0, 2.0),
+            },
+            cell_size: 1.0,
+            cell_height: 1.0,
+        }
+        .build()
+        .unwrap();
+        // Floors with their top at y = 0, a low wall up to y = 1 and a high wall up to y = 3.
+        for (x, z, max) in [
+            (0, 0, 1),
+            (1, 0, 1),
+            (0, 1, 1),
+            (1, 1, 1),
+            (2, 0, 2),
+            (2, 1, 4),
+        ] {
+            heightfield
+                .add_span(SpanInsertion {
+                    x,
+                    z,
+                    flag_merge_threshold: 0,
+                    overlap: SpanOverlap::Merge,
+                    span: SpanBuilder {
+                        min: 0,
+                        max,
+                        area: AreaType::DEFAULT_WALKABLE,
+                        next: None,
+                    }
+                    .build(),
+                })
+                .unwrap();
+        }
+
+        let cover_points = grid
+            .navmesh
+            .cover_points(&heightfield, &CoverPointSettings::default());
+        assert_eq!(cover_points.len(), 2);
+        let low = cover_points
+            .iter()
+            .find(|point| point.position.z < 1.0)
+            .unwrap();
+        assert_eq!(low.kind, CoverKind::Low);
+        assert_eq!(low.position, Vec3::new(2.0, 0.0, 0.5));
+        assert_eq!(low.direction, Vec3::X);
+        assert_eq!(low.height, 1.0);
+        let high = cover_points
+            .iter()
+            .find(|point| point.position.z > 1.0)
+            .unwrap();
+        assert_eq!(high.kind, CoverKind::High);
+        assert_eq!(high.height, 3.0);
+    }
+}
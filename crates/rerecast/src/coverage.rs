@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use glam::{IVec2, Vec3};
+
+use crate::PolygonNavmesh;
+
+/// How much walkable area a part of the navmesh covers and how finely it is divided into polygons.
+///
+/// Comparing these across tiles, e.g. as a heatmap, shows where the cell size is wasteful
+/// (many tiny polygons on open ground) or insufficient (few polygons on detailed geometry).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NavmeshCoverage {
+    /// The total area of all walkable polygons on the xz-plane. `[Units: wu²]`
+    pub walkable_area: f32,
+    /// The number of walkable polygons.
+    pub polygon_count: usize,
+}
+
+impl NavmeshCoverage {
+    /// Returns the average area of a walkable polygon, or 0 if there are none. `[Units: wu²]`
+    #[inline]
+    pub fn average_polygon_area(&self) -> f32 {
+        if self.polygon_count == 0 {
+            0.0
+        } else {
+            self.walkable_area / self.polygon_count as f32
+        }
+    }
+}
+
+impl PolygonNavmesh {
+    /// Returns the coverage of the whole navmesh.
+    pub fn coverage(&self) -> NavmeshCoverage {
+        let mut coverage = NavmeshCoverage::default();
+        for polygon in self.walkable_polygons() {
+            coverage.walkable_area += self.polygon_area(polygon);
+            coverage.polygon_count += 1;
+        }
+        coverage
+    }
+
+    /// Returns the coverage of each tile of a grid on the xz-plane, where tile `(0, 0)` starts at `origin`
+    /// and every tile is `tile_size` wide and deep. `[Units: wu]`
+    ///
+    /// Each polygon is attributed to the tile containing its centroid. Tiles without walkable polygons are omitted.
+    pub fn coverage_per_tile(
+        &self,
+        origin: Vec3,
+        tile_size: f32,
+    ) -> HashMap<IVec2, NavmeshCoverage> {
+        let mut tiles: HashMap<IVec2, NavmeshCoverage> = HashMap::new();
+        for polygon in self.walkable_polygons() {
+            let vertex_count = self.polygon_vertices(polygon).len();
+            if vertex_count == 0 {
+                continue;
+            }
+            let centroid = self.polygon_world_vertices(polygon).sum::<Vec3>() / vertex_count as f32;
+            let tile = ((centroid - origin) / tile_size).floor();
+            let coverage = tiles
+                .entry(IVec2::new(tile.x as i32, tile.z as i32))
+                .or_default();
+            coverage.walkable_area += self.polygon_area(polygon);
+            coverage.polygon_count += 1;
+        }
+        tiles
+    }
+
+    fn walkable_polygons(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.polygon_count()).filter(|&polygon| self.areas[polygon].is_walkable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    #[test]
+    fn reports_coverage_per_tile() {
+        let grid = GridNavmesh::parse(
+            "
+            ..#.
+            .00.
+            ",
+        );
+        let coverage = grid.navmesh.coverage();
+        assert_eq!(coverage.polygon_count, 5);
+        assert_eq!(coverage.walkable_area, 5.0);
+        assert_eq!(coverage.average_polygon_area(), 1.0);
+
+        let tiles = grid.navmesh.coverage_per_tile(Vec3::ZERO, 2.0);
+        assert_eq!(tiles.len(), 2);
+        assert_eq!(tiles[&IVec2::new(0, 0)].polygon_count, 3);
+        assert_eq!(tiles[&IVec2::new(1, 0)].walkable_area, 2.0);
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(feature = "bevy_reflect")]
 use bevy_reflect::prelude::*;
-use glam::{U16Vec3, Vec2, Vec3, Vec3A, Vec3Swizzles as _, u16vec3};
+use glam::{Vec2, Vec3, Vec3A, Vec3Swizzles as _};
 use std::{
     f32,
     ops::{Deref, DerefMut},
@@ -8,10 +8,10 @@ use std::{
 use thiserror::Error;
 
 use crate::{
-    Aabb3d, CompactHeightfield, PolygonNavmesh, RegionId,
+    Aabb3d, AreaType, CompactHeightfield, PolygonNavmesh, RegionId,
     math::{
         dir_offset, dir_offset_x, dir_offset_z, distance_squared_between_point_and_line_vec2,
-        distance_squared_between_point_and_line_vec3, next, prev,
+        distance_squared_between_point_and_line_vec3, height_on_triangle, next, prev,
     },
 };
 
@@ -147,7 +147,53 @@ impl DetailNavmesh {
         sample_distance: f32,
         sample_max_error: f32,
     ) -> Result<Self, DetailNavmeshError> {
-        let mut dmesh = DetailNavmesh::default();
+        Self::with_edge_sample_distance(
+            mesh,
+            heightfield,
+            sample_distance,
+            sample_distance,
+            sample_max_error,
+        )
+    }
+
+    /// Builds a detail mesh from the provided polygon mesh,
+    /// sampling the polygon edges with `edge_sample_distance` and the polygon interiors with `sample_distance`.
+    ///
+    /// A denser edge sampling preserves height changes along the polygon borders without
+    /// paying for the extra vertices in the interior. An `edge_sample_distance` of zero disables edge sampling.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn with_edge_sample_distance(
+        mesh: &PolygonNavmesh,
+        heightfield: &CompactHeightfield,
+        sample_distance: f32,
+        edge_sample_distance: f32,
+        sample_max_error: f32,
+    ) -> Result<Self, DetailNavmeshError> {
+        Self::with_edge_sample_distance_reusing(
+            mesh,
+            heightfield,
+            sample_distance,
+            edge_sample_distance,
+            sample_max_error,
+            DetailNavmesh::default(),
+        )
+    }
+
+    /// Like [`Self::with_edge_sample_distance`], but writes the detail mesh into the buffers of `recycled`,
+    /// so that rebuilding a tile does not allocate them again. See [`TileBuildPool`](crate::TileBuildPool).
+    pub(crate) fn with_edge_sample_distance_reusing(
+        mesh: &PolygonNavmesh,
+        heightfield: &CompactHeightfield,
+        sample_distance: f32,
+        edge_sample_distance: f32,
+        sample_max_error: f32,
+        recycled: DetailNavmesh,
+    ) -> Result<Self, DetailNavmeshError> {
+        let mut dmesh = recycled;
+        dmesh.meshes.clear();
+        dmesh.vertices.clear();
+        dmesh.triangles.clear();
+        dmesh.triangle_flags.clear();
         if mesh.vertices.is_empty() || mesh.polygon_count() == 0 {
             return Ok(dmesh);
         }
@@ -208,13 +254,16 @@ impl DetailNavmesh {
             maxhh = maxhh.max(*zmax - *zmin);
         }
         hp.data = vec![0; maxhw as usize * maxhh as usize];
-        dmesh.meshes = vec![SubMesh::default(); mesh.polygon_count()];
+        hp.offsets = vec![0; maxhw as usize * maxhh as usize];
+        dmesh
+            .meshes
+            .resize(mesh.polygon_count(), SubMesh::default());
 
         let mut vcap = poly_vert_count + poly_vert_count / 2;
         let mut tcap = vcap * 2;
 
-        dmesh.vertices = Vec::with_capacity(vcap);
-        dmesh.triangles = Vec::with_capacity(tcap);
+        dmesh.vertices.reserve(vcap);
+        dmesh.triangles.reserve(tcap);
 
         for (i, bounds_i) in bounds.iter().enumerate().take(mesh.polygon_count()) {
             let p = &mesh.polygons[i * nvp..];
@@ -253,6 +302,7 @@ impl DetailNavmesh {
                 &poly,
                 npoly,
                 sample_distance,
+                edge_sample_distance,
                 sample_max_error,
                 height_search_radius,
                 chf,
@@ -288,7 +338,9 @@ impl DetailNavmesh {
                 while dmesh.vertices.len() + nverts > vcap {
                     vcap += 256;
                 }
-                dmesh.vertices.reserve(vcap - dmesh.vertices.capacity());
+                dmesh
+                    .vertices
+                    .reserve(vcap.saturating_sub(dmesh.vertices.capacity()));
             }
             for vert in &verts[..nverts] {
                 dmesh.vertices.push(Vec3::from(*vert));
@@ -299,7 +351,9 @@ impl DetailNavmesh {
                 while dmesh.triangles.len() + tris.len() > tcap {
                     tcap += 256;
                 }
-                dmesh.triangles.reserve(tcap - dmesh.triangles.capacity());
+                dmesh
+                    .triangles
+                    .reserve(tcap.saturating_sub(dmesh.triangles.capacity()));
             }
             for tri in &tris {
                 dmesh.triangles.push([tri[0], tri[1], tri[2]]);
@@ -311,12 +365,154 @@ impl DetailNavmesh {
 
         Ok(dmesh)
     }
+
+    /// Returns the height of the detail sub-mesh of the polygon at index `polygon` at the xz-coordinates of `point`.
+    ///
+    /// Returns `None` if the detail mesh has no triangles for the polygon, e.g. because detail generation was skipped,
+    /// or if `point` does not lie within any of them on the xz-plane.
+    /// See [`PolygonNavmesh::surface_height`] for a version that falls back to the polygon itself.
+    pub fn polygon_height(&self, polygon: usize, point: Vec3) -> Option<f32> {
+        let submesh = self.meshes.get(polygon)?;
+        let vertices =
+            &self.vertices[submesh.base_vertex_index as usize..][..submesh.vertex_count as usize];
+        let triangles = &self.triangles[submesh.base_triangle_index as usize..]
+            [..submesh.triangle_count as usize];
+        triangles.iter().find_map(|triangle| {
+            height_on_triangle(
+                point.xz(),
+                vertices[triangle[0] as usize],
+                vertices[triangle[1] as usize],
+                vertices[triangle[2] as usize],
+            )
+        })
+    }
+
+    /// Removes cracks between the sub-meshes of neighboring polygons of `mesh`, the polygon mesh this detail mesh was built from.
+    ///
+    /// Neighboring sub-meshes sample their shared edge independently, so their edge vertices can end up at
+    /// different heights, or one side can keep a sample the other side simplified away.
+    /// This pass moves all detail vertices on a shared edge onto a common polyline through the vertices
+    /// both sub-meshes have in common, which makes the seam watertight.
+    /// Height detail at vertices that only exist on one side of a seam is flattened in the process.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn stitch_detail_seams(&mut self, mesh: &PolygonNavmesh) {
+        let tolerance = mesh.cell_size * 0.01;
+        let mut own = Vec::new();
+        let mut other = Vec::new();
+        let mut seam = Vec::new();
+        for polygon in 0..mesh.polygon_count().min(self.meshes.len()) {
+            let vertices = mesh.polygon_vertices(polygon);
+            for edge in 0..vertices.len() {
+                let Some(neighbor) = mesh.internal_neighbor(polygon, edge) else {
+                    continue;
+                };
+                // Handle every seam only once
+                if neighbor < polygon || neighbor >= self.meshes.len() {
+                    continue;
+                }
+                let a = mesh.world_vertex(vertices[edge]).xz();
+                let b = mesh.world_vertex(vertices[next(edge, vertices.len())]).xz();
+                let length = a.distance(b);
+                if length <= f32::EPSILON {
+                    continue;
+                }
+                self.collect_edge_vertices(polygon, (a, b), tolerance, &mut own);
+                self.collect_edge_vertices(neighbor, (a, b), tolerance, &mut other);
+
+                // The seam runs through the vertices both sides have in common,
+                // which always includes the polygon corners.
+                let t_tolerance = tolerance / length;
+                seam.clear();
+                for &(_, t, y) in &own {
+                    if let Some(&(_, _, other_y)) = other
+                        .iter()
+                        .find(|(_, other_t, _)| (t - other_t).abs() <= t_tolerance)
+                    {
+                        seam.push((t, (y + other_y) * 0.5));
+                    }
+                }
+                if seam.len() < 2 {
+                    continue;
+                }
+                seam.sort_unstable_by(|(a, _), (b, _)| a.total_cmp(b));
+
+                for &(index, t, _) in own.iter().chain(&other) {
+                    self.vertices[index].y = seam_height(&seam, t);
+                }
+            }
+        }
+    }
+
+    /// Moves the sub-meshes of the polygons of `mesh`, the polygon mesh this detail mesh was built from,
+    /// vertically by the offset listed for their area in `offsets`. `[Units: wu]`
+    ///
+    /// Polygons whose area is not listed keep their height. Call this after [`Self::stitch_detail_seams`],
+    /// which would otherwise flatten the steps between areas with different offsets.
+    ///
+    /// The offsets of the polygon vertices are kept in [`CompressedDetailNavmesh::corner_heights`](crate::CompressedDetailNavmesh::corner_heights)
+    /// when compressing with [`Self::compress`], so they survive the round trip up to the vertical quantum.
+    pub fn offset_areas(&mut self, mesh: &PolygonNavmesh, offsets: &[(AreaType, f32)]) {
+        if offsets.is_empty() {
+            return;
+        }
+        for (polygon, submesh) in self.meshes.iter().enumerate().take(mesh.polygon_count()) {
+            let area = mesh.areas[polygon];
+            let Some(&(_, offset)) = offsets.iter().find(|(offset_area, _)| *offset_area == area)
+            else {
+                continue;
+            };
+            let vertices = &mut self.vertices[submesh.base_vertex_index as usize..]
+                [..submesh.vertex_count as usize];
+            for vertex in vertices {
+                vertex.y += offset;
+            }
+        }
+    }
+
+    /// Collects the vertices of the sub-mesh of `polygon` that lie on the xz-plane segment `(a, b)`,
+    /// as their index in [`Self::vertices`], their parameter along the segment, and their height.
+    fn collect_edge_vertices(
+        &self,
+        polygon: usize,
+        (a, b): (Vec2, Vec2),
+        tolerance: f32,
+        out: &mut Vec<(usize, f32, f32)>,
+    ) {
+        out.clear();
+        let submesh = &self.meshes[polygon];
+        let base = submesh.base_vertex_index as usize;
+        let ab = b - a;
+        let length_squared = ab.length_squared();
+        for index in base..base + submesh.vertex_count as usize {
+            let vertex = self.vertices[index];
+            let t = ((vertex.xz() - a).dot(ab) / length_squared).clamp(0.0, 1.0);
+            if (a + ab * t).distance_squared(vertex.xz()) <= tolerance * tolerance {
+                out.push((index, t, vertex.y));
+            }
+        }
+    }
+}
+
+/// Linearly interpolates the height of the polyline `seam`, given as `(t, height)` pairs sorted by `t`, at `t`.
+fn seam_height(seam: &[(f32, f32)], t: f32) -> f32 {
+    let segment = seam
+        .windows(2)
+        .find(|segment| t <= segment[1].0)
+        .unwrap_or(&seam[seam.len() - 2..]);
+    let (t0, y0) = segment[0];
+    let (t1, y1) = segment[1];
+    if t1 - t0 <= f32::EPSILON {
+        return y0;
+    }
+    let u = ((t - t0) / (t1 - t0)).clamp(0.0, 1.0);
+    y0 + (y1 - y0) * u
 }
 
 fn build_poly_detail(
     in_: &[Vec3A],
     nin: usize,
     sample_dist: f32,
+    edge_sample_dist: f32,
     sample_max_error: f32,
     height_search_radius: u32,
     chf: &CompactHeightfield,
@@ -326,7 +522,7 @@ fn build_poly_detail(
     tris: &mut Vec<[u8; 3]>,
     flags: &mut Vec<u8>,
     edges: &mut Vec<Edges>,
-    samples: &mut Vec<(U16Vec3, bool)>,
+    samples: &mut Vec<(Vec3A, bool)>,
 ) -> Result<(), DetailNavmeshError> {
     let mut edge = [Vec3A::default(); DetailNavmesh::MAX_VERTS_PER_EDGE + 1];
     let mut hull = [0; DetailNavmesh::MAX_VERTICES_PER_SUBMESH];
@@ -348,7 +544,7 @@ fn build_poly_detail(
     // Tessellate outlines.
     // This is done in separate pass in order to ensure
     // seamless height values across the ply boundaries.
-    if sample_dist > 0.0 {
+    if edge_sample_dist > 0.0 {
         let mut j = nin - 1;
         for i in 0..nin {
             let mut vj = in_[j];
@@ -368,7 +564,7 @@ fn build_poly_detail(
             // Create samples along the edge.
             let dij = vi - vj;
             let d = dij.xz().length();
-            let mut nn = 1 + (d / sample_dist).floor() as usize;
+            let mut nn = 1 + (d / edge_sample_dist).floor() as usize;
             if nn >= DetailNavmesh::MAX_VERTS_PER_EDGE {
                 nn = DetailNavmesh::MAX_VERTS_PER_EDGE - 1;
             }
@@ -378,7 +574,7 @@ fn build_poly_detail(
             for (k, pos) in edge.iter_mut().enumerate().take(nn + 1) {
                 let u = k as f32 / nn as f32;
                 *pos = vj + dij * u;
-                pos.y = get_height(*pos, ics, chf.cell_height, height_search_radius, hp) as f32
+                pos.y = get_height(*pos, ics, chf.cell_height, height_search_radius, hp)
                     * chf.cell_height;
             }
             // Simplify samples.
@@ -487,7 +683,7 @@ fn build_poly_detail(
                     continue;
                 }
                 let y = get_height(pt, ics, chf.cell_height, height_search_radius, hp);
-                samples.push((u16vec3(x as u16, y, z as u16), false));
+                samples.push((Vec3A::new(x as f32, y, z as f32), false));
             }
         }
 
@@ -510,9 +706,9 @@ fn build_poly_detail(
                 let mut pt = Vec3A::default();
                 // The sample location is jittered to get rid of some bad triangulations
                 // which are cause by symmetrical data from the grid structure.
-                pt.x = s.x as f32 * sample_dist + get_jitter_x(i) * cs * 0.1;
-                pt.y = s.y as f32 * chf.cell_height;
-                pt.z = s.z as f32 * sample_dist + get_jitter_y(i) * cs * 0.1;
+                pt.x = s.x * sample_dist + get_jitter_x(i) * cs * 0.1;
+                pt.y = s.y * chf.cell_height;
+                pt.z = s.z * sample_dist + get_jitter_y(i) * cs * 0.1;
                 let d = dist_to_tri_mesh(pt, verts, tris);
                 let Some(d) = d else {
                     // did not hit the mesh.
@@ -1126,12 +1322,14 @@ fn triangulate_hull(
     }
 }
 
-fn get_height(f: Vec3A, ics: f32, ch: f32, radius: u32, hp: &HeightPatch) -> u16 {
+/// Returns the height of the patch at `f` in voxels, including the sub-voxel offset of the sampled span.
+fn get_height(f: Vec3A, ics: f32, ch: f32, radius: u32, hp: &HeightPatch) -> f32 {
     let mut ix = (f.x * ics + 0.01).floor() as i32;
     let mut iz = (f.z * ics + 0.01).floor() as i32;
     ix = (ix - hp.xmin as i32).clamp(0, hp.width as i32 - 1);
     iz = (iz - hp.zmin as i32).clamp(0, hp.height as i32 - 1);
-    let mut h = hp.data[(ix + iz * hp.width as i32) as usize];
+    let mut index = (ix + iz * hp.width as i32) as usize;
+    let mut h = hp.data[index];
     if h == RC_UNSET_HEIGHT {
         // Special case when data might be bad.
         // Walk adjacent cells in a spiral up to 'radius', and look
@@ -1151,11 +1349,13 @@ fn get_height(f: Vec3A, ics: f32, ch: f32, radius: u32, hp: &HeightPatch) -> u16
             let nx = ix + x;
             let nz = iz + z;
             if nx >= 0 && nz >= 0 && nx < hp.width as i32 && nz < hp.height as i32 {
-                let nh = hp.data[(nx + nz * hp.width as i32) as usize];
+                let neighbor_index = (nx + nz * hp.width as i32) as usize;
+                let nh = hp.data[neighbor_index];
                 if nh != RC_UNSET_HEIGHT {
                     let d = (nh as f32 * ch - f.y).abs();
                     if d < dmin {
                         h = nh;
+                        index = neighbor_index;
                         dmin = d;
                     }
                 }
@@ -1194,7 +1394,7 @@ fn get_height(f: Vec3A, ics: f32, ch: f32, radius: u32, hp: &HeightPatch) -> u16
             z += dz;
         }
     }
-    h
+    h as f32 - hp.offsets[index] as f32 / 256.0
 }
 
 /// Calculate minimum extend of the polygon.
@@ -1224,6 +1424,8 @@ pub enum DetailNavmeshError {}
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 struct HeightPatch {
     data: Vec<u16>,
+    /// The [`CompactHeightfield::height_offsets`] of the spans in `data`.
+    offsets: Vec<u8>,
     xmin: u16,
     zmin: u16,
     width: u16,
@@ -1266,6 +1468,7 @@ impl HeightPatch {
                         if s.region == region {
                             // Store height
                             *self.data_at_mut(hx as i32, hz as i32) = s.y;
+                            *self.offset_at_mut(hx as i32, hz as i32) = chf.height_offsets[i];
                             empty = false;
 
                             // If any of the neighbours is not in same region,
@@ -1323,7 +1526,7 @@ impl HeightPatch {
                 let hx = ax - self.xmin as i32 - bs as i32;
                 let hz = az - self.zmin as i32 - bs as i32;
 
-                if hx as u16 > self.width || hz as u16 >= self.height {
+                if hx as u16 >= self.width || hz as u16 >= self.height {
                     continue;
                 }
 
@@ -1335,6 +1538,7 @@ impl HeightPatch {
                 let as_ = &chf.spans[ai];
 
                 *self.data_at_mut(hx, hz) = as_.y;
+                *self.offset_at_mut(hx, hz) = chf.height_offsets[ai];
                 queue.push((ax, az, ai));
             }
         }
@@ -1484,8 +1688,9 @@ impl HeightPatch {
         array.push((cx + bs as i32, cz + bs as i32, ci));
         self.data[..data_len].fill(0xffff);
         let cs = &chf.spans[ci];
-        self.data[(cx - self.xmin as i32 + (cz - self.zmin as i32) * self.width as i32) as usize] =
-            cs.y;
+        let index = (cx - self.xmin as i32 + (cz - self.zmin as i32) * self.width as i32) as usize;
+        self.data[index] = cs.y;
+        self.offsets[index] = chf.height_offsets[ci];
     }
 
     #[inline]
@@ -1502,6 +1707,11 @@ impl HeightPatch {
     fn data_at_mut(&mut self, x: i32, z: i32) -> &mut u16 {
         &mut self.data[(x + z * self.width as i32) as usize]
     }
+
+    #[inline]
+    fn offset_at_mut(&mut self, x: i32, z: i32) -> &mut u8 {
+        &mut self.offsets[(x + z * self.width as i32) as usize]
+    }
 }
 
 const RC_UNSET_HEIGHT: u16 = 0xffff;
@@ -1524,3 +1734,109 @@ impl Bounds {
         self.zmax - self.zmin
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AreaType, test_utils::two_quads};
+
+    #[test]
+    fn stitches_detail_seams() {
+        let mesh = two_quads();
+        let mut dmesh = DetailNavmesh {
+            meshes: vec![
+                SubMesh {
+                    base_vertex_index: 0,
+                    vertex_count: 6,
+                    ..Default::default()
+                },
+                SubMesh {
+                    base_vertex_index: 6,
+                    vertex_count: 5,
+                    ..Default::default()
+                },
+            ],
+            vertices: vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 4.0),
+                Vec3::new(4.0, 0.0, 4.0),
+                Vec3::new(4.0, 0.0, 0.0),
+                Vec3::new(4.0, 1.0, 2.0),
+                Vec3::new(4.0, 1.0, 1.0),
+                Vec3::new(4.0, 0.0, 0.0),
+                Vec3::new(4.0, 0.0, 4.0),
+                Vec3::new(8.0, 0.0, 4.0),
+                Vec3::new(8.0, 0.0, 0.0),
+                Vec3::new(4.0, 0.5, 2.0),
+            ],
+            ..Default::default()
+        };
+
+        dmesh.stitch_detail_seams(&mesh);
+
+        // Shared samples meet in the middle
+        assert_eq!(dmesh.vertices[4].y, 0.75);
+        assert_eq!(dmesh.vertices[10].y, 0.75);
+        // Samples only present on one side are moved onto the seam
+        assert_eq!(dmesh.vertices[5].y, 0.375);
+        // Vertices off the seam are left alone
+        assert_eq!(dmesh.vertices[0].y, 0.0);
+    }
+
+    #[test]
+    fn falls_back_to_polygon_height_without_detail() {
+        let mesh = two_quads();
+        let point = Vec3::new(2.0, 0.0, 1.0);
+        assert_eq!(mesh.surface_height(None, 0, point), Some(0.0));
+
+        let dmesh = DetailNavmesh {
+            meshes: vec![SubMesh {
+                base_vertex_index: 0,
+                vertex_count: 3,
+                base_triangle_index: 0,
+                triangle_count: 1,
+            }],
+            vertices: vec![
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec3::new(0.0, 1.0, 4.0),
+                Vec3::new(4.0, 1.0, 0.0),
+            ],
+            triangles: vec![[0, 1, 2]],
+            triangle_flags: vec![0],
+        };
+        assert_eq!(mesh.surface_height(Some(&dmesh), 0, point), Some(1.0));
+        // The second polygon has no detail data
+        assert_eq!(
+            mesh.surface_height(Some(&dmesh), 1, Vec3::new(6.0, 0.0, 2.0)),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn offsets_areas() {
+        let mut mesh = two_quads();
+        mesh.areas[1] = AreaType::from(3);
+        let mut dmesh = DetailNavmesh {
+            meshes: vec![
+                SubMesh {
+                    base_vertex_index: 0,
+                    vertex_count: 2,
+                    ..Default::default()
+                },
+                SubMesh {
+                    base_vertex_index: 2,
+                    vertex_count: 2,
+                    ..Default::default()
+                },
+            ],
+            vertices: vec![Vec3::ZERO, Vec3::X, Vec3::X, Vec3::Z],
+            ..Default::default()
+        };
+
+        dmesh.offset_areas(&mesh, &[(AreaType::from(3), 0.5)]);
+
+        assert_eq!(dmesh.vertices[1].y, 0.0);
+        assert_eq!(dmesh.vertices[2].y, 0.5);
+        assert_eq!(dmesh.vertices[3].y, 0.5);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{NearestPolygon, PolygonNavmesh, QueryFilter};
+
+/// Precomputed path costs between every pair of a set of anchor points on a [`PolygonNavmesh`], e.g. points of interest,
+/// so strategic AI can compare routes between them without running a path search each time.
+///
+/// The costs are approximate: they run from centroid to centroid of the polygons along the way,
+/// like [`PolygonGraph`](crate::PolygonGraph), instead of along the smoothed path an agent walks.
+/// They are computed once per navmesh and filter with [`Self::build`] and serialize alongside the navmesh with the `serialize` feature.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct DistanceMatrix {
+    /// The anchor points, in the order they were passed to [`Self::build`].
+    pub anchors: Vec<NearestPolygon>,
+    /// The cost from anchor `i` to anchor `j` at `i * anchors.len() + j`,
+    /// or [`f32::INFINITY`] if `j` can not be reached from `i`. `[Size: anchors.len() * anchors.len()]`
+    pub costs: Vec<f32>,
+}
+
+impl DistanceMatrix {
+    /// Computes the costs between all `anchors` on `navmesh`, running Dijkstra's algorithm over its [`PolygonGraph`](crate::PolygonGraph)
+    /// once per anchor.
+    ///
+    /// Walking between two polygons costs the distance between their centroids times the average of their area costs in `filter`,
+    /// and polygons not passing `filter` are never entered. Anchors on the same polygon cost the straight line distance between them.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn build(
+        navmesh: &PolygonNavmesh,
+        anchors: &[NearestPolygon],
+        filter: &QueryFilter,
+    ) -> Self {
+        let graph = navmesh.polygon_graph();
+        let area_cost = |polygon: usize| {
+            if filter.passes(navmesh, polygon) {
+                filter.area_cost(navmesh.areas[polygon])
+            } else {
+                f32::INFINITY
+            }
+        };
+        // The cost of walking from an anchor to the centroid of its polygon, the same in both directions.
+        let to_centroid = |anchor: &NearestPolygon| {
+            anchor.point.distance(graph.nodes[anchor.polygon]) * area_cost(anchor.polygon)
+        };
+
+        let mut costs = Vec::with_capacity(anchors.len() * anchors.len());
+        for from in anchors {
+            let polygon_costs = graph.costs_from(from.polygon, f32::INFINITY, |node, edge| {
+                edge.distance * (area_cost(node) + area_cost(edge.to)) * 0.5
+            });
+            costs.extend(anchors.iter().map(|to| {
+                if to.polygon == from.polygon {
+                    from.point.distance(to.point) * area_cost(from.polygon)
+                } else {
+                    to_centroid(from) + polygon_costs[to.polygon] + to_centroid(to)
+                }
+            }));
+        }
+
+        Self {
+            anchors: anchors.to_vec(),
+            costs,
+        }
+    }
+
+    /// Returns the number of anchors.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.anchors.len()
+    }
+
+    /// Returns whether there are no anchors.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.anchors.is_empty()
+    }
+
+    /// Returns the cost from the anchor at index `from` to the anchor at index `to`,
+    /// or [`f32::INFINITY`] if it can not be reached.
+    #[inline]
+    pub fn cost(&self, from: usize, to: usize) -> f32 {
+        self.costs[from * self.len() + to]
+    }
+
+    /// Returns the costs from the anchor at index `from` to all anchors, indexed by anchor.
+    #[inline]
+    pub fn costs_from(&self, from: usize) -> &[f32] {
+        let len = self.len();
+        &self.costs[from * len..(from + 1) * len]
+    }
+
+    /// Returns the index of and the cost to the cheapest other anchor reachable from the anchor at index `from`,
+    /// or `None` if no other anchor can be reached.
+    pub fn nearest(&self, from: usize) -> Option<(usize, f32)> {
+        self.costs_from(from)
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(to, cost)| *to != from && cost.is_finite())
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AreaType, test_utils::GridNavmesh};
+
+    #[test]
+    fn precomputes_costs_between_anchors() {
+        let grid = GridNavmesh::parse(
+            "
+            a..b#d
+            .11.##
+            ...c##
+            ",
+        );
+        let anchors: Vec<NearestPolygon> = ['a', 'b', 'c', 'd']
+            .into_iter()
+            .map(|marker| NearestPolygon {
+                polygon: grid.polygon(marker),
+                point: grid.position(marker),
+            })
+            .collect();
+        let mut filter = QueryFilter::default();
+        filter.set_area_cost(AreaType::new(1), 10.0);
+
+        let matrix = DistanceMatrix::build(&grid.navmesh, &anchors, &filter);
+        assert_eq!(matrix.len(), 4);
+        assert_eq!(matrix.cost(0, 0), 0.0);
+        assert_eq!(matrix.cost(0, 1), 3.0);
+        assert_eq!(matrix.cost(1, 0), 3.0);
+        // Around the expensive area.
+        assert_eq!(matrix.cost(0, 2), 5.0);
+        assert_eq!(matrix.cost(0, 3), f32::INFINITY);
+        assert_eq!(matrix.costs_from(3)[..3], [f32::INFINITY; 3]);
+        assert_eq!(matrix.nearest(2), Some((1, 2.0)));
+        assert_eq!(matrix.nearest(3), None);
+    }
+}
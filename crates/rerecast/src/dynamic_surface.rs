@@ -0,0 +1,233 @@
+//! Navmeshes for moving platforms, elevators, and other surfaces that move at runtime.
+
+use glam::{Affine3A, Vec3, Vec3Swizzles as _};
+
+use crate::{Aabb3d, NavmeshRaycast, OffMeshConnection, PolygonNavmesh, bv_tree::BvTree};
+
+/// A navmesh built in the local space of a moving object, like a platform or an elevator,
+/// that is placed in the world by a transform that may change every frame.
+///
+/// Instead of rebuilding the navmesh whenever the object moves, positions are converted into the
+/// surface's local space at query time, see [`DynamicSurface::locate`] and [`DynamicSurface::raycast`].
+/// Use [`DynamicSurface::link_to`] to connect the surface to a static navmesh whenever it is aligned with it,
+/// e.g. when an elevator arrives at a floor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicSurface {
+    navmesh: PolygonNavmesh,
+    tree: BvTree,
+    /// The transform from the local space of the surface to world space.
+    pub transform: Affine3A,
+}
+
+impl DynamicSurface {
+    /// Creates a new surface from a navmesh built in local space and its current transform.
+    #[inline]
+    pub fn new(navmesh: PolygonNavmesh, transform: Affine3A) -> Self {
+        let tree = BvTree::new(&navmesh);
+        Self {
+            navmesh,
+            tree,
+            transform,
+        }
+    }
+
+    /// Returns the navmesh of the surface in local space.
+    #[inline]
+    pub fn navmesh(&self) -> &PolygonNavmesh {
+        &self.navmesh
+    }
+
+    /// Converts a point from the local space of the surface to world space.
+    #[inline]
+    pub fn local_to_world(&self, point: Vec3) -> Vec3 {
+        self.transform.transform_point3(point)
+    }
+
+    /// Converts a point from world space to the local space of the surface.
+    #[inline]
+    pub fn world_to_local(&self, point: Vec3) -> Vec3 {
+        self.transform.inverse().transform_point3(point)
+    }
+
+    /// Finds the polygon of the surface below or above the world space `point`.
+    ///
+    /// Returns the polygon index and the world space position on its surface,
+    /// or `None` if no polygon lies within `max_height_difference` of `point`, measured in the local space of the surface.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn locate(&self, point: Vec3, max_height_difference: f32) -> Option<(usize, Vec3)> {
+        let local_point = self.world_to_local(point);
+        let (polygon, height) =
+            self.navmesh
+                .locate_polygon(&self.tree, local_point, max_height_difference)?;
+        let surface_point = Vec3::new(local_point.x, height, local_point.z);
+        Some((polygon, self.local_to_world(surface_point)))
+    }
+
+    /// Casts a ray along the surface from the world space `start` towards the world space `end`.
+    /// See [`PolygonNavmesh::raycast`].
+    ///
+    /// Returns `None` if `start` does not lie on the surface according to [`Self::locate`].
+    /// The hit normal is returned in world space.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn raycast(
+        &self,
+        start: Vec3,
+        end: Vec3,
+        max_height_difference: f32,
+    ) -> Option<NavmeshRaycast> {
+        let (start_polygon, _) = self.locate(start, max_height_difference)?;
+        let mut raycast = self.navmesh.raycast(
+            start_polygon,
+            self.world_to_local(start),
+            self.world_to_local(end),
+        );
+        if let Some(hit) = &mut raycast.hit {
+            // Normals transform with the inverse transpose to stay perpendicular under non-uniform scale
+            let normal_matrix = self.transform.matrix3.inverse().transpose();
+            hit.normal = (normal_matrix * hit.normal).normalize_or_zero();
+        }
+        Some(raycast)
+    }
+
+    /// Creates off-mesh connections between the boundary edges of the surface and the boundary edges of `navmesh`
+    /// wherever they are aligned according to `settings`.
+    ///
+    /// At most one connection is created per boundary edge of the surface. It starts at the midpoint of the edge
+    /// and ends at the closest point of the nearest aligned edge of `navmesh`.
+    /// The connections are only valid for the current [`Self::transform`], so they should be recreated whenever the surface moves.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn link_to(
+        &self,
+        navmesh: &PolygonNavmesh,
+        settings: &SurfaceLinkSettings,
+    ) -> Vec<OffMeshConnection> {
+        let tree = BvTree::new(navmesh);
+        let max_sin = settings.max_angle.sin();
+        let extent = Vec3::new(
+            settings.max_gap,
+            settings.max_height_difference,
+            settings.max_gap,
+        );
+        let mut connections = Vec::new();
+        for (a, b) in self.navmesh.boundary_edges() {
+            let a = self.local_to_world(a);
+            let b = self.local_to_world(b);
+            let Some(direction) = (b - a).xz().try_normalize() else {
+                continue;
+            };
+            let midpoint = (a + b) * 0.5;
+            let search_aabb = Aabb3d {
+                min: midpoint - extent,
+                max: midpoint + extent,
+            };
+
+            let mut best: Option<(f32, Vec3)> = None;
+            for polygon in tree.query_aabb(&search_aabb) {
+                for (c, d) in navmesh.polygon_boundary_edges(polygon) {
+                    let edge = (d - c).xz();
+                    let length_squared = edge.length_squared();
+                    if length_squared <= f32::EPSILON {
+                        continue;
+                    }
+                    // The edges must be parallel, facing each other in any direction
+                    if direction.perp_dot(edge).abs() > max_sin * length_squared.sqrt() {
+                        continue;
+                    }
+                    // The midpoint must lie alongside the edge, not beyond its ends
+                    let t = (midpoint.xz() - c.xz()).dot(edge) / length_squared;
+                    if !(0.0..=1.0).contains(&t) {
+                        continue;
+                    }
+                    let closest = c.lerp(d, t);
+                    let gap = midpoint.xz().distance(closest.xz());
+                    if gap > settings.max_gap
+                        || (closest.y - midpoint.y).abs() > settings.max_height_difference
+                    {
+                        continue;
+                    }
+                    if best.is_none_or(|(best_gap, _)| gap < best_gap) {
+                        best = Some((gap, closest));
+                    }
+                }
+            }
+
+            if let Some((_, closest)) = best {
+                connections.push(OffMeshConnection {
+                    radius: settings.radius,
+                    ..OffMeshConnection::new(midpoint, closest)
+                });
+            }
+        }
+        connections
+    }
+}
+
+/// Settings for [`DynamicSurface::link_to`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfaceLinkSettings {
+    /// The maximum horizontal gap between two edges for them to be linked. `[Limit: >= 0] [Units: wu]`
+    pub max_gap: f32,
+    /// The maximum vertical distance between two edges for them to be linked. `[Limit: >= 0] [Units: wu]`
+    pub max_height_difference: f32,
+    /// The maximum angle between two edges on the xz-plane for them to be considered aligned. `[Limit: 0 <= value < 90] [Units: Radians]`
+    pub max_angle: f32,
+    /// The [`OffMeshConnection::radius`] of the created connections. `[Limit: >= 0] [Units: wu]`
+    pub radius: f32,
+}
+
+impl Default for SurfaceLinkSettings {
+    fn default() -> Self {
+        Self {
+            max_gap: 0.5,
+            max_height_difference: 0.3,
+            max_angle: 10_f32.to_radians(),
+            radius: 0.5,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::unit_quad;
+
+    #[test]
+    fn links_only_when_aligned() {
+        let ground = unit_quad();
+        let settings = SurfaceLinkSettings::default();
+
+        let mut platform = DynamicSurface::new(
+            unit_quad(),
+            Affine3A::from_translation(Vec3::new(4.2, 0.1, 0.0)),
+        );
+        let connections = platform.link_to(&ground, &settings);
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].start, Vec3::new(4.2, 0.1, 2.0));
+        assert_eq!(connections[0].end, Vec3::new(4.0, 0.0, 2.0));
+
+        // The elevator moved up
+        platform.transform = Affine3A::from_translation(Vec3::new(4.2, 3.0, 0.0));
+        assert!(platform.link_to(&ground, &settings).is_empty());
+    }
+
+    #[test]
+    fn queries_in_local_space() {
+        let platform = DynamicSurface::new(
+            unit_quad(),
+            Affine3A::from_translation(Vec3::new(10.0, 2.0, 0.0)),
+        );
+        assert_eq!(
+            platform.locate(Vec3::new(11.0, 2.25, 1.0), 0.5),
+            Some((0, Vec3::new(11.0, 2.0, 1.0)))
+        );
+        assert_eq!(platform.locate(Vec3::new(1.0, 0.0, 1.0), 0.5), None);
+
+        let raycast = platform
+            .raycast(Vec3::new(12.0, 2.0, 2.0), Vec3::new(12.0, 2.0, 8.0), 0.5)
+            .unwrap();
+        assert_eq!(raycast.path, vec![0]);
+        let hit = raycast.hit.unwrap();
+        assert_eq!(hit.t, 1.0 / 3.0);
+        assert_eq!(hit.normal, Vec3::NEG_Z);
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
 
 impl CompactHeightfield {
     /// Erode the walkable area by agent radius.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn erode_walkable_area(&mut self, erosion_radius: u16) {
         let mut distance_to_boundary = vec![u8::MAX; self.spans.len()];
 
@@ -0,0 +1,108 @@
+use glam::{Vec2, Vec3, Vec3A, Vec3Swizzles as _};
+
+use crate::{Aabb3d, math::point_in_poly};
+
+/// A volume in which no navmesh is generated, e.g. the decorated interior of a building that agents never enter.
+///
+/// Triangles that lie completely inside any of the [`Heightfield::exclusion_volumes`](crate::Heightfield::exclusion_volumes)
+/// are skipped during rasterization, so they don't cost any rasterization time.
+/// Triangles that only partially overlap a volume are rasterized as usual.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExclusionVolume {
+    /// An axis-aligned box.
+    Aabb(Aabb3d),
+    /// A convex polygon on the xz-plane, extruded along the y-axis.
+    ConvexPrism {
+        /// The vertices of the convex polygon. In 3D, these represent the X and Z coordinates of the vertices.
+        vertices: Vec<Vec2>,
+        /// The lower Y coordinate of the prism.
+        min_y: f32,
+        /// The upper Y coordinate of the prism.
+        max_y: f32,
+    },
+}
+
+impl ExclusionVolume {
+    /// Returns whether `point` lies inside the volume.
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        match self {
+            ExclusionVolume::Aabb(aabb) => {
+                point.cmpge(aabb.min).all() && point.cmple(aabb.max).all()
+            }
+            ExclusionVolume::ConvexPrism {
+                vertices,
+                min_y,
+                max_y,
+            } => (*min_y..=*max_y).contains(&point.y) && point_in_poly(&point.xz(), vertices),
+        }
+    }
+
+    /// Returns whether the triangle lies completely inside the volume.
+    /// Since the volume is convex, this is the case if all of its vertices do.
+    #[inline]
+    pub(crate) fn contains_triangle(&self, triangle: &[Vec3A; 3]) -> bool {
+        triangle
+            .iter()
+            .all(|vertex| self.contains_point(Vec3::from(*vertex)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AreaType, HeightfieldBuilder};
+
+    #[test]
+    fn skips_triangles_inside_exclusion_volumes() {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 5.0, 5.0]),
+            cell_size: 1.0,
+            cell_height: 1.0,
+        }
+        .build()
+        .unwrap();
+        heightfield.exclusion_volumes = vec![
+            ExclusionVolume::Aabb(Aabb3d {
+                min: Vec3::new(-5.0, -1.0, -5.0),
+                max: Vec3::new(0.0, 1.0, 0.0),
+            }),
+            ExclusionVolume::ConvexPrism {
+                vertices: vec![
+                    Vec2::new(0.0, 0.0),
+                    Vec2::new(0.0, 5.0),
+                    Vec2::new(5.0, 5.0),
+                    Vec2::new(5.0, 0.0),
+                ],
+                min_y: -1.0,
+                max_y: 1.0,
+            },
+        ];
+        let triangle = |offset: Vec3A| {
+            [
+                offset + Vec3A::new(0.5, 0.0, 0.5),
+                offset + Vec3A::new(0.5, 0.0, 3.5),
+                offset + Vec3A::new(3.5, 0.0, 0.5),
+            ]
+        };
+        for offset in [
+            // Inside the box
+            Vec3A::new(-5.0, 0.0, -5.0),
+            // Inside the prism
+            Vec3A::new(0.0, 0.0, 0.0),
+            // Outside of both
+            Vec3A::new(-5.0, 0.0, 0.0),
+            // Straddling the box and the prism
+            Vec3A::new(-2.0, 0.0, -2.0),
+        ] {
+            heightfield
+                .rasterize_triangle(triangle(offset), AreaType::DEFAULT_WALKABLE, 1)
+                .unwrap();
+        }
+
+        assert!(heightfield.span_at(0, 0).is_none());
+        assert!(heightfield.span_at(6, 6).is_none());
+        assert!(heightfield.span_at(0, 5).is_some());
+        assert!(heightfield.span_at(3, 3).is_some());
+    }
+}
@@ -0,0 +1,183 @@
+use glam::{Vec2, Vec3, Vec3Swizzles as _};
+
+use crate::{PolygonNavmesh, math::point_in_poly};
+
+/// A convex volume that adds [`PolygonNavmesh::flags`] to the polygons inside it,
+/// e.g. to mark a region behind a locked door as requiring a key, or a base as only accessible to one faction.
+///
+/// Queries can then include or exclude polygons by these flags per agent.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlagVolume {
+    /// The vertices of the volume on the xz-plane. `[Units: wu]`
+    pub vertices: Vec<Vec2>,
+    /// The lower y-coordinate of the volume. `[Units: wu]`
+    pub min_y: f32,
+    /// The upper y-coordinate of the volume. `[Units: wu]`
+    pub max_y: f32,
+    /// The flags added to the polygons inside the volume.
+    pub flags: u16,
+}
+
+impl PolygonNavmesh {
+    /// Adds the flags of `volume` to all polygons whose centroid lies inside it.
+    ///
+    /// Returns the number of polygons that were marked.
+    pub fn mark_flag_volume(&mut self, volume: &FlagVolume) -> usize {
+        if volume.vertices.len() < 3 {
+            return 0;
+        }
+        let mut marked = 0;
+        for polygon in 0..self.polygon_count() {
+            let vertex_count = self.polygon_vertices(polygon).len();
+            if vertex_count == 0 {
+                continue;
+            }
+            let centroid = self.polygon_world_vertices(polygon).sum::<Vec3>() / vertex_count as f32;
+            if (volume.min_y..=volume.max_y).contains(&centroid.y)
+                && point_in_poly(&centroid.xz(), &volume.vertices)
+            {
+                self.flags[polygon] |= volume.flags;
+                marked += 1;
+            }
+        }
+        marked
+    }
+
+    /// Edits the flags of all polygons intersecting the prism spanned by the convex `vertices` on the xz-plane between `min_y` and `max_y`,
+    /// first clearing the flags in `clear` and then adding the flags in `set`. `[Units: wu]`
+    ///
+    /// Unlike [`Self::mark_flag_volume`], polygons only partially inside the volume are edited as well.
+    /// Meant for large-scale changes at runtime without rebuilding any tiles, e.g. a flooded district becoming swim-only.
+    ///
+    /// Returns the number of polygons that were edited.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn set_flags_in_volume(
+        &mut self,
+        vertices: &[Vec2],
+        min_y: f32,
+        max_y: f32,
+        set: u16,
+        clear: u16,
+    ) -> usize {
+        if vertices.len() < 3 {
+            return 0;
+        }
+        let mut edited = 0;
+        let mut polygon_vertices = Vec::new();
+        for polygon in 0..self.polygon_count() {
+            polygon_vertices.clear();
+            let (mut polygon_min_y, mut polygon_max_y) = (f32::INFINITY, f32::NEG_INFINITY);
+            for vertex in self.polygon_world_vertices(polygon) {
+                polygon_min_y = polygon_min_y.min(vertex.y);
+                polygon_max_y = polygon_max_y.max(vertex.y);
+                polygon_vertices.push(vertex.xz());
+            }
+            if polygon_vertices.len() < 3
+                || polygon_max_y < min_y
+                || polygon_min_y > max_y
+                || !convex_polygons_overlap(&polygon_vertices, vertices)
+            {
+                continue;
+            }
+            self.flags[polygon] = (self.flags[polygon] & !clear) | set;
+            edited += 1;
+        }
+        edited
+    }
+
+    /// Iterates over the indices of all polygons that have at least one of `flags` set,
+    /// e.g. to visualize the regions marked by [`FlagVolume`]s.
+    pub fn polygons_with_flags(&self, flags: u16) -> impl Iterator<Item = usize> + '_ {
+        (0..self.polygon_count()).filter(move |polygon| self.flags[*polygon] & flags != 0)
+    }
+}
+
+/// Returns whether the convex polygons `a` and `b` overlap on the xz-plane, in either winding order.
+/// Polygons only touching along an edge or at a vertex do not overlap.
+fn convex_polygons_overlap(a: &[Vec2], b: &[Vec2]) -> bool {
+    let project = |vertices: &[Vec2], axis: Vec2| {
+        vertices
+            .iter()
+            .map(|vertex| vertex.dot(axis))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), d| {
+                (min.min(d), max.max(d))
+            })
+    };
+    // Separating axis theorem: the polygons are disjoint if any edge normal separates them.
+    [a, b].into_iter().all(|polygon| {
+        (0..polygon.len()).all(|i| {
+            let axis = (polygon[(i + 1) % polygon.len()] - polygon[i]).perp();
+            let (min_a, max_a) = project(a, axis);
+            let (min_b, max_b) = project(b, axis);
+            max_a > min_b && max_b > min_a
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    #[test]
+    fn marks_polygons_inside_volume() {
+        const REQUIRES_KEY: u16 = 1 << 3;
+        let mut grid = GridNavmesh::parse("a.bc");
+        let volume = FlagVolume {
+            vertices: vec![
+                Vec2::new(2.0, -1.0),
+                Vec2::new(2.0, 2.0),
+                Vec2::new(5.0, 2.0),
+                Vec2::new(5.0, -1.0),
+            ],
+            min_y: -1.0,
+            max_y: 1.0,
+            flags: REQUIRES_KEY,
+        };
+        assert_eq!(grid.navmesh.mark_flag_volume(&volume), 2);
+
+        let marked: Vec<_> = grid.navmesh.polygons_with_flags(REQUIRES_KEY).collect();
+        assert_eq!(marked, vec![grid.polygon('b'), grid.polygon('c')]);
+        assert_eq!(grid.navmesh.flags[grid.polygon('a')], 0);
+
+        // Volumes above the navmesh do not mark it
+        let high = FlagVolume {
+            min_y: 2.0,
+            max_y: 3.0,
+            flags: 1,
+            ..volume
+        };
+        assert_eq!(grid.navmesh.mark_flag_volume(&high), 0);
+    }
+
+    #[test]
+    fn edits_flags_of_intersecting_polygons() {
+        const WALK: u16 = 1 << 0;
+        const SWIM: u16 = 1 << 1;
+        let mut grid = GridNavmesh::parse("a.bc");
+        grid.navmesh.flags.fill(WALK | 1 << 4);
+        // Covers half of the floor, all of `b` and touches `c`.
+        let flooded = [
+            Vec2::new(1.5, 3.0),
+            Vec2::new(3.0, 3.0),
+            Vec2::new(3.0, -2.0),
+            Vec2::new(1.5, -2.0),
+        ];
+        assert_eq!(
+            grid.navmesh
+                .set_flags_in_volume(&flooded, -1.0, 1.0, SWIM, WALK),
+            2
+        );
+        let swim_only: Vec<_> = grid.navmesh.polygons_with_flags(SWIM).collect();
+        assert_eq!(swim_only, vec![grid.polygon('a') + 1, grid.polygon('b')]);
+        assert_eq!(grid.navmesh.flags[grid.polygon('b')], SWIM | 1 << 4);
+        assert_eq!(grid.navmesh.flags[grid.polygon('c')], WALK | 1 << 4);
+
+        assert_eq!(
+            grid.navmesh
+                .set_flags_in_volume(&flooded, 2.0, 3.0, WALK, SWIM),
+            0
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use core::future::Future;
+
+use glam::UVec3;
+
+use crate::{
+    Aabb3d, Heightfield, TriMesh, math::TriangleVertices as _, rasterize::RasterizationError,
+};
+
+/// A source of input geometry that hands out only the triangles intersecting a requested region.
+///
+/// Builds ask the provider for the geometry of the region they are currently working on,
+/// i.e. the whole world for a full build or a single tile including its border for a tiled build,
+/// so worlds too large to hold in memory at once can be streamed in piece by piece.
+/// Providers are accepted by [`build_solo_navmesh_from`](crate::build_solo_navmesh_from),
+/// [`TiledNavmeshBuilder`](crate::TiledNavmeshBuilder) and [`Heightfield::rasterize_geometry`].
+///
+/// The returned triangles must already be in world space and have their [`TriMesh::area_types`] assigned,
+/// e.g. through [`TriMesh::mark_walkable_triangles`]. Returning triangles outside the region is allowed,
+/// they are clipped during rasterization.
+pub trait GeometryProvider {
+    /// Returns the triangles intersecting `aabb`.
+    fn triangles(&self, aabb: &Aabb3d) -> TriMesh;
+}
+
+/// The asynchronous counterpart of [`GeometryProvider`], for providers that read geometry from disk or the network.
+///
+/// Accepted by [`build_solo_navmesh_from_async`](crate::build_solo_navmesh_from_async) and [`Heightfield::rasterize_geometry_async`].
+/// Every [`GeometryProvider`] is also an [`AsyncGeometryProvider`] that resolves immediately.
+pub trait AsyncGeometryProvider {
+    /// Returns the triangles intersecting `aabb`.
+    fn triangles(&self, aabb: &Aabb3d) -> impl Future<Output = TriMesh> + Send;
+}
+
+impl<T: GeometryProvider + Sync> AsyncGeometryProvider for T {
+    fn triangles(&self, aabb: &Aabb3d) -> impl Future<Output = TriMesh> + Send {
+        core::future::ready(GeometryProvider::triangles(self, aabb))
+    }
+}
+
+impl<F: Fn(&Aabb3d) -> TriMesh> GeometryProvider for F {
+    fn triangles(&self, aabb: &Aabb3d) -> TriMesh {
+        self(aabb)
+    }
+}
+
+impl GeometryProvider for TriMesh {
+    fn triangles(&self, aabb: &Aabb3d) -> TriMesh {
+        let mut trimesh = TriMesh::default();
+        for (triangle_index, (indices, area_type)) in
+            self.indices.iter().zip(&self.area_types).enumerate()
+        {
+            let triangle = [
+                self.vertices[indices[0] as usize],
+                self.vertices[indices[1] as usize],
+                self.vertices[indices[2] as usize],
+            ];
+            if !triangle.aabb().intersects(aabb) {
+                continue;
+            }
+            // Vertices are duplicated per triangle. The rasterizer does not care about sharing.
+            let next_vertex_index = trimesh.vertices.len() as u32;
+            trimesh.vertices.extend(triangle);
+            trimesh.indices.push(UVec3::new(
+                next_vertex_index,
+                next_vertex_index + 1,
+                next_vertex_index + 2,
+            ));
+            trimesh.area_types.push(*area_type);
+            if !self.materials.is_empty() {
+                trimesh.materials.push(self.material(triangle_index));
+            }
+        }
+        trimesh
+    }
+}
+
+impl Heightfield {
+    /// Rasterizes the geometry the provider returns for the bounds of this heightfield.
+    ///
+    /// See [`Heightfield::rasterize_triangles`] for details on the rasterization itself.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn rasterize_geometry(
+        &mut self,
+        provider: &impl GeometryProvider,
+        walkable_climb: u16,
+    ) -> Result<(), RasterizationError> {
+        let trimesh = GeometryProvider::triangles(provider, &self.aabb);
+        self.rasterize_triangles(&trimesh, walkable_climb)
+    }
+
+    /// Asynchronous version of [`Heightfield::rasterize_geometry`].
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn rasterize_geometry_async(
+        &mut self,
+        provider: &impl AsyncGeometryProvider,
+        walkable_climb: u16,
+    ) -> Result<(), RasterizationError> {
+        let trimesh = AsyncGeometryProvider::triangles(provider, &self.aabb).await;
+        self.rasterize_triangles(&trimesh, walkable_climb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3A;
+
+    use super::*;
+    use crate::AreaType;
+
+    #[test]
+    fn trimesh_only_provides_intersecting_triangles() {
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(1.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 1.0),
+                Vec3A::new(10.0, 0.0, 10.0),
+                Vec3A::new(11.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 11.0),
+            ],
+            indices: vec![UVec3::new(0, 2, 1), UVec3::new(3, 5, 4)],
+            area_types: vec![AreaType::DEFAULT_WALKABLE, AreaType::NOT_WALKABLE],
+            materials: Vec::new(),
+        };
+        let aabb = Aabb3d::new([9.0, 0.0, 9.0], [2.0, 1.0, 2.0]);
+
+        let provided = GeometryProvider::triangles(&trimesh, &aabb);
+
+        assert_eq!(provided.indices, vec![UVec3::new(0, 1, 2)]);
+        assert_eq!(provided.area_types, vec![AreaType::NOT_WALKABLE]);
+        assert_eq!(
+            provided.vertices,
+            vec![
+                Vec3A::new(10.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 11.0),
+                Vec3A::new(11.0, 0.0, 10.0),
+            ]
+        );
+    }
+}
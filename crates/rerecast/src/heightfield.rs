@@ -2,10 +2,13 @@
 //!
 //! A heightfield is a 3D grid of [`Span`]s, where each column contains 0, 1, or more spans.
 
+use std::collections::HashMap;
+
+use glam::{Vec2, Vec3A};
 use thiserror::Error;
 
 use crate::{
-    Aabb3d, TriMesh,
+    Aabb3d, AreaRegistry, AreaType, BuildWarning, ExclusionVolume, SpanSource, TriMesh,
     rasterize::RasterizationError,
     span::{Span, SpanKey, Spans},
 };
@@ -70,10 +73,36 @@ pub struct Heightfield {
     pub spans: Vec<Option<SpanKey>>,
     /// All spans in the heightfield
     pub allocated_spans: Spans,
+    /// Whether to record how far the surface of each rasterized span lies below its quantized top in [`Span::top_offset`].
+    ///
+    /// The offsets are carried over to [`CompactHeightfield::height_offsets`](crate::CompactHeightfield::height_offsets)
+    /// and used when sampling heights for the [`DetailNavmesh`](crate::DetailNavmesh),
+    /// which avoids visible stair-stepping on ramps when using a coarse cell height.
+    pub sub_voxel_heights: bool,
+    /// An optional boundary polygon on the xz-plane that restricts rasterization, e.g. the playable area of an island
+    /// inside a much larger source mesh. The polygon may be concave, but must not intersect itself.
+    ///
+    /// Triangles outside of the boundary are skipped, and spans are only added to columns whose center lies within it,
+    /// so the final navmesh is clipped to the boundary at the resolution of [`Self::cell_size`].
+    pub boundary: Option<Vec<Vec2>>,
+    /// Volumes in which no geometry is rasterized. See [`ExclusionVolume`].
+    pub exclusion_volumes: Vec<ExclusionVolume>,
+    /// Whether [`Heightfield::rasterize_triangles`] records which source triangle produced each span in [`Self::sources`].
+    ///
+    /// Costs 16 bytes per rasterized span, which usually exceeds the size of the heightfield itself,
+    /// so only enable this when debugging the navmesh, e.g. through [`PolygonNavmesh::polygon_sources`](crate::PolygonNavmesh::polygon_sources).
+    pub record_sources: bool,
+    /// The source triangles of all rasterized spans, recorded when [`Self::record_sources`] is enabled.
+    pub sources: Vec<SpanSource>,
+    /// The names used for area types in the [`BuildWarning::AreaOverwritten`] warnings emitted while rasterizing.
+    pub area_registry: Option<AreaRegistry>,
+    /// The areas overwritten since the last [`BuildWarning::AreaOverwritten`] warnings were emitted.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    pub(crate) area_overwrites: AreaOverwrites,
 }
 
 impl Heightfield {
-    /// Rasterizes the triangles of a [`TriMesh`] into a [`Heightfield`].
+    /// Rasterizes the triangles of a [`TriMesh`] into a [`Heightfield`] and filters the resulting spans.
     ///
     /// # Arguments
     ///
@@ -81,50 +110,144 @@ impl Heightfield {
     /// - `walkable_height` Minimum floor to 'ceiling' height that will still allow the floor area to be considered walkable. [Limit: >= 3] [Units: vx]
     /// - `walkable_climb` - Minimum floor to 'ceiling' height that will still allow the floor area to be considered walkable. [Limit: >= 3] [Units: vx]
     ///
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn populate_from_trimesh(
         &mut self,
-        trimesh: TriMesh,
+        trimesh: &TriMesh,
         walkable_height: u16,
         walkable_climb: u16,
     ) -> Result<(), RasterizationError> {
         // Implementation note: flag_merge_threshold and walkable_climb_height are the same thing in practice, so we just chose one name for the param.
+        self.rasterize_triangles(trimesh, walkable_climb)?;
+        self.filter_rasterized_spans(walkable_height, walkable_climb);
+        Ok(())
+    }
 
-        // Find triangles which are walkable based on their slope and rasterize them.
-        for (i, triangle) in trimesh.indices.iter().enumerate() {
-            let triangle = [
-                trimesh.vertices[triangle[0] as usize],
-                trimesh.vertices[triangle[1] as usize],
-                trimesh.vertices[triangle[2] as usize],
-            ];
-            let area_type = trimesh.area_types[i];
-            self.rasterize_triangle(triangle, area_type, walkable_climb)?;
-        }
+    /// Like [`Heightfield::populate_from_trimesh`], but rasterizes the triangles on all threads of the rayon thread pool,
+    /// see [`Heightfield::rasterize_triangles_parallel`]. The result is identical to [`Heightfield::populate_from_trimesh`].
+    ///
+    /// Only available with the `rayon` feature.
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn populate_from_trimesh_parallel(
+        &mut self,
+        trimesh: &TriMesh,
+        walkable_height: u16,
+        walkable_climb: u16,
+    ) -> Result<(), RasterizationError> {
+        self.rasterize_triangles_parallel(trimesh, walkable_climb)?;
+        self.filter_rasterized_spans(walkable_height, walkable_climb);
+        Ok(())
+    }
+
+    /// Like [`Heightfield::populate_from_trimesh`], but rasterizes a stream of triangles and their area types,
+    /// see [`Heightfield::rasterize_triangle_stream`].
+    ///
+    /// Procedural generators can feed their triangles on the fly with this,
+    /// so memory use is bounded by the heightfield instead of the size of the geometry.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn populate_from_triangles(
+        &mut self,
+        triangles: impl IntoIterator<Item = ([Vec3A; 3], AreaType)>,
+        walkable_height: u16,
+        walkable_climb: u16,
+    ) -> Result<(), RasterizationError> {
+        self.rasterize_triangle_stream(triangles, walkable_climb)?;
+        self.filter_rasterized_spans(walkable_height, walkable_climb);
+        Ok(())
+    }
+
+    fn filter_rasterized_spans(&mut self, walkable_height: u16, walkable_climb: u16) {
         // Once all geometry is rasterized, we do initial pass of filtering to
         // remove unwanted overhangs caused by the conservative rasterization
         // as well as filter spans where the character cannot possibly stand.
         self.filter_low_hanging_walkable_obstacles(walkable_climb);
         self.filter_ledge_spans(walkable_height, walkable_climb);
         self.filter_walkable_low_height_spans(walkable_height);
-        Ok(())
     }
 
-    /// https://github.com/recastnavigation/recastnavigation/blob/bd98d84c274ee06842bf51a4088ca82ac71f8c2d/Recast/Source/RecastRasterization.cpp#L105
+    /// Inserts a span into the column at the given coordinates, resolving overlaps with existing spans according to [`SpanInsertion::overlap`].
+    ///
+    /// Returns how the span was inserted.
+    ///
+    /// <https://github.com/recastnavigation/recastnavigation/blob/bd98d84c274ee06842bf51a4088ca82ac71f8c2d/Recast/Source/RecastRasterization.cpp#L105>
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the coordinates are outside the heightfield or if the span is empty.
     #[inline]
-    pub(crate) fn add_span(&mut self, insertion: SpanInsertion) -> Result<(), SpanInsertionError> {
+    pub fn add_span(
+        &mut self,
+        insertion: SpanInsertion,
+    ) -> Result<SpanInsertionOutcome, SpanInsertionError> {
         let column_index = self.column_index(insertion.x, insertion.z);
-        if column_index >= self.spans.len() {
+        if insertion.x >= self.width || column_index >= self.spans.len() {
             return Err(SpanInsertionError::ColumnIndexOutOfBounds {
                 x: insertion.x,
                 y: insertion.z,
             });
         }
+        if insertion.span.min >= insertion.span.max {
+            return Err(SpanInsertionError::EmptySpan {
+                min: insertion.span.min,
+                max: insertion.span.max,
+            });
+        }
+
+        match insertion.overlap {
+            SpanOverlap::Merge => Ok(self.merge_span(column_index, insertion)),
+            SpanOverlap::ClipToTop => {
+                let mut span = insertion.span;
+                let original_min = span.min;
+                let mut span_key_iter = self.spans[column_index];
+                while let Some(span_key) = span_key_iter {
+                    let current_span = self.span(span_key);
+                    span_key_iter = current_span.next;
+                    if current_span.min < span.max && current_span.max > span.min {
+                        span.min = current_span.max;
+                    }
+                }
+                if span.min >= span.max {
+                    return Ok(SpanInsertionOutcome::Rejected);
+                }
+                let clipped = original_min != span.min;
+                self.insert_span(column_index, span);
+                if clipped {
+                    Ok(SpanInsertionOutcome::Clipped)
+                } else {
+                    Ok(SpanInsertionOutcome::Inserted)
+                }
+            }
+            SpanOverlap::Reject => {
+                let mut span_key_iter = self.spans[column_index];
+                while let Some(span_key) = span_key_iter {
+                    let current_span = self.span(span_key);
+                    span_key_iter = current_span.next;
+                    if current_span.min < insertion.span.max
+                        && current_span.max > insertion.span.min
+                    {
+                        return Ok(SpanInsertionOutcome::Rejected);
+                    }
+                }
+                self.insert_span(column_index, insertion.span);
+                Ok(SpanInsertionOutcome::Inserted)
+            }
+        }
+    }
 
+    /// Inserts a span into a column, merging it with all spans it overlaps or touches.
+    fn merge_span(
+        &mut self,
+        column_index: usize,
+        insertion: SpanInsertion,
+    ) -> SpanInsertionOutcome {
+        let mut outcome = SpanInsertionOutcome::Inserted;
         let mut new_span = insertion.span;
         let mut previous_span_key = None;
         let mut current_span_key_iter = self.spans[column_index];
         // Insert the new span, possibly merging it with existing spans.
         while let Some(current_span_key) = current_span_key_iter {
-            let current_span = self.span_mut(current_span_key);
+            let current_span = &self.allocated_spans[current_span_key];
             current_span_key_iter = current_span.next;
             if current_span.min > new_span.max {
                 // Current span is completely below the new span, break.
@@ -141,6 +264,10 @@ impl Heightfield {
             }
             if current_span.max > new_span.max {
                 new_span.max = current_span.max;
+                new_span.top_offset = current_span.top_offset;
+            } else if current_span.max == new_span.max {
+                // Keep the higher of the two surfaces.
+                new_span.top_offset = new_span.top_offset.min(current_span.top_offset);
             }
 
             // Merge flags.
@@ -148,12 +275,18 @@ impl Heightfield {
                 <= insertion.flag_merge_threshold as u32
             {
                 // Higher area ID numbers indicate higher resolution priority.
-                let area = new_span.area.max(current_span.area.0);
-                new_span.area = area.into();
+                self.area_overwrites.record(
+                    insertion.x,
+                    insertion.z,
+                    new_span.area,
+                    current_span.area,
+                );
+                new_span.area = new_span.area.max(current_span.area);
             }
 
             // Remove the current span since it's now merged with newSpan.
             // Keep going because there might be other overlapping spans that also need to be merged.
+            outcome = SpanInsertionOutcome::Merged;
             let next_key = current_span.next;
             self.allocated_spans.remove(current_span_key);
             if let Some(previous_span_key) = previous_span_key {
@@ -176,9 +309,79 @@ impl Heightfield {
             self.spans[column_index] = Some(new_span_key);
         }
 
+        outcome
+    }
+
+    /// Replaces all spans of the column at the given coordinates with `spans`,
+    /// e.g. to write back columns that were rasterized outside of the heightfield.
+    ///
+    /// `spans` must be sorted from bottom to top and must not overlap. Their [`Span::next`] is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the coordinates are outside the heightfield.
+    pub fn replace_column(
+        &mut self,
+        x: u16,
+        z: u16,
+        spans: impl IntoIterator<Item = Span>,
+    ) -> Result<(), SpanInsertionError> {
+        let column_index = self.column_index(x, z);
+        if x >= self.width || column_index >= self.spans.len() {
+            return Err(SpanInsertionError::ColumnIndexOutOfBounds { x, y: z });
+        }
+        let mut span_key_iter = self.spans[column_index].take();
+        while let Some(span_key) = span_key_iter {
+            span_key_iter = self
+                .allocated_spans
+                .remove(span_key)
+                .and_then(|span| span.next);
+        }
+        let mut previous_span_key = None;
+        for mut span in spans {
+            span.next = None;
+            let span_key = self.allocated_spans.insert(span);
+            if let Some(previous_span_key) = previous_span_key {
+                self.span_mut(previous_span_key).next = Some(span_key);
+            } else {
+                self.spans[column_index] = Some(span_key);
+            }
+            previous_span_key = Some(span_key);
+        }
         Ok(())
     }
 
+    /// Iterates over the spans of the column at `column_index` from bottom to top.
+    pub(crate) fn column_spans_at_index(&self, column_index: usize) -> impl Iterator<Item = &Span> {
+        let mut span_key_iter = self.spans[column_index];
+        std::iter::from_fn(move || {
+            let span = self.span(span_key_iter?);
+            span_key_iter = span.next;
+            Some(span)
+        })
+    }
+
+    /// Inserts a span that does not overlap any existing span into a column, keeping the column sorted.
+    fn insert_span(&mut self, column_index: usize, mut span: Span) {
+        let mut previous_span_key = None;
+        let mut span_key_iter = self.spans[column_index];
+        while let Some(span_key) = span_key_iter {
+            let current_span = self.span(span_key);
+            if current_span.min >= span.max {
+                break;
+            }
+            previous_span_key = Some(span_key);
+            span_key_iter = current_span.next;
+        }
+        span.next = span_key_iter;
+        let span_key = self.allocated_spans.insert(span);
+        if let Some(previous_span_key) = previous_span_key {
+            self.span_mut(previous_span_key).next = Some(span_key);
+        } else {
+            self.spans[column_index] = Some(span_key);
+        }
+    }
+
     #[inline]
     pub(crate) fn column_index(&self, x: u16, z: u16) -> usize {
         x as usize + z as usize * self.width as usize
@@ -241,6 +444,107 @@ impl Heightfield {
     }
 }
 
+/// Merges `new_span` into a column held outside of a heightfield, sorted from bottom to top.
+///
+/// Resolves overlaps exactly like [`SpanOverlap::Merge`] does for the columns of a [`Heightfield`],
+/// so columns built with this can be written back with [`Heightfield::replace_column`] without changing the result.
+#[cfg(feature = "rayon")]
+pub(crate) fn merge_span_into_column(
+    column: &mut Vec<Span>,
+    mut new_span: Span,
+    flag_merge_threshold: u16,
+    (x, z): (u16, u16),
+    area_overwrites: &mut AreaOverwrites,
+) {
+    let mut index = 0;
+    while let Some(current_span) = column.get(index) {
+        if current_span.min > new_span.max {
+            // The current span and all spans after it lie above the new span.
+            break;
+        }
+        if current_span.max < new_span.min {
+            // The current span lies below the new span.
+            index += 1;
+            continue;
+        }
+        // The new span overlaps with an existing span.  Merge them.
+        if current_span.min < new_span.min {
+            new_span.min = current_span.min;
+        }
+        if current_span.max > new_span.max {
+            new_span.max = current_span.max;
+            new_span.top_offset = current_span.top_offset;
+        } else if current_span.max == new_span.max {
+            // Keep the higher of the two surfaces.
+            new_span.top_offset = new_span.top_offset.min(current_span.top_offset);
+        }
+        // Merge flags.
+        if (new_span.max as i32 - current_span.max as i32).unsigned_abs()
+            <= flag_merge_threshold as u32
+        {
+            // Higher area ID numbers indicate higher resolution priority.
+            area_overwrites.record(x, z, new_span.area, current_span.area);
+            new_span.area = new_span.area.max(current_span.area);
+        }
+        column.remove(index);
+    }
+    new_span.next = None;
+    column.insert(index, new_span);
+}
+
+/// The walkable areas overwritten by a different walkable area while merging spans,
+/// collected until they are reported as [`BuildWarning::AreaOverwritten`].
+///
+/// Keyed by the overwritten area and the area that took its place, with the first span in row-major order
+/// and the number of spans, so the warnings do not depend on the order the spans were merged in.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct AreaOverwrites(HashMap<(AreaType, AreaType), ((u16, u16), usize)>);
+
+impl AreaOverwrites {
+    /// Records that spans of areas `a` and `b` were merged at `(x, z)`. Nothing is overwritten
+    /// if both areas are the same, or if one of them is [`AreaType::NOT_WALKABLE`].
+    #[inline]
+    pub(crate) fn record(&mut self, x: u16, z: u16, a: AreaType, b: AreaType) {
+        if a == b || a == AreaType::NOT_WALKABLE || b == AreaType::NOT_WALKABLE {
+            return;
+        }
+        let (first, count) = self.0.entry((a.min(b), a.max(b))).or_insert(((x, z), 0));
+        if (z, x) < (first.1, first.0) {
+            *first = (x, z);
+        }
+        *count += 1;
+    }
+
+    /// Adds the overwrites collected in `other`.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn append(&mut self, other: AreaOverwrites) {
+        for (areas, ((x, z), count)) in other.0 {
+            let ((first_x, first_z), total) = self.0.entry(areas).or_insert(((x, z), 0));
+            if (z, x) < (*first_z, *first_x) {
+                (*first_x, *first_z) = (x, z);
+            }
+            *total += count;
+        }
+    }
+
+    /// Emits a [`BuildWarning::AreaOverwritten`] for every pair of areas, ordered by the areas, and clears the overwrites.
+    pub(crate) fn emit(&mut self, registry: Option<&AreaRegistry>) {
+        let mut overwrites: Vec<_> = self.0.drain().collect();
+        overwrites.sort_unstable_by_key(|(areas, _)| *areas);
+        let empty = AreaRegistry::empty();
+        for ((overwritten, by), ((x, z), count)) in overwrites {
+            BuildWarning::AreaOverwritten {
+                overwritten,
+                by,
+                x,
+                z,
+                count,
+            }
+            .emit_with(registry.unwrap_or(&empty));
+        }
+    }
+}
+
 /// A builder for [`Heightfield`]s.
 pub struct HeightfieldBuilder {
     /// The AABB of the heightfield
@@ -273,6 +577,13 @@ impl HeightfieldBuilder {
             cell_height: self.cell_height,
             spans: vec![None; column_count],
             allocated_spans: Spans::with_min_capacity(column_count),
+            sub_voxel_heights: false,
+            boundary: None,
+            exclusion_volumes: Vec::new(),
+            record_sources: false,
+            sources: Vec::new(),
+            area_registry: None,
+            area_overwrites: AreaOverwrites::default(),
         })
     }
 }
@@ -301,17 +612,60 @@ pub enum SpanInsertionError {
         /// The z-coordinate of the span
         y: u16,
     },
+    /// Happens when the span's floor is not below its ceiling.
+    #[error("span is empty: min={min}, max={max}")]
+    EmptySpan {
+        /// The floor of the span
+        min: u16,
+        /// The ceiling of the span
+        max: u16,
+    },
 }
 
-pub(crate) struct SpanInsertion {
+/// A span to insert into a [`Heightfield`] with [`Heightfield::add_span`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanInsertion {
     /// The x-coordinate of the span
-    pub(crate) x: u16,
+    pub x: u16,
     /// The z-coordinate of the span
-    pub(crate) z: u16,
-    /// Maximum difference between the ceilings of two spans to merge area type IDs
-    pub(crate) flag_merge_threshold: u16,
-    /// The span to insert
-    pub(crate) span: Span,
+    pub z: u16,
+    /// Maximum difference between the ceilings of two spans to merge area type IDs.
+    /// Only used by [`SpanOverlap::Merge`]. `[Units: vx]`
+    ///
+    /// Rasterization uses the walkable climb of the agent for this, so that two surfaces an agent can step between
+    /// end up with the higher priority area of the two, see [`Heightfield::rasterize_triangle`].
+    pub flag_merge_threshold: u16,
+    /// How to resolve overlaps with existing spans in the column
+    pub overlap: SpanOverlap,
+    /// The span to insert. Its [`Span::next`] is ignored.
+    pub span: Span,
+}
+
+/// How [`Heightfield::add_span`] resolves a new span overlapping existing spans in its column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SpanOverlap {
+    /// Merge the new span with all spans it overlaps or touches into a single span, like rasterization does.
+    /// The area type of the top-most span wins, see [`AreaType`](crate::AreaType).
+    #[default]
+    Merge,
+    /// Raise the floor of the new span to the ceiling of the spans it overlaps, keeping only the part above them.
+    /// The new span is discarded if nothing of it remains.
+    ClipToTop,
+    /// Discard the new span if it overlaps any existing span.
+    Reject,
+}
+
+/// What [`Heightfield::add_span`] did with a span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpanInsertionOutcome {
+    /// The span did not overlap any existing span and was inserted as is.
+    Inserted,
+    /// The span was merged with one or more existing spans.
+    Merged,
+    /// The span was clipped to the top of the spans it overlapped before being inserted.
+    Clipped,
+    /// The span was discarded.
+    Rejected,
 }
 
 #[cfg(test)]
@@ -340,7 +694,7 @@ mod tests {
         SpanBuilder {
             min: 2,
             max: 4,
-            area: AreaType(2),
+            area: AreaType::new(2),
             next: None,
         }
     }
@@ -349,7 +703,7 @@ mod tests {
         SpanBuilder {
             min: 4,
             max: 7,
-            area: AreaType(2),
+            area: AreaType::new(2),
             next: None,
         }
     }
@@ -358,7 +712,7 @@ mod tests {
         SpanBuilder {
             min: 7,
             max: 10,
-            area: AreaType(2),
+            area: AreaType::new(2),
             next: None,
         }
     }
@@ -377,6 +731,7 @@ mod tests {
                 x: 1,
                 z: 3,
                 flag_merge_threshold: 0,
+                overlap: SpanOverlap::Merge,
                 span: expected_span.clone(),
             })
             .unwrap();
@@ -396,6 +751,7 @@ mod tests {
                 x: 1,
                 z: 3,
                 flag_merge_threshold: 0,
+                overlap: SpanOverlap::Merge,
                 span: expected_span_1.clone(),
             })
             .unwrap();
@@ -406,6 +762,7 @@ mod tests {
                 x: 2,
                 z: 3,
                 flag_merge_threshold: 0,
+                overlap: SpanOverlap::Merge,
                 span: expected_span_2.clone(),
             })
             .unwrap();
@@ -428,6 +785,7 @@ mod tests {
                 x: 1,
                 z: 3,
                 flag_merge_threshold: 0,
+                overlap: SpanOverlap::Merge,
                 span: span_low.clone(),
             })
             .unwrap();
@@ -438,6 +796,7 @@ mod tests {
                 x: 1,
                 z: 3,
                 flag_merge_threshold: 0,
+                overlap: SpanOverlap::Merge,
                 span: span_high.clone(),
             })
             .unwrap();
@@ -461,6 +820,7 @@ mod tests {
                 x: 1,
                 z: 3,
                 flag_merge_threshold: 0,
+                overlap: SpanOverlap::Merge,
                 span: span_high.clone(),
             })
             .unwrap();
@@ -471,6 +831,7 @@ mod tests {
                 x: 1,
                 z: 3,
                 flag_merge_threshold: 0,
+                overlap: SpanOverlap::Merge,
                 span: span_low.clone(),
             })
             .unwrap();
@@ -494,6 +855,7 @@ mod tests {
                 x: 1,
                 z: 3,
                 flag_merge_threshold: 0,
+                overlap: SpanOverlap::Merge,
                 span: span_low.clone(),
             })
             .unwrap();
@@ -504,6 +866,7 @@ mod tests {
                 x: 1,
                 z: 3,
                 flag_merge_threshold: 0,
+                overlap: SpanOverlap::Merge,
                 span: span_mid.clone(),
             })
             .unwrap();
@@ -523,10 +886,286 @@ mod tests {
         assert_eq!(empty_span, None);
     }
 
+    #[test]
+    fn resolves_overlaps_as_requested() {
+        let mut heightfield = height_field();
+        let insertion = |span: SpanBuilder, overlap| SpanInsertion {
+            x: 1,
+            z: 3,
+            flag_merge_threshold: 0,
+            overlap,
+            span: span.build(),
+        };
+        assert_eq!(
+            heightfield
+                .add_span(insertion(span_low(), SpanOverlap::Merge))
+                .unwrap(),
+            SpanInsertionOutcome::Inserted
+        );
+        assert_eq!(
+            heightfield
+                .add_span(insertion(span_high(), SpanOverlap::Reject))
+                .unwrap(),
+            SpanInsertionOutcome::Inserted
+        );
+
+        // Overlaps both the low and the high span
+        let overlapping = || SpanBuilder {
+            min: 3,
+            max: 8,
+            area: AreaType::new(5),
+            next: None,
+        };
+        assert_eq!(
+            heightfield
+                .add_span(insertion(overlapping(), SpanOverlap::Reject))
+                .unwrap(),
+            SpanInsertionOutcome::Rejected
+        );
+        assert_eq!(
+            heightfield
+                .add_span(insertion(overlapping(), SpanOverlap::ClipToTop))
+                .unwrap(),
+            SpanInsertionOutcome::Rejected
+        );
+
+        // Only overlaps the low span
+        let clipped = SpanBuilder {
+            min: 3,
+            max: 6,
+            area: AreaType::new(5),
+            next: None,
+        };
+        assert_eq!(
+            heightfield
+                .add_span(insertion(clipped, SpanOverlap::ClipToTop))
+                .unwrap(),
+            SpanInsertionOutcome::Clipped
+        );
+        let low = heightfield.span_at(1, 3).unwrap();
+        let mid = heightfield.span(low.next.unwrap());
+        assert_eq!((mid.min, mid.max), (4, 6));
+        let high = heightfield.span(mid.next.unwrap());
+        assert_eq!((high.min, high.max), (7, 10));
+
+        let empty = SpanBuilder {
+            min: 3,
+            max: 3,
+            area: AreaType::new(5),
+            next: None,
+        };
+        assert!(matches!(
+            heightfield.add_span(insertion(empty, SpanOverlap::Merge)),
+            Err(SpanInsertionError::EmptySpan { min: 3, max: 3 })
+        ));
+    }
+
+    #[test]
+    fn records_sub_voxel_heights() {
+        let mut heightfield = height_field();
+        heightfield.sub_voxel_heights = true;
+        let floor = |y| {
+            [
+                Vec3A::new(-5.0, y, -5.0),
+                Vec3A::new(-5.0, y, 15.0),
+                Vec3A::new(15.0, y, -5.0),
+            ]
+        };
+
+        heightfield
+            .rasterize_triangle(floor(0.3), AreaType::DEFAULT_WALKABLE, 1)
+            .unwrap();
+        let span = heightfield.span_at(1, 3).unwrap();
+        assert_eq!(span.max, 6);
+        assert_eq!(span.top_offset, 179);
+
+        // Merging keeps the higher surface
+        heightfield
+            .rasterize_triangle(floor(0.5), AreaType::DEFAULT_WALKABLE, 1)
+            .unwrap();
+        let span = heightfield.span_at(1, 3).unwrap();
+        assert_eq!(span.max, 6);
+        assert_eq!(span.top_offset, 128);
+
+        let compact_heightfield = heightfield.into_compact(2, 1).unwrap();
+        assert!(
+            compact_heightfield
+                .height_offsets
+                .iter()
+                .all(|offset| *offset == 128)
+        );
+    }
+
+    #[test]
+    fn clips_rasterization_to_boundary() {
+        let mut heightfield = height_field();
+        // The lower left half of the heightfield, which spans from -5 to 5 on both axes
+        heightfield.boundary = Some(vec![
+            Vec2::new(-5.0, -5.0),
+            Vec2::new(-5.0, 5.0),
+            Vec2::new(5.0, -5.0),
+        ]);
+        heightfield
+            .rasterize_triangle(
+                [
+                    Vec3A::new(-5.0, 0.0, -5.0),
+                    Vec3A::new(-5.0, 0.0, 15.0),
+                    Vec3A::new(15.0, 0.0, -5.0),
+                ],
+                AreaType::DEFAULT_WALKABLE,
+                1,
+            )
+            .unwrap();
+        assert!(heightfield.span_at(1, 1).is_some());
+        assert!(heightfield.span_at(3, 3).is_some());
+        assert!(heightfield.span_at(5, 5).is_none());
+        assert!(heightfield.span_at(8, 2).is_none());
+    }
+
     #[track_caller]
     fn assert_eq_without_next(span: &Span, expected_span: &Span) {
         assert_eq!(span.min, expected_span.min, "min is not equal");
         assert_eq!(span.max, expected_span.max, "max is not equal");
         assert_eq!(span.area, expected_span.area, "area is not equal");
     }
+
+    #[test]
+    fn populates_from_triangle_stream() {
+        // A procedurally generated floor, one quad per cell
+        let floor = (-4..4).flat_map(|x| {
+            (-4..4).flat_map(move |z| {
+                let (x, z) = (x as f32, z as f32);
+                let corners = [
+                    Vec3A::new(x, 0.0, z),
+                    Vec3A::new(x, 0.0, z + 1.0),
+                    Vec3A::new(x + 1.0, 0.0, z + 1.0),
+                    Vec3A::new(x + 1.0, 0.0, z),
+                ];
+                [
+                    (
+                        [corners[0], corners[1], corners[2]],
+                        AreaType::DEFAULT_WALKABLE,
+                    ),
+                    (
+                        [corners[0], corners[2], corners[3]],
+                        AreaType::DEFAULT_WALKABLE,
+                    ),
+                ]
+            })
+        });
+        let trimesh = TriMesh {
+            vertices: floor.clone().flat_map(|(triangle, _)| triangle).collect(),
+            indices: (0..128)
+                .map(|i| glam::UVec3::new(i * 3, i * 3 + 1, i * 3 + 2))
+                .collect(),
+            area_types: vec![AreaType::DEFAULT_WALKABLE; 128],
+            materials: Vec::new(),
+        };
+
+        let mut streamed = height_field();
+        streamed.populate_from_triangles(floor, 3, 1).unwrap();
+        let mut collected = height_field();
+        collected.populate_from_trimesh(&trimesh, 3, 1).unwrap();
+
+        for x in 0..streamed.width {
+            for z in 0..streamed.height {
+                assert_eq!(streamed.span_at(x, z), collected.span_at(x, z));
+            }
+        }
+        assert!(streamed.span_at(5, 5).is_some());
+    }
+
+    #[test]
+    fn merges_areas_within_walkable_climb() {
+        let unwalkable = Span {
+            min: 0,
+            max: 5,
+            area: AreaType::NOT_WALKABLE,
+            top_offset: 0,
+            next: None,
+        };
+        let walkable = Span {
+            min: 2,
+            max: 4,
+            area: AreaType::DEFAULT_WALKABLE,
+            ..unwalkable.clone()
+        };
+        for (walkable_climb, expected) in
+            [(0, AreaType::NOT_WALKABLE), (1, AreaType::DEFAULT_WALKABLE)]
+        {
+            let mut heightfield = height_field();
+            for span in [&walkable, &unwalkable] {
+                heightfield
+                    .add_span(SpanInsertion {
+                        x: 1,
+                        z: 1,
+                        flag_merge_threshold: walkable_climb,
+                        overlap: SpanOverlap::Merge,
+                        span: span.clone(),
+                    })
+                    .unwrap();
+            }
+            let span = heightfield.span_at(1, 1).unwrap();
+            assert_eq!((span.min, span.max), (0, 5));
+            assert_eq!(span.area, expected);
+        }
+    }
+
+    #[test]
+    fn reports_overwritten_areas() {
+        let water = AreaType::new(3);
+        let mut heightfield = height_field();
+        for (x, z, area) in [
+            (1, 1, water),
+            (1, 1, AreaType::DEFAULT_WALKABLE),
+            (2, 0, water),
+            (2, 0, AreaType::DEFAULT_WALKABLE),
+            (0, 2, AreaType::NOT_WALKABLE),
+            (0, 2, water),
+        ] {
+            heightfield
+                .add_span(SpanInsertion {
+                    x,
+                    z,
+                    flag_merge_threshold: 1,
+                    overlap: SpanOverlap::Merge,
+                    span: Span {
+                        min: 0,
+                        max: 4,
+                        area,
+                        top_offset: 0,
+                        next: None,
+                    },
+                })
+                .unwrap();
+        }
+        // Only walkable areas count as overwritten, and the first span is the first in row-major order.
+        let overwrites = &heightfield.area_overwrites.0;
+        assert_eq!(overwrites.len(), 1);
+        assert_eq!(
+            overwrites[&(water, AreaType::DEFAULT_WALKABLE)],
+            ((2, 0), 2)
+        );
+
+        let mut registry = AreaRegistry::default();
+        registry.register(water, "water");
+        let warning = BuildWarning::AreaOverwritten {
+            overwritten: water,
+            by: AreaType::DEFAULT_WALKABLE,
+            x: 2,
+            z: 0,
+            count: 2,
+        };
+        assert_eq!(
+            warning.display_with(&registry).to_string(),
+            "'water' overwritten by 'walkable' in 2 spans, first at (2, 0)."
+        );
+        assert_eq!(
+            warning.to_string(),
+            "area 3 overwritten by area 63 in 2 spans, first at (2, 0)."
+        );
+
+        heightfield.warn_about_area_overwrites();
+        assert!(heightfield.area_overwrites.0.is_empty());
+    }
 }
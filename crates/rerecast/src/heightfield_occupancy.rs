@@ -0,0 +1,126 @@
+use glam::{Vec2, Vec3, Vec3Swizzles as _};
+
+use crate::{Heightfield, Span};
+
+impl Heightfield {
+    /// Returns whether `position` lies inside a solid span.
+    ///
+    /// Together with [`Self::ground_height_at`] and [`Self::clearance_at`], this allows reusing the voxelized geometry
+    /// for gameplay checks such as object placement before, or entirely without, building a navmesh.
+    /// Positions outside the heightfield are never solid.
+    pub fn is_solid_at(&self, position: Vec3) -> bool {
+        self.world_column_spans(position.xz())
+            .any(|span| (self.span_bottom(span)..self.span_top(span)).contains(&position.y))
+    }
+
+    /// Returns the height of the highest solid surface at the world space xz-coordinates `x` and `z`,
+    /// or `None` if the column is empty or lies outside the heightfield. `[Units: wu]`
+    ///
+    /// Uses the precise surface height if [`Self::sub_voxel_heights`] was enabled during rasterization.
+    pub fn ground_height_at(&self, x: f32, z: f32) -> Option<f32> {
+        self.world_column_spans(Vec2::new(x, z))
+            .last()
+            .map(|span| self.span_top(span))
+    }
+
+    /// Returns the free vertical space above the height `y` at the world space xz-coordinates `x` and `z`,
+    /// i.e. the distance to the bottom of the next solid span above it. `[Units: wu]`
+    ///
+    /// Returns [`f32::INFINITY`] if nothing solid lies above `y`,
+    /// and `None` if `y` lies inside a solid span or the column lies outside the heightfield.
+    pub fn clearance_at(&self, x: f32, z: f32, y: f32) -> Option<f32> {
+        let position = Vec2::new(x, z);
+        self.world_column(position)?;
+        let mut clearance = f32::INFINITY;
+        for span in self.world_column_spans(position) {
+            let bottom = self.span_bottom(span);
+            let top = self.span_top(span);
+            if (bottom..top).contains(&y) {
+                return None;
+            }
+            if bottom >= y {
+                clearance = clearance.min(bottom - y);
+            }
+        }
+        Some(clearance)
+    }
+
+    /// Returns the column containing the world space xz-coordinates `position`,
+    /// or `None` if it lies outside the heightfield.
+    fn world_column(&self, position: Vec2) -> Option<(u16, u16)> {
+        let cell = ((position - self.aabb.min.xz()) / self.cell_size).floor();
+        self.contains(cell.x as i32, cell.y as i32)
+            .then_some((cell.x as u16, cell.y as u16))
+    }
+
+    /// Iterates over the spans of the column containing the world space xz-coordinates `position`, from bottom to top.
+    fn world_column_spans(&self, position: Vec2) -> impl Iterator<Item = &Span> {
+        let mut span_key = self
+            .world_column(position)
+            .and_then(|(x, z)| self.span_key_at(x, z));
+        std::iter::from_fn(move || {
+            let span = self.span(span_key?);
+            span_key = span.next;
+            Some(span)
+        })
+    }
+
+    fn span_bottom(&self, span: &Span) -> f32 {
+        self.aabb.min.y + span.min as f32 * self.cell_height
+    }
+
+    fn span_top(&self, span: &Span) -> f32 {
+        self.aabb.min.y + (span.max as f32 - span.top_offset as f32 / 256.0) * self.cell_height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3A;
+
+    use crate::{Aabb3d, AreaType, HeightfieldBuilder, SpanBuilder, SpanInsertion, SpanOverlap};
+
+    use super::*;
+
+    #[test]
+    fn queries_occupancy() {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 5.0, 5.0]),
+            cell_size: 1.0,
+            cell_height: 1.0,
+        }
+        .build()
+        .unwrap();
+        // A floor from y = -5 to -4 and a ceiling from y = -1 to 0 in the column at the origin.
+        for (min, max) in [(0, 1), (4, 5)] {
+            heightfield
+                .add_span(SpanInsertion {
+                    x: 5,
+                    z: 5,
+                    flag_merge_threshold: 0,
+                    overlap: SpanOverlap::Merge,
+                    span: SpanBuilder {
+                        min,
+                        max,
+                        area: AreaType::DEFAULT_WALKABLE,
+                        next: None,
+                    }
+                    .build(),
+                })
+                .unwrap();
+        }
+
+        assert!(heightfield.is_solid_at(Vec3::new(0.5, -4.5, 0.5)));
+        assert!(!heightfield.is_solid_at(Vec3::new(0.5, -3.0, 0.5)));
+        assert!(!heightfield.is_solid_at(Vec3::new(-0.5, -4.5, 0.5)));
+
+        assert_eq!(heightfield.ground_height_at(0.5, 0.5), Some(0.0));
+        assert_eq!(heightfield.ground_height_at(-0.5, 0.5), None);
+        assert_eq!(heightfield.ground_height_at(10.0, 0.5), None);
+
+        assert_eq!(heightfield.clearance_at(0.5, 0.5, -4.0), Some(3.0));
+        assert_eq!(heightfield.clearance_at(0.5, 0.5, 0.0), Some(f32::INFINITY));
+        assert_eq!(heightfield.clearance_at(0.5, 0.5, -4.5), None);
+        assert_eq!(heightfield.clearance_at(10.0, 0.5, 0.0), None);
+    }
+}
@@ -0,0 +1,212 @@
+use glam::{IVec2, Vec3, Vec3Swizzles as _};
+
+use crate::{Heightfield, SpanKey};
+
+/// A solid span hit by [`Heightfield::raycast`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeightfieldRaycastHit {
+    /// The distance from the origin of the ray to [`Self::point`]. `[Units: wu]`
+    pub distance: f32,
+    /// The world space point where the ray entered the span.
+    pub point: Vec3,
+    /// The x-coordinate of the column containing the span. `[Units: vx]`
+    pub x: u16,
+    /// The z-coordinate of the column containing the span. `[Units: vx]`
+    pub z: u16,
+    /// The key of the hit span.
+    pub span: SpanKey,
+}
+
+impl Heightfield {
+    /// Casts a ray from `origin` along `direction` and returns the first solid span it hits,
+    /// or `None` if it leaves the heightfield without hitting anything.
+    ///
+    /// The ray walks the columns it passes through with a DDA on the xz-plane, so no navmesh is needed,
+    /// which makes this handy for cheap grounding checks and tooling.
+    /// `direction` does not need to be normalized, but must not be zero.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn raycast(&self, origin: Vec3, direction: Vec3) -> Option<HeightfieldRaycastHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec3::ZERO || self.width == 0 || self.height == 0 {
+            return None;
+        }
+        let (t_enter, t_exit) = self.clip_ray(origin, direction)?;
+
+        // Set up the DDA over the columns on the xz-plane.
+        let start = (origin + direction * t_enter - self.aabb.min).xz() / self.cell_size;
+        let mut column = start.floor().as_ivec2().clamp(
+            IVec2::ZERO,
+            IVec2::new(self.width as i32 - 1, self.height as i32 - 1),
+        );
+        let direction_xz = direction.xz();
+        let step = direction_xz.signum().as_ivec2();
+        let t_delta = (self.cell_size / direction_xz.abs()).to_array();
+        let mut t_next = [0_usize, 1].map(|axis| {
+            if direction_xz[axis] == 0.0 {
+                return f32::INFINITY;
+            }
+            let boundary = if direction_xz[axis] > 0.0 {
+                column[axis] + 1
+            } else {
+                column[axis]
+            };
+            let boundary = self.aabb.min.xz()[axis] + boundary as f32 * self.cell_size;
+            (boundary - origin.xz()[axis]) / direction_xz[axis]
+        });
+
+        let mut t = t_enter;
+        while t <= t_exit {
+            let t_leave = t_next[0].min(t_next[1]).min(t_exit);
+            if let Some(hit) = self.raycast_column(
+                origin,
+                direction,
+                column.x as u16,
+                column.y as u16,
+                t,
+                t_leave,
+            ) {
+                return Some(hit);
+            }
+
+            let axis = if t_next[0] < t_next[1] { 0 } else { 1 };
+            column[axis] += step[axis];
+            if !self.contains(column.x, column.y) {
+                return None;
+            }
+            t = t_next[axis];
+            t_next[axis] += t_delta[axis];
+        }
+        None
+    }
+
+    /// Clips the ray to the bounds of the heightfield, returning the distances at which it enters and exits them.
+    fn clip_ray(&self, origin: Vec3, direction: Vec3) -> Option<(f32, f32)> {
+        let mut t_enter = 0.0_f32;
+        let mut t_exit = f32::INFINITY;
+        for axis in 0..3 {
+            if direction[axis] == 0.0 {
+                if origin[axis] < self.aabb.min[axis] || origin[axis] > self.aabb.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let t0 = (self.aabb.min[axis] - origin[axis]) / direction[axis];
+            let t1 = (self.aabb.max[axis] - origin[axis]) / direction[axis];
+            t_enter = t_enter.max(t0.min(t1));
+            t_exit = t_exit.min(t0.max(t1));
+        }
+        (t_enter <= t_exit).then_some((t_enter, t_exit))
+    }
+
+    /// Returns the first span in the column at `(x, z)` the ray hits between the distances `t_start` and `t_end`.
+    fn raycast_column(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        x: u16,
+        z: u16,
+        t_start: f32,
+        t_end: f32,
+    ) -> Option<HeightfieldRaycastHit> {
+        let y_start = origin.y + direction.y * t_start;
+        let y_end = origin.y + direction.y * t_end;
+        let (y_low, y_high) = (y_start.min(y_end), y_start.max(y_end));
+
+        let mut nearest: Option<(f32, SpanKey)> = None;
+        let mut span_key = self.span_key_at(x, z);
+        while let Some(key) = span_key {
+            let span = self.span(key);
+            span_key = span.next;
+            let bottom = self.aabb.min.y + span.min as f32 * self.cell_height;
+            let top = self.aabb.min.y + span.max as f32 * self.cell_height;
+            if y_high < bottom || y_low > top {
+                continue;
+            }
+            let t = if (bottom..=top).contains(&y_start) {
+                // Entered through the side of the column
+                t_start
+            } else if y_start > top {
+                t_start + (top - y_start) / direction.y
+            } else {
+                t_start + (bottom - y_start) / direction.y
+            };
+            if nearest.is_none_or(|(nearest_t, _)| t < nearest_t) {
+                nearest = Some((t, key));
+            }
+        }
+
+        nearest.map(|(t, span)| HeightfieldRaycastHit {
+            distance: t,
+            point: origin + direction * t,
+            x,
+            z,
+            span,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3A;
+
+    use super::*;
+    use crate::{Aabb3d, AreaType, HeightfieldBuilder, SpanBuilder, SpanInsertion, SpanOverlap};
+
+    fn heightfield_with_pillar() -> Heightfield {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 5.0, 5.0]),
+            cell_size: 1.0,
+            cell_height: 1.0,
+        }
+        .build()
+        .unwrap();
+        // A floor from y = -5 to y = -4 in every column and a pillar up to y = 0 in column (7, 5).
+        for z in 0..heightfield.height {
+            for x in 0..heightfield.width {
+                let max = if (x, z) == (7, 5) { 5 } else { 1 };
+                heightfield
+                    .add_span(SpanInsertion {
+                        x,
+                        z,
+                        span: SpanBuilder {
+                            min: 0,
+                            max,
+                            area: AreaType::DEFAULT_WALKABLE,
+                            next: None,
+                        }
+                        .build(),
+                        flag_merge_threshold: 1,
+                        overlap: SpanOverlap::Merge,
+                    })
+                    .unwrap();
+            }
+        }
+        heightfield
+    }
+
+    #[test]
+    fn raycast_hits_first_solid_span() {
+        let heightfield = heightfield_with_pillar();
+
+        // Straight down onto the floor
+        let hit = heightfield
+            .raycast(Vec3::new(0.5, 4.0, 0.5), Vec3::NEG_Y)
+            .unwrap();
+        assert_eq!((hit.x, hit.z), (5, 5));
+        assert_eq!(hit.point, Vec3::new(0.5, -4.0, 0.5));
+        assert_eq!(hit.distance, 8.0);
+
+        // Horizontally into the side of the pillar
+        let hit = heightfield
+            .raycast(Vec3::new(-4.5, -1.0, 0.5), Vec3::X)
+            .unwrap();
+        assert_eq!((hit.x, hit.z), (7, 5));
+        assert_eq!(hit.point, Vec3::new(2.0, -1.0, 0.5));
+
+        // Above the pillar
+        assert_eq!(
+            heightfield.raycast(Vec3::new(-4.5, 1.0, 0.5), Vec3::X),
+            None
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use crate::PolygonNavmesh;
+
+/// Per-polygon influence values for tactical AI, propagated across the adjacency of a [`PolygonNavmesh`].
+///
+/// Each channel is an independent layer, e.g. one per team or one for threat and one for interest.
+/// Influence is emitted by sources placed on polygons and spreads to neighboring polygons,
+/// losing [`Self::decay`] of its strength for every edge it crosses.
+///
+/// The map is updated incrementally: every call to [`InfluenceMap::update`] spreads influence by one polygon,
+/// so sources can be moved every frame and the map catches up over the following updates
+/// without ever having to be rebuilt. Unwalkable polygons never carry influence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InfluenceMap {
+    polygon_count: usize,
+    channel_count: usize,
+    /// The influence of every polygon, channel after channel.
+    values: Vec<f32>,
+    /// The strength of the source on every polygon, channel after channel. Polygons without a source have 0.
+    sources: Vec<f32>,
+    /// The fraction of influence lost when spreading across an edge. `[Limit: 0 <= value <= 1]`
+    pub decay: f32,
+    /// The fraction of its previous influence a polygon keeps on each update,
+    /// which smooths out sources that move or flicker. `[Limit: 0 <= value < 1]`
+    pub momentum: f32,
+}
+
+impl InfluenceMap {
+    /// Creates an empty influence map with `channel_count` channels for the polygons of `navmesh`.
+    pub fn new(navmesh: &PolygonNavmesh, channel_count: usize) -> Self {
+        let polygon_count = navmesh.polygon_count();
+        Self {
+            polygon_count,
+            channel_count,
+            values: vec![0.0; polygon_count * channel_count],
+            sources: vec![0.0; polygon_count * channel_count],
+            decay: 0.25,
+            momentum: 0.0,
+        }
+    }
+
+    /// Returns the number of channels.
+    #[inline]
+    pub fn channel_count(&self) -> usize {
+        self.channel_count
+    }
+
+    /// Places a source of influence with the given `strength` on `polygon`, replacing any previous source there.
+    ///
+    /// A `strength` of 0 removes the source. The influence of the map only changes on the next [`Self::update`].
+    #[inline]
+    pub fn set_source(&mut self, channel: usize, polygon: usize, strength: f32) {
+        let index = self.index(channel, polygon);
+        self.sources[index] = strength;
+    }
+
+    /// Removes all sources of a channel, e.g. before placing them again at the current positions of a team.
+    pub fn clear_sources(&mut self, channel: usize) {
+        let range = self.channel_range(channel);
+        self.sources[range].fill(0.0);
+    }
+
+    /// Returns the influence of a channel on `polygon`.
+    #[inline]
+    pub fn influence(&self, channel: usize, polygon: usize) -> f32 {
+        self.values[self.index(channel, polygon)]
+    }
+
+    /// Returns the influence of a channel on all polygons, indexed by polygon.
+    #[inline]
+    pub fn channel(&self, channel: usize) -> &[f32] {
+        &self.values[self.channel_range(channel)]
+    }
+
+    /// Returns the influence of channel `a` minus that of channel `b` on `polygon`.
+    ///
+    /// Positive values mean `a` controls the polygon, values around zero mark the frontline between both.
+    #[inline]
+    pub fn balance(&self, a: usize, b: usize, polygon: usize) -> f32 {
+        self.influence(a, polygon) - self.influence(b, polygon)
+    }
+
+    /// Spreads the influence of all channels by one polygon.
+    ///
+    /// Every polygon takes the strongest of its own source and the decayed influence of its neighbors,
+    /// blended with its previous influence by [`Self::momentum`].
+    /// `navmesh` must be the navmesh the map was created for.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn update(&mut self, navmesh: &PolygonNavmesh) {
+        debug_assert_eq!(navmesh.polygon_count(), self.polygon_count);
+        let falloff = 1.0 - self.decay;
+        let previous = self.values.clone();
+        for channel in 0..self.channel_count {
+            let offset = channel * self.polygon_count;
+            for polygon in 0..self.polygon_count {
+                let index = offset + polygon;
+                if !navmesh.areas[polygon].is_walkable() {
+                    self.values[index] = 0.0;
+                    continue;
+                }
+                let mut target = self.sources[index];
+                for edge in 0..navmesh.polygon_vertices(polygon).len() {
+                    if let Some(neighbor) = navmesh.internal_neighbor(polygon, edge) {
+                        target = target.max(previous[offset + neighbor] * falloff);
+                    }
+                }
+                self.values[index] = target + (previous[index] - target) * self.momentum;
+            }
+        }
+    }
+
+    #[inline]
+    fn index(&self, channel: usize, polygon: usize) -> usize {
+        debug_assert!(channel < self.channel_count && polygon < self.polygon_count);
+        channel * self.polygon_count + polygon
+    }
+
+    #[inline]
+    fn channel_range(&self, channel: usize) -> std::ops::Range<usize> {
+        let start = channel * self.polygon_count;
+        start..start + self.polygon_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    #[test]
+    fn spreads_influence_incrementally() {
+        let grid = GridNavmesh::parse(
+            "
+            a...b#c
+            ",
+        );
+        let navmesh = &grid.navmesh;
+        let (a, b, c) = (grid.polygon('a'), grid.polygon('b'), grid.polygon('c'));
+        let mut map = InfluenceMap::new(navmesh, 2);
+        map.decay = 0.5;
+        map.set_source(0, a, 1.0);
+        map.set_source(1, b, 1.0);
+
+        map.update(navmesh);
+        assert_eq!(map.influence(0, a), 1.0);
+        assert_eq!(map.influence(0, a + 1), 0.0);
+
+        for _ in 0..4 {
+            map.update(navmesh);
+        }
+        assert_eq!(map.channel(0)[a..=b], [1.0, 0.5, 0.25, 0.125, 0.0625]);
+        assert_eq!(map.balance(1, 0, b), 0.9375);
+        // Separated by a wall
+        assert_eq!(map.influence(0, c), 0.0);
+
+        // Sources can move without rebuilding the map
+        map.clear_sources(0);
+        map.set_source(0, b, 2.0);
+        map.update(navmesh);
+        assert_eq!(map.influence(0, b), 2.0);
+    }
+}
@@ -0,0 +1,213 @@
+use glam::{Vec3, Vec3A};
+
+use crate::{
+    BvTree, DetailNavmesh, ExclusionVolume, LayerConstraint, NavmeshConfig, NearestPolygon,
+    OffMeshConnection, PolygonNavmesh, SoloNavmeshError, TileBuildPool, TriMesh,
+    solo_navmesh::build_masked_navmesh_in,
+};
+
+/// Decides which triangles belong to the interior of buildings when building
+/// separate navmeshes with [`build_interior_exterior_navmeshes`].
+///
+/// A triangle is interior if its material is listed in [`Self::materials`]
+/// or its centroid lies inside any of [`Self::volumes`]. All other triangles are exterior.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct InteriorMask {
+    /// The volumes enclosing interiors. Unlike in [`NavmeshConfig::exclusion_volumes`], the geometry inside them is still built.
+    pub volumes: Vec<ExclusionVolume>,
+    /// The [triangle materials](TriMesh::materials) that tag interior geometry.
+    pub materials: Vec<u8>,
+}
+
+impl InteriorMask {
+    /// Returns whether the triangle at index `triangle` of `trimesh` is interior.
+    pub fn is_interior(&self, trimesh: &TriMesh, triangle: usize) -> bool {
+        if self.materials.contains(&trimesh.material(triangle)) {
+            return true;
+        }
+        let centroid = trimesh.indices[triangle]
+            .to_array()
+            .iter()
+            .map(|index| trimesh.vertices[*index as usize])
+            .sum::<Vec3A>()
+            / 3.0;
+        self.volumes
+            .iter()
+            .any(|volume| volume.contains_point(centroid.into()))
+    }
+}
+
+/// A door connecting the exterior navmesh to the interior navmesh, created by [`build_interior_exterior_navmeshes`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DoorLink {
+    /// The connection this door was created from.
+    /// [`OffMeshConnection::start`] lies outside and [`OffMeshConnection::end`] lies inside.
+    pub connection: OffMeshConnection,
+    /// The polygon of the exterior navmesh and the point on it that [`OffMeshConnection::start`] is attached to,
+    /// or `None` if no exterior polygon lies within reach of it.
+    pub exterior: Option<NearestPolygon>,
+    /// The polygon of the interior navmesh and the point on it that [`OffMeshConnection::end`] is attached to,
+    /// or `None` if no interior polygon lies within reach of it.
+    pub interior: Option<NearestPolygon>,
+}
+
+impl DoorLink {
+    /// Returns whether both ends of the door are attached, i.e. whether agents can walk through it.
+    #[inline]
+    pub fn is_attached(&self) -> bool {
+        self.exterior.is_some() && self.interior.is_some()
+    }
+}
+
+/// The result of [`build_interior_exterior_navmeshes`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InteriorExteriorNavmeshes {
+    /// The navmesh of the interior geometry.
+    pub interior: PolygonNavmesh,
+    /// The detail mesh of [`Self::interior`].
+    pub interior_detail: DetailNavmesh,
+    /// The navmesh of the exterior geometry.
+    pub exterior: PolygonNavmesh,
+    /// The detail mesh of [`Self::exterior`].
+    pub exterior_detail: DetailNavmesh,
+    /// The doors connecting both navmeshes, in the order they were passed in.
+    pub doors: Vec<DoorLink>,
+}
+
+/// Like [`build_solo_navmesh`](crate::build_solo_navmesh), but builds one navmesh for the interior geometry
+/// and one for the exterior geometry as decided by `mask`, so that building interiors can be streamed separately.
+///
+/// Each navmesh is built from all of `trimesh`, with the geometry of the other one rasterized as unwalkable,
+/// so walls and ceilings still block and cover both. Both navmeshes span the AABB of `trimesh` and share the same grid.
+///
+/// Every connection in `doors` goes from the exterior at [`OffMeshConnection::start`] to the interior at [`OffMeshConnection::end`].
+/// Its ends are attached within [`OffMeshConnection::radius`] horizontally and [`NavmeshConfig::walkable_climb`] vertically,
+/// see [`DoorLink`].
+#[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+pub fn build_interior_exterior_navmeshes(
+    trimesh: &TriMesh,
+    config: &NavmeshConfig,
+    mask: &InteriorMask,
+    doors: &[OffMeshConnection],
+) -> Result<InteriorExteriorNavmeshes, SoloNavmeshError> {
+    let aabb = trimesh
+        .compute_aabb()
+        .ok_or(SoloNavmeshError::EmptyGeometry)?;
+    let interior: Vec<bool> = (0..trimesh.indices.len())
+        .map(|triangle| mask.is_interior(trimesh, triangle))
+        .collect();
+    // Lets the exterior build reuse the contour buffers of the interior build.
+    let mut pool = TileBuildPool::new(1);
+    let (interior_polygon, interior_detail) = build_masked_navmesh_in(
+        aabb,
+        trimesh,
+        config,
+        |triangle| interior[triangle],
+        &mut pool,
+    )?;
+    let (exterior_polygon, exterior_detail) = build_masked_navmesh_in(
+        aabb,
+        trimesh,
+        config,
+        |triangle| !interior[triangle],
+        &mut pool,
+    )?;
+
+    let interior_tree = BvTree::new(&interior_polygon);
+    let exterior_tree = BvTree::new(&exterior_polygon);
+    let max_climb = config.walkable_climb as f32 * config.cell_height;
+    let doors = doors
+        .iter()
+        .map(|connection| {
+            let half_extents = Vec3::new(connection.radius, max_climb, connection.radius);
+            DoorLink {
+                connection: *connection,
+                exterior: exterior_polygon.find_nearest_polygon(
+                    &exterior_tree,
+                    connection.start,
+                    half_extents,
+                    LayerConstraint::Any,
+                ),
+                interior: interior_polygon.find_nearest_polygon(
+                    &interior_tree,
+                    connection.end,
+                    half_extents,
+                    LayerConstraint::Any,
+                ),
+            }
+        })
+        .collect();
+
+    Ok(InteriorExteriorNavmeshes {
+        interior: interior_polygon,
+        interior_detail,
+        exterior: exterior_polygon,
+        exterior_detail,
+        doors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::UVec3;
+
+    use super::*;
+    use crate::{Aabb3d, AreaType};
+
+    #[test]
+    fn splits_interior_and_exterior() {
+        // Two floors next to each other, the one at x < 5 is the interior of a building.
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 10.0),
+                Vec3A::new(5.0, 0.0, 10.0),
+                Vec3A::new(5.0, 0.0, 0.0),
+                Vec3A::new(10.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 0.0),
+            ],
+            indices: vec![
+                UVec3::new(0, 1, 2),
+                UVec3::new(0, 2, 3),
+                UVec3::new(3, 2, 4),
+                UVec3::new(3, 4, 5),
+            ],
+            area_types: vec![AreaType::NOT_WALKABLE; 4],
+            materials: Vec::new(),
+        };
+        let config = NavmeshConfig {
+            border_size: 0,
+            ..Default::default()
+        };
+        let mask = InteriorMask {
+            volumes: vec![ExclusionVolume::Aabb(Aabb3d {
+                min: Vec3::new(-1.0, -1.0, -1.0),
+                max: Vec3::new(5.0, 1.0, 11.0),
+            })],
+            materials: Vec::new(),
+        };
+        let door = OffMeshConnection {
+            radius: 1.0,
+            ..OffMeshConnection::new(Vec3::new(7.0, 0.0, 5.0), Vec3::new(3.0, 0.0, 5.0))
+        };
+        let missing_door =
+            OffMeshConnection::new(Vec3::new(3.0, 0.0, 5.0), Vec3::new(7.0, 0.0, 5.0));
+
+        let navmeshes =
+            build_interior_exterior_navmeshes(&trimesh, &config, &mask, &[door, missing_door])
+                .unwrap();
+        assert!(navmeshes.interior.polygon_count() > 0);
+        assert!(navmeshes.exterior.polygon_count() > 0);
+        for polygon in 0..navmeshes.interior.polygon_count() {
+            assert!(navmeshes.interior.polygon_aabb(polygon).unwrap().max.x <= 5.0 + 1e-3);
+        }
+        for polygon in 0..navmeshes.exterior.polygon_count() {
+            assert!(navmeshes.exterior.polygon_aabb(polygon).unwrap().min.x >= 5.0 - 1e-3);
+        }
+
+        assert!(navmeshes.doors[0].is_attached());
+        // Doors are only attached from the exterior to the interior.
+        assert!(!navmeshes.doors[1].is_attached());
+    }
+}
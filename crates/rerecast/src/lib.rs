@@ -1,34 +1,163 @@
 #![doc = include_str!("../../../readme.md")]
 
+mod agent_placement;
+mod area_measurement;
+mod area_registry;
+mod audit;
+mod blob;
+mod boundary_walls;
+mod build_progress;
+mod build_record;
+mod build_warning;
+mod bv_tree;
+mod cell_grid;
 mod compact_cell;
 mod compact_heightfield;
 mod compact_span;
+mod compressed_detail_mesh;
+mod compressed_heightfield;
 mod config;
+#[cfg(feature = "console")]
+pub mod console;
+mod contour_outline;
 mod contours;
+mod cover;
+mod cover_points;
+mod coverage;
 mod detail_mesh;
+mod distance_matrix;
+mod dynamic_surface;
 mod erosion;
+mod exclusion_volume;
+mod flag_volume;
+mod geometry_provider;
 mod heightfield;
+mod heightfield_occupancy;
+mod heightfield_raycast;
+mod influence_map;
+mod interior_navmesh;
+#[cfg(feature = "mmap")]
+mod mapped_navmesh;
 mod mark_convex_poly_area;
+mod mark_path_area;
 pub(crate) mod math;
+mod nav_blocker;
+mod navmesh_network;
+mod navmesh_query;
+mod navmesh_zones;
+mod nearest_polygon;
+mod off_mesh_connection;
+mod off_mesh_links;
+mod open_list;
+#[cfg(feature = "rayon")]
+mod parallel_rasterize;
+mod path_snapping;
 mod poly_mesh;
+mod polygon_adjacency;
+mod polygon_edge_flags;
+mod polygon_graph;
+mod position_validation;
 mod pre_filter;
+mod predicates;
+mod query_counters;
+mod query_tolerances;
+mod random_point;
 mod rasterize;
+mod raycast;
 mod region;
+mod region_areas;
+mod region_remap;
+mod solo_navmesh;
+mod solo_navmesh_build;
+mod source_trace;
 mod span;
+mod straight_path;
+#[cfg(any(test, feature = "test_utils"))]
+pub mod test_utils;
+mod tile_build_pool;
+mod tiled_navmesh;
 mod trimesh;
+mod walkability_grid;
 mod watershed_build_regions;
 mod watershed_distance_field;
 
+pub use agent_placement::AgentPlacement;
+pub use area_registry::{AreaName, AreaRegistry};
+pub use audit::{NavmeshAudit, NavmeshAuditDisplay, NavmeshStatistics};
+pub use blob::{NavmeshBlob, NavmeshBlobError, NavmeshBlobHeader};
+pub use build_progress::{ContourProgress, RegionProgress};
+#[cfg(feature = "serialize")]
+pub use build_record::BuildRecordError;
+pub use build_record::{BuildRecord, RecordedVolume};
+pub use build_warning::{BuildWarning, BuildWarningDisplay};
+pub use bv_tree::{BvTree, BvTreeQuery};
+pub use cell_grid::CellGrid;
 pub use compact_cell::CompactCell;
 pub use compact_heightfield::CompactHeightfield;
 pub use compact_span::CompactSpan;
+pub use compressed_detail_mesh::{
+    CompressedDetailNavmesh, CompressedDetailVertex, DetailCompressionError,
+    DetailCompressionSettings,
+};
+pub use compressed_heightfield::{
+    CompressedHeightfield, CompressedSpan, HeightfieldColumnRun, HeightfieldDecompressionError,
+};
 pub use config::NavmeshConfig;
+pub use contour_outline::{ContourOutline, merge_tile_contours};
 pub use contours::{BuildContoursFlags, Contour, ContourSet, RegionVertexId};
+pub use cover_points::{CoverKind, CoverPoint, CoverPointSettings};
+pub use coverage::NavmeshCoverage;
 pub use detail_mesh::{DetailNavmesh, SubMesh};
-pub use heightfield::{Heightfield, HeightfieldBuilder, HeightfieldBuilderError};
+pub use distance_matrix::DistanceMatrix;
+pub use dynamic_surface::{DynamicSurface, SurfaceLinkSettings};
+pub use exclusion_volume::ExclusionVolume;
+pub use flag_volume::FlagVolume;
+pub use geometry_provider::{AsyncGeometryProvider, GeometryProvider};
+pub use heightfield::{
+    Heightfield, HeightfieldBuilder, HeightfieldBuilderError, SpanInsertion, SpanInsertionError,
+    SpanInsertionOutcome, SpanOverlap,
+};
+pub use heightfield_raycast::HeightfieldRaycastHit;
+pub use influence_map::InfluenceMap;
+pub use interior_navmesh::{
+    DoorLink, InteriorExteriorNavmeshes, InteriorMask, build_interior_exterior_navmeshes,
+};
+#[cfg(feature = "mmap")]
+pub use mapped_navmesh::{MappedNavmesh, MappedNavmeshError};
 pub use mark_convex_poly_area::ConvexVolume;
+pub use mark_path_area::PathVolume;
 pub use math::{Aabb2d, Aabb3d};
+pub use nav_blocker::{NavBlockerKind, NavBlockerOutline};
+pub use navmesh_network::{
+    NavmeshNetwork, NavmeshNetworkLink, NavmeshTransition, NetworkPath, NetworkPathSegment,
+    NetworkPosition,
+};
+pub use navmesh_query::{
+    BufferedPath, NavmeshPath, NavmeshQuery, NavmeshQueryBuffers, QueryFilter, SearchStrategy,
+};
+pub use navmesh_zones::{NavmeshZones, ZoneVolume};
+pub use nearest_polygon::{LayerConstraint, NearestPolygon};
+pub use off_mesh_connection::OffMeshConnection;
+pub use off_mesh_links::{OffMeshLink, OffMeshLinkId, OffMeshLinks, OffMeshTraversal};
 pub use poly_mesh::PolygonNavmesh;
+pub use polygon_adjacency::{EdgeNeighbor, PolygonEdge};
+pub use polygon_edge_flags::PolygonEdgeFlags;
+pub use polygon_graph::{PolygonGraph, PolygonGraphEdge};
+pub use position_validation::{PositionConstraints, PositionValidation, PositionValidationFailure};
+pub use query_counters::QueryCounters;
+pub use query_tolerances::QueryTolerances;
+pub use raycast::{NavmeshRaycast, NavmeshRaycastHit};
 pub use region::RegionId;
-pub use span::{AreaType, Span, SpanKey, Spans};
+pub use region_remap::PolygonOrigin;
+pub use solo_navmesh::{
+    SoloNavmeshError, build_solo_navmesh, build_solo_navmesh_from, build_solo_navmesh_from_async,
+};
+pub use solo_navmesh_build::{SoloNavmeshBuild, SoloNavmeshBuildStage};
+pub use source_trace::SpanSource;
+pub use span::{AreaType, Span, SpanBuilder, SpanKey, Spans};
+pub use straight_path::{PortalCrossing, StraightPathPoint, StraightPathPointKind};
+pub use tile_build_pool::TileBuildPool;
+pub use tiled_navmesh::{NavmeshTile, TiledNavmeshBuilder, TiledNavmeshError};
 pub use trimesh::TriMesh;
+pub use walkability_grid::WalkabilityGrid;
+pub use watershed_build_regions::WatershedSettings;
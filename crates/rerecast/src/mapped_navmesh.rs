@@ -0,0 +1,83 @@
+use std::{fs::File, io, path::Path};
+
+use memmap2::Mmap;
+use thiserror::Error;
+
+use crate::{NavmeshBlob, NavmeshBlobError};
+
+/// A navmesh blob file mapped into memory, for loading large navmeshes without reading or deserializing them.
+///
+/// The file is validated once when it is opened, including its checksum,
+/// after which [`MappedNavmesh::blob`] gives zero-copy access to it.
+/// Pages are only read from disk when they are first accessed, and read-only mappings of the same file
+/// are shared between all processes that open it.
+///
+/// Only available with the `mmap` feature.
+#[derive(Debug)]
+pub struct MappedNavmesh {
+    mmap: Mmap,
+}
+
+impl MappedNavmesh {
+    /// Maps the blob file at `path`, as written by [`PolygonNavmesh::to_blob`](crate::PolygonNavmesh::to_blob), into memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other process, as long as the returned
+    /// [`MappedNavmesh`] is alive. Otherwise the bytes behind [`Self::blob`] change after they were validated,
+    /// or reading them crashes the process.
+    pub unsafe fn open(path: impl AsRef<Path>) -> Result<Self, MappedNavmeshError> {
+        let file = File::open(path)?;
+        // SAFETY: The caller guarantees that the file is not modified while it is mapped.
+        let mmap = unsafe { Mmap::map(&file)? };
+        NavmeshBlob::new(&mmap)?;
+        Ok(Self { mmap })
+    }
+
+    /// Returns a view of the mapped navmesh.
+    #[inline]
+    pub fn blob(&self) -> NavmeshBlob<'_> {
+        NavmeshBlob::new_unverified(&self.mmap)
+            .expect("The blob was validated when the file was mapped")
+    }
+}
+
+/// Errors that can occur when mapping a navmesh with [`MappedNavmesh::open`].
+#[derive(Error, Debug)]
+pub enum MappedNavmeshError {
+    /// The file could not be opened or mapped.
+    #[error("Failed to map navmesh file: {0}")]
+    Io(#[from] io::Error),
+    /// The file is not a valid navmesh blob.
+    #[error("Invalid navmesh file: {0}")]
+    Blob(#[from] NavmeshBlobError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DetailNavmesh, test_utils::GridNavmesh};
+
+    #[test]
+    fn maps_navmesh_file() {
+        let navmesh = GridNavmesh::parse(".#.").navmesh;
+        let path = std::env::temp_dir().join(format!(
+            "rerecast_mapped_navmesh_{}.bin",
+            std::process::id()
+        ));
+        std::fs::write(&path, navmesh.to_blob(&DetailNavmesh::default())).unwrap();
+
+        // SAFETY: The file is only rewritten after the mapping was dropped.
+        let mapped = unsafe { MappedNavmesh::open(&path) }.unwrap();
+        assert_eq!(mapped.blob().to_navmesh(), navmesh);
+        drop(mapped);
+
+        std::fs::write(&path, b"not a navmesh").unwrap();
+        assert!(matches!(
+            // SAFETY: The file is not modified while it is mapped.
+            unsafe { MappedNavmesh::open(&path) },
+            Err(MappedNavmeshError::Blob(NavmeshBlobError::TooShort))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
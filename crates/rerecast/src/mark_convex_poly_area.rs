@@ -1,44 +1,92 @@
-use glam::{IVec3, Vec2};
+use glam::{IVec3, Vec2, Vec3, Vec3Swizzles as _};
 
-use crate::{Aabb2d, AreaType, CompactHeightfield};
+use crate::{Aabb2d, Aabb3d, AreaType, CompactHeightfield, Heightfield, math::point_in_poly};
 
 impl CompactHeightfield {
     /// Sets the [`AreaType`] of the spans within the given convex volume.
+    ///
+    /// Like all area marking methods, this overwrites the areas set by earlier calls where the volumes overlap,
+    /// so volumes should be marked in order of increasing priority.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn mark_convex_poly_area(&mut self, volume: ConvexVolume) {
-        // Compute the bounding box of the polygon
-        let Some(aabb) = Aabb2d::from_verts(&volume.vertices) else {
-            // The volume is empty
+        let Some(footprint) = volume.grid_footprint(
+            self.aabb.min,
+            self.cell_size,
+            self.cell_height,
+            self.width,
+            self.height,
+        ) else {
             return;
         };
-        let aabb = aabb.extend_y(volume.min_y, volume.max_y);
-
-        // Compute the grid footprint of the polygon
-        let mut min = aabb.min - self.aabb.min;
-        min.x /= self.cell_size;
-        min.y /= self.cell_height;
-        min.z /= self.cell_size;
-        let mut max = aabb.max - self.aabb.min;
-        max.x /= self.cell_size;
-        max.y /= self.cell_height;
-        max.z /= self.cell_size;
-        let mut min = IVec3::new(min.x as i32, min.y as i32, min.z as i32);
-        let mut max = IVec3::new(max.x as i32, max.y as i32, max.z as i32);
-
-        // Early-out if the polygon lies entirely outside the grid.
-        if max.x < 0 || min.x >= self.width as i32 || max.z < 0 || min.z >= self.height as i32 {
+        self.mark_area_in_footprint(footprint, volume.area, |point| {
+            point_in_poly(&point, &volume.vertices)
+        });
+    }
+
+    /// Sets the [`AreaType`] of the spans whose floor lies within `aabb`, like Recast's `rcMarkBoxArea`,
+    /// e.g. to stamp a road or a danger zone from a gameplay volume before building regions.
+    ///
+    /// Overwrites the areas set by earlier calls, see [`Self::mark_convex_poly_area`]. Unwalkable spans are left untouched.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn mark_box_area(&mut self, aabb: &Aabb3d, area: AreaType) {
+        let Some(footprint) = grid_footprint(
+            *aabb,
+            self.aabb.min,
+            self.cell_size,
+            self.cell_height,
+            self.width,
+            self.height,
+        ) else {
             return;
-        }
+        };
+        self.mark_area_in_footprint(footprint, area, |_| true);
+    }
 
-        // Clamp the polygon footprint to the grid
-        min.x = min.x.max(0);
-        max.x = max.x.min(self.width as i32 - 1);
-        min.z = min.z.max(0);
-        max.z = max.z.min(self.height as i32 - 1);
+    /// Sets the [`AreaType`] of the spans whose floor lies within the vertical cylinder standing on `position`,
+    /// like Recast's `rcMarkCylinderArea`, e.g. to stamp a pond as water. `[Units: wu]`
+    ///
+    /// Overwrites the areas set by earlier calls, see [`Self::mark_convex_poly_area`]. Unwalkable spans are left untouched.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn mark_cylinder_area(&mut self, position: Vec3, radius: f32, height: f32, area: AreaType) {
+        let aabb = Aabb3d {
+            min: position - Vec3::new(radius, 0.0, radius),
+            max: position + Vec3::new(radius, height, radius),
+        };
+        let Some(footprint) = grid_footprint(
+            aabb,
+            self.aabb.min,
+            self.cell_size,
+            self.cell_height,
+            self.width,
+            self.height,
+        ) else {
+            return;
+        };
+        let radius_squared = radius * radius;
+        self.mark_area_in_footprint(footprint, area, |point| {
+            point.distance_squared(position.xz()) < radius_squared
+        });
+    }
 
+    /// Sets the area of the walkable spans within the inclusive grid `footprint`
+    /// whose column center passes `contains`.
+    fn mark_area_in_footprint(
+        &mut self,
+        (min, max): (IVec3, IVec3),
+        area: AreaType,
+        contains: impl Fn(Vec2) -> bool,
+    ) {
         // Jan: This comment is taken from the original
         // TODO: Optimize.
         for z in min.z..=max.z {
             for x in min.x..=max.x {
+                let point = Vec2::new(
+                    self.aabb.min.x + (x as f32 + 0.5) * self.cell_size,
+                    self.aabb.min.z + (z as f32 + 0.5) * self.cell_size,
+                );
+                if !contains(point) {
+                    continue;
+                }
                 let cell_index = (x + z * self.width as i32) as usize;
                 let cell = &self.cells[cell_index];
                 let max_index = cell.index() as usize + cell.count() as usize;
@@ -55,38 +103,65 @@ impl CompactHeightfield {
                         continue;
                     }
 
-                    let point = Vec2::new(
-                        self.aabb.min.x + (x as f32 + 0.5) * self.cell_size,
-                        self.aabb.min.z + (z as f32 + 0.5) * self.cell_size,
-                    );
-                    if point_in_poly(&point, &volume.vertices) {
-                        self.areas[i] = volume.area;
-                    }
+                    self.areas[i] = area;
                 }
             }
         }
     }
 }
 
-fn point_in_poly(point: &Vec2, vertices: &[Vec2]) -> bool {
-    let mut inside = false;
-    let mut j = vertices.len() - 1;
-    for i in 0..vertices.len() {
-        let xi = vertices[i].x;
-        let yi = vertices[i].y;
-        let xj = vertices[j].x;
-        let yj = vertices[j].y;
-        if ((yi > point.y) != (yj > point.y))
-            && (point.x < (xj - xi) * (point.y - yi) / (yj - yi) + xi)
-        {
-            inside = !inside;
+impl Heightfield {
+    /// Sets the [`AreaType`] of the spans within the given convex volume.
+    ///
+    /// Unlike [`CompactHeightfield::mark_convex_poly_area`], this operates on the raw heightfield before compaction.
+    /// This is useful for tools that decide walkability only after all geometry has been rasterized,
+    /// e.g. marking the spans under a roof as interior.
+    ///
+    /// A span is inside the volume if the center of its column lies within [`ConvexVolume::vertices`]
+    /// and its top lies between [`ConvexVolume::min_y`] and [`ConvexVolume::max_y`].
+    /// Unwalkable spans are left untouched.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn remark_spans_in_volume(&mut self, volume: ConvexVolume) {
+        let Some((min, max)) = volume.grid_footprint(
+            self.aabb.min,
+            self.cell_size,
+            self.cell_height,
+            self.width,
+            self.height,
+        ) else {
+            return;
+        };
+
+        for z in min.z..=max.z {
+            for x in min.x..=max.x {
+                let point = Vec2::new(
+                    self.aabb.min.x + (x as f32 + 0.5) * self.cell_size,
+                    self.aabb.min.z + (z as f32 + 0.5) * self.cell_size,
+                );
+                if !point_in_poly(&point, &volume.vertices) {
+                    continue;
+                }
+                let mut span_key_iter = self.span_key_at(x as u16, z as u16);
+                while let Some(span_key) = span_key_iter {
+                    let span = self.span_mut(span_key);
+                    span_key_iter = span.next;
+                    if !span.area.is_walkable() {
+                        continue;
+                    }
+                    if (span.max as i32) < min.y || (span.max as i32) > max.y {
+                        continue;
+                    }
+                    span.area = volume.area;
+                }
+            }
         }
-        j = i;
     }
-    inside
 }
 
-/// A convex volume that marks an area within a [`CompactHeightfield`] as belonging to a specific [`AreaType`] through [`CompactHeightfield::mark_convex_poly_area`].
+/// A convex volume that marks an area within a [`CompactHeightfield`] or [`Heightfield`] as belonging to a specific [`AreaType`]
+/// through [`CompactHeightfield::mark_convex_poly_area`] or [`Heightfield::remark_spans_in_volume`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConvexVolume {
     /// The vertices of the convex volume. In 3D, these represent the X and Z coordinates of the vertices.
     pub vertices: Vec<Vec2>,
@@ -97,3 +172,173 @@ pub struct ConvexVolume {
     /// The area type of the convex volume.
     pub area: AreaType,
 }
+
+impl ConvexVolume {
+    /// Returns the inclusive grid cells covered by the volume, clamped to a grid of `width` by `height` columns.
+    /// `None` if the volume is empty or lies entirely outside the grid.
+    fn grid_footprint(
+        &self,
+        origin: Vec3,
+        cell_size: f32,
+        cell_height: f32,
+        width: u16,
+        height: u16,
+    ) -> Option<(IVec3, IVec3)> {
+        // Compute the bounding box of the polygon
+        let aabb = Aabb2d::from_verts(&self.vertices)?;
+        let aabb = aabb.extend_y(self.min_y, self.max_y);
+        grid_footprint(aabb, origin, cell_size, cell_height, width, height)
+    }
+}
+
+/// Returns the inclusive grid cells covered by `aabb`, clamped to a grid of `width` by `height` columns starting at `origin`.
+/// `None` if `aabb` lies entirely outside the grid, including entirely below it.
+fn grid_footprint(
+    aabb: Aabb3d,
+    origin: Vec3,
+    cell_size: f32,
+    cell_height: f32,
+    width: u16,
+    height: u16,
+) -> Option<(IVec3, IVec3)> {
+    // Compute the grid footprint of the volume
+    let scale = Vec3::new(cell_size, cell_height, cell_size);
+    let min = (aabb.min - origin) / scale;
+    let max = (aabb.max - origin) / scale;
+
+    // Early-out if the volume lies entirely outside the grid.
+    // Checked before truncating, or volumes just below the grid would reach into its first layer.
+    if max.cmplt(Vec3::ZERO).any() || min.x >= width as f32 || min.z >= height as f32 {
+        return None;
+    }
+
+    // Clamp the footprint to the grid
+    let min = min.as_ivec3().max(IVec3::ZERO);
+    let max = max
+        .as_ivec3()
+        .min(IVec3::new(width as i32 - 1, i32::MAX, height as i32 - 1));
+    Some((min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3A;
+
+    use super::*;
+    use crate::{
+        Aabb3d, HeightfieldBuilder,
+        heightfield::{SpanInsertion, SpanOverlap},
+        span::SpanBuilder,
+    };
+
+    #[test]
+    fn remarks_raw_spans_in_volume() {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 5.0, 5.0]),
+            cell_size: 1.0,
+            cell_height: 1.0,
+        }
+        .build()
+        .unwrap();
+        // A floor at height 2 and a roof at height 7 in the same column, and another floor next to it.
+        for (x, min, max) in [(1, 0, 2), (1, 6, 7), (2, 0, 2)] {
+            heightfield
+                .add_span(SpanInsertion {
+                    x,
+                    z: 1,
+                    flag_merge_threshold: 0,
+                    overlap: SpanOverlap::Merge,
+                    span: SpanBuilder {
+                        min,
+                        max,
+                        area: AreaType::DEFAULT_WALKABLE,
+                        next: None,
+                    }
+                    .build(),
+                })
+                .unwrap();
+        }
+
+        heightfield.remark_spans_in_volume(ConvexVolume {
+            vertices: vec![
+                Vec2::new(-4.0, -4.0),
+                Vec2::new(-4.0, -2.0),
+                Vec2::new(-3.0, -2.0),
+                Vec2::new(-3.0, -4.0),
+            ],
+            min_y: -5.0,
+            max_y: -2.0,
+            area: AreaType::new(3),
+        });
+
+        let floor = heightfield.span_at(1, 1).unwrap();
+        assert_eq!(floor.area, AreaType::new(3));
+        let roof = heightfield.span(floor.next.unwrap());
+        assert_eq!(roof.area, AreaType::DEFAULT_WALKABLE);
+        let neighbor = heightfield.span_at(2, 1).unwrap();
+        assert_eq!(neighbor.area, AreaType::DEFAULT_WALKABLE);
+    }
+
+    #[test]
+    fn marks_box_and_cylinder_areas() {
+        const ROAD: AreaType = AreaType::new(3);
+        const WATER: AreaType = AreaType::new(4);
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 5.0, 5.0]),
+            cell_size: 1.0,
+            cell_height: 1.0,
+        }
+        .build()
+        .unwrap();
+        // A row of floors whose tops lie at y = -3
+        for x in 0..10 {
+            heightfield
+                .add_span(SpanInsertion {
+                    x,
+                    z: 5,
+                    flag_merge_threshold: 0,
+                    overlap: SpanOverlap::Merge,
+                    span: SpanBuilder {
+                        min: 0,
+                        max: 2,
+                        area: AreaType::DEFAULT_WALKABLE,
+                        next: None,
+                    }
+                    .build(),
+                })
+                .unwrap();
+        }
+        let mut compact = heightfield.into_compact(1, 1).unwrap();
+        let area_at = |compact: &CompactHeightfield, x: usize| {
+            let cell = &compact.cells[x + 5 * compact.width as usize];
+            compact.areas[cell.index() as usize]
+        };
+
+        compact.mark_box_area(
+            &Aabb3d {
+                min: Vec3::new(-5.0, -4.0, -1.0),
+                max: Vec3::new(-1.0, -2.0, 1.0),
+            },
+            ROAD,
+        );
+        // Overlaps the road, and wins because it is marked later
+        compact.mark_cylinder_area(Vec3::new(-1.5, -4.0, 0.5), 1.2, 2.0, WATER);
+        // Volumes below the floors mark nothing
+        compact.mark_box_area(
+            &Aabb3d {
+                min: Vec3::new(-5.0, -7.0, -1.0),
+                max: Vec3::new(5.0, -5.5, 1.0),
+            },
+            AreaType::new(5),
+        );
+
+        let areas: Vec<_> = (0..10).map(|x| area_at(&compact, x)).collect();
+        assert_eq!(&areas[..2], &[ROAD, ROAD]);
+        assert_eq!(&areas[2..5], &[WATER, WATER, WATER]);
+        assert!(
+            areas[5..]
+                .iter()
+                .all(|area| *area == AreaType::DEFAULT_WALKABLE)
+        );
+    }
+}
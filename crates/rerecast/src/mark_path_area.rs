@@ -0,0 +1,256 @@
+use glam::{Vec2, Vec3, Vec3Swizzles as _};
+
+use crate::{AreaType, CompactHeightfield, ConvexVolume};
+
+impl CompactHeightfield {
+    /// Sets the [`AreaType`] of the spans covered by a path of the given width, e.g. to paint a road network
+    /// with a cheap area before building regions. See [`PathVolume`].
+    ///
+    /// Overwrites the areas set by earlier calls, see [`Self::mark_convex_poly_area`]. Unwalkable spans are left untouched.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn mark_path_area(&mut self, path: &PathVolume) {
+        for volume in path.convex_volumes() {
+            self.mark_convex_poly_area(volume);
+        }
+    }
+}
+
+/// A path of a constant width along a polyline, which marks the spans it covers as belonging to a specific [`AreaType`]
+/// through [`CompactHeightfield::mark_path_area`].
+///
+/// At corners, the edges of the path are extended until they meet, like a mitered line join.
+/// Sharp corners whose miter would reach farther than [`Self::miter_limit`] are beveled instead.
+/// The ends of the path are cut off flat at the first and last point.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathVolume {
+    /// The points along the center of the path, lying on the surface to mark. `[Units: wu]`
+    ///
+    /// Use [`Self::smoothed`] to follow a curve through them instead.
+    pub points: Vec<Vec3>,
+    /// The width of the path. `[Limit: > 0] [Units: wu]`
+    pub width: f32,
+    /// How far above and below [`Self::points`] spans are marked. `[Limit: >= 0] [Units: wu]`
+    pub height: f32,
+    /// How far a mitered corner may reach from the center of the path, relative to half of [`Self::width`],
+    /// before the corner is beveled. `[Limit: >= 1]`
+    pub miter_limit: f32,
+    /// The area type the path marks.
+    pub area: AreaType,
+}
+
+impl PathVolume {
+    /// The default of [`Self::miter_limit`], which bevels corners sharper than 60 degrees.
+    pub const DEFAULT_MITER_LIMIT: f32 = 2.0;
+
+    /// Creates a path along `points` with the given width, marking spans within `height` above and below the points.
+    pub fn new(points: Vec<Vec3>, width: f32, height: f32, area: AreaType) -> Self {
+        Self {
+            points,
+            width,
+            height,
+            miter_limit: Self::DEFAULT_MITER_LIMIT,
+            area,
+        }
+    }
+
+    /// Replaces [`Self::points`] with a Catmull-Rom spline passing through them,
+    /// sampled `subdivisions` times between each pair of points, e.g. to follow the curve of a road from its control points.
+    pub fn smoothed(mut self, subdivisions: u32) -> Self {
+        let points = &self.points;
+        if points.len() < 3 || subdivisions < 2 {
+            return self;
+        }
+        let mut smoothed = Vec::with_capacity((points.len() - 1) * subdivisions as usize + 1);
+        for i in 0..points.len() - 1 {
+            // The first and last segments mirror their outer neighbor.
+            let p0 = if i == 0 {
+                2.0 * points[0] - points[1]
+            } else {
+                points[i - 1]
+            };
+            let (p1, p2) = (points[i], points[i + 1]);
+            let p3 = points.get(i + 2).copied().unwrap_or(2.0 * p2 - p1);
+            for step in 0..subdivisions {
+                let t = step as f32 / subdivisions as f32;
+                smoothed.push(catmull_rom(p0, p1, p2, p3, t));
+            }
+        }
+        smoothed.push(points[points.len() - 1]);
+        self.points = smoothed;
+        self
+    }
+
+    /// Splits the path into the convex volumes it covers: one quad per segment and one triangle per beveled corner.
+    pub fn convex_volumes(&self) -> Vec<ConvexVolume> {
+        // Segments of zero length have no direction, so skip their repeated points.
+        let mut points: Vec<Vec3> = Vec::with_capacity(self.points.len());
+        for point in &self.points {
+            if points
+                .last()
+                .is_none_or(|last| last.xz().distance_squared(point.xz()) > f32::EPSILON)
+            {
+                points.push(*point);
+            }
+        }
+        if points.len() < 2 {
+            return Vec::new();
+        }
+        let half_width = self.width * 0.5;
+        let normals: Vec<Vec2> = points
+            .windows(2)
+            .map(|segment| (segment[1].xz() - segment[0].xz()).normalize().perp())
+            .collect();
+
+        let volume = |vertices: Vec<Vec2>, min_y: f32, max_y: f32| ConvexVolume {
+            vertices,
+            min_y: min_y - self.height,
+            max_y: max_y + self.height,
+            area: self.area,
+        };
+        let mut volumes = Vec::with_capacity(normals.len() * 2);
+        // The offset from the center to the left edge at the start of the current segment.
+        let mut start_offset = normals[0] * half_width;
+        for (segment, normal) in normals.iter().enumerate() {
+            let (start, end) = (points[segment], points[segment + 1]);
+            let end_offset = match normals.get(segment + 1) {
+                None => *normal * half_width,
+                Some(next_normal) => {
+                    let miter = (*normal + *next_normal).normalize_or_zero();
+                    let miter_length = half_width / miter.dot(*normal);
+                    if miter != Vec2::ZERO && miter_length <= self.miter_limit * half_width {
+                        miter * miter_length
+                    } else {
+                        // Fill the gaps the two segments leave at the corner on either side.
+                        let corner = end.xz();
+                        for side in [1.0, -1.0] {
+                            volumes.push(volume(
+                                vec![
+                                    corner,
+                                    corner + *normal * half_width * side,
+                                    corner + *next_normal * half_width * side,
+                                ],
+                                end.y,
+                                end.y,
+                            ));
+                        }
+                        *normal * half_width
+                    }
+                }
+            };
+            volumes.push(volume(
+                vec![
+                    start.xz() + start_offset,
+                    end.xz() + end_offset,
+                    end.xz() - end_offset,
+                    start.xz() - start_offset,
+                ],
+                start.y.min(end.y),
+                start.y.max(end.y),
+            ));
+            // A beveled corner starts the next segment with its own normal, a mitered one continues the miter.
+            start_offset = match normals.get(segment + 1) {
+                Some(next_normal) if end_offset == *normal * half_width => {
+                    *next_normal * half_width
+                }
+                _ => end_offset,
+            };
+        }
+        volumes
+    }
+}
+
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Aabb3d, HeightfieldBuilder,
+        heightfield::{SpanInsertion, SpanOverlap},
+        span::SpanBuilder,
+    };
+
+    fn floor() -> CompactHeightfield {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d {
+                min: Vec3::ZERO,
+                max: Vec3::new(10.0, 4.0, 10.0),
+            },
+            cell_size: 1.0,
+            cell_height: 1.0,
+        }
+        .build()
+        .unwrap();
+        for z in 0..10 {
+            for x in 0..10 {
+                heightfield
+                    .add_span(SpanInsertion {
+                        x,
+                        z,
+                        flag_merge_threshold: 0,
+                        overlap: SpanOverlap::Merge,
+                        span: SpanBuilder {
+                            min: 0,
+                            max: 1,
+                            area: AreaType::DEFAULT_WALKABLE,
+                            next: None,
+                        }
+                        .build(),
+                    })
+                    .unwrap();
+            }
+        }
+        heightfield.into_compact(1, 1).unwrap()
+    }
+
+    fn area_at(compact: &CompactHeightfield, x: usize, z: usize) -> AreaType {
+        let cell = &compact.cells[x + z * compact.width as usize];
+        compact.areas[cell.index() as usize]
+    }
+
+    #[test]
+    fn marks_path_with_mitered_and_beveled_corners() {
+        const ROAD: AreaType = AreaType::new(3);
+        // Along the x-axis, then turning left along the z-axis.
+        let path = PathVolume::new(
+            vec![
+                Vec3::new(1.2, 1.0, 2.25),
+                Vec3::new(7.25, 1.0, 2.25),
+                Vec3::new(7.25, 1.0, 9.0),
+            ],
+            3.0,
+            1.0,
+            ROAD,
+        );
+
+        let mut mitered = floor();
+        mitered.mark_path_area(&path);
+        assert_eq!(area_at(&mitered, 1, 2), ROAD);
+        assert_eq!(area_at(&mitered, 0, 2), AreaType::DEFAULT_WALKABLE);
+        assert_eq!(area_at(&mitered, 7, 8), ROAD);
+        assert_eq!(area_at(&mitered, 2, 8), AreaType::DEFAULT_WALKABLE);
+        // The outer corner of the turn.
+        assert_eq!(area_at(&mitered, 8, 1), ROAD);
+
+        let mut beveled = floor();
+        beveled.mark_path_area(&PathVolume {
+            miter_limit: 1.0,
+            ..path.clone()
+        });
+        assert_eq!(area_at(&beveled, 8, 1), AreaType::DEFAULT_WALKABLE);
+        assert_eq!(area_at(&beveled, 7, 2), ROAD);
+        assert_eq!(area_at(&beveled, 6, 3), ROAD);
+
+        let smoothed = path.smoothed(4);
+        assert_eq!(smoothed.points.len(), 9);
+        assert_eq!(smoothed.points[4], Vec3::new(7.25, 1.0, 2.25));
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(feature = "bevy_reflect")]
 use bevy_reflect::prelude::*;
-use glam::{U16Vec2, UVec3, Vec2, Vec3, Vec3A};
+use glam::{UVec3, Vec2, Vec3, Vec3A, Vec3Swizzles as _};
 
 /// A 3D axis-aligned bounding box
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
@@ -51,7 +51,7 @@ impl Aabb3d {
 
     /// Checks if this AABB intersects with another AABB.
     #[inline]
-    pub(crate) fn intersects(&self, other: &Aabb3d) -> bool {
+    pub fn intersects(&self, other: &Aabb3d) -> bool {
         self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
     }
 }
@@ -83,6 +83,12 @@ impl Aabb2d {
         Some(Self { min, max })
     }
 
+    /// Checks if this AABB intersects another AABB.
+    #[inline]
+    pub(crate) fn intersects(&self, other: &Aabb2d) -> bool {
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
     /// Extends the AABB into an [`Aabb3d`] by treating the existing coordinates as X and Z values,
     /// and `y_min` and `y_max` are the new minimum and maximum Y values.
     #[inline]
@@ -172,13 +178,6 @@ pub(crate) fn next(i: usize, n: usize) -> usize {
     (i + 1) % n
 }
 
-pub(crate) fn distance_squared_between_point_and_line_u16vec2(
-    point: U16Vec2,
-    (p, q): (U16Vec2, U16Vec2),
-) -> f32 {
-    distance_squared_between_point_and_line_vec2(point.as_vec2(), (p.as_vec2(), q.as_vec2()))
-}
-
 pub(crate) fn distance_squared_between_point_and_line_vec2(pt: Vec2, (p, q): (Vec2, Vec2)) -> f32 {
     let pq = q - p;
     let dt = pt - p;
@@ -216,6 +215,153 @@ pub(crate) fn distance_squared_between_point_and_line_vec3(
     dt.length_squared()
 }
 
+pub(crate) fn point_in_poly(point: &Vec2, vertices: &[Vec2]) -> bool {
+    let mut inside = false;
+    let mut j = vertices.len() - 1;
+    for i in 0..vertices.len() {
+        let xi = vertices[i].x;
+        let yi = vertices[i].y;
+        let xj = vertices[j].x;
+        let yj = vertices[j].y;
+        if ((yi > point.y) != (yj > point.y))
+            && (point.x < (xj - xi) * (point.y - yi) / (yj - yi) + xi)
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Returns the height of the triangle `(a, b, c)` at the xz-coordinates `point`.
+/// Returns `None` if the point does not lie within the triangle on the xz-plane.
+pub(crate) fn height_on_triangle(point: Vec2, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+    const EPSILON: f32 = 1e-6;
+    let v0 = c - a;
+    let v1 = b - a;
+    let v2 = point - a.xz();
+
+    // Compute scaled barycentric coordinates
+    let mut denominator = v0.x * v1.z - v0.z * v1.x;
+    if denominator.abs() < EPSILON {
+        return None;
+    }
+    let mut u = v1.z * v2.x - v1.x * v2.y;
+    let mut v = v0.x * v2.y - v0.z * v2.x;
+    if denominator < 0.0 {
+        denominator = -denominator;
+        u = -u;
+        v = -v;
+    }
+
+    // If point lies inside the triangle, return interpolated y-coordinate.
+    if u >= 0.0 && v >= 0.0 && (u + v) <= denominator {
+        Some(a.y + (v0.y * u + v1.y * v) / denominator)
+    } else {
+        None
+    }
+}
+
+/// The result of [`intersect_segment_polygon_2d`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SegmentPolygonIntersection {
+    /// The parameter along the segment where it enters the polygon.
+    pub(crate) t_min: f32,
+    /// The parameter along the segment where it leaves the polygon.
+    pub(crate) t_max: f32,
+    /// The index of the edge through which the segment enters the polygon, if it starts outside of it.
+    pub(crate) edge_min: Option<usize>,
+    /// The index of the edge through which the segment leaves the polygon, if it ends outside of it.
+    pub(crate) edge_max: Option<usize>,
+}
+
+/// Intersects the segment `(start, end)` with the convex polygon `vertices` on the xz-plane.
+/// Edge `i` goes from vertex `i` to vertex `i + 1`.
+/// The segment counts as parallel to an edge if the magnitude of their cross product is below `epsilon`.
+///
+/// Returns `None` if the segment does not overlap the polygon.
+pub(crate) fn intersect_segment_polygon_2d(
+    start: Vec2,
+    end: Vec2,
+    vertices: &[Vec2],
+    epsilon: f32,
+) -> Option<SegmentPolygonIntersection> {
+    // This is Detour's `dtVperp2D`, which has the opposite sign of `perp_dot`
+    let perp = |u: Vec2, v: Vec2| u.y * v.x - u.x * v.y;
+
+    let mut intersection = SegmentPolygonIntersection {
+        t_min: 0.0,
+        t_max: 1.0,
+        edge_min: None,
+        edge_max: None,
+    };
+    let direction = end - start;
+    let mut j = vertices.len() - 1;
+    for i in 0..vertices.len() {
+        let edge = vertices[i] - vertices[j];
+        let diff = start - vertices[j];
+        let n = perp(edge, diff);
+        let d = perp(direction, edge);
+        if d.abs() < epsilon {
+            // The segment is nearly parallel to this edge
+            if n < 0.0 {
+                return None;
+            }
+            j = i;
+            continue;
+        }
+        let t = n / d;
+        if d < 0.0 {
+            // The segment is entering across this edge
+            if t > intersection.t_min {
+                intersection.t_min = t;
+                intersection.edge_min = Some(j);
+                // The segment enters after leaving the polygon
+                if intersection.t_min > intersection.t_max {
+                    return None;
+                }
+            }
+        } else {
+            // The segment is leaving across this edge
+            if t < intersection.t_max {
+                intersection.t_max = t;
+                intersection.edge_max = Some(j);
+                // The segment leaves before entering the polygon
+                if intersection.t_max < intersection.t_min {
+                    return None;
+                }
+            }
+        }
+        j = i;
+    }
+    Some(intersection)
+}
+
+/// A FNV-1a hasher, for hashes that must be stable across platforms and builds, which std's hashers are not guaranteed to be.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Fnv1a(u64);
+
+impl Fnv1a {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    #[inline]
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(PRIME);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn finish(self) -> u64 {
+        self.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
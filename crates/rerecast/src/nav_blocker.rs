@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use glam::{UVec3, Vec2, Vec3, Vec3A, Vec3Swizzles as _};
+
+use crate::{AreaType, PolygonNavmesh, TriMesh};
+
+/// Where the unwalkable area lies relative to a [`NavBlockerOutline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum NavBlockerKind {
+    /// A closed loop around an unwalkable area that is surrounded by the navmesh, e.g. a pillar.
+    Hole,
+    /// A closed loop around an island of the navmesh. The unwalkable area lies outside of it.
+    Outer,
+    /// An open chain of boundary edges that ends at portals to neighboring tiles.
+    Open,
+}
+
+/// A chain of solid boundary edges of the navmesh, separating walkable from unwalkable area.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct NavBlockerOutline {
+    /// The world space vertices of the outline in order.
+    /// For closed outlines, the last vertex connects back to the first one.
+    pub vertices: Vec<Vec3>,
+    /// Where the unwalkable area lies relative to the outline.
+    pub kind: NavBlockerKind,
+}
+
+impl NavBlockerOutline {
+    /// Returns whether the last vertex connects back to the first one.
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.kind != NavBlockerKind::Open
+    }
+
+    /// Returns the vertices projected onto the xz-plane, e.g. for drawing impassable zones on a map.
+    pub fn to_2d(&self) -> Vec<Vec2> {
+        self.vertices.iter().map(|vertex| vertex.xz()).collect()
+    }
+
+    /// Extrudes the outline upwards by `height` into the walls of a prism, e.g. for an invisible wall collider. `[Units: wu]`
+    ///
+    /// The prism has no caps, so the result only blocks movement across the outline.
+    /// All triangles are marked as [`AreaType::NOT_WALKABLE`].
+    pub fn extrude(&self, height: f32) -> TriMesh {
+        let vertex_count = self.vertices.len();
+        let segment_count = if self.is_closed() {
+            vertex_count
+        } else {
+            vertex_count.saturating_sub(1)
+        };
+
+        let mut vertices = Vec::with_capacity(vertex_count * 2);
+        for vertex in &self.vertices {
+            vertices.push(Vec3A::from(*vertex));
+            vertices.push(Vec3A::from(*vertex) + Vec3A::Y * height);
+        }
+        let mut indices = Vec::with_capacity(segment_count * 2);
+        for segment in 0..segment_count {
+            let bottom_a = (segment * 2) as u32;
+            let top_a = bottom_a + 1;
+            let bottom_b = (((segment + 1) % vertex_count) * 2) as u32;
+            let top_b = bottom_b + 1;
+            indices.push(UVec3::new(bottom_a, bottom_b, top_b));
+            indices.push(UVec3::new(bottom_a, top_b, top_a));
+        }
+
+        TriMesh {
+            vertices,
+            area_types: vec![AreaType::NOT_WALKABLE; indices.len()],
+            materials: Vec::new(),
+            indices,
+        }
+    }
+}
+
+impl PolygonNavmesh {
+    /// Extracts the outlines of the unwalkable areas, i.e. the solid boundary edges of the walkable polygons chained together.
+    ///
+    /// Edges to polygons with an unwalkable area count as solid, while portals to neighboring tiles do not,
+    /// so outlines that run into a tile border are returned as [`NavBlockerKind::Open`] chains.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn nav_blocker_outlines(&self) -> Vec<NavBlockerOutline> {
+        let mut edges = Vec::new();
+        // Twice the signed area of all walkable polygons, whose sign tells the winding of the navmesh.
+        let mut winding = 0.0;
+        for polygon in 0..self.polygon_count() {
+            if !self.areas[polygon].is_walkable() {
+                continue;
+            }
+            let vertices = self.polygon_vertices(polygon);
+            let world_vertices: Vec<Vec3> = self.polygon_world_vertices(polygon).collect();
+            winding += signed_area(&world_vertices);
+            let nvp = self.max_vertices_per_polygon as usize;
+            for edge in 0..vertices.len() {
+                let is_solid = match self.internal_neighbor(polygon, edge) {
+                    Some(neighbor) => !self.areas[neighbor].is_walkable(),
+                    None => self.polygon_neighbors[polygon * nvp + edge] == Self::NO_CONNECTION,
+                };
+                if is_solid {
+                    edges.push((vertices[edge], vertices[(edge + 1) % vertices.len()]));
+                }
+            }
+        }
+
+        let mut outgoing: HashMap<u16, Vec<usize>> = HashMap::new();
+        let mut has_incoming = vec![false; self.vertices.len()];
+        for (index, (start, end)) in edges.iter().enumerate() {
+            outgoing.entry(*start).or_default().push(index);
+            has_incoming[*end as usize] = true;
+        }
+
+        // Start with the heads of open chains so that they are not split in the middle.
+        let starts = (0..edges.len())
+            .filter(|edge| !has_incoming[edges[*edge].0 as usize])
+            .chain(0..edges.len());
+        let mut used = vec![false; edges.len()];
+        let mut outlines = Vec::new();
+        for start in starts {
+            if used[start] {
+                continue;
+            }
+            let mut chain = vec![edges[start].0];
+            let mut edge = start;
+            loop {
+                used[edge] = true;
+                let end = edges[edge].1;
+                chain.push(end);
+                let next = outgoing
+                    .get(&end)
+                    .and_then(|candidates| candidates.iter().find(|next| !used[**next]));
+                match next {
+                    Some(next) => edge = *next,
+                    None => break,
+                }
+            }
+
+            let closed = chain.first() == chain.last();
+            if closed {
+                chain.pop();
+            }
+            let vertices: Vec<Vec3> = chain.iter().map(|i| self.world_vertex(*i)).collect();
+            let kind = if !closed {
+                NavBlockerKind::Open
+            } else if signed_area(&vertices).signum() == winding.signum() {
+                NavBlockerKind::Outer
+            } else {
+                NavBlockerKind::Hole
+            };
+            outlines.push(NavBlockerOutline { vertices, kind });
+        }
+        outlines
+    }
+}
+
+/// Twice the signed area of a polygon on the xz-plane.
+fn signed_area(vertices: &[Vec3]) -> f32 {
+    (0..vertices.len())
+        .map(|i| {
+            let next = vertices[(i + 1) % vertices.len()];
+            vertices[i].xz().perp_dot(next.xz())
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    #[test]
+    fn extracts_holes_and_outer_borders() {
+        let grid = GridNavmesh::parse(
+            "
+            ...
+            .#.
+            ...
+            ",
+        );
+        let outlines = grid.navmesh.nav_blocker_outlines();
+        assert_eq!(outlines.len(), 2);
+
+        let hole = outlines
+            .iter()
+            .find(|outline| outline.kind == NavBlockerKind::Hole)
+            .unwrap();
+        assert_eq!(hole.vertices.len(), 4);
+        assert!(
+            hole.to_2d()
+                .iter()
+                .all(|vertex| vertex.cmpge(Vec2::ONE).all() && vertex.cmple(Vec2::splat(2.0)).all())
+        );
+
+        let outer = outlines
+            .iter()
+            .find(|outline| outline.kind == NavBlockerKind::Outer)
+            .unwrap();
+        assert_eq!(outer.vertices.len(), 12);
+
+        let walls = hole.extrude(2.0);
+        assert_eq!(walls.vertices.len(), 8);
+        assert_eq!(walls.indices.len(), 8);
+        assert!(walls.vertices.iter().any(|vertex| vertex.y == 2.0));
+    }
+
+    #[test]
+    fn unwalkable_polygons_block() {
+        let mut grid = GridNavmesh::parse("..");
+        grid.navmesh.areas[1] = AreaType::NOT_WALKABLE;
+        let outlines = grid.navmesh.nav_blocker_outlines();
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].kind, NavBlockerKind::Outer);
+        assert_eq!(outlines[0].vertices.len(), 4);
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::{NavmeshPath, NavmeshQuery, NearestPolygon, QueryFilter, polygon_graph::OpenNode};
+
+/// A position on one of the navmeshes of a [`NavmeshNetwork`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkPosition {
+    /// The index of the navmesh, as returned by [`NavmeshNetwork::add_navmesh`].
+    pub navmesh: usize,
+    /// The polygon and point on the navmesh.
+    pub location: NearestPolygon,
+}
+
+/// A link between two navmeshes of a [`NavmeshNetwork`], e.g. the entrance from the overworld into a separately baked dungeon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavmeshNetworkLink {
+    /// Where the link starts.
+    pub start: NetworkPosition,
+    /// Where the link ends.
+    pub end: NetworkPosition,
+    /// The cost of taking the link, added to the cost of the paths on both navmeshes. `[Limit: >= 0]`
+    pub cost: f32,
+    /// Whether the link can also be taken from [`Self::end`] to [`Self::start`].
+    pub bidirectional: bool,
+}
+
+/// Taking a [`NavmeshNetworkLink`] from one segment of a [`NetworkPath`] to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NavmeshTransition {
+    /// The index of the link, as returned by [`NavmeshNetwork::add_link`].
+    pub link: usize,
+    /// Whether the link is taken from [`NavmeshNetworkLink::end`] to [`NavmeshNetworkLink::start`].
+    pub reversed: bool,
+}
+
+/// The part of a [`NetworkPath`] on a single navmesh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkPathSegment {
+    /// The index of the navmesh the segment lies on.
+    pub navmesh: usize,
+    /// The path on the navmesh. Always complete.
+    pub path: NavmeshPath,
+    /// The link taken at the end of the segment to reach the next one, or `None` for the last segment.
+    pub transition: Option<NavmeshTransition>,
+}
+
+/// The result of [`NavmeshNetwork::find_path`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NetworkPath {
+    /// The segments of the path in order, each ending where the link to the next one starts.
+    pub segments: Vec<NetworkPathSegment>,
+    /// The total cost of the path, including the costs of the links taken.
+    pub cost: f32,
+}
+
+/// Separately built navmeshes connected by [`NavmeshNetworkLink`]s, e.g. an overworld and the dungeons below it,
+/// for finding paths that lead from one navmesh to another.
+///
+/// The navmeshes are searched through their [`NavmeshQuery`], so their settings and off-mesh links apply as usual.
+#[derive(Debug, Clone, Default)]
+pub struct NavmeshNetwork<'a> {
+    queries: Vec<NavmeshQuery<'a>>,
+    links: Vec<NavmeshNetworkLink>,
+}
+
+impl<'a> NavmeshNetwork<'a> {
+    /// The node of the search standing for the start of the path.
+    const START: usize = 0;
+    /// The node of the search standing for the end of the path.
+    const END: usize = 1;
+
+    /// Creates an empty network.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a navmesh to the network and returns its index.
+    pub fn add_navmesh(&mut self, query: NavmeshQuery<'a>) -> usize {
+        self.queries.push(query);
+        self.queries.len() - 1
+    }
+
+    /// Adds a link between two navmeshes of the network and returns its index.
+    pub fn add_link(&mut self, link: NavmeshNetworkLink) -> usize {
+        self.links.push(link);
+        self.links.len() - 1
+    }
+
+    /// Returns the query of the navmesh at index `navmesh`, or `None` if there is no such navmesh.
+    #[inline]
+    pub fn query(&self, navmesh: usize) -> Option<&NavmeshQuery<'a>> {
+        self.queries.get(navmesh)
+    }
+
+    /// Returns all links, indexed by link.
+    #[inline]
+    pub fn links(&self) -> &[NavmeshNetworkLink] {
+        &self.links
+    }
+
+    /// Finds the cheapest path from `start` to `end`, possibly leading across other navmeshes of the network.
+    ///
+    /// The links form a graph searched with Dijkstra's algorithm, where walking from one link to the next
+    /// costs the [`NavmeshPath::cost`] of the path between them on their navmesh, as found with [`NavmeshQuery::find_path`].
+    /// Every search thus runs a path search on a navmesh for each pair of links it considers,
+    /// which is cheap for the few entrances separately baked areas are usually connected by.
+    ///
+    /// Returns `None` if `end` can not be reached, or if either position lies on a navmesh that is not part of the network.
+    /// Links to navmeshes that are not part of the network are never taken.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn find_path(
+        &self,
+        start: NetworkPosition,
+        end: NetworkPosition,
+        filter: &QueryFilter,
+    ) -> Option<NetworkPath> {
+        if start.navmesh >= self.queries.len() || end.navmesh >= self.queries.len() {
+            return None;
+        }
+        // The nodes are the start, the end, and the arrival at either end of every link.
+        let position = |node: usize| match node {
+            Self::START => start,
+            Self::END => end,
+            _ => {
+                let link = &self.links[(node - 2) / 2];
+                if (node - 2).is_multiple_of(2) {
+                    link.end
+                } else {
+                    link.start
+                }
+            }
+        };
+        let mut costs: HashMap<usize, f32> = HashMap::from([(Self::START, 0.0)]);
+        // The previous node of every node and the segment leading from it.
+        let mut parents: HashMap<usize, (usize, NetworkPathSegment)> = HashMap::new();
+        let mut open = BinaryHeap::from([OpenNode {
+            cost: 0.0,
+            node: Self::START,
+        }]);
+
+        while let Some(OpenNode { cost, node }) = open.pop() {
+            if cost > costs[&node] {
+                continue;
+            }
+            if node == Self::END {
+                return Some(Self::collect_path(&mut parents, cost));
+            }
+            let from = position(node);
+            let Some(query) = self.queries.get(from.navmesh) else {
+                continue;
+            };
+
+            let mut targets = Vec::new();
+            if end.navmesh == from.navmesh {
+                targets.push((Self::END, end.location, 0.0, None));
+            }
+            for (index, link) in self.links.iter().enumerate() {
+                if link.start.navmesh == from.navmesh {
+                    let transition = NavmeshTransition {
+                        link: index,
+                        reversed: false,
+                    };
+                    targets.push((
+                        2 + index * 2,
+                        link.start.location,
+                        link.cost,
+                        Some(transition),
+                    ));
+                }
+                if link.bidirectional && link.end.navmesh == from.navmesh {
+                    let transition = NavmeshTransition {
+                        link: index,
+                        reversed: true,
+                    };
+                    targets.push((
+                        3 + index * 2,
+                        link.end.location,
+                        link.cost,
+                        Some(transition),
+                    ));
+                }
+            }
+
+            for (target, departure, link_cost, transition) in targets {
+                if departure.polygon >= query.navmesh().polygon_count() {
+                    continue;
+                }
+                let path = query.find_path(from.location, departure, filter);
+                if !path.complete {
+                    continue;
+                }
+                let target_cost = cost + path.cost + link_cost;
+                if costs
+                    .get(&target)
+                    .is_some_and(|existing| *existing <= target_cost)
+                {
+                    continue;
+                }
+                costs.insert(target, target_cost);
+                let segment = NetworkPathSegment {
+                    navmesh: from.navmesh,
+                    path,
+                    transition,
+                };
+                parents.insert(target, (node, segment));
+                open.push(OpenNode {
+                    cost: target_cost,
+                    node: target,
+                });
+            }
+        }
+        None
+    }
+
+    /// Walks the parents back from the end to the start, collecting the segments of the path.
+    fn collect_path(
+        parents: &mut HashMap<usize, (usize, NetworkPathSegment)>,
+        cost: f32,
+    ) -> NetworkPath {
+        let mut segments = Vec::new();
+        let mut node = Self::END;
+        while let Some((parent, segment)) = parents.remove(&node) {
+            segments.push(segment);
+            node = parent;
+        }
+        segments.reverse();
+        NetworkPath { segments, cost }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BvTree, test_utils::GridNavmesh};
+
+    #[test]
+    fn finds_paths_across_linked_navmeshes() {
+        let overworld = GridNavmesh::parse("a..e");
+        let dungeon = GridNavmesh::parse(
+            "
+            x..b
+            ...c
+            ",
+        );
+        let (overworld_tree, dungeon_tree) = (
+            BvTree::new(&overworld.navmesh),
+            BvTree::new(&dungeon.navmesh),
+        );
+        let position = |navmesh: usize, grid: &GridNavmesh, marker: char| NetworkPosition {
+            navmesh,
+            location: NearestPolygon {
+                polygon: grid.polygon(marker),
+                point: grid.position(marker),
+            },
+        };
+
+        let mut network = NavmeshNetwork::new();
+        let overworld_index =
+            network.add_navmesh(NavmeshQuery::new(&overworld.navmesh, &overworld_tree));
+        let dungeon_index = network.add_navmesh(NavmeshQuery::new(&dungeon.navmesh, &dungeon_tree));
+        let entrance = network.add_link(NavmeshNetworkLink {
+            start: position(overworld_index, &overworld, 'e'),
+            end: position(dungeon_index, &dungeon, 'x'),
+            cost: 1.0,
+            bidirectional: false,
+        });
+        let filter = QueryFilter::default();
+
+        let path = network
+            .find_path(
+                position(overworld_index, &overworld, 'a'),
+                position(dungeon_index, &dungeon, 'b'),
+                &filter,
+            )
+            .unwrap();
+        assert_eq!(path.segments.len(), 2);
+        assert_eq!(path.segments[0].navmesh, overworld_index);
+        assert_eq!(
+            path.segments[0].transition,
+            Some(NavmeshTransition {
+                link: entrance,
+                reversed: false,
+            })
+        );
+        assert_eq!(path.segments[0].path.end, overworld.position('e'));
+        assert_eq!(path.segments[1].navmesh, dungeon_index);
+        assert_eq!(path.segments[1].transition, None);
+        assert_eq!(path.segments[1].path.start, dungeon.position('x'));
+        assert_eq!(path.segments[1].path.end, dungeon.position('b'));
+        // 3 on each navmesh and 1 for the entrance.
+        assert_eq!(path.cost, 7.0);
+
+        // Paths on a single navmesh do not take any links.
+        let path = network
+            .find_path(
+                position(dungeon_index, &dungeon, 'c'),
+                position(dungeon_index, &dungeon, 'b'),
+                &filter,
+            )
+            .unwrap();
+        assert_eq!(path.segments.len(), 1);
+        assert_eq!(path.segments[0].transition, None);
+
+        // The entrance is one-way.
+        let back = (
+            position(dungeon_index, &dungeon, 'b'),
+            position(overworld_index, &overworld, 'a'),
+        );
+        assert_eq!(network.find_path(back.0, back.1, &filter), None);
+        let mut exit = network.links()[entrance];
+        exit.bidirectional = true;
+        network.add_link(exit);
+        let path = network.find_path(back.0, back.1, &filter).unwrap();
+        assert_eq!(path.segments.len(), 2);
+        assert!(path.segments[0].transition.unwrap().reversed);
+    }
+}
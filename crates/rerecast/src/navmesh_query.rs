@@ -0,0 +1,982 @@
+use std::collections::{HashMap, hash_map::Entry};
+
+use glam::Vec3;
+
+use crate::{
+    AreaType, BvTree, LayerConstraint, NavmeshRaycast, NearestPolygon, OffMeshConnection,
+    OffMeshLinks, OffMeshTraversal, PolygonNavmesh, PortalCrossing, QueryCounters, QueryTolerances,
+    StraightPathPoint, StraightPathPointKind,
+    math::next,
+    open_list::{OpenList, PathOpenList},
+    polygon_graph::OpenNode,
+    straight_path::mark_off_mesh_segment,
+};
+
+/// Decides which polygons a [`NavmeshQuery`] may visit and what walking over them costs, like Detour's `dtQueryFilter`.
+///
+/// Different agents can use different filters over the same navmesh, e.g. to let vehicles avoid mud
+/// or to keep civilians out of the polygons marked by a [`FlagVolume`](crate::FlagVolume).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueryFilter {
+    /// The cost per wu of walking over each area, indexed by [`AreaType::id`]. Areas without an entry cost 1.
+    /// Areas with an infinite cost are never visited. `[Limit: >= 0]`
+    pub area_costs: Vec<f32>,
+    /// If not 0, only polygons and off-mesh connections with at least one of these flags are visited.
+    pub include_flags: u16,
+    /// Polygons and off-mesh connections with any of these flags are never visited.
+    pub exclude_flags: u16,
+}
+
+impl Default for QueryFilter {
+    fn default() -> Self {
+        Self {
+            area_costs: vec![1.0; AreaType::MAX_ID as usize + 1],
+            include_flags: 0,
+            exclude_flags: 0,
+        }
+    }
+}
+
+impl QueryFilter {
+    /// Returns the cost per wu of walking over `area`.
+    #[inline]
+    pub fn area_cost(&self, area: AreaType) -> f32 {
+        self.area_costs
+            .get(area.id() as usize)
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// Sets the cost per wu of walking over `area`. `[Limit: >= 0]`
+    pub fn set_area_cost(&mut self, area: AreaType, cost: f32) {
+        let index = area.id() as usize;
+        if self.area_costs.len() <= index {
+            self.area_costs.resize(index + 1, 1.0);
+        }
+        self.area_costs[index] = cost;
+    }
+
+    /// Returns whether the polygon at index `polygon` may be visited.
+    #[inline]
+    pub fn passes(&self, navmesh: &PolygonNavmesh, polygon: usize) -> bool {
+        self.passes_area(navmesh.areas[polygon], navmesh.flags[polygon])
+    }
+
+    /// Returns whether `connection` may be traversed.
+    #[inline]
+    pub fn passes_connection(&self, connection: &OffMeshConnection) -> bool {
+        self.passes_area(connection.area, connection.flags)
+    }
+
+    fn passes_area(&self, area: AreaType, flags: u16) -> bool {
+        area.is_walkable()
+            && self.area_cost(area).is_finite()
+            && (self.include_flags == 0 || flags & self.include_flags != 0)
+            && flags & self.exclude_flags == 0
+    }
+}
+
+/// The result of [`NavmeshQuery::find_path`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NavmeshPath {
+    /// The indices of the polygons along the path, starting with the start polygon.
+    pub polygons: Vec<usize>,
+    /// The off-mesh traversal leading from `polygons[i]` to `polygons[i + 1]`,
+    /// or `None` if both polygons share an edge. `[Size: polygons.len() - 1]`
+    pub traversals: Vec<Option<OffMeshTraversal>>,
+    /// The point on the start polygon the path starts at.
+    pub start: Vec3,
+    /// The point the path ends at. If the path is not complete, this is the point on the last polygon closest to the requested end.
+    pub end: Vec3,
+    /// Whether the path reaches the end polygon. Otherwise, it leads to the polygon closest to the end
+    /// that could be reached, like Detour's `DT_PARTIAL_RESULT`.
+    pub complete: bool,
+    /// The cost of the path as estimated by the search, see [`NavmeshQuery::find_path`].
+    pub cost: f32,
+}
+
+/// The order in which [`NavmeshQuery::find_path`] explores polygons, see [`NavmeshQuery::strategy`].
+///
+/// All strategies share the node pool, the filter and the limits of the query, and return the path to the polygon
+/// closest to the end if it can not be reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum SearchStrategy {
+    /// Explores the polygons with the lowest cost plus estimated remaining cost first.
+    /// Finds the cheapest path while exploring few polygons, as long as the heuristic never overestimates.
+    #[default]
+    AStar,
+    /// Explores the polygons with the lowest estimated remaining cost first, ignoring the cost so far.
+    /// Explores the fewest polygons, but the path only leads roughly towards the end and may be far from the cheapest,
+    /// which suits behaviors like wandering towards a sound.
+    GreedyBestFirst,
+    /// Explores the polygons with the lowest cost first, ignoring the heuristic, like Dijkstra's algorithm.
+    /// Finds the cheapest path even with a heuristic that overestimates, at the price of exploring more polygons.
+    UniformCost,
+    /// Explores the polygons with the fewest polygons between them and the start first, ignoring all costs.
+    /// Finds the path crossing the fewest polygons.
+    BreadthFirst,
+}
+
+/// Pathfinding and spatial queries over a [`PolygonNavmesh`], like Detour's `dtNavMeshQuery`.
+///
+/// A query borrows the navmesh together with its [`BvTree`] and, optionally, the [`OffMeshLinks`] attached to it.
+/// It is cheap to create, so there is no need to keep one around between frames.
+/// Every query takes a [`QueryFilter`] deciding which polygons may be visited.
+#[derive(Debug, Clone, Copy)]
+pub struct NavmeshQuery<'a> {
+    navmesh: &'a PolygonNavmesh,
+    tree: &'a BvTree,
+    off_mesh_links: Option<&'a OffMeshLinks>,
+    /// The maximum number of polygons [`Self::find_path`] keeps track of before it stops exploring new ones. `[Limit: > 0]`
+    pub max_nodes: usize,
+    /// The maximum cost of a path found by [`Self::find_path`], e.g. the movement budget of a unit for this turn.
+    /// Polygons that can only be reached at a higher cost are not explored, so the path leads to the polygon
+    /// closest to the end within the budget instead. Defaults to [`f32::INFINITY`]. `[Limit: >= 0]`
+    pub max_path_cost: f32,
+    /// The maximum straight line distance from the start that [`Self::find_path`] explores,
+    /// which bounds the work spent on ends that can not be reached. Defaults to [`f32::INFINITY`]. `[Limit: >= 0] [Units: wu]`
+    pub max_search_radius: f32,
+    /// The order in which [`Self::find_path`] explores polygons. Defaults to [`SearchStrategy::AStar`].
+    pub strategy: SearchStrategy,
+    /// The geometric tolerances of all queries. Defaults to [`QueryTolerances::for_navmesh`].
+    pub tolerances: QueryTolerances,
+}
+
+impl<'a> NavmeshQuery<'a> {
+    /// The default of [`Self::max_nodes`].
+    pub const DEFAULT_MAX_NODES: usize = 2048;
+
+    /// Scales the straight line distance used as the heuristic of [`Self::find_path`], like Detour's `H_SCALE`.
+    /// Slightly underestimating the remaining distance keeps the search from overshooting
+    /// when all area costs are at least 1.
+    pub const HEURISTIC_SCALE: f32 = 0.999;
+
+    /// Creates a query over `navmesh`, using `tree` to find polygons by position.
+    pub fn new(navmesh: &'a PolygonNavmesh, tree: &'a BvTree) -> Self {
+        Self {
+            navmesh,
+            tree,
+            off_mesh_links: None,
+            max_nodes: Self::DEFAULT_MAX_NODES,
+            max_path_cost: f32::INFINITY,
+            max_search_radius: f32::INFINITY,
+            strategy: SearchStrategy::AStar,
+            tolerances: QueryTolerances::for_navmesh(navmesh),
+        }
+    }
+
+    /// Lets [`Self::find_path`] take the off-mesh connections in `links`.
+    pub fn with_off_mesh_links(mut self, links: &'a OffMeshLinks) -> Self {
+        self.off_mesh_links = Some(links);
+        self
+    }
+
+    /// Returns the navmesh this query runs on.
+    #[inline]
+    pub fn navmesh(&self) -> &'a PolygonNavmesh {
+        self.navmesh
+    }
+
+    /// Finds the polygon passing `filter` nearest to `center` within the box of `half_extents` around it.
+    /// See [`PolygonNavmesh::find_nearest_polygon`].
+    ///
+    /// Polygons whose surface lies within [`QueryTolerances::height`] of `center` count as equally near,
+    /// like Detour does with the walkable climb of a tile.
+    pub fn find_nearest_poly(
+        &self,
+        center: Vec3,
+        half_extents: Vec3,
+        filter: &QueryFilter,
+    ) -> Option<NearestPolygon> {
+        self.navmesh.find_nearest_polygon_where(
+            self.tree,
+            center,
+            half_extents,
+            LayerConstraint::Any,
+            &mut QueryCounters::default(),
+            &self.tolerances,
+            |polygon| filter.passes(self.navmesh, polygon),
+        )
+    }
+
+    /// Casts a ray along the surface of the navmesh from `start` towards `end`, starting in the polygon at index `start_polygon`.
+    /// Edges leading to polygons that do not pass `filter` block the ray like walls. See [`PolygonNavmesh::raycast`].
+    pub fn raycast(
+        &self,
+        start_polygon: usize,
+        start: Vec3,
+        end: Vec3,
+        filter: &QueryFilter,
+    ) -> NavmeshRaycast {
+        self.navmesh.raycast_where(
+            start_polygon,
+            start,
+            end,
+            &mut QueryCounters::default(),
+            &self.tolerances,
+            |polygon| filter.passes(self.navmesh, polygon),
+        )
+    }
+
+    /// Finds the cheapest corridor of polygons from `start` to `end` with A*, where walking over a polygon
+    /// costs the distance walked times the cost of its area in `filter`. Other strategies can be chosen with [`Self::strategy`].
+    ///
+    /// Like Detour, the search moves between the midpoints of the edges shared by the polygons,
+    /// so the cost is an approximation of the distance actually walked along the smoothed path.
+    /// If `end` can not be reached, the path leads to the polygon closest to it instead, see [`NavmeshPath::complete`].
+    /// The same happens if `end` lies beyond [`Self::max_path_cost`] or [`Self::max_search_radius`].
+    /// Use [`Self::find_straight_path`] to turn the corridor into points to walk along.
+    pub fn find_path(
+        &self,
+        start: NearestPolygon,
+        end: NearestPolygon,
+        filter: &QueryFilter,
+    ) -> NavmeshPath {
+        self.find_path_with_counters(start, end, filter, &mut QueryCounters::default())
+    }
+
+    /// Same as [`NavmeshQuery::find_path`], but accumulates the work done into `counters`.
+    pub fn find_path_with_counters(
+        &self,
+        start: NearestPolygon,
+        end: NearestPolygon,
+        filter: &QueryFilter,
+        counters: &mut QueryCounters,
+    ) -> NavmeshPath {
+        self.find_path_with_heuristic(
+            start,
+            end,
+            filter,
+            |position, end| position.distance(end) * Self::HEURISTIC_SCALE,
+            counters,
+        )
+    }
+
+    /// Same as [`NavmeshQuery::find_path_with_counters`], but estimates the remaining cost from a position to the end
+    /// with `heuristic` instead of the straight line distance, e.g. to guide the search with precomputed distances between regions.
+    ///
+    /// `heuristic` is called with the position of a search node and the end point.
+    /// As long as it never overestimates the remaining cost, the cheapest path is found.
+    /// [`SearchStrategy::UniformCost`] and [`SearchStrategy::BreadthFirst`] only use it to find the polygon closest to an unreachable end.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn find_path_with_heuristic(
+        &self,
+        start: NearestPolygon,
+        end: NearestPolygon,
+        filter: &QueryFilter,
+        heuristic: impl Fn(Vec3, Vec3) -> f32,
+        counters: &mut QueryCounters,
+    ) -> NavmeshPath {
+        let mut buffers = NavmeshQueryBuffers::default();
+        let best = self.search(start, end, filter, heuristic, counters, &mut buffers);
+
+        let mut polygons = vec![best];
+        let mut traversals = Vec::new();
+        let mut current = best;
+        while let Some((parent, traversal)) = buffers.nodes[&current].parent {
+            polygons.push(parent);
+            traversals.push(traversal);
+            current = parent;
+        }
+        polygons.reverse();
+        traversals.reverse();
+
+        NavmeshPath {
+            polygons,
+            traversals,
+            start: start.point,
+            end: self.path_end(best, end),
+            complete: best == end.polygon,
+            cost: buffers.nodes[&best].cost,
+        }
+    }
+
+    /// Like [`Self::find_path`], but writes the polygons of the corridor into `polygons` instead of allocating a [`NavmeshPath`],
+    /// for hot per-frame use. All memory the search needs is kept in `buffers` and reused by the next query.
+    ///
+    /// If the corridor does not fit into `polygons`, its beginning is written and [`BufferedPath::truncated`] is set,
+    /// like Detour's `DT_BUFFER_TOO_SMALL`. Off-mesh traversals are not written,
+    /// [`Self::find_straight_path_into`] finds them again between consecutive polygons that do not share an edge.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn find_path_into(
+        &self,
+        start: NearestPolygon,
+        end: NearestPolygon,
+        filter: &QueryFilter,
+        buffers: &mut NavmeshQueryBuffers,
+        polygons: &mut [usize],
+    ) -> BufferedPath {
+        let best = self.search(
+            start,
+            end,
+            filter,
+            |position, end| position.distance(end) * Self::HEURISTIC_SCALE,
+            &mut QueryCounters::default(),
+            buffers,
+        );
+
+        let mut count = 1;
+        let mut current = best;
+        while let Some((parent, _)) = buffers.nodes[&current].parent {
+            count += 1;
+            current = parent;
+        }
+        // Walk the corridor backwards again, writing only the polygons that fit.
+        let mut index = count;
+        let mut current = Some(best);
+        while let Some(polygon) = current {
+            index -= 1;
+            if let Some(slot) = polygons.get_mut(index) {
+                *slot = polygon;
+            }
+            current = buffers.nodes[&polygon].parent.map(|(parent, _)| parent);
+        }
+
+        BufferedPath {
+            count: count.min(polygons.len()),
+            truncated: count > polygons.len(),
+            complete: best == end.polygon,
+            end: self.path_end(best, end),
+        }
+    }
+
+    /// Runs A* from `start` towards `end` in `buffers` and returns the polygon the path ends at:
+    /// the end polygon if it was reached, the polygon closest to it otherwise.
+    /// The corridor can be read from the parents of the nodes in `buffers` afterwards.
+    fn search(
+        &self,
+        start: NearestPolygon,
+        end: NearestPolygon,
+        filter: &QueryFilter,
+        heuristic: impl Fn(Vec3, Vec3) -> f32,
+        counters: &mut QueryCounters,
+        buffers: &mut NavmeshQueryBuffers,
+    ) -> usize {
+        let navmesh = self.navmesh;
+        let NavmeshQueryBuffers {
+            nodes,
+            open,
+            successors,
+            ..
+        } = buffers;
+        nodes.clear();
+        successors.clear();
+        nodes.reserve(self.max_nodes);
+
+        let start_heuristic = heuristic(start.point, end.point);
+        let start_priority = match self.strategy {
+            SearchStrategy::AStar | SearchStrategy::GreedyBestFirst => start_heuristic,
+            SearchStrategy::UniformCost | SearchStrategy::BreadthFirst => 0.0,
+        };
+        OpenList::reset(
+            open,
+            match self.strategy {
+                SearchStrategy::BreadthFirst => 0.0,
+                _ => start_heuristic,
+            },
+        );
+        nodes.insert(
+            start.polygon,
+            SearchNode {
+                position: start.point,
+                cost: 0.0,
+                priority: start_priority,
+                parent: None,
+            },
+        );
+        OpenList::push(
+            open,
+            OpenNode {
+                cost: start_priority,
+                node: start.polygon,
+            },
+        );
+        let (mut best, mut best_heuristic) = (start.polygon, start_heuristic);
+        while let Some(OpenNode {
+            cost: priority,
+            node: polygon,
+        }) = OpenList::pop(open)
+        {
+            let current = nodes[&polygon];
+            if priority > current.priority {
+                // A cheaper way to this polygon was found after it was queued.
+                continue;
+            }
+            if polygon == end.polygon {
+                best = polygon;
+                break;
+            }
+            counters.nodes_expanded += 1;
+
+            let area_cost = filter.area_cost(navmesh.areas[polygon]);
+            let vertices = navmesh.polygon_vertices(polygon);
+            for edge in 0..vertices.len() {
+                let Some(neighbor) = navmesh.internal_neighbor(polygon, edge) else {
+                    continue;
+                };
+                let a = navmesh.world_vertex(vertices[edge]);
+                let b = navmesh.world_vertex(vertices[next(edge, vertices.len())]);
+                let position = a.lerp(b, 0.5);
+                let step = current.position.distance(position) * area_cost;
+                successors.push((neighbor, position, step, None));
+            }
+            if let Some(links) = self.off_mesh_links {
+                let traversals = links.links_from_with_cost(polygon, |connection, traversal| {
+                    filter.passes_connection(connection).then(|| {
+                        traversal.start.distance(traversal.end) * filter.area_cost(connection.area)
+                    })
+                });
+                for (traversal, link_cost) in traversals {
+                    let step = current.position.distance(traversal.start) * area_cost + link_cost;
+                    successors.push((traversal.polygon, traversal.end, step, Some(traversal)));
+                }
+            }
+
+            for (neighbor, position, step, traversal) in successors.drain(..) {
+                if !filter.passes(navmesh, neighbor) {
+                    continue;
+                }
+                counters.polygons_touched += 1;
+                let mut cost = current.cost + step;
+                let remaining = if neighbor == end.polygon {
+                    cost +=
+                        position.distance(end.point) * filter.area_cost(navmesh.areas[neighbor]);
+                    0.0
+                } else {
+                    heuristic(position, end.point)
+                };
+                let farthest = if neighbor == end.polygon {
+                    end.point
+                } else {
+                    position
+                };
+                if cost > self.max_path_cost
+                    || farthest.distance_squared(start.point)
+                        > self.max_search_radius * self.max_search_radius
+                {
+                    continue;
+                }
+                let node = SearchNode {
+                    position,
+                    cost,
+                    priority: match self.strategy {
+                        SearchStrategy::AStar => cost + remaining,
+                        SearchStrategy::GreedyBestFirst => remaining,
+                        SearchStrategy::UniformCost => cost,
+                        SearchStrategy::BreadthFirst => current.priority + 1.0,
+                    },
+                    parent: Some((polygon, traversal)),
+                };
+                let is_full = nodes.len() >= self.max_nodes;
+                match nodes.entry(neighbor) {
+                    // Greedy searches never revisit polygons, as the estimate from another edge says nothing about the path.
+                    Entry::Occupied(_) if self.strategy == SearchStrategy::GreedyBestFirst => {
+                        continue;
+                    }
+                    Entry::Occupied(entry) if entry.get().priority <= node.priority => continue,
+                    Entry::Occupied(mut entry) => {
+                        entry.insert(node);
+                    }
+                    Entry::Vacant(_) if is_full => continue,
+                    Entry::Vacant(entry) => {
+                        entry.insert(node);
+                    }
+                }
+                if remaining < best_heuristic {
+                    best = neighbor;
+                    best_heuristic = remaining;
+                }
+                OpenList::push(
+                    open,
+                    OpenNode {
+                        cost: node.priority,
+                        node: neighbor,
+                    },
+                );
+            }
+        }
+        best
+    }
+
+    /// The point a path ending at the polygon `best` ends at:
+    /// `end` itself if the end polygon was reached, the closest point on `best` to it otherwise.
+    fn path_end(&self, best: usize, end: NearestPolygon) -> Vec3 {
+        if best == end.polygon {
+            end.point
+        } else {
+            self.navmesh
+                .closest_point_on_polygon(best, end.point, &self.tolerances)
+                .map_or(end.point, |(point, _)| point)
+        }
+    }
+
+    /// Turns the corridor of a [`NavmeshPath`] into the points an agent walks along, see [`PolygonNavmesh::straight_path`].
+    ///
+    /// Off-mesh traversals split the path: it walks to the start of each traversal and continues from its end.
+    pub fn find_straight_path(
+        &self,
+        path: &NavmeshPath,
+        crossing: PortalCrossing,
+    ) -> Vec<StraightPathPoint> {
+        let mut points = Vec::new();
+        let mut segment_start = 0;
+        let mut start = path.start;
+        let mut arrival = None;
+        for (index, traversal) in path.traversals.iter().enumerate() {
+            let Some(traversal) = traversal else {
+                continue;
+            };
+            let mut segment = self.navmesh.straight_path(
+                &path.polygons[segment_start..=index],
+                start,
+                traversal.start,
+                crossing,
+            );
+            let (departure, next_arrival) = self.off_mesh_kinds(traversal);
+            mark_off_mesh_segment(&mut segment, arrival, Some(departure));
+            points.append(&mut segment);
+            arrival = Some(next_arrival);
+            segment_start = index + 1;
+            start = traversal.end;
+        }
+        let mut segment =
+            self.navmesh
+                .straight_path(&path.polygons[segment_start..], start, path.end, crossing);
+        mark_off_mesh_segment(&mut segment, arrival, None);
+        points.append(&mut segment);
+        points
+    }
+
+    /// Returns the kinds of the straight path points at the start and the end of `traversal`.
+    pub(crate) fn off_mesh_kinds(
+        &self,
+        traversal: &OffMeshTraversal,
+    ) -> (StraightPathPointKind, StraightPathPointKind) {
+        let link = traversal.link;
+        let flags = self
+            .off_mesh_links
+            .and_then(|links| links.get(link))
+            .map_or(0, |link| link.connection.flags);
+        (
+            StraightPathPointKind::OffMeshStart { link, flags },
+            StraightPathPointKind::OffMeshEnd { link, flags },
+        )
+    }
+
+    /// Like [`Self::find_straight_path`], but takes a corridor written by [`Self::find_path_into`]
+    /// and writes the points into `points` instead of allocating, for hot per-frame use.
+    /// All memory needed in between is kept in `buffers` and reused by the next query.
+    ///
+    /// `start` and `end` are the points the corridor starts and ends at, e.g. [`NearestPolygon::point`] and [`BufferedPath::end`].
+    /// Where two consecutive polygons of `corridor` do not share an edge, the path takes the first attached off-mesh link
+    /// between them that passes `filter`. If there is none, the path ends at the last polygon that could be reached.
+    ///
+    /// If the path does not fit into `points`, its beginning is written and [`BufferedPath::truncated`] is set.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn find_straight_path_into(
+        &self,
+        corridor: &[usize],
+        start: Vec3,
+        end: Vec3,
+        filter: &QueryFilter,
+        crossing: PortalCrossing,
+        buffers: &mut NavmeshQueryBuffers,
+        points: &mut [StraightPathPoint],
+    ) -> BufferedPath {
+        let NavmeshQueryBuffers {
+            portals,
+            segment,
+            points: path,
+            ..
+        } = buffers;
+        path.clear();
+        let mut complete = true;
+        let mut segment_start = 0;
+        let mut segment_end = end;
+        let mut start = start;
+        let mut arrival = None;
+        for index in 0..corridor.len() {
+            let Some(&next_polygon) = corridor.get(index + 1) else {
+                break;
+            };
+            if self
+                .navmesh
+                .portal_points(corridor[index], next_polygon)
+                .is_some()
+            {
+                continue;
+            }
+            let traversal = self.off_mesh_links.and_then(|links| {
+                links.links_from(corridor[index]).find(|traversal| {
+                    traversal.polygon == next_polygon
+                        && links
+                            .get(traversal.link)
+                            .is_some_and(|link| filter.passes_connection(&link.connection))
+                })
+            });
+            let Some(traversal) = traversal else {
+                // The corridor is broken here, so the path ends at the closest point of the last reachable polygon.
+                self.navmesh.straight_path_reusing(
+                    &corridor[segment_start..],
+                    start,
+                    end,
+                    crossing,
+                    portals,
+                    segment,
+                );
+                mark_off_mesh_segment(segment, arrival, None);
+                path.extend_from_slice(segment);
+                complete = false;
+                segment_end = path.last().map_or(start, |point| point.position);
+                segment_start = corridor.len();
+                break;
+            };
+            self.navmesh.straight_path_reusing(
+                &corridor[segment_start..=index],
+                start,
+                traversal.start,
+                crossing,
+                portals,
+                segment,
+            );
+            let (departure, next_arrival) = self.off_mesh_kinds(&traversal);
+            mark_off_mesh_segment(segment, arrival, Some(departure));
+            path.extend_from_slice(segment);
+            arrival = Some(next_arrival);
+            segment_start = index + 1;
+            start = traversal.end;
+        }
+        if segment_start < corridor.len() {
+            self.navmesh.straight_path_reusing(
+                &corridor[segment_start..],
+                start,
+                end,
+                crossing,
+                portals,
+                segment,
+            );
+            mark_off_mesh_segment(segment, arrival, None);
+            path.extend_from_slice(segment);
+        }
+
+        let count = path.len().min(points.len());
+        points[..count].copy_from_slice(&path[..count]);
+        BufferedPath {
+            count,
+            truncated: path.len() > points.len(),
+            complete,
+            end: segment_end,
+        }
+    }
+}
+
+/// The result of the allocation-free queries [`NavmeshQuery::find_path_into`] and [`NavmeshQuery::find_straight_path_into`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BufferedPath {
+    /// The number of entries written to the beginning of the buffer.
+    pub count: usize,
+    /// Whether the path did not fit into the buffer, so only its beginning was written.
+    pub truncated: bool,
+    /// Whether the path reaches its end, see [`NavmeshPath::complete`].
+    pub complete: bool,
+    /// The point the path ends at, even if it was truncated, see [`NavmeshPath::end`].
+    pub end: Vec3,
+}
+
+/// The memory used by the allocation-free queries [`NavmeshQuery::find_path_into`] and [`NavmeshQuery::find_straight_path_into`],
+/// like Detour's node pool.
+///
+/// The buffers grow to the size of the largest query and keep their memory afterwards,
+/// so after the first few queries no more allocations happen. Keep one around per thread doing queries.
+#[derive(Debug, Clone, Default)]
+pub struct NavmeshQueryBuffers {
+    nodes: HashMap<usize, SearchNode>,
+    open: PathOpenList,
+    successors: Vec<(usize, Vec3, f32, Option<OffMeshTraversal>)>,
+    portals: Vec<(Vec3, Vec3)>,
+    segment: Vec<StraightPathPoint>,
+    points: Vec<StraightPathPoint>,
+}
+
+/// A polygon visited by [`NavmeshQuery::find_path`].
+#[derive(Debug, Clone, Copy)]
+struct SearchNode {
+    /// Where the search entered the polygon.
+    position: Vec3,
+    /// The cost from the start to [`Self::position`].
+    cost: f32,
+    /// The key of the node in the open list, e.g. [`Self::cost`] plus the estimated remaining cost to the end for A*.
+    /// See [`SearchStrategy`].
+    priority: f32,
+    /// The polygon the search came from and the off-mesh traversal it took, if any.
+    parent: Option<(usize, Option<OffMeshTraversal>)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    fn nearest(grid: &GridNavmesh, marker: char) -> NearestPolygon {
+        NearestPolygon {
+            polygon: grid.polygon(marker),
+            point: grid.position(marker),
+        }
+    }
+
+    #[test]
+    fn avoids_expensive_areas() {
+        let grid = GridNavmesh::parse(
+            "
+            a1b
+            ...
+            ",
+        );
+        let tree = BvTree::new(&grid.navmesh);
+        let query = NavmeshQuery::new(&grid.navmesh, &tree);
+        let (a, b) = (grid.polygon('a'), grid.polygon('b'));
+        let (start, end) = (nearest(&grid, 'a'), nearest(&grid, 'b'));
+
+        let mut filter = QueryFilter::default();
+        let path = query.find_path(start, end, &filter);
+        assert!(path.complete);
+        assert_eq!(path.polygons, vec![a, a + 1, b]);
+        assert_eq!(path.traversals, vec![None; 2]);
+
+        filter.set_area_cost(AreaType::new(1), 10.0);
+        let mut counters = QueryCounters::default();
+        let path = query.find_path_with_counters(start, end, &filter, &mut counters);
+        assert!(path.complete);
+        assert_eq!(path.polygons, vec![a, a + 3, a + 4, a + 5, b]);
+        assert!(counters.nodes_expanded > 0);
+
+        let points: Vec<_> = query
+            .find_straight_path(&path, PortalCrossing::Funnel)
+            .iter()
+            .map(|point| point.position)
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                grid.position('a'),
+                Vec3::new(1.0, 0.0, 1.0),
+                Vec3::new(2.0, 0.0, 1.0),
+                grid.position('b'),
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_at_cost_and_radius_limits() {
+        let grid = GridNavmesh::parse("a....b");
+        let tree = BvTree::new(&grid.navmesh);
+        let (start, end) = (nearest(&grid, 'a'), nearest(&grid, 'b'));
+        let filter = QueryFilter::default();
+        let mut query = NavmeshQuery::new(&grid.navmesh, &tree);
+        let path = query.find_path(start, end, &filter);
+        assert!(path.complete);
+        assert_eq!(path.cost, 5.0);
+
+        // Crossing the edges costs 0.5, 1.5, 2.5, 3.5, ... from the center of `a`.
+        query.max_path_cost = 2.6;
+        let path = query.find_path(start, end, &filter);
+        assert!(!path.complete);
+        assert_eq!(path.polygons.len(), 4);
+        assert_eq!(path.end, Vec3::new(4.0, 0.0, 0.5));
+
+        query.max_path_cost = f32::INFINITY;
+        query.max_search_radius = 2.0;
+        let path = query.find_path(start, end, &filter);
+        assert!(!path.complete);
+        assert_eq!(path.polygons.len(), 3);
+        assert_eq!(path.end, Vec3::new(3.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn explores_with_selected_strategy() {
+        let grid = GridNavmesh::parse(
+            "
+            a1b
+            ...
+            ",
+        );
+        let tree = BvTree::new(&grid.navmesh);
+        let (a, b) = (grid.polygon('a'), grid.polygon('b'));
+        let (start, end) = (nearest(&grid, 'a'), nearest(&grid, 'b'));
+        let mut filter = QueryFilter::default();
+        filter.set_area_cost(AreaType::new(1), 10.0);
+        let mut query = NavmeshQuery::new(&grid.navmesh, &tree);
+        let mut find_path = |strategy| {
+            query.strategy = strategy;
+            let mut counters = QueryCounters::default();
+            let path = query.find_path_with_counters(start, end, &filter, &mut counters);
+            assert!(path.complete);
+            (path.polygons, counters.nodes_expanded)
+        };
+
+        let (cheapest, a_star_expanded) = find_path(SearchStrategy::AStar);
+        assert_eq!(cheapest, vec![a, a + 3, a + 4, a + 5, b]);
+        let (path, uniform_expanded) = find_path(SearchStrategy::UniformCost);
+        assert_eq!(path, cheapest);
+        assert!(uniform_expanded >= a_star_expanded);
+
+        // Both head straight through the expensive area.
+        let (path, greedy_expanded) = find_path(SearchStrategy::GreedyBestFirst);
+        assert_eq!(path, vec![a, a + 1, b]);
+        assert_eq!(greedy_expanded, 2);
+        let (path, _) = find_path(SearchStrategy::BreadthFirst);
+        assert_eq!(path, vec![a, a + 1, b]);
+    }
+
+    #[test]
+    fn takes_off_mesh_links_passing_filter() {
+        let grid = GridNavmesh::parse("a#b");
+        let tree = BvTree::new(&grid.navmesh);
+        let (start, end) = (nearest(&grid, 'a'), nearest(&grid, 'b'));
+        let filter = QueryFilter::default();
+
+        let path = NavmeshQuery::new(&grid.navmesh, &tree).find_path(start, end, &filter);
+        assert!(!path.complete);
+        assert_eq!(path.polygons, vec![start.polygon]);
+        assert_eq!(path.end, Vec3::new(1.0, 0.0, 0.5));
+
+        const JUMP: u16 = 1;
+        let mut links = OffMeshLinks::new(0.5);
+        links.insert(
+            &grid.navmesh,
+            &tree,
+            OffMeshConnection {
+                flags: JUMP,
+                ..OffMeshConnection::new(start.point, end.point)
+            },
+        );
+        let query = NavmeshQuery::new(&grid.navmesh, &tree).with_off_mesh_links(&links);
+        let path = query.find_path(start, end, &filter);
+        assert!(path.complete);
+        assert_eq!(path.polygons, vec![start.polygon, end.polygon]);
+        let link = path.traversals[0].unwrap().link;
+        let points: Vec<_> = query
+            .find_straight_path(&path, PortalCrossing::Funnel)
+            .iter()
+            .map(|point| (point.position, point.kind))
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                (
+                    start.point,
+                    StraightPathPointKind::OffMeshStart { link, flags: JUMP }
+                ),
+                (
+                    end.point,
+                    StraightPathPointKind::OffMeshEnd { link, flags: JUMP }
+                )
+            ]
+        );
+
+        let no_jumping = QueryFilter {
+            exclude_flags: JUMP,
+            ..QueryFilter::default()
+        };
+        assert!(!query.find_path(start, end, &no_jumping).complete);
+    }
+
+    #[test]
+    fn filters_nearest_polygon_and_raycast() {
+        let mut grid = GridNavmesh::parse("abc");
+        let b = grid.polygon('b');
+        grid.navmesh.flags[b] = 1;
+        let tree = BvTree::new(&grid.navmesh);
+        let query = NavmeshQuery::new(&grid.navmesh, &tree);
+        let filter = QueryFilter {
+            exclude_flags: 1,
+            ..QueryFilter::default()
+        };
+
+        let nearest = query.find_nearest_poly(grid.position('b'), Vec3::ONE, &filter);
+        assert!(nearest.is_some_and(|nearest| nearest.polygon != b));
+
+        let (a, start, end) = (grid.polygon('a'), grid.position('a'), grid.position('c'));
+        assert!(
+            query
+                .raycast(a, start, end, &QueryFilter::default())
+                .hit
+                .is_none()
+        );
+        let blocked = query.raycast(a, start, end, &filter);
+        assert_eq!(blocked.hit.map(|hit| hit.polygon), Some(a));
+    }
+
+    #[test]
+    fn writes_paths_into_buffers() {
+        let grid = GridNavmesh::parse(
+            "
+            a1b#c
+            ...##
+            ",
+        );
+        let tree = BvTree::new(&grid.navmesh);
+        let mut filter = QueryFilter::default();
+        filter.set_area_cost(AreaType::new(1), 10.0);
+        let (start, end) = (nearest(&grid, 'a'), nearest(&grid, 'c'));
+        let mut links = OffMeshLinks::new(0.5);
+        links.insert(
+            &grid.navmesh,
+            &tree,
+            OffMeshConnection::new(grid.position('b'), end.point),
+        );
+        let query = NavmeshQuery::new(&grid.navmesh, &tree).with_off_mesh_links(&links);
+        let path = query.find_path(start, end, &filter);
+        let straight = query.find_straight_path(&path, PortalCrossing::Funnel);
+        assert!(path.complete);
+
+        let mut buffers = NavmeshQueryBuffers::default();
+        let mut polygons = [0; 16];
+        let corridor = query.find_path_into(start, end, &filter, &mut buffers, &mut polygons);
+        assert_eq!(
+            corridor,
+            BufferedPath {
+                count: path.polygons.len(),
+                truncated: false,
+                complete: true,
+                end: path.end,
+            }
+        );
+        assert_eq!(polygons[..corridor.count], path.polygons[..]);
+
+        let mut points = [StraightPathPoint::default(); 16];
+        let result = query.find_straight_path_into(
+            &polygons[..corridor.count],
+            start.point,
+            corridor.end,
+            &filter,
+            PortalCrossing::Funnel,
+            &mut buffers,
+            &mut points,
+        );
+        assert!(result.complete && !result.truncated);
+        assert_eq!(points[..result.count], straight[..]);
+
+        // Buffers that are too small keep the beginning of the path.
+        let mut short_polygons = [0; 2];
+        let truncated =
+            query.find_path_into(start, end, &filter, &mut buffers, &mut short_polygons);
+        assert_eq!(truncated.count, 2);
+        assert!(truncated.truncated && truncated.complete);
+        assert_eq!(short_polygons[..], path.polygons[..2]);
+        let mut short_points = [StraightPathPoint::default(); 2];
+        let truncated = query.find_straight_path_into(
+            &polygons[..corridor.count],
+            start.point,
+            corridor.end,
+            &filter,
+            PortalCrossing::Funnel,
+            &mut buffers,
+            &mut short_points,
+        );
+        assert!(truncated.truncated);
+        assert_eq!(short_points[..], straight[..2]);
+    }
+}
@@ -0,0 +1,201 @@
+use glam::{Vec2, Vec3, Vec3Swizzles as _};
+
+use crate::{PolygonNavmesh, math::point_in_poly};
+
+/// A named convex volume that groups the polygons inside it into a zone of [`NavmeshZones`],
+/// e.g. a market district or a boss arena.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZoneVolume {
+    /// The name of the zone. Volumes with the same name make up a single zone.
+    pub name: String,
+    /// The vertices of the volume on the xz-plane. `[Units: wu]`
+    pub vertices: Vec<Vec2>,
+    /// The lower y-coordinate of the volume. `[Units: wu]`
+    pub min_y: f32,
+    /// The upper y-coordinate of the volume. `[Units: wu]`
+    pub max_y: f32,
+}
+
+/// The polygons of a navmesh grouped into named zones by [`ZoneVolume`]s, for gameplay logic driven by the navmesh,
+/// e.g. noticing that the player entered the market district by looking up the zone of the polygon they stand on.
+///
+/// Zones are identified by their index into [`Self::names`]. Every polygon belongs to at most one zone.
+/// After the navmesh changed, build the zones again.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct NavmeshZones {
+    names: Vec<String>,
+    /// The zone of every polygon. `[Size: polygon_count]`
+    polygon_zones: Vec<Option<usize>>,
+    /// The zones sharing an edge with every zone, sorted. `[Size: names.len()]`
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl NavmeshZones {
+    /// Groups the polygons of `navmesh` whose centroid lies inside a volume into the zone named by the volume.
+    ///
+    /// Where volumes overlap, later volumes win, so volumes should be given in order of increasing priority.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn build(navmesh: &PolygonNavmesh, volumes: &[ZoneVolume]) -> Self {
+        let mut names: Vec<String> = Vec::new();
+        let mut volume_zones = Vec::with_capacity(volumes.len());
+        for volume in volumes {
+            let zone = match names.iter().position(|name| *name == volume.name) {
+                Some(zone) => zone,
+                None => {
+                    names.push(volume.name.clone());
+                    names.len() - 1
+                }
+            };
+            volume_zones.push(zone);
+        }
+
+        let polygon_zones: Vec<Option<usize>> = (0..navmesh.polygon_count())
+            .map(|polygon| {
+                let vertex_count = navmesh.polygon_vertices(polygon).len();
+                if vertex_count == 0 {
+                    return None;
+                }
+                let centroid =
+                    navmesh.polygon_world_vertices(polygon).sum::<Vec3>() / vertex_count as f32;
+                volumes
+                    .iter()
+                    .zip(&volume_zones)
+                    .rev()
+                    .find(|(volume, _)| {
+                        volume.vertices.len() >= 3
+                            && (volume.min_y..=volume.max_y).contains(&centroid.y)
+                            && point_in_poly(&centroid.xz(), &volume.vertices)
+                    })
+                    .map(|(_, zone)| *zone)
+            })
+            .collect();
+
+        let mut adjacency = vec![Vec::new(); names.len()];
+        for (polygon, zone) in polygon_zones.iter().enumerate() {
+            let Some(zone) = *zone else {
+                continue;
+            };
+            for edge in 0..navmesh.polygon_vertices(polygon).len() {
+                let Some(neighbor_zone) = navmesh
+                    .internal_neighbor(polygon, edge)
+                    .and_then(|neighbor| polygon_zones[neighbor])
+                else {
+                    continue;
+                };
+                if neighbor_zone != zone {
+                    adjacency[zone].push(neighbor_zone);
+                }
+            }
+        }
+        for zones in &mut adjacency {
+            zones.sort_unstable();
+            zones.dedup();
+        }
+
+        Self {
+            names,
+            polygon_zones,
+            adjacency,
+        }
+    }
+
+    /// Returns the names of all zones, indexed by zone.
+    #[inline]
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Returns the name of `zone`, or `None` if there is no such zone.
+    #[inline]
+    pub fn name(&self, zone: usize) -> Option<&str> {
+        self.names.get(zone).map(String::as_str)
+    }
+
+    /// Returns the zone with the given name, or `None` if no volume had this name or its volumes contained no polygon.
+    pub fn zone_by_name(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|zone_name| zone_name == name)
+    }
+
+    /// Returns the zone the polygon at index `polygon` belongs to, or `None` if it lies outside all zones.
+    #[inline]
+    pub fn zone_of(&self, polygon: usize) -> Option<usize> {
+        self.polygon_zones.get(polygon).copied().flatten()
+    }
+
+    /// Iterates over the indices of all polygons in `zone`.
+    pub fn polygons_in(&self, zone: usize) -> impl Iterator<Item = usize> + '_ {
+        self.polygon_zones
+            .iter()
+            .enumerate()
+            .filter(move |(_, polygon_zone)| **polygon_zone == Some(zone))
+            .map(|(polygon, _)| polygon)
+    }
+
+    /// Returns the zones that share at least one polygon edge with `zone`, sorted.
+    /// Zones only connected through polygons outside any zone or through off-mesh connections are not adjacent.
+    pub fn adjacent_zones(&self, zone: usize) -> &[usize] {
+        self.adjacency.get(zone).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns whether the zones `a` and `b` share at least one polygon edge.
+    pub fn are_adjacent(&self, a: usize, b: usize) -> bool {
+        self.adjacent_zones(a).binary_search(&b).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    fn volume(name: &str, min_x: f32, max_x: f32) -> ZoneVolume {
+        ZoneVolume {
+            name: name.to_string(),
+            vertices: vec![
+                Vec2::new(min_x, -1.0),
+                Vec2::new(min_x, 2.0),
+                Vec2::new(max_x, 2.0),
+                Vec2::new(max_x, -1.0),
+            ],
+            min_y: -1.0,
+            max_y: 1.0,
+        }
+    }
+
+    #[test]
+    fn groups_polygons_into_zones() {
+        let grid = GridNavmesh::parse("ab.cd#e");
+        let zones = NavmeshZones::build(
+            &grid.navmesh,
+            &[
+                volume("market", 0.0, 2.0),
+                volume("docks", 3.0, 5.0),
+                // Overrides the market for `b`.
+                volume("plaza", 1.0, 2.0),
+                volume("market", 6.0, 7.0),
+            ],
+        );
+        let market = zones.zone_by_name("market").unwrap();
+        let docks = zones.zone_by_name("docks").unwrap();
+        let plaza = zones.zone_by_name("plaza").unwrap();
+        assert_eq!(zones.name(plaza), Some("plaza"));
+
+        assert_eq!(zones.zone_of(grid.polygon('a')), Some(market));
+        assert_eq!(zones.zone_of(grid.polygon('b')), Some(plaza));
+        assert_eq!(zones.zone_of(grid.polygon('d')), Some(docks));
+        assert_eq!(zones.zone_of(grid.polygon('e')), Some(market));
+        let mut market_polygons: Vec<_> = zones.polygons_in(market).collect();
+        market_polygons.sort_unstable();
+        let mut expected = vec![grid.polygon('a'), grid.polygon('e')];
+        expected.sort_unstable();
+        assert_eq!(market_polygons, expected);
+
+        // The unzoned floor between `b` and `c` separates the plaza from the docks.
+        assert_eq!(zones.adjacent_zones(market), &[plaza]);
+        assert!(zones.are_adjacent(plaza, market));
+        assert!(!zones.are_adjacent(plaza, docks));
+        assert!(zones.adjacent_zones(docks).is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+use glam::{Vec3, Vec3Swizzles as _};
+
+use crate::{Aabb3d, BvTree, PolygonNavmesh, QueryCounters, QueryTolerances, math::next};
+
+/// Constrains which vertical layer [`PolygonNavmesh::find_nearest_polygon`] may return a polygon from.
+///
+/// Generous search extents are often needed to find the navmesh at all, but without a constraint they also find polygons
+/// on the floors above and below, snapping agents through floors and ceilings.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LayerConstraint {
+    /// Any polygon within the search extents may be returned.
+    #[default]
+    Any,
+    /// Only polygons whose surface at the nearest point lies between `min` and `max` may be returned. `[Units: wu]`
+    Band {
+        /// The lowest allowed surface height.
+        min: f32,
+        /// The highest allowed surface height.
+        max: f32,
+    },
+    /// Only polygons on the same floor as the query point may be returned,
+    /// i.e. whose surface at the nearest point lies within `max_climb` of the query point, e.g. an agent's feet.
+    /// The floors above and below are ignored even if they are horizontally closer.
+    SameFloor {
+        /// The maximum height difference between the query point and the surface. `[Limit: >= 0] [Units: wu]`
+        max_climb: f32,
+    },
+}
+
+impl LayerConstraint {
+    /// Returns whether a surface at `height` is allowed for a query at `center`.
+    #[inline]
+    fn allows(&self, center: Vec3, height: f32) -> bool {
+        match *self {
+            LayerConstraint::Any => true,
+            LayerConstraint::Band { min, max } => (min..=max).contains(&height),
+            LayerConstraint::SameFloor { max_climb } => (height - center.y).abs() <= max_climb,
+        }
+    }
+}
+
+/// The result of [`PolygonNavmesh::find_nearest_polygon`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct NearestPolygon {
+    /// The index of the nearest polygon.
+    pub polygon: usize,
+    /// The point on [`Self::polygon`] closest to the query point.
+    pub point: Vec3,
+}
+
+impl PolygonNavmesh {
+    /// Finds the polygon nearest to `center` among the polygons overlapping the box of `half_extents` around it,
+    /// using `tree` to find candidate polygons.
+    ///
+    /// If `center` lies within a polygon on the xz-plane, the distance to that polygon is the vertical distance to its surface.
+    /// Otherwise, it is the distance to the closest point on the polygon's edges.
+    /// Polygons whose nearest point is rejected by `layer` are skipped.
+    ///
+    /// Returns `None` if no polygon satisfies the constraints.
+    pub fn find_nearest_polygon(
+        &self,
+        tree: &BvTree,
+        center: Vec3,
+        half_extents: Vec3,
+        layer: LayerConstraint,
+    ) -> Option<NearestPolygon> {
+        self.find_nearest_polygon_with_counters(
+            tree,
+            center,
+            half_extents,
+            layer,
+            &mut QueryCounters::default(),
+        )
+    }
+
+    /// Same as [`PolygonNavmesh::find_nearest_polygon`], but accumulates the work done into `counters`.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn find_nearest_polygon_with_counters(
+        &self,
+        tree: &BvTree,
+        center: Vec3,
+        half_extents: Vec3,
+        layer: LayerConstraint,
+        counters: &mut QueryCounters,
+    ) -> Option<NearestPolygon> {
+        self.find_nearest_polygon_where(
+            tree,
+            center,
+            half_extents,
+            layer,
+            counters,
+            &QueryTolerances::default(),
+            |_| true,
+        )
+    }
+
+    /// Same as [`PolygonNavmesh::find_nearest_polygon_with_counters`], but measures distances with `tolerances`
+    /// and skips polygons for which `accept` returns `false`.
+    pub(crate) fn find_nearest_polygon_where(
+        &self,
+        tree: &BvTree,
+        center: Vec3,
+        half_extents: Vec3,
+        layer: LayerConstraint,
+        counters: &mut QueryCounters,
+        tolerances: &QueryTolerances,
+        accept: impl Fn(usize) -> bool,
+    ) -> Option<NearestPolygon> {
+        let search_aabb = Aabb3d {
+            min: center - half_extents,
+            max: center + half_extents,
+        };
+        let mut nearest: Option<NearestPolygon> = None;
+        let mut nearest_distance_squared = f32::MAX;
+        let mut query = tree.query_aabb(&search_aabb);
+        for polygon in query.by_ref() {
+            if !accept(polygon) {
+                continue;
+            }
+            counters.polygons_touched += 1;
+            let Some((point, distance_squared)) =
+                self.closest_point_on_polygon(polygon, center, tolerances)
+            else {
+                continue;
+            };
+            if !layer.allows(center, point.y) {
+                continue;
+            }
+            if distance_squared < nearest_distance_squared {
+                nearest_distance_squared = distance_squared;
+                nearest = Some(NearestPolygon { polygon, point });
+            }
+        }
+        counters.nodes_expanded += query.nodes_visited();
+        nearest
+    }
+
+    /// Returns the point on the polygon at index `polygon` closest to `point` and the squared distance to it.
+    ///
+    /// Points within [`QueryTolerances::point_in_polygon`] of the polygon on the xz-plane count as lying over it,
+    /// and vertical distances within [`QueryTolerances::height`] count as zero.
+    ///
+    /// Returns `None` if the polygon has no edges.
+    pub(crate) fn closest_point_on_polygon(
+        &self,
+        polygon: usize,
+        point: Vec3,
+        tolerances: &QueryTolerances,
+    ) -> Option<(Vec3, f32)> {
+        let vertical_distance_squared = |height: f32| {
+            let distance = ((height - point.y).abs() - tolerances.height).max(0.0);
+            distance * distance
+        };
+        if let Some(height) = self.polygon_height(polygon, point) {
+            return Some((
+                Vec3::new(point.x, height, point.z),
+                vertical_distance_squared(height),
+            ));
+        }
+
+        let vertices = self.polygon_vertices(polygon);
+        let mut closest: Option<(Vec3, f32)> = None;
+        for edge in 0..vertices.len() {
+            let a = self.world_vertex(vertices[edge]);
+            let b = self.world_vertex(vertices[next(edge, vertices.len())]);
+            let direction = (b - a).xz();
+            let length_squared = direction.length_squared();
+            let t = if length_squared > 0.0 {
+                ((point - a).xz().dot(direction) / length_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let candidate = a.lerp(b, t);
+            let distance_squared = candidate.distance_squared(point);
+            if closest.is_none_or(|(_, closest_distance_squared)| {
+                distance_squared < closest_distance_squared
+            }) {
+                closest = Some((candidate, distance_squared));
+            }
+        }
+        let (candidate, distance_squared) = closest?;
+        let tolerance = tolerances.point_in_polygon;
+        if (candidate - point).xz().length_squared() <= tolerance * tolerance {
+            return Some((candidate, vertical_distance_squared(candidate.y)));
+        }
+        Some((candidate, distance_squared))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::U16Vec3;
+
+    use super::*;
+    use crate::test_utils::quad_navmesh;
+
+    /// An 8x4 floor with a 4x4 mezzanine 2 units above its left half.
+    fn mezzanine() -> PolygonNavmesh {
+        quad_navmesh(
+            vec![
+                U16Vec3::new(0, 0, 0),
+                U16Vec3::new(0, 0, 4),
+                U16Vec3::new(8, 0, 4),
+                U16Vec3::new(8, 0, 0),
+                U16Vec3::new(0, 2, 0),
+                U16Vec3::new(0, 2, 4),
+                U16Vec3::new(4, 2, 4),
+                U16Vec3::new(4, 2, 0),
+            ],
+            &[[0, 1, 2, 3], [4, 5, 6, 7]],
+        )
+    }
+
+    #[test]
+    fn constrains_nearest_polygon_to_layer() {
+        let mesh = mezzanine();
+        let tree = BvTree::new(&mesh);
+        let half_extents = Vec3::new(2.0, 4.0, 2.0);
+        let nearest = |center, layer| {
+            mesh.find_nearest_polygon(&tree, center, half_extents, layer)
+                .map(|nearest| (nearest.polygon, nearest.point))
+        };
+
+        // Without a constraint, the mezzanine above is closer
+        let center = Vec3::new(2.0, 1.2, 2.0);
+        assert_eq!(
+            nearest(center, LayerConstraint::Any),
+            Some((1, Vec3::new(2.0, 2.0, 2.0)))
+        );
+        assert_eq!(
+            nearest(
+                center,
+                LayerConstraint::Band {
+                    min: -0.5,
+                    max: 0.5
+                }
+            ),
+            Some((0, Vec3::new(2.0, 0.0, 2.0)))
+        );
+        assert_eq!(
+            nearest(center, LayerConstraint::SameFloor { max_climb: 0.5 }),
+            None
+        );
+
+        // Next to the mezzanine, its edge is closer than the floor below
+        let center = Vec3::new(5.0, 2.0, 2.0);
+        assert_eq!(
+            nearest(center, LayerConstraint::Any),
+            Some((1, Vec3::new(4.0, 2.0, 2.0)))
+        );
+        assert_eq!(
+            nearest(
+                Vec3::new(5.0, 0.2, 2.0),
+                LayerConstraint::SameFloor { max_climb: 0.5 }
+            ),
+            Some((0, Vec3::new(5.0, 0.0, 2.0)))
+        );
+    }
+}
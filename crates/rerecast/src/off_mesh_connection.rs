@@ -0,0 +1,81 @@
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::prelude::*;
+use glam::{Vec3, Vec3Swizzles as _};
+
+use crate::AreaType;
+
+/// A connection between two points on the navmesh that agents can traverse without walking over polygons,
+/// e.g. a jump, a ladder, or the gap between a moving platform and the ground.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct OffMeshConnection {
+    /// The world space position where the connection starts.
+    pub start: Vec3,
+    /// The world space position where the connection ends.
+    pub end: Vec3,
+    /// The radius around [`Self::start`] and [`Self::end`] within which the endpoints are attached to the navmesh. `[Limit: >= 0] [Units: wu]`
+    pub radius: f32,
+    /// Whether the connection can also be traversed from [`Self::end`] to [`Self::start`].
+    pub bidirectional: bool,
+    /// The area type of the connection.
+    pub area: AreaType,
+    /// User defined flags of the connection.
+    pub flags: u16,
+    /// A user defined id of the connection, e.g. to tell jumps and ladders apart
+    /// when deciding who may traverse it, see [`OffMeshLinks::links_from_with_cost`](crate::OffMeshLinks::links_from_with_cost).
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub user_id: u32,
+}
+
+impl OffMeshConnection {
+    /// Creates a bidirectional connection between `start` and `end` with the default walkable area, no flags, no user id and no radius.
+    #[inline]
+    pub fn new(start: Vec3, end: Vec3) -> Self {
+        Self {
+            start,
+            end,
+            radius: 0.0,
+            bidirectional: true,
+            area: AreaType::DEFAULT_WALKABLE,
+            flags: 0,
+            user_id: 0,
+        }
+    }
+
+    /// Returns the height of the connection at the xz-coordinates of `point`, like Detour's `getPolyHeight` for off-mesh connections.
+    ///
+    /// `point` is projected onto the connection on the xz-plane and the height is interpolated between [`Self::start`] and [`Self::end`],
+    /// so animation systems can place an agent on the straight line between the two endpoints. Points beyond the endpoints are clamped to them.
+    pub fn height_at(&self, point: Vec3) -> f32 {
+        let along = (self.end - self.start).xz();
+        let length_squared = along.length_squared();
+        let t = if length_squared <= f32::EPSILON {
+            0.0
+        } else {
+            ((point - self.start).xz().dot(along) / length_squared).clamp(0.0, 1.0)
+        };
+        self.start.y + (self.end.y - self.start.y) * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_height_along_connection() {
+        let connection = OffMeshConnection::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(4.0, 2.0, 0.0));
+        assert_eq!(connection.height_at(Vec3::new(1.0, 5.0, 3.0)), 0.5);
+        assert_eq!(connection.height_at(Vec3::new(-1.0, 0.0, 0.0)), 0.0);
+        assert_eq!(connection.height_at(Vec3::new(6.0, 0.0, 0.0)), 2.0);
+
+        // Vertical connections such as ladders have no extent on the xz-plane
+        let ladder = OffMeshConnection::new(Vec3::ZERO, Vec3::Y * 3.0);
+        assert_eq!(ladder.height_at(Vec3::ZERO), 0.0);
+    }
+}
@@ -0,0 +1,267 @@
+use glam::Vec3;
+use slotmap::SlotMap;
+
+use crate::{BvTree, LayerConstraint, NearestPolygon, OffMeshConnection, PolygonNavmesh};
+
+slotmap::new_key_type! {
+    /// A key for a link in [`OffMeshLinks`].
+    pub struct OffMeshLinkId;
+}
+
+/// An [`OffMeshConnection`] together with the polygons its endpoints are attached to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OffMeshLink {
+    /// The connection this link was created from.
+    pub connection: OffMeshConnection,
+    /// The polygon and point on it that [`OffMeshConnection::start`] is attached to,
+    /// or `None` if no polygon lies within reach of it.
+    pub start: Option<NearestPolygon>,
+    /// The polygon and point on it that [`OffMeshConnection::end`] is attached to,
+    /// or `None` if no polygon lies within reach of it.
+    pub end: Option<NearestPolygon>,
+}
+
+impl OffMeshLink {
+    /// Returns whether both endpoints are attached to the navmesh, i.e. whether the link can be traversed.
+    #[inline]
+    pub fn is_attached(&self) -> bool {
+        self.start.is_some() && self.end.is_some()
+    }
+}
+
+/// A traversal of an [`OffMeshLink`] leaving a polygon, returned by [`OffMeshLinks::links_from`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OffMeshTraversal {
+    /// The link being traversed.
+    pub link: OffMeshLinkId,
+    /// The point on the polygon the traversal leaves from.
+    pub start: Vec3,
+    /// The point on [`Self::polygon`] the traversal arrives at.
+    pub end: Vec3,
+    /// The polygon the traversal arrives at.
+    pub polygon: usize,
+}
+
+/// The off-mesh connections attached to a navmesh, which can be added and removed at runtime,
+/// e.g. for a ladder that is deployed during gameplay.
+///
+/// The endpoints of a connection are attached to the nearest polygon within [`OffMeshConnection::radius`]
+/// horizontally and [`Self::max_climb`] vertically, like Detour does when a tile is added.
+/// After the navmesh or its [`BvTree`] changed, call [`Self::relink`] to attach all connections again.
+#[derive(Debug, Clone, Default)]
+pub struct OffMeshLinks {
+    links: SlotMap<OffMeshLinkId, OffMeshLink>,
+    /// How far above or below an endpoint the navmesh surface may lie for the endpoint to be attached to it. `[Limit: >= 0] [Units: wu]`
+    pub max_climb: f32,
+}
+
+impl OffMeshLinks {
+    /// Creates an empty set of links, attaching endpoints within `max_climb` of the navmesh surface. `[Units: wu]`
+    pub fn new(max_climb: f32) -> Self {
+        Self {
+            links: SlotMap::with_key(),
+            max_climb,
+        }
+    }
+
+    /// Attaches `connection` to the navmesh and returns the key of the new link.
+    ///
+    /// The link is kept even if an endpoint could not be attached, see [`OffMeshLink::is_attached`],
+    /// so that it starts working once the navmesh below it exists and [`Self::relink`] was called.
+    pub fn insert(
+        &mut self,
+        navmesh: &PolygonNavmesh,
+        tree: &BvTree,
+        connection: OffMeshConnection,
+    ) -> OffMeshLinkId {
+        let link = Self::attach(navmesh, tree, connection, self.max_climb);
+        self.links.insert(link)
+    }
+
+    /// Removes the link with the key `id` and returns its connection,
+    /// or `None` if the link does not exist anymore.
+    pub fn remove(&mut self, id: OffMeshLinkId) -> Option<OffMeshConnection> {
+        self.links.remove(id).map(|link| link.connection)
+    }
+
+    /// Returns the link with the key `id`, or `None` if it does not exist.
+    #[inline]
+    pub fn get(&self, id: OffMeshLinkId) -> Option<&OffMeshLink> {
+        self.links.get(id)
+    }
+
+    /// Iterates over all links and their keys.
+    pub fn iter(&self) -> impl Iterator<Item = (OffMeshLinkId, &OffMeshLink)> {
+        self.links.iter()
+    }
+
+    /// Returns the number of links.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Returns whether there are no links.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Attaches the endpoints of all links again, e.g. after the navmesh was rebuilt or polygons were made unwalkable.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn relink(&mut self, navmesh: &PolygonNavmesh, tree: &BvTree) {
+        for link in self.links.values_mut() {
+            *link = Self::attach(navmesh, tree, link.connection, self.max_climb);
+        }
+    }
+
+    /// Iterates over the traversals of all attached links leaving the polygon at index `polygon`,
+    /// including bidirectional links arriving at it.
+    pub fn links_from(&self, polygon: usize) -> impl Iterator<Item = OffMeshTraversal> + '_ {
+        self.links.iter().filter_map(move |(id, link)| {
+            let (start, end) = (link.start?, link.end?);
+            if start.polygon == polygon {
+                Some(OffMeshTraversal {
+                    link: id,
+                    start: start.point,
+                    end: end.point,
+                    polygon: end.polygon,
+                })
+            } else if link.connection.bidirectional && end.polygon == polygon {
+                Some(OffMeshTraversal {
+                    link: id,
+                    start: end.point,
+                    end: start.point,
+                    polygon: start.polygon,
+                })
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Like [`Self::links_from`], but calls `cost` with the connection of every traversal to decide
+    /// whether it may be taken and what it costs, e.g. to only let units that can jump take jump links.
+    ///
+    /// `cost` returns `None` for traversals that may not be taken. It is evaluated on every call,
+    /// so different queries can pass different callbacks over the same links.
+    /// [`Self::default_cost`] gives the cost of walking the straight line between the endpoints.
+    pub fn links_from_with_cost<'a>(
+        &'a self,
+        polygon: usize,
+        cost: impl Fn(&OffMeshConnection, &OffMeshTraversal) -> Option<f32> + 'a,
+    ) -> impl Iterator<Item = (OffMeshTraversal, f32)> + 'a {
+        self.links_from(polygon).filter_map(move |traversal| {
+            let connection = &self.links[traversal.link].connection;
+            cost(connection, &traversal).map(|cost| (traversal, cost))
+        })
+    }
+
+    /// The cost of a traversal when no callback is given, i.e. the distance between its endpoints.
+    #[inline]
+    pub fn default_cost(
+        _connection: &OffMeshConnection,
+        traversal: &OffMeshTraversal,
+    ) -> Option<f32> {
+        Some(traversal.start.distance(traversal.end))
+    }
+
+    fn attach(
+        navmesh: &PolygonNavmesh,
+        tree: &BvTree,
+        connection: OffMeshConnection,
+        max_climb: f32,
+    ) -> OffMeshLink {
+        let half_extents = Vec3::new(connection.radius, max_climb, connection.radius);
+        let attach =
+            |point| navmesh.find_nearest_polygon(tree, point, half_extents, LayerConstraint::Any);
+        OffMeshLink {
+            connection,
+            start: attach(connection.start),
+            end: attach(connection.end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AreaType, test_utils::GridNavmesh};
+
+    #[test]
+    fn adds_and_removes_links_at_runtime() {
+        let mut grid = GridNavmesh::parse("a#b");
+        let a = grid.polygon('a');
+        let b = grid.polygon('b');
+        let mut tree = BvTree::new(&grid.navmesh);
+        let mut links = OffMeshLinks::new(0.5);
+
+        let ladder = links.insert(
+            &grid.navmesh,
+            &tree,
+            OffMeshConnection {
+                bidirectional: false,
+                ..OffMeshConnection::new(grid.position('a'), grid.position('b'))
+            },
+        );
+        assert!(links.get(ladder).unwrap().is_attached());
+        let traversals: Vec<_> = links.links_from(a).collect();
+        assert_eq!(traversals.len(), 1);
+        assert_eq!(traversals[0].polygon, b);
+        assert_eq!(links.links_from(b).count(), 0);
+
+        // Endpoints that miss the navmesh are kept, but not traversed.
+        let dangling = links.insert(
+            &grid.navmesh,
+            &tree,
+            OffMeshConnection::new(grid.position('a'), Vec3::new(10.0, 0.0, 10.0)),
+        );
+        assert!(!links.get(dangling).unwrap().is_attached());
+        assert_eq!(links.links_from(a).count(), 1);
+
+        grid.navmesh.areas[b] = AreaType::NOT_WALKABLE;
+        tree.rebuild(&grid.navmesh);
+        links.relink(&grid.navmesh, &tree);
+        assert_eq!(links.links_from(a).count(), 0);
+
+        assert!(links.remove(ladder).is_some());
+        assert!(links.remove(ladder).is_none());
+        assert_eq!(links.len(), 1);
+    }
+
+    #[test]
+    fn filters_links_per_query() {
+        const JUMP: u32 = 1;
+        let grid = GridNavmesh::parse("a#b#c");
+        let a = grid.polygon('a');
+        let tree = BvTree::new(&grid.navmesh);
+        let mut links = OffMeshLinks::new(0.5);
+        links.insert(
+            &grid.navmesh,
+            &tree,
+            OffMeshConnection {
+                user_id: JUMP,
+                ..OffMeshConnection::new(grid.position('a'), grid.position('b'))
+            },
+        );
+        links.insert(
+            &grid.navmesh,
+            &tree,
+            OffMeshConnection::new(grid.position('a'), grid.position('c')),
+        );
+
+        let everyone: Vec<_> = links
+            .links_from_with_cost(a, OffMeshLinks::default_cost)
+            .collect();
+        assert_eq!(everyone.len(), 2);
+
+        let cannot_jump: Vec<_> = links
+            .links_from_with_cost(a, |connection, traversal| {
+                (connection.user_id != JUMP).then(|| 2.0 * traversal.start.distance(traversal.end))
+            })
+            .collect();
+        assert_eq!(cannot_jump.len(), 1);
+        assert_eq!(cannot_jump[0].0.polygon, grid.polygon('c'));
+        assert_eq!(cannot_jump[0].1, 8.0);
+    }
+}
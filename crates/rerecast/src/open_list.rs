@@ -0,0 +1,225 @@
+use std::collections::BinaryHeap;
+
+use crate::polygon_graph::OpenNode;
+
+/// The open list of [`NavmeshQuery::find_path`](crate::NavmeshQuery::find_path),
+/// a `BucketQueue` with the `bucket_queue` feature and a [`BinaryHeap`] otherwise.
+#[cfg(not(feature = "bucket_queue"))]
+pub(crate) type PathOpenList = BinaryHeap<OpenNode>;
+/// The open list of [`NavmeshQuery::find_path`](crate::NavmeshQuery::find_path),
+/// a `BucketQueue` with the `bucket_queue` feature and a [`BinaryHeap`] otherwise.
+#[cfg(feature = "bucket_queue")]
+pub(crate) type PathOpenList = BucketQueue;
+
+/// A priority queue of [`OpenNode`]s that pops the cheapest node first.
+pub(crate) trait OpenList {
+    /// Removes all nodes, preparing the list for a search whose path is expected to cost about `expected_cost`,
+    /// e.g. the heuristic from the start to the end.
+    fn reset(&mut self, expected_cost: f32);
+
+    /// Adds `node` to the list.
+    fn push(&mut self, node: OpenNode);
+
+    /// Removes and returns the cheapest node, or `None` if the list is empty.
+    fn pop(&mut self) -> Option<OpenNode>;
+}
+
+impl OpenList for BinaryHeap<OpenNode> {
+    #[inline]
+    fn reset(&mut self, _expected_cost: f32) {
+        self.clear();
+    }
+
+    #[inline]
+    fn push(&mut self, node: OpenNode) {
+        BinaryHeap::push(self, node);
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<OpenNode> {
+        BinaryHeap::pop(self)
+    }
+}
+
+/// An open list that sorts nodes into buckets of similar cost, which beats a [`BinaryHeap`]
+/// when the costs of a search lie in a narrow range, as they do on most navmeshes.
+///
+/// The buckets form a window of [`Self::BUCKET_COUNT`] buckets sliding along with the cheapest node,
+/// each covering 1 / [`Self::BUCKETS_PER_EXPECTED_COST`] of the expected cost of the search.
+/// The cheapest node of a bucket is found by scanning it, so nodes are still popped in exactly the same order as from a heap.
+/// Nodes too expensive for the window are kept in a heap until it slides far enough,
+/// so unexpectedly expensive searches only lose the speedup.
+#[cfg(any(test, feature = "bucket_queue"))]
+#[derive(Debug, Clone)]
+pub(crate) struct BucketQueue {
+    /// The buckets of the window, as a ring buffer starting at the bucket of index [`Self::first`].
+    buckets: Vec<Vec<OpenNode>>,
+    /// The index of the cheapest bucket that may contain nodes. Nodes of cost `c` belong to the bucket at `c / width`.
+    first: i64,
+    /// An upper bound of the index of the most expensive bucket containing nodes.
+    last: i64,
+    /// The cost range covered by each bucket. `None` if the expected cost was unusable, so all nodes go to [`Self::overflow`].
+    width: Option<f32>,
+    /// The number of nodes in [`Self::buckets`].
+    bucketed: usize,
+    /// The nodes beyond the window.
+    overflow: BinaryHeap<OpenNode>,
+}
+
+#[cfg(any(test, feature = "bucket_queue"))]
+impl Default for BucketQueue {
+    fn default() -> Self {
+        Self {
+            buckets: vec![Vec::new(); Self::BUCKET_COUNT],
+            first: 0,
+            last: 0,
+            width: None,
+            bucketed: 0,
+            overflow: BinaryHeap::new(),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "bucket_queue"))]
+impl BucketQueue {
+    /// The number of buckets in the window.
+    const BUCKET_COUNT: usize = 256;
+    /// The number of buckets the expected cost of a search is split into.
+    const BUCKETS_PER_EXPECTED_COST: f32 = 64.0;
+
+    /// Returns the index of the bucket `cost` belongs to.
+    #[inline]
+    fn bucket_index(width: f32, cost: f32) -> i64 {
+        // Saturates for huge and non-finite costs, which then always end up in the overflow.
+        (cost / width).floor() as i64
+    }
+}
+
+#[cfg(any(test, feature = "bucket_queue"))]
+impl OpenList for BucketQueue {
+    fn reset(&mut self, expected_cost: f32) {
+        for bucket in &mut self.buckets {
+            bucket.clear();
+        }
+        self.overflow.clear();
+        self.bucketed = 0;
+        let width = expected_cost / Self::BUCKETS_PER_EXPECTED_COST;
+        self.width = (width.is_finite() && width > 0.0).then_some(width);
+    }
+
+    fn push(&mut self, node: OpenNode) {
+        let Some(width) = self.width else {
+            self.overflow.push(node);
+            return;
+        };
+        let index = Self::bucket_index(width, node.cost);
+        if self.bucketed == 0 {
+            (self.first, self.last) = (index, index);
+        } else if index < self.first && self.last - index < Self::BUCKET_COUNT as i64 {
+            // Slide the window back, e.g. for greedy searches whose estimates shrink towards the end.
+            self.first = index;
+        }
+        // Nodes cheaper than the window are still cheaper than everything else in its first bucket.
+        let index = index.max(self.first);
+        if index - self.first >= Self::BUCKET_COUNT as i64 {
+            self.overflow.push(node);
+            return;
+        }
+        let slot = index.rem_euclid(Self::BUCKET_COUNT as i64) as usize;
+        self.buckets[slot].push(node);
+        self.bucketed += 1;
+        self.last = self.last.max(index);
+    }
+
+    fn pop(&mut self) -> Option<OpenNode> {
+        if self.bucketed == 0 {
+            return self.overflow.pop();
+        }
+        loop {
+            let slot = self.first.rem_euclid(Self::BUCKET_COUNT as i64) as usize;
+            let bucket = &mut self.buckets[slot];
+            let Some((cheapest, node)) = bucket
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.cost.total_cmp(&b.cost))
+            else {
+                self.first += 1;
+                continue;
+            };
+            if self
+                .overflow
+                .peek()
+                .is_some_and(|overflow| overflow.cost < node.cost)
+            {
+                return self.overflow.pop();
+            }
+            self.bucketed -= 1;
+            return Some(bucket.swap_remove(cheapest));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_queue_pops_like_a_heap() {
+        let mut heap = BinaryHeap::new();
+        let mut buckets = BucketQueue::default();
+        OpenList::reset(&mut heap, 10.0);
+        buckets.reset(10.0);
+
+        // Costs clustered around the expected cost, a few far beyond the window and a few below it.
+        let mut state = 12345_u32;
+        let mut next_cost = || {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            match state >> 28 {
+                0 => 1000.0 + (state % 1000) as f32,
+                1 => -((state % 100) as f32),
+                _ => 10.0 + (state % 2000) as f32 / 100.0,
+            }
+        };
+        for round in 0..50 {
+            for node in 0..20 {
+                let node = OpenNode {
+                    cost: next_cost(),
+                    node: round * 20 + node,
+                };
+                OpenList::push(&mut heap, node);
+                buckets.push(node);
+            }
+            for _ in 0..15 {
+                let (expected, actual) = (OpenList::pop(&mut heap), buckets.pop());
+                assert_eq!(expected.map(|node| node.cost), actual.map(|node| node.cost));
+            }
+        }
+        while let Some(expected) = OpenList::pop(&mut heap) {
+            assert_eq!(buckets.pop().map(|node| node.cost), Some(expected.cost));
+        }
+        assert_eq!(buckets.pop(), None);
+
+        // Greedy searches push ever cheaper nodes.
+        buckets.reset(100.0);
+        for cost in (0..=100).rev() {
+            buckets.push(OpenNode {
+                cost: cost as f32,
+                node: 0,
+            });
+            buckets.push(OpenNode {
+                cost: cost as f32 + 0.5,
+                node: 1,
+            });
+            assert_eq!(buckets.pop().map(|node| node.cost), Some(cost as f32));
+        }
+        assert_eq!(buckets.pop().map(|node| node.cost), Some(0.5));
+
+        // Without a usable expected cost, the queue degrades to a heap.
+        buckets.reset(0.0);
+        for cost in [3.0, 1.0, 2.0] {
+            buckets.push(OpenNode { cost, node: 0 });
+        }
+        let costs: Vec<f32> = std::iter::from_fn(|| buckets.pop().map(|node| node.cost)).collect();
+        assert_eq!(costs, vec![1.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,237 @@
+//! Rasterizes the triangles of a [`TriMesh`] into a [`Heightfield`] on multiple threads.
+//!
+//! Only available with the `rayon` feature.
+
+use rayon::prelude::*;
+
+use crate::{
+    Heightfield, SpanSource, TriMesh,
+    heightfield::{AreaOverwrites, merge_span_into_column},
+    math::TriangleVertices as _,
+    rasterize::{ClippedSpan, RasterizationError},
+    span::Span,
+};
+
+impl Heightfield {
+    /// Like [`Heightfield::rasterize_triangles`], but rasterizes the triangles on all threads of the rayon thread pool.
+    ///
+    /// The rows of the heightfield are split into stripes, and the triangles are binned by the stripes their footprint overlaps.
+    /// Every stripe rasterizes its triangles into a copy of its own columns on a worker thread, and the stripes are then
+    /// written back in order. Since every column only receives the spans of its own stripe, in the order of the triangles in `trimesh`,
+    /// the resulting spans and [`Heightfield::sources`] are identical to the ones of [`Heightfield::rasterize_triangles`].
+    ///
+    /// Only available with the `rayon` feature.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn rasterize_triangles_parallel(
+        &mut self,
+        trimesh: &TriMesh,
+        walkable_climb: u16,
+    ) -> Result<(), RasterizationError> {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        if trimesh.indices.is_empty() || trimesh.area_types.is_empty() {
+            return Ok(());
+        }
+        self.warn_if_span_height_clamped();
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        // A few stripes per thread, so that threads whose stripes contain little geometry can pick up more work.
+        let rows_per_stripe = height.div_ceil(rayon::current_num_threads() * 4).max(1);
+        let stripe_count = height.div_ceil(rows_per_stripe);
+        let mut bins: Vec<Vec<u32>> = vec![Vec::new(); stripe_count];
+        let inverse_cell_size = 1.0 / self.cell_size;
+        let row = |z: f32| {
+            (((z - self.aabb.min.z) * inverse_cell_size) as i32).clamp(0, height as i32 - 1)
+                as usize
+        };
+        for (i, triangle) in trimesh
+            .triangles()
+            .take(trimesh.area_types.len())
+            .enumerate()
+        {
+            let aabb = triangle.aabb();
+            for bin in
+                &mut bins[row(aabb.min.z) / rows_per_stripe..=row(aabb.max.z) / rows_per_stripe]
+            {
+                bin.push(i as u32);
+            }
+        }
+
+        let stripes = bins
+            .par_iter()
+            .enumerate()
+            .map(|(stripe, triangles)| {
+                let first_row = stripe * rows_per_stripe;
+                let rows = first_row as u16..(first_row + rows_per_stripe).min(height) as u16;
+                self.rasterize_stripe(trimesh, triangles, rows, walkable_climb)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let first_source = self.sources.len();
+        for (
+            stripe,
+            RasterizedStripe {
+                columns,
+                sources,
+                area_overwrites,
+            },
+        ) in stripes.into_iter().enumerate()
+        {
+            let first_column = stripe * rows_per_stripe * width;
+            for (offset, column) in columns.into_iter().enumerate() {
+                let Some(column) = column else {
+                    continue;
+                };
+                let column_index = first_column + offset;
+                self.replace_column(
+                    (column_index % width) as u16,
+                    (column_index / width) as u16,
+                    column,
+                )?;
+            }
+            self.sources.extend(sources);
+            self.area_overwrites.append(area_overwrites);
+        }
+        // The stripes are in row order, so a stable sort restores the order in which the serial path records sources.
+        self.sources[first_source..].sort_by_key(|source| source.triangle);
+        self.warn_about_area_overwrites();
+        Ok(())
+    }
+
+    /// Rasterizes `triangles` of `trimesh` into copies of the columns in `rows`.
+    fn rasterize_stripe(
+        &self,
+        trimesh: &TriMesh,
+        triangles: &[u32],
+        rows: std::ops::Range<u16>,
+        walkable_climb: u16,
+    ) -> Result<RasterizedStripe, RasterizationError> {
+        let width = self.width as usize;
+        let first_column = rows.start as usize * width;
+        let mut columns: Vec<Option<Vec<Span>>> = vec![None; rows.len() * width];
+        let mut sources = Vec::new();
+        let mut area_overwrites = AreaOverwrites::default();
+        let mut clipped = Vec::new();
+        for &i in triangles {
+            let indices = trimesh.indices[i as usize];
+            let triangle = indices
+                .to_array()
+                .map(|index| trimesh.vertices[index as usize]);
+            clipped.clear();
+            self.clip_triangle(
+                triangle,
+                trimesh.area_types[i as usize],
+                rows.clone(),
+                &mut clipped,
+            )?;
+            for ClippedSpan {
+                x,
+                z,
+                span,
+                surface,
+            } in clipped.drain(..)
+            {
+                let column_index = self.column_index(x, z);
+                let column = columns[column_index - first_column].get_or_insert_with(|| {
+                    self.column_spans_at_index(column_index).cloned().collect()
+                });
+                merge_span_into_column(column, span, walkable_climb, (x, z), &mut area_overwrites);
+                if self.record_sources {
+                    sources.push(SpanSource {
+                        position: surface,
+                        triangle: i,
+                    });
+                }
+            }
+        }
+        Ok(RasterizedStripe {
+            columns,
+            sources,
+            area_overwrites,
+        })
+    }
+}
+
+/// The result of rasterizing a stripe of rows in [`Heightfield::rasterize_triangles_parallel`].
+struct RasterizedStripe {
+    /// The columns of the stripe in row-major order, or `None` for columns that received no spans.
+    columns: Vec<Option<Vec<Span>>>,
+    /// The sources of the spans, in the order they were rasterized.
+    sources: Vec<SpanSource>,
+    /// The areas overwritten while merging the spans of the stripe.
+    area_overwrites: AreaOverwrites,
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec3, Vec3A};
+
+    use crate::{Aabb3d, AreaType, HeightfieldBuilder};
+
+    use super::*;
+
+    fn heightfield() -> Heightfield {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 5.0, 5.0]),
+            cell_size: 0.5,
+            cell_height: 0.25,
+        }
+        .build()
+        .unwrap();
+        heightfield.record_sources = true;
+        heightfield
+    }
+
+    #[test]
+    fn matches_serial_rasterization() {
+        // A floor, a ramp crossing it and an unwalkable triangle just above it, overlapping in many columns.
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(-4.0, 0.0, -4.0),
+                Vec3A::new(-4.0, 0.0, 4.0),
+                Vec3A::new(4.0, 0.0, 4.0),
+                Vec3A::new(4.0, 0.0, -4.0),
+                Vec3A::new(-3.0, -0.5, -3.7),
+                Vec3A::new(3.0, 2.0, 3.1),
+                Vec3A::new(2.5, 2.0, -1.3),
+                Vec3A::new(-1.0, 0.1, -1.0),
+                Vec3A::new(-1.0, 0.1, 1.0),
+                Vec3A::new(1.0, 0.3, 1.0),
+            ],
+            indices: vec![
+                UVec3::new(0, 1, 2),
+                UVec3::new(0, 2, 3),
+                UVec3::new(4, 5, 6),
+                UVec3::new(7, 8, 9),
+            ],
+            area_types: vec![
+                AreaType::DEFAULT_WALKABLE,
+                AreaType::DEFAULT_WALKABLE,
+                AreaType::new(3),
+                AreaType::NOT_WALKABLE,
+            ],
+            materials: Vec::new(),
+        };
+
+        let mut serial = heightfield();
+        serial.rasterize_triangles(&trimesh, 1).unwrap();
+        let mut parallel = heightfield();
+        parallel.rasterize_triangles_parallel(&trimesh, 1).unwrap();
+
+        let columns = |heightfield: &Heightfield| -> Vec<Vec<(u16, u16, AreaType)>> {
+            (0..heightfield.spans.len())
+                .map(|column| {
+                    heightfield
+                        .column_spans_at_index(column)
+                        .map(|span| (span.min, span.max, span.area))
+                        .collect()
+                })
+                .collect()
+        };
+        assert!(!serial.allocated_spans.is_empty());
+        assert_eq!(columns(&serial), columns(&parallel));
+        assert_eq!(serial.sources, parallel.sources);
+    }
+}
@@ -0,0 +1,223 @@
+use glam::Vec3;
+
+use crate::{
+    AreaType, NavmeshPath, NavmeshQuery, PolygonNavmesh, PortalCrossing, StraightPathPoint,
+    StraightPathPointKind,
+    straight_path::{closest_point_on_segment, crossing_towards, equal_2d, mark_off_mesh_segment},
+};
+
+impl PolygonNavmesh {
+    /// Like [`Self::straight_path`] with [`PortalCrossing::Funnel`], but pulls the path towards the center of the polygons
+    /// with the area `preferred` wherever it runs through them, e.g. to keep agents on the middle of a road or sidewalk
+    /// instead of cutting its corners.
+    ///
+    /// Every portal between two polygons of the `preferred` area is crossed as close to its midpoint as `max_deviation` allows,
+    /// measured along the portal from where the shortest path crosses it. All other portals are crossed like the shortest path does,
+    /// so the path only changes within the preferred area and always stays within the corridor.
+    /// A `max_deviation` of 0 results in the shortest path. `[Limit: >= 0] [Units: wu]`
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn snapped_straight_path(
+        &self,
+        corridor: &[usize],
+        start: Vec3,
+        end: Vec3,
+        preferred: AreaType,
+        max_deviation: f32,
+    ) -> Vec<StraightPathPoint> {
+        let shortest = self.straight_path(corridor, start, end, PortalCrossing::Funnel);
+        let (Some(first), Some(last)) = (shortest.first(), shortest.last()) else {
+            return shortest;
+        };
+        // The index in the corridor of the polygon every point of the shortest path continues through.
+        let mut corridor_indices = Vec::with_capacity(shortest.len());
+        let mut cursor = 0;
+        for point in &shortest {
+            while cursor + 1 < corridor.len() && corridor[cursor] != point.polygon {
+                cursor += 1;
+            }
+            corridor_indices.push(cursor);
+        }
+
+        let mut path = vec![*first];
+        let mut segment = 0;
+        for (portal, window) in corridor.windows(2).enumerate() {
+            let (from, to) = (window[0], window[1]);
+            let Some((left, right)) = self.portal_points(from, to) else {
+                break;
+            };
+            // The segment of the shortest path that crosses this portal.
+            while segment + 2 < shortest.len() && corridor_indices[segment + 1] <= portal {
+                segment += 1;
+            }
+            if segment + 1 >= shortest.len() {
+                break;
+            }
+            let (previous, next) = (shortest[segment].position, shortest[segment + 1].position);
+            let mut crossing = crossing_towards(previous, next, left, right);
+            // Corners of the shortest path lie on a portal, so keep them exact.
+            if equal_2d(crossing, next) {
+                crossing = next;
+            }
+            if self.areas[from] == preferred && self.areas[to] == preferred {
+                let towards_center = left.lerp(right, 0.5) - crossing;
+                let distance = towards_center.length();
+                if distance > max_deviation {
+                    crossing += towards_center * (max_deviation / distance);
+                } else {
+                    crossing += towards_center;
+                }
+            }
+            push_unless_collinear(&mut path, crossing, to);
+        }
+        push_unless_collinear(&mut path, last.position, last.polygon);
+        path
+    }
+}
+
+impl NavmeshQuery<'_> {
+    /// Turns the corridor of a [`NavmeshPath`] into the points an agent walks along while keeping to the center of
+    /// the `preferred` area, see [`PolygonNavmesh::snapped_straight_path`].
+    ///
+    /// Off-mesh traversals split the path like in [`Self::find_straight_path`].
+    pub fn find_snapped_straight_path(
+        &self,
+        path: &NavmeshPath,
+        preferred: AreaType,
+        max_deviation: f32,
+    ) -> Vec<StraightPathPoint> {
+        let mut points = Vec::new();
+        let mut segment_start = 0;
+        let mut start = path.start;
+        let mut arrival = None;
+        for (index, traversal) in path.traversals.iter().enumerate() {
+            let Some(traversal) = traversal else {
+                continue;
+            };
+            let mut segment = self.navmesh().snapped_straight_path(
+                &path.polygons[segment_start..=index],
+                start,
+                traversal.start,
+                preferred,
+                max_deviation,
+            );
+            let (departure, next_arrival) = self.off_mesh_kinds(traversal);
+            mark_off_mesh_segment(&mut segment, arrival, Some(departure));
+            points.append(&mut segment);
+            arrival = Some(next_arrival);
+            segment_start = index + 1;
+            start = traversal.end;
+        }
+        let mut segment = self.navmesh().snapped_straight_path(
+            &path.polygons[segment_start..],
+            start,
+            path.end,
+            preferred,
+            max_deviation,
+        );
+        mark_off_mesh_segment(&mut segment, arrival, None);
+        points.append(&mut segment);
+        points
+    }
+}
+
+/// Appends `position` to `path`, first removing the points that lie on the straight line from the point before them to `position`,
+/// so portals crossed in a straight line do not leave points behind.
+fn push_unless_collinear(path: &mut Vec<StraightPathPoint>, position: Vec3, polygon: usize) {
+    while let [.., before, last] = path.as_slice() {
+        let on_line = closest_point_on_segment(last.position, before.position, position);
+        if !equal_2d(on_line, last.position) {
+            break;
+        }
+        path.pop();
+    }
+    if path
+        .last()
+        .is_some_and(|last| equal_2d(last.position, position))
+    {
+        return;
+    }
+    path.push(StraightPathPoint {
+        position,
+        polygon,
+        kind: StraightPathPointKind::Walk,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    #[test]
+    fn keeps_to_center_of_preferred_area() {
+        let road = AreaType::new(3);
+        let grid = GridNavmesh::parse(
+            "
+            a####
+            33333
+            ####b
+            ",
+        );
+        let road_cell = |x| {
+            (0..grid.navmesh.polygon_count())
+                .find(|polygon| {
+                    let aabb = grid.navmesh.polygon_aabb(*polygon).unwrap();
+                    aabb.min.x == x as f32 && aabb.min.z == 1.0
+                })
+                .unwrap()
+        };
+        let mut corridor = vec![grid.polygon('a')];
+        corridor.extend((0..5).map(road_cell));
+        corridor.push(grid.polygon('b'));
+        let positions = |max_deviation| {
+            grid.navmesh
+                .snapped_straight_path(
+                    &corridor,
+                    grid.position('a'),
+                    grid.position('b'),
+                    road,
+                    max_deviation,
+                )
+                .iter()
+                .map(|point| point.position)
+                .collect::<Vec<_>>()
+        };
+
+        let shortest: Vec<_> = grid
+            .navmesh
+            .straight_path(
+                &corridor,
+                grid.position('a'),
+                grid.position('b'),
+                PortalCrossing::Funnel,
+            )
+            .iter()
+            .map(|point| point.position)
+            .collect();
+        assert_eq!(positions(0.0), shortest);
+
+        // Enters the road where the shortest path does, then follows its center.
+        assert_eq!(
+            positions(1.0),
+            vec![
+                grid.position('a'),
+                Vec3::new(1.0, 0.0, 1.0),
+                Vec3::new(1.0, 0.0, 1.5),
+                Vec3::new(4.0, 0.0, 1.5),
+                Vec3::new(4.0, 0.0, 2.0),
+                grid.position('b'),
+            ]
+        );
+
+        let deviation = 0.1;
+        for point in positions(deviation) {
+            let on_shortest = shortest
+                .windows(2)
+                .map(|segment| {
+                    closest_point_on_segment(point, segment[0], segment[1]).distance(point)
+                })
+                .fold(f32::INFINITY, f32::min);
+            assert!(on_shortest <= deviation + 1e-4);
+        }
+    }
+}
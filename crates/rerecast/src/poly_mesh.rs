@@ -1,11 +1,15 @@
+use std::collections::HashMap;
+
 use crate::{
-    Aabb3d, AreaType, RegionId,
+    Aabb3d, AreaType, BuildWarning, DetailNavmesh, PolygonEdgeFlags, RegionId,
+    bv_tree::BvTree,
     contours::{ContourSet, RegionVertexId},
-    math::{next, prev},
+    math::{height_on_triangle, next, prev},
+    predicates::orient_2d,
 };
 #[cfg(feature = "bevy_reflect")]
 use bevy_reflect::prelude::*;
-use glam::{U16Vec2, U16Vec3, Vec3Swizzles as _, u16vec3, uvec3};
+use glam::{U16Vec2, U16Vec3, Vec3, Vec3Swizzles as _, u16vec3, uvec3};
 use thiserror::Error;
 
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -17,6 +21,7 @@ struct InternalPolygonNavmesh {
     regions: Vec<RegionId>,
     flags: Vec<u16>,
     areas: Vec<AreaType>,
+    edge_flags: Vec<PolygonEdgeFlags>,
     max_polygons: usize,
     max_vertices_per_polygon: u16,
     aabb: Aabb3d,
@@ -118,6 +123,8 @@ pub struct PolygonNavmesh {
     /// Contains indices to each edge's connected polygons.
     /// A value of [`Self::NO_CONNECTION`] indicates no connection for the associated edge.
     /// (i.e. The edge is a solid border.)
+    ///
+    /// Prefer [`Self::polygon_edges`] and [`Self::shared_edges`], which decode these values into [`PolygonEdge`](crate::PolygonEdge)s.
     pub polygon_neighbors: Vec<u16>,
     /// The user-defined flags assigned to each polygon.
     pub flags: Vec<u16>,
@@ -128,6 +135,12 @@ pub struct PolygonNavmesh {
     /// The standard build process assigns the value of [`AreaType::DEFAULT_WALKABLE`] to all walkable polygons.
     /// This value can then be changed to meet user requirements.
     pub areas: Vec<AreaType>,
+    /// Describes what lies on the other side of each polygon edge, carried over from the contours the mesh was built from.
+    ///
+    /// Laid out like [`Self::polygon_neighbors`]. May be empty, e.g. for navmeshes loaded from a [`NavmeshBlob`](crate::NavmeshBlob),
+    /// so prefer [`Self::polygon_edge_flags`], which derives the flags from the neighbors in that case.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub edge_flags: Vec<PolygonEdgeFlags>,
     /// The maximum number of vertices per polygon
     pub max_vertices_per_polygon: u16,
     /// The bounding box of the mesh in world space.
@@ -162,45 +175,258 @@ impl PolygonNavmesh {
             .chunks_exact(self.max_vertices_per_polygon as usize)
             .map(|chunk| chunk.iter().take_while(|i| **i != Self::NO_INDEX).copied())
     }
+
+    /// Returns the vertex indices of the polygon at index `polygon`, without the trailing [`Self::NO_INDEX`] entries.
+    #[inline]
+    pub fn polygon_vertices(&self, polygon: usize) -> &[u16] {
+        let nvp = self.max_vertices_per_polygon as usize;
+        let vertices = &self.polygons[polygon * nvp..(polygon + 1) * nvp];
+        let count = vertices
+            .iter()
+            .position(|i| *i == Self::NO_INDEX)
+            .unwrap_or(nvp);
+        &vertices[..count]
+    }
+
+    /// Returns the world space position of the vertex at `index` in [`Self::vertices`].
+    #[inline]
+    pub fn world_vertex(&self, index: u16) -> Vec3 {
+        let vertex = self.vertices[index as usize].as_vec3();
+        self.aabb.min
+            + Vec3::new(
+                vertex.x * self.cell_size,
+                vertex.y * self.cell_height,
+                vertex.z * self.cell_size,
+            )
+    }
+
+    /// Iterates over the world space vertices of the polygon at index `polygon`.
+    #[inline]
+    pub fn polygon_world_vertices(&self, polygon: usize) -> impl Iterator<Item = Vec3> + '_ {
+        self.polygon_vertices(polygon)
+            .iter()
+            .map(|i| self.world_vertex(*i))
+    }
+
+    /// Returns the world space AABB of the polygon at index `polygon`.
+    /// Returns `None` if the polygon has no vertices.
+    pub fn polygon_aabb(&self, polygon: usize) -> Option<Aabb3d> {
+        let mut vertices = self.polygon_world_vertices(polygon);
+        let first = vertices.next()?;
+        Some(vertices.fold(
+            Aabb3d {
+                min: first,
+                max: first,
+            },
+            |aabb, vertex| Aabb3d {
+                min: aabb.min.min(vertex),
+                max: aabb.max.max(vertex),
+            },
+        ))
+    }
+
+    /// Returns the index of the polygon on the other side of the edge starting at vertex `edge` of the polygon at index `polygon`.
+    ///
+    /// Returns `None` if the edge is a solid border or a portal to a neighboring tile.
+    #[inline]
+    pub(crate) fn internal_neighbor(&self, polygon: usize, edge: usize) -> Option<usize> {
+        let nvp = self.max_vertices_per_polygon as usize;
+        let neighbor = self.polygon_neighbors[polygon * nvp + edge];
+        // Both `NO_CONNECTION` and tile portals have the border bit set.
+        if neighbor & RegionId::BORDER_REGION.bits() != 0 {
+            None
+        } else {
+            Some(neighbor as usize)
+        }
+    }
+
+    /// Returns the height of the navmesh surface on the polygon at index `polygon` at the xz-coordinates of `point`.
+    ///
+    /// Uses the detail mesh if one is given and has data for the polygon, see [`DetailNavmesh::polygon_height`].
+    /// Otherwise, e.g. when the navmesh was built without detail meshes, falls back to interpolating the polygon itself
+    /// with [`Self::polygon_height`].
+    pub fn surface_height(
+        &self,
+        detail: Option<&DetailNavmesh>,
+        polygon: usize,
+        point: Vec3,
+    ) -> Option<f32> {
+        detail
+            .and_then(|detail| detail.polygon_height(polygon, point))
+            .or_else(|| self.polygon_height(polygon, point))
+    }
+
+    /// Iterates over the world space edges of the polygon at index `polygon` that are not shared with another polygon of this mesh,
+    /// i.e. solid borders and portals to neighboring tiles.
+    pub fn polygon_boundary_edges(
+        &self,
+        polygon: usize,
+    ) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+        let vertices = self.polygon_vertices(polygon);
+        (0..vertices.len())
+            .filter(move |edge| self.internal_neighbor(polygon, *edge).is_none())
+            .map(move |edge| {
+                (
+                    self.world_vertex(vertices[edge]),
+                    self.world_vertex(vertices[next(edge, vertices.len())]),
+                )
+            })
+    }
+
+    /// Iterates over the world space edges of all polygons that are not shared with another polygon of this mesh.
+    /// See [`Self::polygon_boundary_edges`].
+    pub fn boundary_edges(&self) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+        (0..self.polygon_count()).flat_map(|polygon| self.polygon_boundary_edges(polygon))
+    }
+
+    /// Returns the height of the polygon at index `polygon` at the xz-coordinates of `point`,
+    /// interpolated from the polygon's vertices.
+    ///
+    /// Returns `None` if `point` does not lie within the polygon on the xz-plane.
+    pub fn polygon_height(&self, polygon: usize, point: Vec3) -> Option<f32> {
+        let vertices = self.polygon_vertices(polygon);
+        if vertices.len() < 3 {
+            return None;
+        }
+        let a = self.world_vertex(vertices[0]);
+        for window in vertices[1..].windows(2) {
+            let b = self.world_vertex(window[0]);
+            let c = self.world_vertex(window[1]);
+            if let Some(height) = height_on_triangle(point.xz(), a, b, c) {
+                return Some(height);
+            }
+        }
+        None
+    }
+
+    /// Finds the polygon whose surface is vertically closest to `point` among the polygons directly above or below it,
+    /// using `tree` to find candidate polygons.
+    ///
+    /// Returns the polygon index and the height of its surface at `point`,
+    /// or `None` if no polygon lies within `max_height_difference` of `point`.
+    pub(crate) fn locate_polygon(
+        &self,
+        tree: &BvTree,
+        point: Vec3,
+        max_height_difference: f32,
+    ) -> Option<(usize, f32)> {
+        let vertical_extent = Vec3::new(0.0, max_height_difference, 0.0);
+        let search_aabb = Aabb3d {
+            min: point - vertical_extent,
+            max: point + vertical_extent,
+        };
+        let mut best: Option<(usize, f32)> = None;
+        let mut best_height_difference = f32::MAX;
+        for polygon in tree.query_aabb(&search_aabb) {
+            let Some(height) = self.polygon_height(polygon, point) else {
+                continue;
+            };
+            let height_difference = (height - point.y).abs();
+            if height_difference <= max_height_difference
+                && height_difference < best_height_difference
+            {
+                best_height_difference = height_difference;
+                best = Some((polygon, height));
+            }
+        }
+        best
+    }
+
+    /// Groups the polygons into islands, i.e. sets of polygons that can be reached from each other by walking over shared edges.
+    ///
+    /// Returns the island id of each polygon in the same order as the polygons. Island ids start at 0 and are contiguous.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn islands(&self) -> Vec<u32> {
+        const UNASSIGNED: u32 = u32::MAX;
+        let polygon_count = self.polygon_count();
+        let mut islands = vec![UNASSIGNED; polygon_count];
+        let mut stack = Vec::new();
+        let mut island = 0;
+        for start in 0..polygon_count {
+            if islands[start] != UNASSIGNED {
+                continue;
+            }
+            islands[start] = island;
+            stack.push(start);
+            while let Some(polygon) = stack.pop() {
+                for edge in 0..self.polygon_vertices(polygon).len() {
+                    let Some(neighbor) = self.internal_neighbor(polygon, edge) else {
+                        continue;
+                    };
+                    if islands[neighbor] == UNASSIGNED {
+                        islands[neighbor] = island;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            island += 1;
+        }
+        islands
+    }
 }
 
-impl From<InternalPolygonNavmesh> for PolygonNavmesh {
-    fn from(mut value: InternalPolygonNavmesh) -> Self {
-        let nvp = value.max_vertices_per_polygon as usize;
-        value.polygons.truncate(value.npolys * 2 * nvp);
-        let mut polygons = Vec::with_capacity(value.polygons.len() / 2);
-        let mut polygon_neighbors = Vec::with_capacity(value.polygons.len() / 2);
-        for poly in value.polygons.chunks_exact(nvp * 2) {
+impl InternalPolygonNavmesh {
+    /// Splits the interleaved vertex indices and neighbors of the polygons into the given buffers,
+    /// which may be recycled from an earlier build.
+    fn into_navmesh(
+        mut self,
+        mut polygons: Vec<u16>,
+        mut polygon_neighbors: Vec<u16>,
+    ) -> PolygonNavmesh {
+        let nvp = self.max_vertices_per_polygon as usize;
+        self.polygons.truncate(self.npolys * 2 * nvp);
+        polygons.clear();
+        polygon_neighbors.clear();
+        polygons.reserve(self.polygons.len() / 2);
+        polygon_neighbors.reserve(self.polygons.len() / 2);
+        for poly in self.polygons.chunks_exact(nvp * 2) {
             let (vertices, neighbors) = poly.split_at(nvp);
             polygons.extend_from_slice(vertices);
             polygon_neighbors.extend_from_slice(neighbors);
         }
-        value.vertices.truncate(value.nvertices as usize);
-        value.areas.truncate(value.npolys);
+        self.vertices.truncate(self.nvertices as usize);
+        self.areas.truncate(self.npolys);
         PolygonNavmesh {
-            vertices: value.vertices,
+            vertices: self.vertices,
             polygons,
             polygon_neighbors,
-            regions: value.regions,
-            flags: value.flags,
-            areas: value.areas,
-            max_vertices_per_polygon: value.max_vertices_per_polygon,
-            aabb: value.aabb,
-            cell_size: value.cell_size,
-            cell_height: value.cell_height,
-            border_size: value.border_size,
-            max_edge_error: value.max_edge_error,
+            regions: self.regions,
+            flags: self.flags,
+            areas: self.areas,
+            edge_flags: self.edge_flags,
+            max_vertices_per_polygon: self.max_vertices_per_polygon,
+            aabb: self.aabb,
+            cell_size: self.cell_size,
+            cell_height: self.cell_height,
+            border_size: self.border_size,
+            max_edge_error: self.max_edge_error,
         }
     }
 }
 
 impl ContourSet {
     /// Builds a polygon mesh from the provided contours.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn into_polygon_mesh(
         self,
         max_vertices_per_polygon: u16,
+    ) -> Result<PolygonNavmesh, PolygonNavmeshError> {
+        self.polygon_mesh_reusing(max_vertices_per_polygon, PolygonNavmesh::default())
+    }
+
+    /// Like [`Self::into_polygon_mesh`], but writes the polygon mesh into the buffers of `recycled`
+    /// and leaves the contours intact, so that both can be reused when rebuilding a tile. See [`TileBuildPool`](crate::TileBuildPool).
+    pub(crate) fn polygon_mesh_reusing(
+        &self,
+        max_vertices_per_polygon: u16,
+        recycled: PolygonNavmesh,
     ) -> Result<PolygonNavmesh, PolygonNavmeshError> {
         let mut mesh = InternalPolygonNavmesh {
+            vertices: recycled.vertices,
+            regions: recycled.regions,
+            flags: recycled.flags,
+            areas: recycled.areas,
+            edge_flags: recycled.edge_flags,
             aabb: self.aabb,
             cell_size: self.cell_size,
             cell_height: self.cell_height,
@@ -210,6 +436,13 @@ impl ContourSet {
             ..Default::default()
         };
         let nvp = max_vertices_per_polygon as usize;
+        if max_vertices_per_polygon > DETOUR_MAX_VERTICES_PER_POLYGON {
+            BuildWarning::TooManyVerticesPerPolygon {
+                actual: max_vertices_per_polygon,
+                max: DETOUR_MAX_VERTICES_PER_POLYGON,
+            }
+            .emit();
+        }
 
         let mut max_vertices = 0;
         let mut max_tris = 0;
@@ -226,6 +459,11 @@ impl ContourSet {
 
         if max_vertices > u16::MAX as usize {
             // Jan: Is this sensible? It's the original, but I suspect u32 is fine
+            BuildWarning::TooManyVertices {
+                actual: max_vertices,
+                max: u16::MAX as usize,
+            }
+            .emit();
             return Err(PolygonNavmeshError::TooManyVertices {
                 actual: max_vertices,
                 max: u16::MAX as usize,
@@ -233,10 +471,10 @@ impl ContourSet {
         }
 
         let mut vflags = vec![false; max_vertices];
-        mesh.vertices = vec![U16Vec3::ZERO; max_vertices];
+        refill(&mut mesh.vertices, U16Vec3::ZERO, max_vertices);
         mesh.polygons = vec![u16::MAX; max_tris * nvp * 2];
-        mesh.regions = vec![RegionId::default(); max_tris];
-        mesh.areas = vec![AreaType::default(); max_tris];
+        refill(&mut mesh.regions, RegionId::default(), max_tris);
+        refill(&mut mesh.areas, AreaType::default(), max_tris);
 
         let mut next_vert = vec![Some(0); max_vertices];
         let mut first_vert = [None; VERTEX_BUCKET_COUNT];
@@ -245,6 +483,7 @@ impl ContourSet {
         let mut polys = vec![u16::MAX; (max_verts_per_cont + 1) * nvp];
 
         let temp_poly_index = max_verts_per_cont * nvp;
+        let mut contour_edges = HashMap::new();
 
         for cont in &self.contours {
             // Skip null contours.
@@ -259,7 +498,9 @@ impl ContourSet {
             }
 
             // Jan: we treat an invalid triangulation as an error instead of a warning.
-            let ntris = triangulate(cont.vertices.len(), &cont.vertices, &mut indices, &mut tris)?;
+            let (ntris, repair) =
+                triangulate(cont.vertices.len(), &cont.vertices, &mut indices, &mut tris)?;
+            repair.emit(cont.region);
             // Add and merge vertices.
             for j in 0..cont.vertices.len() {
                 let (v, region) = &cont.vertices[j];
@@ -275,6 +516,12 @@ impl ContourSet {
                     vflags[indices[j]] = true;
                 }
             }
+            // Remember the flags of the contour edges by their vertices, which keep their position when border vertices are removed.
+            for (j, (_, region)) in cont.vertices.iter().enumerate() {
+                let a = mesh.vertices[indices[j]];
+                let b = mesh.vertices[indices[next(j, cont.vertices.len())]];
+                contour_edges.insert((a, b), PolygonEdgeFlags::from_contour_edge(*region));
+            }
             // Build initial polygons.
             let mut npolys = 0;
             polys.fill(u16::MAX);
@@ -355,6 +602,11 @@ impl ContourSet {
                 mesh.npolys += 1;
                 if mesh.npolys > max_tris {
                     // Jan: we are comparing polys with tris. Why? Shouldn't we compare polys with polys?
+                    BuildWarning::TooManyPolygons {
+                        actual: mesh.npolys,
+                        max: max_tris,
+                    }
+                    .emit();
                     return Err(PolygonNavmeshError::TooManyPolygons {
                         actual: mesh.npolys,
                         max: max_tris,
@@ -419,14 +671,46 @@ impl ContourSet {
                 }
             }
         }
+        // Carry the contour edge flags over to the polygon edges.
+        // Edges created by removing border vertices have no contour edge, so their flags are derived from the adjacency.
+        refill(
+            &mut mesh.edge_flags,
+            PolygonEdgeFlags::empty(),
+            mesh.npolys * nvp,
+        );
+        for i in 0..mesh.npolys {
+            let p = &mesh.polygons[i * 2 * nvp..];
+            for j in 0..nvp {
+                if p[j] == PolygonNavmesh::NO_INDEX {
+                    break;
+                }
+                let nj = j + 1;
+                let nj = if nj >= nvp || p[nj] == PolygonNavmesh::NO_INDEX {
+                    0
+                } else {
+                    nj
+                };
+                let edge = (mesh.vertices[p[j] as usize], mesh.vertices[p[nj] as usize]);
+                mesh.edge_flags[i * nvp + j] = match contour_edges.get(&edge) {
+                    Some(flags) => *flags,
+                    None => PolygonEdgeFlags::from_neighbor(p[nvp + j], &mesh.areas, i),
+                };
+            }
+        }
         // Just allocate the mesh flags array. The user is resposible to fill it.
-        mesh.flags = vec![0; mesh.npolys];
+        refill(&mut mesh.flags, 0, mesh.npolys);
         // Jan: Rust's type system makes it impossible for the number of verts and polys to be greater than the max index.
 
-        Ok(mesh.into())
+        Ok(mesh.into_navmesh(recycled.polygons, recycled.polygon_neighbors))
     }
 }
 
+/// Clears `buffer` and fills it with `len` copies of `value`, keeping its allocation.
+fn refill<T: Clone>(buffer: &mut Vec<T>, value: T, len: usize) {
+    buffer.clear();
+    buffer.resize(len, value);
+}
+
 #[derive(Debug, Default, Clone)]
 struct Edge {
     vert: U16Vec2,
@@ -666,7 +950,8 @@ impl InternalPolygonNavmesh {
 
         // Triangulate the hole.
         // Jan: we treat errors here as a hard error instead of printing a warning.
-        let ntris = triangulate(nhole, &tverts, &mut thole, &mut tris)?;
+        let (ntris, repair) = triangulate(nhole, &tverts, &mut thole, &mut tris)?;
+        repair.emit(hreg[0]);
 
         // Merge the hole triangles back to polygons.
         let mut polys = vec![0; (ntris + 1) * nvp];
@@ -772,6 +1057,11 @@ impl InternalPolygonNavmesh {
             self.areas[self.npolys] = pareas[i];
             self.npolys += 1;
             if self.npolys > max_tris {
+                BuildWarning::TooManyPolygons {
+                    actual: self.npolys,
+                    max: max_tris,
+                }
+                .emit();
                 return Err(PolygonNavmeshError::TooManyPolygons {
                     actual: self.npolys,
                     max: max_tris,
@@ -969,9 +1259,7 @@ fn get_poly_merge_value(
 
 #[inline]
 fn uleft(a: U16Vec3, b: U16Vec3, c: U16Vec3) -> bool {
-    let cross = (b.x as i32 - a.x as i32) * (c.z as i32 - a.z as i32)
-        - (c.x as i32 - a.x as i32) * (b.z as i32 - a.z as i32);
-    cross < 0
+    orient_2d(a, b, c) < 0
 }
 
 fn count_poly_verts(p: &[u16], nvp: usize) -> usize {
@@ -1032,13 +1320,45 @@ fn compute_vertex_hash(vertex: U16Vec3) -> usize {
 
 const VERTEX_BUCKET_COUNT: usize = 1 << 12;
 
+/// Detour's `DT_VERTS_PER_POLYGON`.
+const DETOUR_MAX_VERTICES_PER_POLYGON: u16 = 6;
+
+/// What [`triangulate`] had to do to triangulate a contour on which ear clipping got stuck.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct TriangulationRepair {
+    /// The number of ears clipped even though the contour crosses them.
+    forced_ears: usize,
+    /// The number of collinear vertices removed without a triangle.
+    dropped_vertices: usize,
+}
+
+impl TriangulationRepair {
+    /// Emits [`BuildWarning::TriangulationRepaired`] if anything was repaired.
+    fn emit(self, region: RegionId) {
+        if self != Self::default() {
+            BuildWarning::TriangulationRepaired {
+                region: region.bits(),
+                forced_ears: self.forced_ears,
+                dropped_vertices: self.dropped_vertices,
+            }
+            .emit();
+        }
+    }
+}
+
+/// Triangulates the polygon `indices[..n]` into `tris` by ear clipping and returns the number of triangles.
+///
+/// Near-degenerate contours can leave no proper diagonal. Unlike Recast, which then drops the rest of the contour,
+/// the triangulation falls back to clipping the shortest convex ear regardless of the contour crossing it
+/// and to dropping collinear vertices, and reports this in the returned [`TriangulationRepair`].
 fn triangulate(
     mut n: usize,
     verts: &[(U16Vec3, u32)],
     indices: &mut [usize],
     tris: &mut [U16Vec3],
-) -> Result<usize, PolygonNavmeshError> {
+) -> Result<(usize, TriangulationRepair), PolygonNavmeshError> {
     let mut ntris = 0;
+    let mut repair = TriangulationRepair::default();
 
     // The last bit of the index is used to indicate if the vertex can be removed.
     for i in 0..n {
@@ -1091,9 +1411,40 @@ fn triangulate(
             }
         }
 
-        let Some(mini) = mini else {
+        let mut clip_ear = true;
+        if mini.is_none() {
             // The contour is messed up. This sometimes happens
             // if the contour simplification is too aggressive.
+            // Clip the shortest convex ear anyway, or drop a collinear vertex if there is none.
+            min_len = None;
+            let mut collinear_vertex = None;
+            for i in 0..n {
+                let i1 = next(i, n);
+                let p0 = verts[indices[i] & INDEX_MASK].0;
+                let p1 = verts[indices[i1] & INDEX_MASK].0;
+                let p2 = verts[indices[next(i1, n)] & INDEX_MASK].0;
+                if left(p0, p1, p2) {
+                    let d = p2.as_ivec3() - p0.as_ivec3();
+                    let len = d.xz().length_squared() as u16;
+                    if min_len.is_none_or(|min| len < min) {
+                        min_len = Some(len);
+                        mini = Some(i);
+                    }
+                } else if collinear(p0, p1, p2) && collinear_vertex.is_none() {
+                    collinear_vertex = Some(i);
+                }
+            }
+            if mini.is_some() {
+                repair.forced_ears += 1;
+            } else if collinear_vertex.is_some() {
+                mini = collinear_vertex;
+                clip_ear = false;
+                repair.dropped_vertices += 1;
+            }
+        }
+
+        let Some(mini) = mini else {
+            // Every vertex is reflex, so the contour is not a polygon at all.
             return Err(PolygonNavmeshError::InvalidContour);
         };
 
@@ -1101,10 +1452,12 @@ fn triangulate(
         let mut i1 = next(i, n);
         let i2 = next(i1, n);
 
-        tris[ntris].x = (indices[i] & INDEX_MASK) as u16;
-        tris[ntris].y = (indices[i1] & INDEX_MASK) as u16;
-        tris[ntris].z = (indices[i2] & INDEX_MASK) as u16;
-        ntris += 1;
+        if clip_ear {
+            tris[ntris].x = (indices[i] & INDEX_MASK) as u16;
+            tris[ntris].y = (indices[i1] & INDEX_MASK) as u16;
+            tris[ntris].z = (indices[i2] & INDEX_MASK) as u16;
+            ntris += 1;
+        }
 
         // Removes P[i1] by copying P[i+1]...P[n-1] left one index.
         n -= 1;
@@ -1135,7 +1488,7 @@ fn triangulate(
     tris[ntris].z = (indices[2] & INDEX_MASK) as u16;
     ntris += 1;
 
-    Ok(ntris)
+    Ok((ntris, repair))
 }
 
 const CAN_REMOVE: usize = 0x80000000;
@@ -1175,11 +1528,8 @@ fn is_left(a: U16Vec3, b: U16Vec3, c: U16Vec3) -> bool {
 }
 
 #[inline]
-fn area2(a: U16Vec3, b: U16Vec3, c: U16Vec3) -> i32 {
-    let a = a.as_ivec3();
-    let b = b.as_ivec3();
-    let c = c.as_ivec3();
-    (b.x - a.x) * (c.z - a.z) - (c.x - a.x) * (b.z - a.z)
+fn area2(a: U16Vec3, b: U16Vec3, c: U16Vec3) -> i64 {
+    orient_2d(a, b, c)
 }
 
 // Returns T iff (v_i, v_j) is a proper internal *or* external
@@ -1342,3 +1692,47 @@ pub enum PolygonNavmeshError {
     )]
     InvalidContour,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contour(points: &[(u16, u16)]) -> Vec<(U16Vec3, u32)> {
+        points
+            .iter()
+            .map(|&(x, z)| (U16Vec3::new(x, 0, z), 0))
+            .collect()
+    }
+
+    #[test]
+    fn repairs_triangulation_of_self_intersecting_contour() {
+        // A pentagram, where every ear crosses the contour.
+        let verts = contour(&[(15, 4), (2, 12), (18, 12), (5, 4), (10, 18)]);
+        let mut indices: Vec<usize> = (0..verts.len()).collect();
+        let mut tris = vec![U16Vec3::ZERO; verts.len()];
+        let (ntris, repair) = triangulate(verts.len(), &verts, &mut indices, &mut tris).unwrap();
+        assert_eq!(ntris, 3);
+        assert_eq!(
+            repair,
+            TriangulationRepair {
+                forced_ears: 1,
+                dropped_vertices: 0,
+            }
+        );
+
+        // A well-formed contour needs no repair.
+        let verts = contour(&[(0, 0), (0, 1), (1, 1), (1, 0)]);
+        let mut indices: Vec<usize> = (0..verts.len()).collect();
+        let (ntris, repair) = triangulate(verts.len(), &verts, &mut indices, &mut tris).unwrap();
+        assert_eq!(ntris, 2);
+        assert_eq!(repair, TriangulationRepair::default());
+
+        // Wound the wrong way, every vertex is reflex.
+        let verts = contour(&[(0, 0), (1, 0), (1, 1), (0, 1)]);
+        let mut indices: Vec<usize> = (0..verts.len()).collect();
+        assert!(matches!(
+            triangulate(verts.len(), &verts, &mut indices, &mut tris),
+            Err(PolygonNavmeshError::InvalidContour)
+        ));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{PolygonNavmesh, RegionId, math::next};
+
+/// An edge of a polygon of a [`PolygonNavmesh`] together with what lies on its other side,
+/// see [`PolygonNavmesh::polygon_edges`].
+///
+/// Unlike the raw [`PolygonNavmesh::polygon_neighbors`], this does not require knowing how borders and tile portals are encoded,
+/// so it is the intended way to build custom runtime structures from the adjacency of a navmesh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct PolygonEdge {
+    /// The index of the polygon the edge belongs to.
+    pub polygon: usize,
+    /// The index of the edge within the polygon, i.e. the index of the vertex it starts at in [`PolygonNavmesh::polygon_vertices`].
+    pub edge: usize,
+    /// The indices into [`PolygonNavmesh::vertices`] of the start and end vertex of the edge,
+    /// in the winding order of [`Self::polygon`].
+    pub vertices: [u16; 2],
+    /// What lies on the other side of the edge.
+    pub neighbor: EdgeNeighbor,
+}
+
+/// What lies on the other side of a [`PolygonEdge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgeNeighbor {
+    /// The edge is shared with another polygon of the same mesh.
+    Polygon {
+        /// The index of the neighboring polygon.
+        polygon: usize,
+        /// The index of the same edge within the neighboring polygon, whose vertices are in the opposite order.
+        edge: usize,
+    },
+    /// The edge lies on the border of a tile and continues in the neighboring tile.
+    TilePortal {
+        /// The side of the tile the edge lies on: 0 at the minimum x, 1 at the maximum z,
+        /// 2 at the maximum x and 3 at the minimum z, like Detour's portal sides. `[Limit: 0..=3]`
+        side: u8,
+    },
+    /// The edge is a solid border not shared with any polygon.
+    Border,
+}
+
+impl EdgeNeighbor {
+    /// Returns the index of the neighboring polygon, or `None` if the edge is not shared with a polygon of the same mesh.
+    #[inline]
+    pub fn polygon(&self) -> Option<usize> {
+        match self {
+            Self::Polygon { polygon, .. } => Some(*polygon),
+            _ => None,
+        }
+    }
+}
+
+impl PolygonNavmesh {
+    /// Returns the edge starting at vertex `edge` of the polygon at index `polygon`, see [`PolygonEdge`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `edge` is not smaller than the number of vertices of the polygon.
+    pub fn polygon_edge(&self, polygon: usize, edge: usize) -> PolygonEdge {
+        let vertices = self.polygon_vertices(polygon);
+        let nvp = self.max_vertices_per_polygon as usize;
+        let raw_neighbor = self.polygon_neighbors[polygon * nvp + edge];
+        let neighbor = if raw_neighbor == Self::NO_CONNECTION {
+            EdgeNeighbor::Border
+        } else if raw_neighbor & RegionId::BORDER_REGION.bits() != 0 {
+            EdgeNeighbor::TilePortal {
+                side: (raw_neighbor & 0xf) as u8,
+            }
+        } else {
+            let neighbor = raw_neighbor as usize;
+            let (start, end) = (vertices[edge], vertices[next(edge, vertices.len())]);
+            let neighbor_vertices = self.polygon_vertices(neighbor);
+            // The shared edge runs the other way around in the neighbor.
+            let neighbor_edge = (0..neighbor_vertices.len())
+                .find(|neighbor_edge| {
+                    neighbor_vertices[*neighbor_edge] == end
+                        && neighbor_vertices[next(*neighbor_edge, neighbor_vertices.len())] == start
+                })
+                .or_else(|| {
+                    (0..neighbor_vertices.len()).find(|neighbor_edge| {
+                        self.internal_neighbor(neighbor, *neighbor_edge) == Some(polygon)
+                    })
+                })
+                .unwrap_or_default();
+            EdgeNeighbor::Polygon {
+                polygon: neighbor,
+                edge: neighbor_edge,
+            }
+        };
+        PolygonEdge {
+            polygon,
+            edge,
+            vertices: [vertices[edge], vertices[next(edge, vertices.len())]],
+            neighbor,
+        }
+    }
+
+    /// Iterates over the edges of the polygon at index `polygon` in winding order, see [`PolygonEdge`].
+    pub fn polygon_edges(&self, polygon: usize) -> impl Iterator<Item = PolygonEdge> + '_ {
+        (0..self.polygon_vertices(polygon).len()).map(move |edge| self.polygon_edge(polygon, edge))
+    }
+
+    /// Iterates over every edge shared by two polygons of the mesh exactly once,
+    /// from the side of the polygon with the lower index.
+    pub fn shared_edges(&self) -> impl Iterator<Item = PolygonEdge> + '_ {
+        (0..self.polygon_count()).flat_map(move |polygon| {
+            self.polygon_edges(polygon).filter(move |edge| {
+                edge.neighbor
+                    .polygon()
+                    .is_some_and(|neighbor| neighbor > polygon)
+            })
+        })
+    }
+
+    /// Returns the edge of the polygon at index `polygon` that is shared with the polygon at index `neighbor`,
+    /// or `None` if both polygons are not adjacent.
+    pub fn shared_edge(&self, polygon: usize, neighbor: usize) -> Option<PolygonEdge> {
+        self.polygon_edges(polygon)
+            .find(|edge| edge.neighbor.polygon() == Some(neighbor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    #[test]
+    fn reports_shared_edges_from_both_sides() {
+        let grid = GridNavmesh::parse(
+            "
+            ab
+            c#
+            ",
+        );
+        let navmesh = &grid.navmesh;
+        let (a, b, c) = (grid.polygon('a'), grid.polygon('b'), grid.polygon('c'));
+
+        let shared: Vec<_> = navmesh.shared_edges().collect();
+        assert_eq!(shared.len(), 2);
+        for edge in &shared {
+            let EdgeNeighbor::Polygon {
+                polygon: neighbor,
+                edge: neighbor_edge,
+            } = edge.neighbor
+            else {
+                panic!("shared edges have a neighboring polygon");
+            };
+            assert!(edge.polygon < neighbor);
+            let other = navmesh.polygon_edge(neighbor, neighbor_edge);
+            assert_eq!(other.vertices, [edge.vertices[1], edge.vertices[0]]);
+            assert_eq!(
+                other.neighbor,
+                EdgeNeighbor::Polygon {
+                    polygon: edge.polygon,
+                    edge: edge.edge,
+                }
+            );
+        }
+
+        assert!(navmesh.shared_edge(a, b).is_some());
+        assert!(navmesh.shared_edge(c, a).is_some());
+        assert_eq!(navmesh.shared_edge(b, c), None);
+        assert_eq!(
+            navmesh
+                .polygon_edges(b)
+                .filter(|edge| edge.neighbor == EdgeNeighbor::Border)
+                .count(),
+            3
+        );
+    }
+}
@@ -0,0 +1,137 @@
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::prelude::*;
+
+use crate::{AreaType, PolygonNavmesh, RegionId, RegionVertexId};
+
+/// Describes what lies on the other side of a polygon edge, see [`PolygonNavmesh::edge_flags`].
+///
+/// Edges between two polygons of the same area have no flags set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[repr(transparent)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct PolygonEdgeFlags(u8);
+bitflags::bitflags! {
+    impl PolygonEdgeFlags: u8 {
+        /// The edge borders unwalkable space, i.e. it is part of the outer boundary of the navmesh or of an obstacle.
+        const SOLID_BORDER = 1;
+        /// The edge lies on the border of a tile and continues in the neighboring tile.
+        const TILE_PORTAL = 2;
+        /// The edge separates two different areas.
+        const AREA_BORDER = 4;
+    }
+}
+
+impl PolygonEdgeFlags {
+    /// Converts the flags of a [`Contour`](crate::Contour) edge, i.e. the neighbor region and [`RegionVertexId::AREA_BORDER`].
+    pub(crate) fn from_contour_edge(region: u32) -> Self {
+        let neighbor = (region & RegionVertexId::REGION_MASK.bits()) as u16;
+        let mut flags = if neighbor == RegionId::NONE.bits() {
+            Self::SOLID_BORDER
+        } else if neighbor & RegionId::BORDER_REGION.bits() != 0 {
+            Self::TILE_PORTAL
+        } else {
+            Self::empty()
+        };
+        if region & RegionVertexId::AREA_BORDER.bits() != 0 {
+            flags |= Self::AREA_BORDER;
+        }
+        flags
+    }
+
+    /// Derives the flags of an edge from its entry in [`PolygonNavmesh::polygon_neighbors`],
+    /// for edges that have no contour edge to take them from.
+    pub(crate) fn from_neighbor(neighbor: u16, areas: &[AreaType], polygon: usize) -> Self {
+        if neighbor == PolygonNavmesh::NO_CONNECTION {
+            Self::SOLID_BORDER
+        } else if neighbor & RegionId::BORDER_REGION.bits() != 0 {
+            Self::TILE_PORTAL
+        } else if areas[neighbor as usize] != areas[polygon] {
+            Self::AREA_BORDER
+        } else {
+            Self::empty()
+        }
+    }
+}
+
+impl PolygonNavmesh {
+    /// Returns the flags of the edge starting at vertex `edge` of the polygon at index `polygon`,
+    /// e.g. to tell the outer boundary of the navmesh apart from portals for boundary-following behaviors.
+    ///
+    /// Falls back to deriving the flags from [`Self::polygon_neighbors`] and [`Self::areas`]
+    /// if [`Self::edge_flags`] is empty, e.g. for navmeshes loaded from a [`NavmeshBlob`](crate::NavmeshBlob).
+    pub fn polygon_edge_flags(&self, polygon: usize, edge: usize) -> PolygonEdgeFlags {
+        let index = polygon * self.max_vertices_per_polygon as usize + edge;
+        if self.edge_flags.len() == self.polygon_neighbors.len() {
+            self.edge_flags[index]
+        } else {
+            PolygonEdgeFlags::from_neighbor(self.polygon_neighbors[index], &self.areas, polygon)
+        }
+    }
+
+    /// Returns whether the edge starting at vertex `edge` of the polygon at index `polygon` borders unwalkable space.
+    /// See [`PolygonEdgeFlags::SOLID_BORDER`].
+    #[inline]
+    pub fn is_solid_edge(&self, polygon: usize, edge: usize) -> bool {
+        self.polygon_edge_flags(polygon, edge)
+            .contains(PolygonEdgeFlags::SOLID_BORDER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec3, Vec3A};
+
+    use super::*;
+    use crate::{NavmeshConfig, TriMesh, build_solo_navmesh, test_utils::GridNavmesh};
+
+    #[test]
+    fn derives_flags_without_contours() {
+        let mut grid = GridNavmesh::parse("ab");
+        let (a, b) = (grid.polygon('a'), grid.polygon('b'));
+        grid.navmesh.areas[b] = AreaType::from(3);
+        let flags: Vec<_> = (0..4)
+            .map(|edge| grid.navmesh.polygon_edge_flags(a, edge))
+            .collect();
+        assert_eq!(
+            flags
+                .iter()
+                .filter(|flags| **flags == PolygonEdgeFlags::SOLID_BORDER)
+                .count(),
+            3
+        );
+        assert!(flags.contains(&PolygonEdgeFlags::AREA_BORDER));
+    }
+
+    #[test]
+    fn carries_contour_flags_into_navmesh() {
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 0.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2), UVec3::new(0, 2, 3)],
+            area_types: vec![AreaType::NOT_WALKABLE; 2],
+            materials: Vec::new(),
+        };
+        let config = NavmeshConfig {
+            border_size: 0,
+            ..Default::default()
+        };
+        let (navmesh, _detail) = build_solo_navmesh(&trimesh, &config).unwrap();
+        assert_eq!(navmesh.edge_flags.len(), navmesh.polygon_neighbors.len());
+
+        for polygon in 0..navmesh.polygon_count() {
+            for edge in 0..navmesh.polygon_vertices(polygon).len() {
+                let is_boundary = navmesh.internal_neighbor(polygon, edge).is_none();
+                assert_eq!(navmesh.is_solid_edge(polygon, edge), is_boundary);
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use glam::Vec3;
+
+use crate::PolygonNavmesh;
+
+/// The adjacency of the polygons of a [`PolygonNavmesh`] as a plain weighted graph.
+///
+/// Every polygon is a node, walkable or not, and every shared edge is a pair of directed edges annotated
+/// with the width of the portal between the polygons. Nothing about the graph assumes agents walking over it,
+/// so it can be reused for e.g. sound propagation, where the portal width decides how much sound passes through.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct PolygonGraph {
+    /// The centroid of every polygon, indexed by polygon.
+    pub nodes: Vec<Vec3>,
+    /// The edges of all nodes, grouped by the node they start at.
+    pub edges: Vec<PolygonGraphEdge>,
+    /// The edges of node `i` are `edges[edge_offsets[i]..edge_offsets[i + 1]]`. `[Size: nodes.len() + 1]`
+    pub edge_offsets: Vec<usize>,
+}
+
+/// A directed edge of a [`PolygonGraph`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct PolygonGraphEdge {
+    /// The node the edge leads to.
+    pub to: usize,
+    /// The length of the edge shared by both polygons. `[Units: wu]`
+    pub portal_width: f32,
+    /// The distance between the centroids of both polygons. `[Units: wu]`
+    pub distance: f32,
+}
+
+impl PolygonNavmesh {
+    /// Exports the adjacency of the polygons as a [`PolygonGraph`].
+    ///
+    /// Portals to neighboring tiles are not included.
+    pub fn polygon_graph(&self) -> PolygonGraph {
+        let polygon_count = self.polygon_count();
+        let nodes: Vec<Vec3> = (0..polygon_count)
+            .map(|polygon| {
+                let vertex_count = self.polygon_vertices(polygon).len().max(1);
+                self.polygon_world_vertices(polygon).sum::<Vec3>() / vertex_count as f32
+            })
+            .collect();
+
+        let mut edges = Vec::new();
+        let mut edge_offsets = Vec::with_capacity(polygon_count + 1);
+        for polygon in 0..polygon_count {
+            edge_offsets.push(edges.len());
+            let vertices: Vec<Vec3> = self.polygon_world_vertices(polygon).collect();
+            for edge in 0..vertices.len() {
+                let Some(neighbor) = self.internal_neighbor(polygon, edge) else {
+                    continue;
+                };
+                let next = vertices[(edge + 1) % vertices.len()];
+                edges.push(PolygonGraphEdge {
+                    to: neighbor,
+                    portal_width: vertices[edge].distance(next),
+                    distance: nodes[polygon].distance(nodes[neighbor]),
+                });
+            }
+        }
+        edge_offsets.push(edges.len());
+
+        PolygonGraph {
+            nodes,
+            edges,
+            edge_offsets,
+        }
+    }
+}
+
+impl PolygonGraph {
+    /// Returns the edges starting at `node`.
+    #[inline]
+    pub fn neighbors(&self, node: usize) -> &[PolygonGraphEdge] {
+        &self.edges[self.edge_offsets[node]..self.edge_offsets[node + 1]]
+    }
+
+    /// Returns the number of edges between `start` and every node, or `None` for nodes that can not be reached.
+    pub fn hops(&self, start: usize) -> Vec<Option<u32>> {
+        let mut hops = vec![None; self.nodes.len()];
+        hops[start] = Some(0);
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            let next_hops = hops[node].map(|hops| hops + 1);
+            for edge in self.neighbors(node) {
+                if hops[edge.to].is_none() {
+                    hops[edge.to] = next_hops;
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+        hops
+    }
+
+    /// Returns the cheapest total cost from `start` to every node, where `cost` returns the cost of traversing an edge.
+    ///
+    /// Nodes that can not be reached, or only at a cost above `max_cost`, get [`f32::INFINITY`].
+    /// `cost` must not return negative values. Returning [`f32::INFINITY`] blocks an edge.
+    pub fn costs_from(
+        &self,
+        start: usize,
+        max_cost: f32,
+        cost: impl Fn(usize, &PolygonGraphEdge) -> f32,
+    ) -> Vec<f32> {
+        let mut costs = vec![f32::INFINITY; self.nodes.len()];
+        costs[start] = 0.0;
+        let mut open = BinaryHeap::from([OpenNode {
+            cost: 0.0,
+            node: start,
+        }]);
+        while let Some(OpenNode {
+            cost: current,
+            node,
+        }) = open.pop()
+        {
+            if current > costs[node] {
+                // A cheaper way to this node was found after it was queued.
+                continue;
+            }
+            for edge in self.neighbors(node) {
+                let next = current + cost(node, edge);
+                if next <= max_cost && next < costs[edge.to] {
+                    costs[edge.to] = next;
+                    open.push(OpenNode {
+                        cost: next,
+                        node: edge.to,
+                    });
+                }
+            }
+        }
+        costs
+    }
+}
+
+/// A node in the open list of [`PolygonGraph::costs_from`] and [`NavmeshQuery::find_path`](crate::NavmeshQuery::find_path),
+/// ordered so that the cheapest one is popped first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct OpenNode {
+    pub(crate) cost: f32,
+    pub(crate) node: usize,
+}
+
+impl Eq for OpenNode {}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    #[test]
+    fn exports_and_traverses_graph() {
+        let grid = GridNavmesh::parse(
+            "
+            a..#d
+            .#b..
+            ",
+        );
+        let graph = grid.navmesh.polygon_graph();
+        let (a, b, d) = (grid.polygon('a'), grid.polygon('b'), grid.polygon('d'));
+        assert_eq!(graph.nodes[a], Vec3::new(0.5, 0.0, 0.5));
+        assert_eq!(graph.neighbors(a).len(), 2);
+        assert!(
+            graph
+                .neighbors(a)
+                .iter()
+                .all(|edge| edge.portal_width == 1.0 && edge.distance == 1.0)
+        );
+
+        let hops = graph.hops(a);
+        assert_eq!(hops[a + 2], Some(2));
+        assert_eq!(hops[b], Some(3));
+        assert_eq!(graph.hops(b)[d], Some(3));
+
+        // Sound that loses half its volume per wu and is inaudible after losing all of it.
+        let costs = graph.costs_from(b, 1.0, |_, edge| edge.distance * 0.5);
+        assert_eq!(costs[b + 1], 0.5);
+        assert_eq!(costs[b + 2], 1.0);
+        assert_eq!(costs[d], f32::INFINITY);
+    }
+}
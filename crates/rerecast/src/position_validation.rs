@@ -0,0 +1,231 @@
+//! Bulk validation of candidate positions against a [`PolygonNavmesh`], e.g. for procedural spawners.
+
+use glam::{Vec3, Vec3Swizzles as _};
+
+use crate::{
+    Aabb3d, PolygonNavmesh, bv_tree::BvTree, math::distance_squared_between_point_and_line_vec2,
+};
+
+/// The constraints a position must satisfy to pass [`PolygonNavmesh::validate_positions`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionConstraints {
+    /// The maximum vertical distance between a position and the navmesh surface below or above it
+    /// for the position to be considered on the navmesh. `[Limit: >= 0] [Units: wu]`
+    pub max_height_difference: f32,
+    /// The minimum horizontal distance a position must keep to the boundary edges of the navmesh,
+    /// e.g. the radius of the agent that will be spawned there. `[Limit: >= 0] [Units: wu]`
+    ///
+    /// A value of zero disables the check.
+    pub min_edge_distance: f32,
+    /// If set, positions are only valid if they lie on the island with this id.
+    /// See [`PolygonNavmesh::islands`].
+    pub island: Option<u32>,
+}
+
+impl Default for PositionConstraints {
+    fn default() -> Self {
+        Self {
+            max_height_difference: 0.5,
+            min_edge_distance: 0.0,
+            island: None,
+        }
+    }
+}
+
+/// The result of validating a single position with [`PolygonNavmesh::validate_positions`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionValidation {
+    /// The index of the polygon the position lies on, if any.
+    pub polygon: Option<usize>,
+    /// The position projected onto the surface of [`Self::polygon`], if any.
+    pub surface_position: Option<Vec3>,
+    /// The horizontal distance to the closest boundary edge of the navmesh,
+    /// if one is closer than [`PositionConstraints::min_edge_distance`].
+    pub edge_distance: Option<f32>,
+    /// The island [`Self::polygon`] belongs to, if any.
+    pub island: Option<u32>,
+    /// The first constraint the position failed, or `None` if the position is valid.
+    pub failure: Option<PositionValidationFailure>,
+}
+
+impl PositionValidation {
+    /// Returns whether the position satisfied all constraints.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// The reason a position failed [`PolygonNavmesh::validate_positions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PositionValidationFailure {
+    /// No polygon lies within [`PositionConstraints::max_height_difference`] of the position.
+    OffMesh,
+    /// The position is closer to a boundary edge than [`PositionConstraints::min_edge_distance`].
+    TooCloseToEdge,
+    /// The position is not on the island given by [`PositionConstraints::island`].
+    WrongIsland,
+}
+
+impl PolygonNavmesh {
+    /// Checks a batch of candidate positions for whether they lie on the navmesh, keep enough distance to its boundary,
+    /// and belong to the requested island.
+    ///
+    /// The spatial acceleration structure and island assignment are computed once and shared between all positions,
+    /// so prefer this over validating positions one by one.
+    ///
+    /// Returns one [`PositionValidation`] per position, in the same order as `positions`.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn validate_positions(
+        &self,
+        positions: &[Vec3],
+        constraints: &PositionConstraints,
+    ) -> Vec<PositionValidation> {
+        let tree = BvTree::new(self);
+        let islands = self.islands();
+        positions
+            .iter()
+            .map(|position| self.validate_position(*position, constraints, &tree, &islands))
+            .collect()
+    }
+
+    fn validate_position(
+        &self,
+        position: Vec3,
+        constraints: &PositionConstraints,
+        tree: &BvTree,
+        islands: &[u32],
+    ) -> PositionValidation {
+        let mut validation = PositionValidation {
+            polygon: None,
+            surface_position: None,
+            edge_distance: None,
+            island: None,
+            failure: None,
+        };
+
+        let Some((polygon, height)) =
+            self.locate_polygon(tree, position, constraints.max_height_difference)
+        else {
+            validation.failure = Some(PositionValidationFailure::OffMesh);
+            return validation;
+        };
+        validation.polygon = Some(polygon);
+        validation.surface_position = Some(Vec3::new(position.x, height, position.z));
+        let island = islands[polygon];
+        validation.island = Some(island);
+
+        if constraints.min_edge_distance > 0.0 {
+            validation.edge_distance = self.closest_boundary_distance(position, constraints, tree);
+            if validation.edge_distance.is_some() {
+                validation.failure = Some(PositionValidationFailure::TooCloseToEdge);
+                return validation;
+            }
+        }
+
+        if constraints
+            .island
+            .is_some_and(|required| required != island)
+        {
+            validation.failure = Some(PositionValidationFailure::WrongIsland);
+        }
+        validation
+    }
+
+    /// Returns the horizontal distance to the closest boundary edge within [`PositionConstraints::min_edge_distance`].
+    fn closest_boundary_distance(
+        &self,
+        position: Vec3,
+        constraints: &PositionConstraints,
+        tree: &BvTree,
+    ) -> Option<f32> {
+        let extent = Vec3::new(
+            constraints.min_edge_distance,
+            constraints.max_height_difference,
+            constraints.min_edge_distance,
+        );
+        let search_aabb = Aabb3d {
+            min: position - extent,
+            max: position + extent,
+        };
+        let max_distance_squared = constraints.min_edge_distance * constraints.min_edge_distance;
+        let mut closest_distance_squared: Option<f32> = None;
+        for polygon in tree.query_aabb(&search_aabb) {
+            let vertices = self.polygon_vertices(polygon);
+            for (edge, vertex) in vertices.iter().enumerate() {
+                if self.internal_neighbor(polygon, edge).is_some() {
+                    continue;
+                }
+                let next_vertex = vertices[(edge + 1) % vertices.len()];
+                let a = self.world_vertex(*vertex);
+                let b = self.world_vertex(next_vertex);
+                let distance_squared =
+                    distance_squared_between_point_and_line_vec2(position.xz(), (a.xz(), b.xz()));
+                if distance_squared < max_distance_squared
+                    && closest_distance_squared.is_none_or(|closest| distance_squared < closest)
+                {
+                    closest_distance_squared = Some(distance_squared);
+                }
+            }
+        }
+        closest_distance_squared.map(f32::sqrt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::U16Vec3;
+
+    use super::*;
+    use crate::test_utils::quad_navmesh;
+
+    /// Two disconnected 10x10 quads, 1 unit apart on the x-axis.
+    fn two_quads() -> PolygonNavmesh {
+        quad_navmesh(
+            vec![
+                U16Vec3::new(0, 0, 0),
+                U16Vec3::new(0, 0, 10),
+                U16Vec3::new(10, 0, 10),
+                U16Vec3::new(10, 0, 0),
+                U16Vec3::new(11, 0, 0),
+                U16Vec3::new(11, 0, 10),
+                U16Vec3::new(21, 0, 10),
+                U16Vec3::new(21, 0, 0),
+            ],
+            &[[0, 1, 2, 3], [4, 5, 6, 7]],
+        )
+    }
+
+    #[test]
+    fn validates_positions() {
+        let mesh = two_quads();
+        let constraints = PositionConstraints {
+            max_height_difference: 0.5,
+            min_edge_distance: 1.0,
+            island: Some(0),
+        };
+        let results = mesh.validate_positions(
+            &[
+                Vec3::new(5.0, 0.2, 5.0),
+                Vec3::new(5.0, 3.0, 5.0),
+                Vec3::new(9.5, 0.0, 5.0),
+                Vec3::new(16.0, 0.0, 5.0),
+            ],
+            &constraints,
+        );
+        assert!(results[0].is_valid());
+        assert_eq!(results[0].polygon, Some(0));
+        assert_eq!(results[0].surface_position, Some(Vec3::new(5.0, 0.0, 5.0)));
+        assert_eq!(results[1].failure, Some(PositionValidationFailure::OffMesh));
+        assert_eq!(
+            results[2].failure,
+            Some(PositionValidationFailure::TooCloseToEdge)
+        );
+        assert_eq!(results[2].edge_distance, Some(0.5));
+        assert_eq!(
+            results[3].failure,
+            Some(PositionValidationFailure::WrongIsland)
+        );
+        assert_eq!(results[3].island, Some(1));
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
 
 impl Heightfield {
     /// Adds the walkable flag to spans which are adjacent to a walkable span and the height difference is small enough for the agent to walk over.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn filter_low_hanging_walkable_obstacles(&mut self, walkable_climb: u16) {
         for z in 0..self.height {
             for x in 0..self.width {
@@ -41,6 +42,7 @@ impl Heightfield {
     }
 
     /// Removes the walkable flag from spans which are adjacent to a ledge.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn filter_ledge_spans(&mut self, walkable_height: u16, walkable_climb: u16) {
         // Mark spans that are adjacent to a ledge as unwalkable..
         for z in 0..self.height {
@@ -163,6 +165,7 @@ impl Heightfield {
     const MAX_HEIGHTFIELD_HEIGHT: u16 = u16::MAX;
 
     /// Removes the walkable flag from spans which do not have enough space above them for the agent to stand there.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn filter_walkable_low_height_spans(&mut self, walkable_height: u16) {
         // Remove walkable flag from spans which do not have enough
         // space above them for the agent to stand there.
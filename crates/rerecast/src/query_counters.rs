@@ -0,0 +1,34 @@
+use core::ops::AddAssign;
+
+/// Counts the work done by navmesh queries, e.g. to monitor pathfinding cost on a server
+/// and catch pathological queries issued by scripted AI.
+///
+/// Pass a [`QueryCounters`] to the `*_with_counters` variant of a query to have it accumulate its work into it.
+/// The counters are never reset by the queries themselves, so one instance can either be used per query
+/// or be shared across many queries to aggregate their cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueryCounters {
+    /// The number of nodes of the search structure that were visited, e.g. [`BvTree`](crate::BvTree) nodes.
+    pub nodes_expanded: u32,
+    /// The number of polygons whose geometry was inspected.
+    pub polygons_touched: u32,
+    /// The number of polygon-to-polygon steps taken by raycasts.
+    pub raycast_steps: u32,
+}
+
+impl QueryCounters {
+    /// Resets all counters to zero.
+    #[inline]
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl AddAssign for QueryCounters {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        self.nodes_expanded += rhs.nodes_expanded;
+        self.polygons_touched += rhs.polygons_touched;
+        self.raycast_steps += rhs.raycast_steps;
+    }
+}
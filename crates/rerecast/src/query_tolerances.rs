@@ -0,0 +1,61 @@
+use crate::PolygonNavmesh;
+
+/// The geometric tolerances used by a [`NavmeshQuery`](crate::NavmeshQuery).
+///
+/// Fixed epsilons that work for a world measured in meters break down for worlds built at a much smaller or larger scale,
+/// e.g. a tabletop game with 0.1 wu agents or a space game spanning 10k wu. [`QueryTolerances::for_navmesh`]
+/// derives tolerances from the cell size of the navmesh, which already reflects the scale of the world.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueryTolerances {
+    /// How far outside a polygon on the xz-plane a point may lie and still count as lying over it,
+    /// e.g. for points exactly on a shared edge that rounding pushes out of both polygons. `[Limit: >= 0] [Units: wu]`
+    pub point_in_polygon: f32,
+    /// How far above or below the surface of a polygon a point may lie and still count as lying on it,
+    /// so that nearest polygon queries treat all such polygons as equally near. `[Limit: >= 0] [Units: wu]`
+    pub height: f32,
+    /// Below this magnitude, the cross product of a ray and a polygon edge counts as zero during raycasts,
+    /// i.e. the ray runs parallel to the edge. `[Limit: >= 0] [Units: wu²]`
+    pub raycast_edge: f32,
+}
+
+impl Default for QueryTolerances {
+    /// The tolerances used by the queries on [`PolygonNavmesh`] itself.
+    fn default() -> Self {
+        Self {
+            point_in_polygon: 0.0,
+            height: 0.0,
+            raycast_edge: 1e-6,
+        }
+    }
+}
+
+impl QueryTolerances {
+    /// Derives tolerances from the resolution of `navmesh`:
+    /// a thousandth of a cell on the xz-plane, one cell vertically, and the raycast epsilon scaled with the cell area.
+    pub fn for_navmesh(navmesh: &PolygonNavmesh) -> Self {
+        let cell_size = navmesh.cell_size;
+        Self {
+            point_in_polygon: cell_size * 1e-3,
+            height: navmesh.cell_height,
+            raycast_edge: Self::default().raycast_edge * cell_size * cell_size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    #[test]
+    fn scales_with_cell_size() {
+        let mut grid = GridNavmesh::parse("a");
+        let unit = QueryTolerances::for_navmesh(&grid.navmesh);
+        grid.navmesh.cell_size = 0.01;
+        let tiny = QueryTolerances::for_navmesh(&grid.navmesh);
+        assert!(tiny.point_in_polygon < unit.point_in_polygon);
+        assert!(tiny.raycast_edge < unit.raycast_edge);
+        assert_eq!(unit.raycast_edge, QueryTolerances::default().raycast_edge);
+    }
+}
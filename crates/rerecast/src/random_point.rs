@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use glam::{IVec2, Vec3, Vec3Swizzles as _};
+use rand_core::RngCore;
+
+use crate::{PolygonNavmesh, QueryFilter};
+
+impl PolygonNavmesh {
+    /// Picks a uniformly distributed random point on the walkable polygons of the navmesh.
+    ///
+    /// All randomness is drawn from `rng`, so the same generator state always produces the same point,
+    /// which keeps tests reproducible and lockstep simulations deterministic.
+    ///
+    /// Returns the index of the polygon containing the point and the point itself,
+    /// or `None` if the navmesh has no walkable polygon with a non-zero area.
+    ///
+    /// This is a port of Detour's `dtNavMeshQuery::findRandomPoint`.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn random_point(&self, rng: &mut impl RngCore) -> Option<(usize, Vec3)> {
+        // Reservoir sampling weighted by polygon area, so that a single pass suffices.
+        let mut selected = None;
+        let mut total_area = 0.0;
+        for polygon in 0..self.polygon_count() {
+            if !self.areas[polygon].is_walkable() {
+                continue;
+            }
+            let area = self.polygon_area(polygon);
+            if area <= 0.0 {
+                continue;
+            }
+            total_area += area;
+            if random_unit(rng) * total_area <= area {
+                selected = Some(polygon);
+            }
+        }
+        let polygon = selected?;
+
+        let vertices: Vec<Vec3> = self.polygon_world_vertices(polygon).collect();
+        let point = random_point_in_convex_polygon(&vertices, random_unit(rng), random_unit(rng));
+        let height = self.polygon_height(polygon, point).unwrap_or(point.y);
+        Some((polygon, Vec3::new(point.x, height, point.z)))
+    }
+
+    /// The number of candidates [`Self::sample_points_poisson`] tries per point that fits into the walkable area.
+    pub const POISSON_ATTEMPTS: f32 = 30.0;
+
+    /// Scatters points evenly across the polygons passing `filter`, so that no two points are closer than `radius`,
+    /// e.g. to place ambient NPCs, loot or vegetation only where agents can walk. `[Limit: > 0] [Units: wu]`
+    ///
+    /// Candidates are drawn uniformly like in [`Self::random_point`] and kept if they are far enough from all points kept before,
+    /// until [`Self::POISSON_ATTEMPTS`] times as many candidates as disks of `radius` fit into the walkable area were tried.
+    /// The points thus cover the navmesh densely without any visible pattern. Distances are measured in 3D,
+    /// so floors above each other are sampled independently.
+    ///
+    /// All randomness is drawn from `rng`, see [`Self::random_point`]. Returns the index of the polygon containing each point
+    /// and the point itself, in the order they were placed.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn sample_points_poisson(
+        &self,
+        radius: f32,
+        filter: &QueryFilter,
+        rng: &mut impl RngCore,
+    ) -> Vec<(usize, Vec3)> {
+        // The accumulated area of all polygons up to and including each candidate polygon.
+        let mut polygons = Vec::new();
+        let mut total_area = 0.0;
+        for polygon in 0..self.polygon_count() {
+            if !filter.passes(self, polygon) {
+                continue;
+            }
+            let area = self.polygon_area(polygon);
+            if area > 0.0 {
+                total_area += area;
+                polygons.push((polygon, total_area));
+            }
+        }
+        if polygons.is_empty() || radius.is_nan() || radius <= 0.0 {
+            return Vec::new();
+        }
+
+        let attempts = (total_area / (radius * radius) * Self::POISSON_ATTEMPTS).ceil() as usize;
+        // Points bucketed by the cell of size `radius` they lie in on the xz-plane,
+        // so only the 3x3 cells around a candidate need to be checked.
+        let mut grid: HashMap<IVec2, Vec<Vec3>> = HashMap::new();
+        let cell = |point: Vec3| (point.xz() / radius).floor().as_ivec2();
+        let mut points = Vec::new();
+        for _ in 0..attempts {
+            let threshold = random_unit(rng) * total_area;
+            let index = polygons
+                .partition_point(|(_, accumulated)| *accumulated <= threshold)
+                .min(polygons.len() - 1);
+            let polygon = polygons[index].0;
+            let vertices: Vec<Vec3> = self.polygon_world_vertices(polygon).collect();
+            let mut point =
+                random_point_in_convex_polygon(&vertices, random_unit(rng), random_unit(rng));
+            point.y = self.polygon_height(polygon, point).unwrap_or(point.y);
+
+            let center = cell(point);
+            let too_close = (-1..=1).any(|z| {
+                (-1..=1).any(|x| {
+                    grid.get(&(center + IVec2::new(x, z)))
+                        .is_some_and(|cell| cell.iter().any(|other| other.distance(point) < radius))
+                })
+            });
+            if !too_close {
+                grid.entry(center).or_default().push(point);
+                points.push((polygon, point));
+            }
+        }
+        points
+    }
+}
+
+/// Returns a random number in `[0, 1)`.
+#[inline]
+fn random_unit(rng: &mut impl RngCore) -> f32 {
+    // Use the 24 most significant bits, which is all an f32 can represent exactly.
+    (rng.next_u32() >> 8) as f32 / (1 << 24) as f32
+}
+
+#[inline]
+fn triangle_area_2d(a: Vec3, b: Vec3, c: Vec3) -> f32 {
+    let ab = (b - a).xz();
+    let ac = (c - a).xz();
+    ab.perp_dot(ac).abs() * 0.5
+}
+
+/// Picks a point on a convex polygon from the two random numbers `s` and `t` in `[0, 1)`.
+///
+/// Port of Detour's `dtRandomPointInConvexPoly`.
+fn random_point_in_convex_polygon(vertices: &[Vec3], s: f32, t: f32) -> Vec3 {
+    let areas: Vec<f32> = (2..vertices.len())
+        .map(|i| triangle_area_2d(vertices[0], vertices[i - 1], vertices[i]))
+        .collect();
+    let total_area: f32 = areas.iter().sum();
+
+    // Find the triangle of the fan that `s` falls into.
+    let threshold = s * total_area;
+    let mut accumulated = 0.0;
+    let mut triangle = areas.len() - 1;
+    let mut u = 1.0;
+    for (i, &area) in areas.iter().enumerate() {
+        if threshold >= accumulated && threshold < accumulated + area {
+            triangle = i;
+            u = (threshold - accumulated) / area;
+            break;
+        }
+        accumulated += area;
+    }
+
+    let v = t.sqrt();
+    let a = 1.0 - v;
+    let b = (1.0 - u) * v;
+    let c = u * v;
+    vertices[0] * a + vertices[triangle + 1] * b + vertices[triangle + 2] * c
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    /// A xorshift generator, so the tests don't depend on a specific `rand` version.
+    struct XorShift(u64);
+
+    impl RngCore for XorShift {
+        fn next_u32(&mut self) -> u32 {
+            (self.next_u64() >> 32) as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            rand_core::impls::fill_bytes_via_next(self, dst);
+        }
+    }
+
+    #[test]
+    fn random_points_are_reproducible_and_on_walkable_polygons() {
+        let grid = GridNavmesh::parse(
+            "
+            ..#..
+            0.#.0
+            ",
+        );
+        let points: Vec<_> = (0..32)
+            .scan(XorShift(0x2545_f491_4f6c_dd1d), |rng, _| {
+                grid.navmesh.random_point(rng)
+            })
+            .collect();
+        assert_eq!(points.len(), 32);
+        for &(polygon, point) in &points {
+            assert!(grid.navmesh.areas[polygon].is_walkable());
+            assert!(grid.navmesh.polygon_height(polygon, point).is_some());
+        }
+
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        assert_eq!(grid.navmesh.random_point(&mut rng), Some(points[0]));
+    }
+
+    #[test]
+    fn poisson_points_keep_their_distance() {
+        let grid = GridNavmesh::parse(
+            "
+            ......11
+            ......11
+            ......11
+            ......11
+            ......11
+            ......11
+            ",
+        );
+        let mut filter = QueryFilter::default();
+        filter.set_area_cost(crate::AreaType::new(1), f32::INFINITY);
+        let sample = |filter: &QueryFilter, seed| {
+            grid.navmesh
+                .sample_points_poisson(1.0, filter, &mut XorShift(seed))
+        };
+
+        let points = sample(&filter, 0x2545_f491_4f6c_dd1d);
+        // At least the points of a square grid with a spacing of twice the radius fit in.
+        assert!(points.len() >= 9, "{}", points.len());
+        for (i, &(polygon, point)) in points.iter().enumerate() {
+            assert!(filter.passes(&grid.navmesh, polygon));
+            assert!(point.x <= 6.0);
+            assert!(grid.navmesh.polygon_height(polygon, point).is_some());
+            for &(_, other) in &points[i + 1..] {
+                assert!(point.distance(other) >= 1.0);
+            }
+        }
+        assert_eq!(sample(&filter, 0x2545_f491_4f6c_dd1d), points);
+
+        filter.set_area_cost(crate::AreaType::DEFAULT_WALKABLE, f32::INFINITY);
+        assert!(sample(&filter, 7).is_empty());
+    }
+}
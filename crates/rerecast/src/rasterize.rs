@@ -1,47 +1,185 @@
-//! Contains methods for rasterizing triangles of a [`TrimeshedCollider`] into a [`Heightfield`].
+//! Contains methods for rasterizing triangles of a [`TriMesh`] into a [`Heightfield`].
 
-use glam::Vec3A;
-use std::fmt::Display;
+use glam::{Vec2, Vec3, Vec3A, Vec3Swizzles as _};
+use std::{fmt::Display, ops::Range};
 use thiserror::Error;
 
 use crate::{
-    TriMesh,
-    heightfield::{Heightfield, SpanInsertion, SpanInsertionError},
-    math::TriangleVertices as _,
+    BuildWarning, SpanSource, TriMesh,
+    heightfield::{Heightfield, SpanInsertion, SpanInsertionError, SpanOverlap},
+    math::{Aabb2d, TriangleVertices as _, point_in_poly},
     span::{AreaType, Span, SpanBuilder},
 };
 
 impl Heightfield {
     /// Rasterizes the triangles of a [`TriMesh`] into a [`Heightfield`].
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn rasterize_triangles(
         &mut self,
         trimesh: &TriMesh,
         walkable_climb: u16,
     ) -> Result<(), RasterizationError> {
-        for (i, triangle) in trimesh.indices.iter().enumerate() {
-            let triangle = [
-                trimesh.vertices[triangle[0] as usize],
-                trimesh.vertices[triangle[1] as usize],
-                trimesh.vertices[triangle[2] as usize],
-            ];
-            let area_type = trimesh.area_types[i];
-            self.rasterize_triangle(triangle, area_type, walkable_climb)?;
+        let triangles = trimesh.triangles().zip(trimesh.area_types.iter().copied());
+        self.rasterize_triangle_stream(triangles, walkable_climb)
+    }
+
+    /// Rasterizes a stream of triangles and their area types into a [`Heightfield`].
+    ///
+    /// Unlike [`Heightfield::rasterize_triangles`], this does not require the geometry to be collected into a [`TriMesh`] first,
+    /// so procedural generators can produce triangles on the fly without materializing all of them at once.
+    /// With [`Heightfield::record_sources`], the position of a triangle in the stream is recorded as its source index.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn rasterize_triangle_stream(
+        &mut self,
+        triangles: impl IntoIterator<Item = ([Vec3A; 3], AreaType)>,
+        walkable_climb: u16,
+    ) -> Result<(), RasterizationError> {
+        self.rasterize_triangle_stream_from(triangles, 0, walkable_climb)?;
+        self.warn_about_area_overwrites();
+        Ok(())
+    }
+
+    /// Like [`Self::rasterize_triangle_stream`], but the stream continues an earlier one at source index `first_source`,
+    /// so that a mesh can be rasterized in several chunks.
+    pub(crate) fn rasterize_triangle_stream_from(
+        &mut self,
+        triangles: impl IntoIterator<Item = ([Vec3A; 3], AreaType)>,
+        first_source: u32,
+        walkable_climb: u16,
+    ) -> Result<(), RasterizationError> {
+        let mut triangles = triangles.into_iter().peekable();
+        if first_source == 0 && triangles.peek().is_some() {
+            self.warn_if_span_height_clamped();
+        }
+        let mut clipped = Vec::new();
+        for (i, (triangle, area_type)) in triangles.enumerate() {
+            self.rasterize_triangle_from_source(
+                triangle,
+                area_type,
+                walkable_climb,
+                Some(first_source + i as u32),
+                &mut clipped,
+            )?;
         }
         Ok(())
     }
 
+    /// Emits a [`BuildWarning::AreaOverwritten`] for every pair of areas overwritten since the last call,
+    /// naming the areas through [`Heightfield::area_registry`].
+    pub(crate) fn warn_about_area_overwrites(&mut self) {
+        let mut area_overwrites = core::mem::take(&mut self.area_overwrites);
+        area_overwrites.emit(self.area_registry.as_ref());
+    }
+
+    /// Emits [`BuildWarning::SpanHeightClamped`] if the heightfield is taller than a span can represent.
+    pub(crate) fn warn_if_span_height_clamped(&self) {
+        let cells = ((self.aabb.max.y - self.aabb.min.y) / self.cell_height).ceil() as usize;
+        if cells > Span::MAX_HEIGHT as usize {
+            BuildWarning::SpanHeightClamped {
+                cells,
+                max: Span::MAX_HEIGHT,
+            }
+            .emit();
+        }
+    }
+
     /// Rasterizes a triangle into a [`Heightfield`].
+    ///
+    /// The triangle is not recorded in [`Heightfield::sources`], since it has no index in a source mesh.
+    ///
+    /// `walkable_climb` is used as Recast's `flagMergeThreshold`: when the span of the triangle merges with a span
+    /// whose top lies within `walkable_climb` of its own, the merged span takes the higher area of the two,
+    /// so a walkable floor is not made unwalkable by e.g. an unwalkable triangle lying just above it. `[Units: vx]`
+    ///
+    /// Walkable areas overwritten this way are reported as [`BuildWarning::AreaOverwritten`] after the next rasterized stream of triangles.
     pub fn rasterize_triangle(
         &mut self,
         triangle: [Vec3A; 3],
         area_type: AreaType,
-        flag_merge_threshold: u16,
+        walkable_climb: u16,
+    ) -> Result<(), RasterizationError> {
+        self.rasterize_triangle_from_source(
+            triangle,
+            area_type,
+            walkable_climb,
+            None,
+            &mut Vec::new(),
+        )
+    }
+
+    fn rasterize_triangle_from_source(
+        &mut self,
+        triangle: [Vec3A; 3],
+        area_type: AreaType,
+        walkable_climb: u16,
+        source: Option<u32>,
+        clipped: &mut Vec<ClippedSpan>,
+    ) -> Result<(), RasterizationError> {
+        clipped.clear();
+        self.clip_triangle(triangle, area_type, 0..self.height, clipped)?;
+        for ClippedSpan {
+            x,
+            z,
+            span,
+            surface,
+        } in clipped.drain(..)
+        {
+            self.add_span(SpanInsertion {
+                x,
+                z,
+                span,
+                flag_merge_threshold: walkable_climb,
+                overlap: SpanOverlap::Merge,
+            })?;
+            if let (true, Some(triangle)) = (self.record_sources, source) {
+                self.sources.push(SpanSource {
+                    position: surface,
+                    triangle,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Clips a triangle against the grid and appends the spans it produces in the rows `rows` to `clipped`,
+    /// ordered by row and then by column.
+    ///
+    /// Only reads the heightfield, so that separate rows can be clipped on separate threads.
+    /// The triangle is clipped from its first row regardless of `rows`, which keeps the spans bit-for-bit identical
+    /// no matter which rows are requested.
+    pub(crate) fn clip_triangle(
+        &self,
+        triangle: [Vec3A; 3],
+        area_type: AreaType,
+        rows: Range<u16>,
+        clipped: &mut Vec<ClippedSpan>,
     ) -> Result<(), RasterizationError> {
         let aabb = triangle.aabb();
         // If the triangle does not touch the bounding box of the heightfield, skip the triangle.
         if !self.aabb.intersects(&aabb) {
             return Ok(());
         }
+        // Skip triangles that are completely excluded.
+        if self
+            .exclusion_volumes
+            .iter()
+            .any(|volume| volume.contains_triangle(&triangle))
+        {
+            return Ok(());
+        }
+        // Skip triangles that don't touch the bounding box of the boundary.
+        if let Some(boundary) = &self.boundary {
+            let Some(boundary_aabb) = Aabb2d::from_verts(boundary) else {
+                // An empty boundary contains nothing.
+                return Ok(());
+            };
+            if !boundary_aabb.intersects(&Aabb2d {
+                min: aabb.min.xz(),
+                max: aabb.max.xz(),
+            }) {
+                return Ok(());
+            }
+        }
 
         let inverse_cell_size = 1.0 / self.cell_size;
         let inverse_cell_height = 1.0 / self.cell_height;
@@ -81,6 +219,9 @@ impl Heightfield {
         let mut nv_in = 3_u8;
 
         for z in z0..=z1 {
+            if z as i32 >= rows.end as i32 {
+                break;
+            }
             // Clip polygon to row. Store the remaining polygon as well
             let cell_z = self.aabb.min[2] + z as f32 * self.cell_size;
             divide_poly(
@@ -95,7 +236,7 @@ impl Heightfield {
             )?;
             std::mem::swap(&mut in_tri, &mut p1);
 
-            if nv_row < 3 || z < 0 {
+            if nv_row < 3 || (z as i32) < rows.start as i32 {
                 continue;
             }
 
@@ -135,6 +276,15 @@ impl Heightfield {
                     continue;
                 }
 
+                // Skip columns outside of the boundary.
+                if let Some(boundary) = &self.boundary {
+                    let center =
+                        Vec2::new(cx + self.cell_size * 0.5, cell_z + self.cell_size * 0.5);
+                    if !point_in_poly(&center, boundary) {
+                        continue;
+                    }
+                }
+
                 // Calculate min and max of the span.
                 let mut span_min = p1[0].y;
                 let mut span_max = span_min;
@@ -162,24 +312,47 @@ impl Heightfield {
                     .clamp(span_min_cell_index as i32 + 1, Span::MAX_HEIGHT as i32)
                     as u16;
 
-                self.add_span(SpanInsertion {
+                let mut span = SpanBuilder {
+                    min: span_min_cell_index,
+                    max: span_max_cell_index,
+                    area: area_type,
+                    next: None,
+                }
+                .build();
+                if self.sub_voxel_heights {
+                    let offset = span_max_cell_index as f32 - span_max * inverse_cell_height;
+                    span.top_offset = (offset * 256.0).round().clamp(0.0, u8::MAX as f32) as u8;
+                }
+
+                clipped.push(ClippedSpan {
                     x: x as u16,
                     z: z as u16,
-                    span: SpanBuilder {
-                        min: span_min_cell_index,
-                        max: span_max_cell_index,
-                        area: area_type,
-                        next: None,
-                    }
-                    .build(),
-                    flag_merge_threshold,
-                })?;
+                    span,
+                    surface: Vec3::new(
+                        cx + self.cell_size * 0.5,
+                        self.aabb.min.y + span_max,
+                        cell_z + self.cell_size * 0.5,
+                    ),
+                });
             }
         }
         Ok(())
     }
 }
 
+/// A span produced by [`Heightfield::clip_triangle`].
+#[derive(Debug, Clone)]
+pub(crate) struct ClippedSpan {
+    /// The x-coordinate of the column.
+    pub(crate) x: u16,
+    /// The z-coordinate of the column.
+    pub(crate) z: u16,
+    /// The span to insert into the column.
+    pub(crate) span: Span,
+    /// The world space center of the span's top before snapping, recorded as [`SpanSource::position`].
+    pub(crate) surface: Vec3,
+}
+
 /// Errors that can occur when rasterizing a triangle into a heightfield with [`Heightfield::populate_from_trimesh`].
 #[derive(Error, Debug)]
 pub enum RasterizationError {
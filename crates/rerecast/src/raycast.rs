@@ -0,0 +1,162 @@
+use glam::{Vec2, Vec3, Vec3Swizzles as _};
+
+use crate::{
+    PolygonNavmesh, QueryCounters, QueryTolerances,
+    math::{intersect_segment_polygon_2d, next},
+};
+
+/// The result of [`PolygonNavmesh::raycast`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NavmeshRaycast {
+    /// The indices of the polygons the ray passed through, starting with the start polygon.
+    pub path: Vec<usize>,
+    /// Where the ray hit the boundary of the navmesh, or `None` if it reached its end.
+    pub hit: Option<NavmeshRaycastHit>,
+}
+
+/// A wall hit by [`PolygonNavmesh::raycast`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavmeshRaycastHit {
+    /// The parameter along the ray where the wall was hit, where 0 is the start and 1 is the end of the ray.
+    pub t: f32,
+    /// The normal of the wall that was hit, pointing into the navmesh.
+    pub normal: Vec3,
+    /// The polygon containing the hit wall.
+    pub polygon: usize,
+    /// The index of the hit edge within [`Self::polygon`].
+    pub edge: usize,
+}
+
+impl PolygonNavmesh {
+    /// Casts a ray along the surface of the navmesh from `start` towards `end`, starting in the polygon at index `start_polygon`.
+    ///
+    /// The ray is cast on the xz-plane and walks from polygon to polygon through shared edges
+    /// until it either reaches `end` or hits an edge without a neighbor.
+    /// The y-coordinates of `start` and `end` are ignored.
+    ///
+    /// This is a port of Detour's `dtNavMeshQuery::raycast`.
+    pub fn raycast(&self, start_polygon: usize, start: Vec3, end: Vec3) -> NavmeshRaycast {
+        self.raycast_with_counters(start_polygon, start, end, &mut QueryCounters::default())
+    }
+
+    /// Same as [`PolygonNavmesh::raycast`], but accumulates the work done into `counters`.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn raycast_with_counters(
+        &self,
+        start_polygon: usize,
+        start: Vec3,
+        end: Vec3,
+        counters: &mut QueryCounters,
+    ) -> NavmeshRaycast {
+        self.raycast_where(
+            start_polygon,
+            start,
+            end,
+            counters,
+            &QueryTolerances::default(),
+            |_| true,
+        )
+    }
+
+    /// Same as [`PolygonNavmesh::raycast_with_counters`], but uses the edge epsilon of `tolerances`
+    /// and treats edges leading to polygons for which `accept` returns `false` as walls.
+    pub(crate) fn raycast_where(
+        &self,
+        start_polygon: usize,
+        start: Vec3,
+        end: Vec3,
+        counters: &mut QueryCounters,
+        tolerances: &QueryTolerances,
+        accept: impl Fn(usize) -> bool,
+    ) -> NavmeshRaycast {
+        let start = start.xz();
+        let end = end.xz();
+        let mut raycast = NavmeshRaycast::default();
+        let mut vertices: Vec<Vec2> = Vec::with_capacity(self.max_vertices_per_polygon as usize);
+        let mut t = 0.0_f32;
+        let mut current = start_polygon;
+        loop {
+            counters.raycast_steps += 1;
+            counters.polygons_touched += 1;
+            vertices.clear();
+            vertices.extend(self.polygon_world_vertices(current).map(|v| v.xz()));
+            let Some(intersection) =
+                intersect_segment_polygon_2d(start, end, &vertices, tolerances.raycast_edge)
+            else {
+                // The ray could not hit the polygon, keep the previous result
+                return raycast;
+            };
+            t = t.max(intersection.t_max);
+            raycast.path.push(current);
+
+            let Some(edge) = intersection.edge_max else {
+                // The end of the ray lies inside the polygon
+                return raycast;
+            };
+            if let Some(neighbor) = self
+                .internal_neighbor(current, edge)
+                .filter(|neighbor| accept(*neighbor))
+            {
+                current = neighbor;
+                continue;
+            }
+
+            // Hit a wall
+            let a = vertices[edge];
+            let b = vertices[next(edge, vertices.len())];
+            let direction = b - a;
+            raycast.hit = Some(NavmeshRaycastHit {
+                t,
+                normal: Vec3::new(direction.y, 0.0, -direction.x).normalize_or_zero(),
+                polygon: current,
+                edge,
+            });
+            return raycast;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::two_quads;
+
+    #[test]
+    fn raycast_crosses_polygons_and_hits_walls() {
+        let mesh = two_quads();
+
+        let raycast = mesh.raycast(0, Vec3::new(1.0, 0.0, 2.0), Vec3::new(7.0, 0.0, 2.0));
+        assert_eq!(raycast.path, vec![0, 1]);
+        assert_eq!(raycast.hit, None);
+
+        let raycast = mesh.raycast(0, Vec3::new(2.0, 0.0, 2.0), Vec3::new(10.0, 0.0, 2.0));
+        assert_eq!(raycast.path, vec![0, 1]);
+        let hit = raycast.hit.unwrap();
+        assert_eq!(hit.t, 0.75);
+        assert_eq!(hit.normal, Vec3::NEG_X);
+        assert_eq!((hit.polygon, hit.edge), (1, 2));
+    }
+
+    #[test]
+    fn raycast_counts_steps() {
+        let mesh = two_quads();
+        let mut counters = QueryCounters::default();
+
+        mesh.raycast_with_counters(
+            0,
+            Vec3::new(1.0, 0.0, 2.0),
+            Vec3::new(7.0, 0.0, 2.0),
+            &mut counters,
+        );
+        assert_eq!(counters.raycast_steps, 2);
+        assert_eq!(counters.polygons_touched, 2);
+
+        mesh.raycast_with_counters(
+            0,
+            Vec3::new(1.0, 0.0, 2.0),
+            Vec3::new(3.0, 0.0, 2.0),
+            &mut counters,
+        );
+        assert_eq!(counters.raycast_steps, 3);
+    }
+}
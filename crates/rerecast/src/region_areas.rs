@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use crate::{AreaType, CompactHeightfield, RegionId};
+
+impl CompactHeightfield {
+    /// Sets the area of all walkable spans to [`AreaType::DEFAULT_WALKABLE`] and returns the previous areas of all spans,
+    /// so that [`Self::build_distance_field`] and [`Self::build_regions`] grow regions across area boundaries.
+    ///
+    /// Call [`Self::assign_region_areas`] with the returned areas after building the regions.
+    /// See [`NavmeshConfig::split_area_boundaries`](crate::NavmeshConfig::split_area_boundaries).
+    pub fn merge_walkable_areas(&mut self) -> Vec<AreaType> {
+        let areas = self.areas.clone();
+        for area in &mut self.areas {
+            if area.is_walkable() {
+                *area = AreaType::DEFAULT_WALKABLE;
+            }
+        }
+        areas
+    }
+
+    /// Sets the area of every span that belongs to a region to the area in `areas` covering most spans of its region,
+    /// and restores the area in `areas` of all other spans. Ties go to the lower area.
+    ///
+    /// Every region then has a single area, so the polygons built from it do as well. `[Size: areas.len() == spans.len()]`
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn assign_region_areas(&mut self, areas: &[AreaType]) {
+        let mut counts: HashMap<RegionId, HashMap<AreaType, u32>> = HashMap::new();
+        for (span, area) in self.spans.iter().zip(areas) {
+            if has_own_area(span.region) {
+                *counts
+                    .entry(span.region)
+                    .or_default()
+                    .entry(*area)
+                    .or_default() += 1;
+            }
+        }
+        let region_areas: HashMap<RegionId, AreaType> = counts
+            .into_iter()
+            .filter_map(|(region, counts)| {
+                let (area, _) = counts
+                    .into_iter()
+                    .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))?;
+                Some((region, area))
+            })
+            .collect();
+
+        for ((span, area), original) in self.spans.iter().zip(&mut self.areas).zip(areas) {
+            *area = region_areas.get(&span.region).copied().unwrap_or(*original);
+        }
+    }
+}
+
+/// Whether spans of `region` are given the area of their region by [`CompactHeightfield::assign_region_areas`].
+/// Spans outside any region and in the tile border keep their own area.
+fn has_own_area(region: RegionId) -> bool {
+    region != RegionId::NONE && !region.contains(RegionId::BORDER_REGION)
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec3, Vec2, Vec3A};
+
+    use crate::{ConvexVolume, NavmeshConfig, TriMesh, build_solo_navmesh};
+
+    use super::*;
+
+    #[test]
+    fn splits_polygons_on_area_boundaries_unless_disabled() {
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 0.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2), UVec3::new(0, 2, 3)],
+            area_types: vec![AreaType::NOT_WALKABLE; 2],
+            materials: Vec::new(),
+        };
+        let water = AreaType::new(3);
+        let config = NavmeshConfig {
+            border_size: 0,
+            area_volumes: vec![ConvexVolume {
+                vertices: vec![
+                    Vec2::new(-1.0, -1.0),
+                    Vec2::new(-1.0, 11.0),
+                    Vec2::new(3.0, 11.0),
+                    Vec2::new(3.0, -1.0),
+                ],
+                min_y: -1.0,
+                max_y: 1.0,
+                area: water,
+            }],
+            ..Default::default()
+        };
+
+        let (split, _detail) = build_solo_navmesh(&trimesh, &config).unwrap();
+        assert!(split.areas.contains(&water));
+        for polygon in 0..split.polygon_count() {
+            let aabb = split.polygon_aabb(polygon).unwrap();
+            if split.areas[polygon] == water {
+                assert!(aabb.max.x <= 3.0 + split.cell_size);
+            } else {
+                assert!(aabb.min.x >= 3.0 - split.cell_size);
+            }
+        }
+
+        let (merged, _detail) = build_solo_navmesh(
+            &trimesh,
+            &NavmeshConfig {
+                split_area_boundaries: false,
+                ..config
+            },
+        )
+        .unwrap();
+        assert!(merged.polygon_count() < split.polygon_count());
+    }
+}
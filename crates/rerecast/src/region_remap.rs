@@ -0,0 +1,136 @@
+//! Maps the output of each build stage back to the regions it was built from,
+//! so debug tooling can trace a bad polygon all the way back to the heightfield cells that produced it.
+
+use crate::{AreaType, CompactHeightfield, ContourSet, PolygonNavmesh, RegionId, RegionVertexId};
+
+/// The region and area a polygon of a [`PolygonNavmesh`] was built from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolygonOrigin {
+    /// The region of the [`CompactHeightfield`] the polygon's contour was traced from.
+    pub region: RegionId,
+    /// The area of the spans in [`Self::region`].
+    pub area: AreaType,
+}
+
+impl PolygonNavmesh {
+    /// Returns the region and area the polygon at index `polygon` was built from.
+    ///
+    /// Use [`ContourSet::contours_in_region`] and [`CompactHeightfield::spans_in_region`]
+    /// to find the contours and heightfield spans belonging to the returned region.
+    #[inline]
+    pub fn polygon_origin(&self, polygon: usize) -> PolygonOrigin {
+        PolygonOrigin {
+            region: self.regions[polygon],
+            area: self.areas[polygon],
+        }
+    }
+}
+
+impl ContourSet {
+    /// Iterates over the indices of all contours traced from the given region.
+    ///
+    /// A region usually produces a single contour, but regions with holes can produce several.
+    pub fn contours_in_region(&self, region: RegionId) -> impl Iterator<Item = usize> + '_ {
+        self.contours
+            .iter()
+            .enumerate()
+            .filter(move |(_, contour)| contour.region == region)
+            .map(|(i, _)| i)
+    }
+
+    /// Replaces every region id in the set by the result of `remap`, including the neighbor regions
+    /// stored in the contour vertices, e.g. to recolor regions in debug views
+    /// or to keep region ids stable across rebuilds.
+    ///
+    /// [`RegionId::NONE`] is never remapped. All other ids must be mapped to ids other than [`RegionId::NONE`].
+    pub fn remap_regions(&mut self, mut remap: impl FnMut(RegionId) -> RegionId) {
+        for contour in &mut self.contours {
+            if contour.region != RegionId::NONE {
+                contour.region = remap(contour.region);
+            }
+            for (_, r) in &mut contour.vertices {
+                *r = remap_vertex_region(*r, &mut remap);
+            }
+            for (_, r) in &mut contour.raw_vertices {
+                *r = RegionVertexId::from_bits_retain(remap_vertex_region(r.bits(), &mut remap));
+            }
+        }
+    }
+}
+
+/// Remaps the region stored in the region field of a contour vertex, keeping its flags.
+fn remap_vertex_region(r: u32, remap: &mut impl FnMut(RegionId) -> RegionId) -> u32 {
+    let mask = RegionVertexId::REGION_MASK.bits();
+    let region = RegionId::from((r & mask) as u16);
+    if region == RegionId::NONE {
+        return r;
+    }
+    (r & !mask) | remap(region).bits() as u32
+}
+
+impl CompactHeightfield {
+    /// Iterates over the spans belonging to the given region as `(x, z, span index)`,
+    /// where the span index can be used to look up [`CompactHeightfield::spans`] and [`CompactHeightfield::areas`].
+    pub fn spans_in_region(
+        &self,
+        region: RegionId,
+    ) -> impl Iterator<Item = (u16, u16, usize)> + '_ {
+        (0..self.height).flat_map(move |z| {
+            (0..self.width).flat_map(move |x| {
+                self.cell_at(x, z)
+                    .index_range()
+                    .filter(move |&i| self.spans[i].region == region)
+                    .map(move |i| (x, z, i))
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::U16Vec3;
+
+    use super::*;
+    use crate::Contour;
+
+    #[test]
+    fn remaps_contour_regions_and_neighbors() {
+        let neighbor = 2 | RegionVertexId::AREA_BORDER.bits();
+        let mut contours = ContourSet {
+            contours: vec![
+                Contour {
+                    vertices: vec![(U16Vec3::ZERO, neighbor), (U16Vec3::X, 0)],
+                    raw_vertices: vec![(U16Vec3::ZERO, RegionVertexId::from_bits_retain(neighbor))],
+                    region: RegionId::from(1),
+                    area: AreaType::DEFAULT_WALKABLE,
+                },
+                Contour {
+                    region: RegionId::from(2),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            contours
+                .contours_in_region(RegionId::from(2))
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+
+        contours.remap_regions(|region| region + 10);
+
+        let remapped_neighbor = 12 | RegionVertexId::AREA_BORDER.bits();
+        assert_eq!(contours.contours[0].region, RegionId::from(11));
+        assert_eq!(
+            contours.contours[0].vertices,
+            vec![(U16Vec3::ZERO, remapped_neighbor), (U16Vec3::X, 0)]
+        );
+        assert_eq!(
+            contours.contours[0].raw_vertices[0].1.bits(),
+            remapped_neighbor
+        );
+        assert_eq!(contours.contours[1].region, RegionId::from(12));
+        assert_eq!(contours.contours_in_region(RegionId::from(2)).count(), 0);
+    }
+}
@@ -0,0 +1,353 @@
+use thiserror::Error;
+
+use crate::{
+    Aabb3d, AreaType, AsyncGeometryProvider, CompactHeightfield, ContourSet, DetailNavmesh,
+    GeometryProvider, Heightfield, HeightfieldBuilder, HeightfieldBuilderError, NavmeshConfig,
+    PolygonNavmesh, TileBuildPool, TriMesh, compact_heightfield::CompactHeightfieldError,
+    detail_mesh::DetailNavmeshError, poly_mesh::PolygonNavmeshError, rasterize::RasterizationError,
+    watershed_build_regions::BuildRegionsError,
+};
+
+/// Runs the whole pipeline on `trimesh` and builds a single navmesh covering all of it,
+/// like Recast's `Sample_SoloMesh`.
+///
+/// Triangles flatter than [`NavmeshConfig::walkable_slope_angle`], or the angle in [`NavmeshConfig::material_slope_angles`] for their material, are marked as [`AreaType::DEFAULT_WALKABLE`](crate::AreaType::DEFAULT_WALKABLE),
+/// all others keep their area, so triangles should usually start out as [`AreaType::NOT_WALKABLE`](crate::AreaType::NOT_WALKABLE).
+/// `trimesh` itself is left untouched, so the same geometry can be built with several configs without cloning it.
+/// The heightfield spans the AABB of `trimesh`, [`NavmeshConfig::aabb`] is ignored.
+///
+/// Returns an empty [`DetailNavmesh`] if [`NavmeshConfig::build_detail_mesh`] is `false`.
+#[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+pub fn build_solo_navmesh(
+    trimesh: &TriMesh,
+    config: &NavmeshConfig,
+) -> Result<(PolygonNavmesh, DetailNavmesh), SoloNavmeshError> {
+    let aabb = trimesh
+        .compute_aabb()
+        .ok_or(SoloNavmeshError::EmptyGeometry)?;
+    build_navmesh_in(aabb, trimesh, config)
+}
+
+/// Like [`build_solo_navmesh`], but builds a single navmesh covering `aabb` from the geometry `provider` returns for it.
+///
+/// Triangles are marked walkable like in [`build_solo_navmesh`]. Returns [`SoloNavmeshError::EmptyGeometry`]
+/// if the provider returns no triangles.
+#[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+pub fn build_solo_navmesh_from(
+    provider: &impl GeometryProvider,
+    aabb: Aabb3d,
+    config: &NavmeshConfig,
+) -> Result<(PolygonNavmesh, DetailNavmesh), SoloNavmeshError> {
+    let trimesh = GeometryProvider::triangles(provider, &aabb);
+    if trimesh.indices.is_empty() {
+        return Err(SoloNavmeshError::EmptyGeometry);
+    }
+    build_navmesh_in(aabb, &trimesh, config)
+}
+
+/// Asynchronous version of [`build_solo_navmesh_from`].
+#[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+pub async fn build_solo_navmesh_from_async(
+    provider: &impl AsyncGeometryProvider,
+    aabb: Aabb3d,
+    config: &NavmeshConfig,
+) -> Result<(PolygonNavmesh, DetailNavmesh), SoloNavmeshError> {
+    let trimesh = AsyncGeometryProvider::triangles(provider, &aabb).await;
+    if trimesh.indices.is_empty() {
+        return Err(SoloNavmeshError::EmptyGeometry);
+    }
+    build_navmesh_in(aabb, &trimesh, config)
+}
+
+/// Runs the whole pipeline on the parts of `trimesh` inside `aabb`.
+///
+/// Shared by [`build_solo_navmesh`] and the tiles of a [`TiledNavmeshBuilder`](crate::TiledNavmeshBuilder),
+/// which pass the bounds of a tile including its [`NavmeshConfig::border_size`].
+pub(crate) fn build_navmesh_in(
+    aabb: Aabb3d,
+    trimesh: &TriMesh,
+    config: &NavmeshConfig,
+) -> Result<(PolygonNavmesh, DetailNavmesh), SoloNavmeshError> {
+    build_masked_navmesh_in(aabb, trimesh, config, |_| true, &mut TileBuildPool::new(0))
+}
+
+/// Like [`build_navmesh_in`], but triangles for which `walkable` returns `false` are rasterized as [`AreaType::NOT_WALKABLE`],
+/// so they still block and cover the walkable triangles without producing a surface of their own.
+///
+/// The polygon mesh, detail mesh and contours are built into the buffers of values recycled into `pool`,
+/// and the contours are recycled into it again afterwards.
+///
+/// Used by [`build_interior_exterior_navmeshes`](crate::build_interior_exterior_navmeshes) and [`TileBuildPool`].
+pub(crate) fn build_masked_navmesh_in(
+    aabb: Aabb3d,
+    trimesh: &TriMesh,
+    config: &NavmeshConfig,
+    walkable: impl Fn(usize) -> bool,
+    pool: &mut TileBuildPool,
+) -> Result<(PolygonNavmesh, DetailNavmesh), SoloNavmeshError> {
+    build_marked_navmesh_in(aabb, trimesh, config, walkable, |_| {}, pool)
+}
+
+/// Like [`build_masked_navmesh_in`], but calls `mark` on the compact heightfield after the areas of `config` are marked,
+/// so callers can mark additional areas before the regions are built.
+///
+/// Used by [`BuildRecord::replay`](crate::BuildRecord::replay).
+pub(crate) fn build_marked_navmesh_in(
+    aabb: Aabb3d,
+    trimesh: &TriMesh,
+    config: &NavmeshConfig,
+    walkable: impl Fn(usize) -> bool,
+    mark: impl FnOnce(&mut CompactHeightfield),
+    pool: &mut TileBuildPool,
+) -> Result<(PolygonNavmesh, DetailNavmesh), SoloNavmeshError> {
+    let mut heightfield = create_heightfield(aabb, config)?;
+    let area_types = trimesh
+        .walkable_area_types(config.walkable_slope_angle, &config.material_slope_angles)
+        .enumerate()
+        .map(|(i, area)| {
+            if walkable(i) {
+                area
+            } else {
+                AreaType::NOT_WALKABLE
+            }
+        });
+    heightfield
+        .rasterize_triangle_stream(trimesh.triangles().zip(area_types), config.walkable_climb)?;
+
+    filter_heightfield(&mut heightfield, config);
+    let mut compact_heightfield = build_compact_heightfield(heightfield, config)?;
+    mark(&mut compact_heightfield);
+    build_regions(&mut compact_heightfield, config)?;
+
+    let contours = build_contours(&compact_heightfield, config, pool.take_contours());
+    let poly_mesh = build_polygon_mesh(&contours, config, pool.take_polygon());
+    pool.recycle_contours(contours);
+    let poly_mesh = poly_mesh?;
+    let detail_mesh =
+        build_detail_mesh(&poly_mesh, &compact_heightfield, config, pool.take_detail())?;
+
+    Ok((poly_mesh, detail_mesh))
+}
+
+// The stages of the pipeline, shared with the stepped build of a `SoloNavmeshBuild`.
+
+/// Creates the empty heightfield covering `aabb` that the geometry is rasterized into.
+pub(crate) fn create_heightfield(
+    aabb: Aabb3d,
+    config: &NavmeshConfig,
+) -> Result<Heightfield, SoloNavmeshError> {
+    let mut heightfield = HeightfieldBuilder {
+        aabb,
+        cell_size: config.cell_size,
+        cell_height: config.cell_height,
+    }
+    .build()?;
+    heightfield.sub_voxel_heights = config.sub_voxel_heights;
+    heightfield.boundary = config.boundary.clone();
+    heightfield.exclusion_volumes = config.exclusion_volumes.clone();
+    heightfield.area_registry = config.area_registry.clone();
+    Ok(heightfield)
+}
+
+/// Filters the rasterized spans agents can not stand on.
+pub(crate) fn filter_heightfield(heightfield: &mut Heightfield, config: &NavmeshConfig) {
+    // Once all geometry is rasterized, we do initial pass of filtering to
+    // remove unwanted overhangs caused by the conservative rasterization
+    // as well as filter spans where the character cannot possibly stand.
+    heightfield.filter_low_hanging_walkable_obstacles(config.walkable_climb);
+    heightfield.filter_ledge_spans(config.walkable_height, config.walkable_climb);
+    heightfield.filter_walkable_low_height_spans(
+        config
+            .min_layer_separation
+            .unwrap_or(config.walkable_height)
+            .max(config.walkable_height),
+    );
+}
+
+/// Compacts the filtered heightfield, erodes it by the agent radius and marks the area volumes and paths.
+pub(crate) fn build_compact_heightfield(
+    heightfield: Heightfield,
+    config: &NavmeshConfig,
+) -> Result<CompactHeightfield, SoloNavmeshError> {
+    let mut compact_heightfield =
+        heightfield.into_compact(config.walkable_height, config.walkable_climb)?;
+    compact_heightfield.erode_walkable_area(config.walkable_radius);
+    for volume in &config.area_volumes {
+        compact_heightfield.mark_convex_poly_area(volume.clone());
+    }
+    for path in &config.area_paths {
+        compact_heightfield.mark_path_area(path);
+    }
+    Ok(compact_heightfield)
+}
+
+/// Partitions the compact heightfield into regions.
+pub(crate) fn build_regions(
+    compact_heightfield: &mut CompactHeightfield,
+    config: &NavmeshConfig,
+) -> Result<(), SoloNavmeshError> {
+    let areas = (!config.split_area_boundaries).then(|| compact_heightfield.merge_walkable_areas());
+    #[cfg(feature = "rayon")]
+    compact_heightfield.build_distance_field_parallel();
+    #[cfg(not(feature = "rayon"))]
+    compact_heightfield.build_distance_field();
+    compact_heightfield.build_regions_with_settings(
+        config.border_size,
+        config.min_region_area,
+        config.merge_region_area,
+        &config.watershed,
+        |_| {},
+    )?;
+    if let Some(areas) = areas {
+        compact_heightfield.assign_region_areas(&areas);
+    }
+    Ok(())
+}
+
+/// Traces the contours of the regions into the buffers of `recycled`.
+pub(crate) fn build_contours(
+    compact_heightfield: &CompactHeightfield,
+    config: &NavmeshConfig,
+    recycled: ContourSet,
+) -> ContourSet {
+    compact_heightfield.build_contours_reusing(
+        config.max_simplification_error,
+        config.max_edge_len,
+        config.contour_flags,
+        recycled,
+        |_| {},
+    )
+}
+
+/// Builds the polygon mesh from the contours into the buffers of `recycled` and marks the flag volumes.
+pub(crate) fn build_polygon_mesh(
+    contours: &ContourSet,
+    config: &NavmeshConfig,
+    recycled: PolygonNavmesh,
+) -> Result<PolygonNavmesh, SoloNavmeshError> {
+    let mut poly_mesh = contours.polygon_mesh_reusing(config.max_vertices_per_polygon, recycled)?;
+    for volume in &config.flag_volumes {
+        poly_mesh.mark_flag_volume(volume);
+    }
+    Ok(poly_mesh)
+}
+
+/// Builds the detail mesh into the buffers of `recycled`,
+/// or returns an empty one if [`NavmeshConfig::build_detail_mesh`] is `false`.
+pub(crate) fn build_detail_mesh(
+    poly_mesh: &PolygonNavmesh,
+    compact_heightfield: &CompactHeightfield,
+    config: &NavmeshConfig,
+    recycled: DetailNavmesh,
+) -> Result<DetailNavmesh, SoloNavmeshError> {
+    if !config.build_detail_mesh {
+        return Ok(DetailNavmesh::default());
+    }
+    let mut detail_mesh = DetailNavmesh::with_edge_sample_distance_reusing(
+        poly_mesh,
+        compact_heightfield,
+        config.detail_sample_dist,
+        config
+            .detail_edge_sample_dist
+            .unwrap_or(config.detail_sample_dist),
+        config.detail_sample_max_error,
+        recycled,
+    )?;
+    detail_mesh.stitch_detail_seams(poly_mesh);
+    detail_mesh.offset_areas(poly_mesh, &config.area_height_offsets);
+    Ok(detail_mesh)
+}
+
+/// Errors that can occur in [`build_solo_navmesh`].
+#[derive(Error, Debug)]
+pub enum SoloNavmeshError {
+    /// The geometry contains no triangles.
+    #[error("Cannot build a navmesh from empty geometry")]
+    EmptyGeometry,
+    /// The heightfield could not be created.
+    #[error(transparent)]
+    Heightfield(#[from] HeightfieldBuilderError),
+    /// The geometry could not be rasterized.
+    #[error(transparent)]
+    Rasterization(#[from] RasterizationError),
+    /// The compact heightfield could not be created.
+    #[error(transparent)]
+    CompactHeightfield(#[from] CompactHeightfieldError),
+    /// The regions could not be built.
+    #[error(transparent)]
+    Regions(#[from] BuildRegionsError),
+    /// The polygon mesh could not be built.
+    #[error(transparent)]
+    PolygonNavmesh(#[from] PolygonNavmeshError),
+    /// The detail mesh could not be built.
+    #[error(transparent)]
+    DetailNavmesh(#[from] DetailNavmeshError),
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec3, Vec3A};
+
+    use super::*;
+
+    #[test]
+    fn builds_navmesh_for_plane() {
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 0.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2), UVec3::new(0, 2, 3)],
+            area_types: vec![AreaType::NOT_WALKABLE; 2],
+            materials: Vec::new(),
+        };
+        let config = NavmeshConfig {
+            border_size: 0,
+            ..Default::default()
+        };
+        let (navmesh, detail) = build_solo_navmesh(&trimesh, &config).unwrap();
+        assert!(navmesh.polygon_count() > 0);
+        assert!(!detail.meshes.is_empty());
+        // The input is only borrowed and can be built again
+        assert_eq!(trimesh.area_types, vec![AreaType::NOT_WALKABLE; 2]);
+
+        assert!(matches!(
+            build_solo_navmesh(&TriMesh::default(), &config),
+            Err(SoloNavmeshError::EmptyGeometry)
+        ));
+    }
+
+    #[test]
+    fn builds_navmesh_from_provider() {
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 0.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2), UVec3::new(0, 2, 3)],
+            area_types: vec![AreaType::NOT_WALKABLE; 2],
+            materials: Vec::new(),
+        };
+        let config = NavmeshConfig {
+            border_size: 0,
+            ..Default::default()
+        };
+        let aabb = trimesh.compute_aabb().unwrap();
+
+        let (navmesh, detail) = build_solo_navmesh_from(&trimesh, aabb, &config).unwrap();
+        let (expected_navmesh, expected_detail) = build_solo_navmesh(&trimesh, &config).unwrap();
+        assert_eq!(navmesh.polygon_count(), expected_navmesh.polygon_count());
+        assert_eq!(navmesh.vertices, expected_navmesh.vertices);
+        assert_eq!(detail.meshes, expected_detail.meshes);
+
+        let far_away = Aabb3d::new([100.0, 0.0, 100.0], [1.0, 1.0, 1.0]);
+        assert!(matches!(
+            build_solo_navmesh_from(&trimesh, far_away, &config),
+            Err(SoloNavmeshError::EmptyGeometry)
+        ));
+    }
+}
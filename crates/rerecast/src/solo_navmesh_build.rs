@@ -0,0 +1,256 @@
+use crate::{
+    AreaType, CompactHeightfield, ContourSet, DetailNavmesh, Heightfield, NavmeshConfig,
+    PolygonNavmesh, SoloNavmeshError, TriMesh, solo_navmesh,
+};
+
+/// A [`build_solo_navmesh`](crate::build_solo_navmesh) that runs in small steps, see [`Self::step`].
+///
+/// Lets platforms without threads, e.g. the web, spread a build over several frames instead of freezing until it is done.
+/// Every step runs a single stage of the pipeline, except for rasterization, which is split into steps of
+/// [`Self::triangles_per_step`] triangles. The result is the same as that of [`build_solo_navmesh`](crate::build_solo_navmesh).
+///
+/// To cancel a build, drop it.
+#[derive(Debug)]
+pub struct SoloNavmeshBuild {
+    trimesh: TriMesh,
+    config: NavmeshConfig,
+    area_types: Vec<AreaType>,
+    state: BuildState,
+    /// The number of triangles rasterized by a single step. `[Limit: > 0]`
+    pub triangles_per_step: usize,
+}
+
+/// The stage a [`SoloNavmeshBuild`] runs with its next step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SoloNavmeshBuildStage {
+    /// The triangles are rasterized into the heightfield, see [`SoloNavmeshBuild::triangles_per_step`].
+    Rasterize,
+    /// The spans agents can not stand on are filtered from the heightfield.
+    Filter,
+    /// The heightfield is compacted, eroded and the area volumes are marked.
+    Compact,
+    /// The compact heightfield is partitioned into regions.
+    Regions,
+    /// The contours of the regions are traced.
+    Contours,
+    /// The polygon mesh is built from the contours.
+    PolygonMesh,
+    /// The detail mesh is built.
+    DetailMesh,
+    /// The build finished or failed, so there is nothing left to do.
+    Finished,
+}
+
+#[derive(Debug, Default)]
+enum BuildState {
+    Rasterize {
+        heightfield: Heightfield,
+        next_triangle: usize,
+    },
+    Filter(Heightfield),
+    Compact(Heightfield),
+    Regions(CompactHeightfield),
+    Contours(CompactHeightfield),
+    PolygonMesh(CompactHeightfield, ContourSet),
+    DetailMesh(CompactHeightfield, PolygonNavmesh),
+    #[default]
+    Finished,
+}
+
+impl SoloNavmeshBuild {
+    /// The default of [`Self::triangles_per_step`].
+    pub const DEFAULT_TRIANGLES_PER_STEP: usize = 1024;
+
+    /// Prepares building a navmesh from `trimesh` with `config`, see [`build_solo_navmesh`](crate::build_solo_navmesh).
+    /// No work is done until [`Self::step`] is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `trimesh` contains no triangles or the heightfield could not be created.
+    pub fn new(trimesh: TriMesh, config: NavmeshConfig) -> Result<Self, SoloNavmeshError> {
+        let aabb = trimesh
+            .compute_aabb()
+            .ok_or(SoloNavmeshError::EmptyGeometry)?;
+        let heightfield = solo_navmesh::create_heightfield(aabb, &config)?;
+        let area_types = trimesh
+            .walkable_area_types(config.walkable_slope_angle, &config.material_slope_angles)
+            .collect();
+        Ok(Self {
+            trimesh,
+            config,
+            area_types,
+            state: BuildState::Rasterize {
+                heightfield,
+                next_triangle: 0,
+            },
+            triangles_per_step: Self::DEFAULT_TRIANGLES_PER_STEP,
+        })
+    }
+
+    /// Runs the next step of the build.
+    ///
+    /// Returns the navmesh after the last step, and `None` before that and after the build finished.
+    /// Once an error was returned, the build is finished.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn step(&mut self) -> Result<Option<(PolygonNavmesh, DetailNavmesh)>, SoloNavmeshError> {
+        let config = &self.config;
+        self.state = match std::mem::take(&mut self.state) {
+            BuildState::Rasterize {
+                mut heightfield,
+                next_triangle,
+            } => {
+                let end = (next_triangle + self.triangles_per_step.max(1))
+                    .min(self.trimesh.indices.len());
+                let triangles = self.trimesh.indices[next_triangle..end]
+                    .iter()
+                    .map(|indices| {
+                        indices
+                            .to_array()
+                            .map(|i| self.trimesh.vertices[i as usize])
+                    })
+                    .zip(self.area_types[next_triangle..end].iter().copied());
+                heightfield.rasterize_triangle_stream_from(
+                    triangles,
+                    next_triangle as u32,
+                    config.walkable_climb,
+                )?;
+                if end < self.trimesh.indices.len() {
+                    BuildState::Rasterize {
+                        heightfield,
+                        next_triangle: end,
+                    }
+                } else {
+                    heightfield.warn_about_area_overwrites();
+                    BuildState::Filter(heightfield)
+                }
+            }
+            BuildState::Filter(mut heightfield) => {
+                solo_navmesh::filter_heightfield(&mut heightfield, config);
+                BuildState::Compact(heightfield)
+            }
+            BuildState::Compact(heightfield) => BuildState::Regions(
+                solo_navmesh::build_compact_heightfield(heightfield, config)?,
+            ),
+            BuildState::Regions(mut compact_heightfield) => {
+                solo_navmesh::build_regions(&mut compact_heightfield, config)?;
+                BuildState::Contours(compact_heightfield)
+            }
+            BuildState::Contours(compact_heightfield) => {
+                let contours = solo_navmesh::build_contours(
+                    &compact_heightfield,
+                    config,
+                    ContourSet::default(),
+                );
+                BuildState::PolygonMesh(compact_heightfield, contours)
+            }
+            BuildState::PolygonMesh(compact_heightfield, contours) => {
+                let poly_mesh =
+                    solo_navmesh::build_polygon_mesh(&contours, config, PolygonNavmesh::default())?;
+                BuildState::DetailMesh(compact_heightfield, poly_mesh)
+            }
+            BuildState::DetailMesh(compact_heightfield, poly_mesh) => {
+                let detail_mesh = solo_navmesh::build_detail_mesh(
+                    &poly_mesh,
+                    &compact_heightfield,
+                    config,
+                    DetailNavmesh::default(),
+                )?;
+                return Ok(Some((poly_mesh, detail_mesh)));
+            }
+            BuildState::Finished => BuildState::Finished,
+        };
+        Ok(None)
+    }
+
+    /// Returns the stage the next call to [`Self::step`] runs.
+    pub fn stage(&self) -> SoloNavmeshBuildStage {
+        match self.state {
+            BuildState::Rasterize { .. } => SoloNavmeshBuildStage::Rasterize,
+            BuildState::Filter(_) => SoloNavmeshBuildStage::Filter,
+            BuildState::Compact(_) => SoloNavmeshBuildStage::Compact,
+            BuildState::Regions(_) => SoloNavmeshBuildStage::Regions,
+            BuildState::Contours(_) => SoloNavmeshBuildStage::Contours,
+            BuildState::PolygonMesh(..) => SoloNavmeshBuildStage::PolygonMesh,
+            BuildState::DetailMesh(..) => SoloNavmeshBuildStage::DetailMesh,
+            BuildState::Finished => SoloNavmeshBuildStage::Finished,
+        }
+    }
+
+    /// Returns whether the build finished or failed.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.stage() == SoloNavmeshBuildStage::Finished
+    }
+
+    /// Returns a rough estimate of the share of the build that is done, where every stage counts the same. `[Limit: 0..=1]`
+    pub fn progress(&self) -> f32 {
+        const STAGE_COUNT: f32 = SoloNavmeshBuildStage::Finished as u8 as f32;
+        let within_stage = match self.state {
+            BuildState::Rasterize { next_triangle, .. } => {
+                next_triangle as f32 / self.trimesh.indices.len().max(1) as f32
+            }
+            _ => 0.0,
+        };
+        (self.stage() as u8 as f32 + within_stage) / STAGE_COUNT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec3, Vec3A};
+
+    use super::*;
+    use crate::build_solo_navmesh;
+
+    #[test]
+    fn steps_to_same_navmesh_as_solo_build() {
+        // A floor made of a strip of quads, so rasterization takes several steps.
+        let mut trimesh = TriMesh::default();
+        for x in 0..10 {
+            let first = trimesh.vertices.len() as u32;
+            let x = x as f32;
+            trimesh.vertices.extend([
+                Vec3A::new(x, 0.0, 0.0),
+                Vec3A::new(x, 0.0, 5.0),
+                Vec3A::new(x + 1.0, 0.0, 5.0),
+                Vec3A::new(x + 1.0, 0.0, 0.0),
+            ]);
+            trimesh.indices.extend([
+                UVec3::new(first, first + 1, first + 2),
+                UVec3::new(first, first + 2, first + 3),
+            ]);
+            trimesh
+                .area_types
+                .extend([AreaType::NOT_WALKABLE, AreaType::NOT_WALKABLE]);
+        }
+        let config = NavmeshConfig {
+            border_size: 0,
+            ..Default::default()
+        };
+        let expected = build_solo_navmesh(&trimesh, &config).unwrap();
+
+        let mut build = SoloNavmeshBuild::new(trimesh, config).unwrap();
+        build.triangles_per_step = 3;
+        let mut steps = 0;
+        let mut progress = build.progress();
+        let result = loop {
+            steps += 1;
+            if let Some(result) = build.step().unwrap() {
+                break result;
+            }
+            assert!(build.progress() >= progress);
+            progress = build.progress();
+        };
+        // 7 steps of rasterization and one for every other stage.
+        assert_eq!(steps, 7 + 6);
+        assert_eq!(result, expected);
+        assert!(build.is_finished());
+        assert_eq!(build.progress(), 1.0);
+        assert_eq!(build.step().unwrap(), None);
+
+        assert!(matches!(
+            SoloNavmeshBuild::new(TriMesh::default(), NavmeshConfig::default()),
+            Err(SoloNavmeshError::EmptyGeometry)
+        ));
+    }
+}
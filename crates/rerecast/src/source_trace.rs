@@ -0,0 +1,86 @@
+use glam::Vec3;
+
+use crate::PolygonNavmesh;
+
+/// Records which source triangle produced a span during rasterization.
+/// See [`Heightfield::record_sources`](crate::Heightfield::record_sources).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpanSource {
+    /// The world space center of the span's top, before it was snapped to the height grid.
+    pub position: Vec3,
+    /// The index of the triangle in the [`TriMesh`](crate::TriMesh) passed to [`Heightfield::rasterize_triangles`](crate::Heightfield::rasterize_triangles).
+    ///
+    /// When the trimesh was assembled with [`TriMesh::extend`](crate::TriMesh::extend),
+    /// the triangles of each part occupy a contiguous range, which can be used to map the index back to e.g. an entity.
+    pub triangle: u32,
+}
+
+impl PolygonNavmesh {
+    /// Returns the sorted and deduplicated indices of all source triangles that contributed to the polygon at index `polygon`,
+    /// i.e. whose spans lie within the polygon on the xz-plane and within `max_height_difference` of its surface.
+    ///
+    /// `sources` are the [`Heightfield::sources`](crate::Heightfield::sources) recorded while building this navmesh.
+    pub fn polygon_sources(
+        &self,
+        polygon: usize,
+        sources: &[SpanSource],
+        max_height_difference: f32,
+    ) -> Vec<u32> {
+        let mut triangles: Vec<u32> = sources
+            .iter()
+            .filter(|source| {
+                self.polygon_height(polygon, source.position)
+                    .is_some_and(|height| {
+                        (height - source.position.y).abs() <= max_height_difference
+                    })
+            })
+            .map(|source| source.triangle)
+            .collect();
+        triangles.sort_unstable();
+        triangles.dedup();
+        triangles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec3, Vec3A};
+
+    use crate::{Aabb3d, AreaType, HeightfieldBuilder, TriMesh, test_utils::unit_quad};
+
+    #[test]
+    fn traces_polygon_back_to_source_triangles() {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 5.0, 5.0]),
+            cell_size: 1.0,
+            cell_height: 1.0,
+        }
+        .build()
+        .unwrap();
+        heightfield.record_sources = true;
+        // A floor level with the polygon and a floor well above it.
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 8.0),
+                Vec3A::new(8.0, 0.0, 0.0),
+                Vec3A::new(0.0, 3.0, 0.0),
+                Vec3A::new(0.0, 3.0, 4.0),
+                Vec3A::new(4.0, 3.0, 0.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2), UVec3::new(3, 4, 5)],
+            area_types: vec![AreaType::DEFAULT_WALKABLE; 2],
+            materials: Vec::new(),
+        };
+        heightfield.rasterize_triangles(&trimesh, 1).unwrap();
+        assert!(!heightfield.sources.is_empty());
+
+        let mesh = unit_quad();
+        assert_eq!(mesh.polygon_sources(0, &heightfield.sources, 1.0), vec![0]);
+        assert_eq!(
+            mesh.polygon_sources(0, &heightfield.sources, 5.0),
+            vec![0, 1]
+        );
+    }
+}
@@ -42,19 +42,26 @@ impl Spans {
     }
 }
 
-pub(crate) struct SpanBuilder {
-    pub(crate) min: u16,
-    pub(crate) max: u16,
-    pub(crate) area: AreaType,
-    pub(crate) next: Option<SpanKey>,
+/// A builder for [`Span`]s, e.g. for inserting them with [`Heightfield::add_span`](crate::Heightfield::add_span).
+pub struct SpanBuilder {
+    /// Height of the floor.
+    pub min: u16,
+    /// Height of the ceiling.
+    pub max: u16,
+    /// Area type ID.
+    pub area: AreaType,
+    /// The key of the next-higher span in the column
+    pub next: Option<SpanKey>,
 }
 
 impl SpanBuilder {
-    pub(crate) fn build(self) -> Span {
+    /// Builds the span without a [`Span::top_offset`].
+    pub fn build(self) -> Span {
         Span {
             min: self.min,
             max: self.max,
             area: self.area,
+            top_offset: 0,
             next: self.next,
         }
     }
@@ -79,6 +86,10 @@ pub struct Span {
     /// Area type ID.
     // Original uses 6 bits, but that results in the same alignment AFAIK, so we don't bother
     pub area: AreaType,
+    /// How far the actual surface lies below [`Self::max`], in 1/256ths of the cell height.
+    ///
+    /// Only recorded when [`Heightfield::sub_voxel_heights`](crate::Heightfield::sub_voxel_heights) is enabled, zero otherwise.
+    pub top_offset: u8,
     /// The key of the next-higher span in the column
     pub next: Option<SpanKey>,
 }
@@ -87,31 +98,59 @@ impl Span {
     pub(crate) const MAX_HEIGHT: u16 = u16::MAX;
 }
 
-/// An identifier for the area type of a span.
-/// The values 0 ([`AreaType::NOT_WALKABLE`]) and [`u8::MAX`] ([`AreaType::DEFAULT_WALKABLE`]) are reserved.
-/// The rest can be used for custom area types to e.g. assign different costs to different areas.
-/// When two spans are merged, the area type of the merged span is the maximum of the two area types.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// An identifier for the area type of a span, matching the semantics of Recast's area ids.
+///
+/// An area type is a 6-bit id in the range `0..=`[`AreaType::MAX_ID`]. The id also encodes walkability:
+/// 0 ([`AreaType::NOT_WALKABLE`]) is the only id that is not walkable, and [`AreaType::MAX_ID`] ([`AreaType::DEFAULT_WALKABLE`])
+/// is assigned to walkable geometry by default. The ids in between can be used for custom area types
+/// to e.g. assign different costs to different areas.
+///
+/// When two spans are merged, the area type of the merged span is the maximum of the two area types,
+/// so higher ids take precedence over lower ones.
+///
+/// ```rust
+/// # use rerecast::AreaType;
+/// const WATER: AreaType = AreaType::new(3);
+/// assert!(WATER.is_walkable());
+/// assert_eq!(WATER.id(), 3);
+/// assert_eq!(AreaType::from_id(64), None);
+/// assert!(!AreaType::NOT_WALKABLE.is_walkable());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serialize",
+    serde(try_from = "SerializedAreaId", into = "u8")
+)]
 #[cfg_attr(feature = "bevy_reflect", derive(Reflect))]
 #[cfg_attr(
     all(feature = "serialize", feature = "bevy_reflect"),
     reflect(Serialize, Deserialize)
 )]
 #[repr(transparent)]
-pub struct AreaType(pub u8);
+pub struct AreaType(u8);
 
-impl Deref for AreaType {
-    type Target = u8;
+/// The serialized id of an [`AreaType`]. Ids above [`AreaType::MAX_ID`] are rejected,
+/// except for 255, which older navmeshes used for [`AreaType::DEFAULT_WALKABLE`].
+#[cfg(feature = "serialize")]
+#[derive(serde::Deserialize)]
+#[serde(transparent)]
+struct SerializedAreaId(u8);
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
-}
+#[cfg(feature = "serialize")]
+impl TryFrom<SerializedAreaId> for AreaType {
+    type Error = String;
 
-impl DerefMut for AreaType {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+    fn try_from(SerializedAreaId(id): SerializedAreaId) -> Result<Self, Self::Error> {
+        if id == u8::MAX {
+            return Ok(Self::DEFAULT_WALKABLE);
+        }
+        Self::from_id(id).ok_or_else(|| {
+            format!(
+                "area id {id} is out of range, expected at most {}",
+                Self::MAX_ID
+            )
+        })
     }
 }
 
@@ -122,23 +161,62 @@ impl Default for AreaType {
 }
 
 impl From<u8> for AreaType {
+    /// Converts an id into an area type, clamping it to [`AreaType::MAX_ID`].
+    /// Use [`AreaType::from_id`] to reject ids that are out of range instead.
     fn from(value: u8) -> Self {
-        AreaType(value)
+        Self(value.min(Self::MAX_ID))
+    }
+}
+
+impl From<AreaType> for u8 {
+    fn from(value: AreaType) -> Self {
+        value.0
     }
 }
 
 impl AreaType {
+    /// The number of bits used for an area id.
+    pub const BITS: u32 = 6;
+    /// The highest possible area id.
+    pub const MAX_ID: u8 = (1 << Self::BITS) - 1;
     /// The area type 0. Triangles with this area type are not walkable.
     /// All other area types are walkable.
     pub const NOT_WALKABLE: Self = Self(0);
     /// Default area type for walkable triangles. The highest possible area type.
     /// Other area types that are not [`AreaType::NOT_WALKABLE`] are also walkable.
-    pub const DEFAULT_WALKABLE: Self = Self(u8::MAX);
+    pub const DEFAULT_WALKABLE: Self = Self(Self::MAX_ID);
+
+    /// Creates an area type from its id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is greater than [`AreaType::MAX_ID`]. When used in a constant, this is a compile-time error.
+    #[inline]
+    pub const fn new(id: u8) -> Self {
+        assert!(id <= Self::MAX_ID, "area id out of range");
+        Self(id)
+    }
+
+    /// Creates an area type from its id. Returns `None` if `id` is greater than [`AreaType::MAX_ID`].
+    #[inline]
+    pub const fn from_id(id: u8) -> Option<Self> {
+        if id <= Self::MAX_ID {
+            Some(Self(id))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the id of the area type.
+    #[inline]
+    pub const fn id(self) -> u8 {
+        self.0
+    }
 
     /// Returns whether the area type is walkable.
     #[inline]
-    pub fn is_walkable(&self) -> bool {
-        self != &Self::NOT_WALKABLE
+    pub const fn is_walkable(&self) -> bool {
+        self.0 != Self::NOT_WALKABLE.0
     }
 }
 
@@ -181,4 +259,28 @@ mod tests {
         assert_eq!(span.area, AreaType(3));
         assert_eq!(span.next, Some(span_key));
     }
+
+    #[test]
+    fn area_types_follow_recast_ids() {
+        assert_eq!(AreaType::DEFAULT_WALKABLE.id(), 63);
+        assert_eq!(AreaType::from(u8::MAX), AreaType::DEFAULT_WALKABLE);
+        assert_eq!(AreaType::from_id(5), Some(AreaType::new(5)));
+        assert_eq!(AreaType::from_id(AreaType::MAX_ID + 1), None);
+        assert!(AreaType::new(1) < AreaType::DEFAULT_WALKABLE);
+        assert!(!AreaType::default().is_walkable());
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn deserializing_area_types_validates_ids() {
+        let area: AreaType = serde_json::from_str("3").unwrap();
+        assert_eq!(area, AreaType::new(3));
+        assert_eq!(serde_json::to_string(&area).unwrap(), "3");
+
+        let legacy: AreaType = serde_json::from_str("255").unwrap();
+        assert_eq!(legacy, AreaType::DEFAULT_WALKABLE);
+
+        assert!(serde_json::from_str::<AreaType>("64").is_err());
+        assert!(serde_json::from_str::<AreaType>("200").is_err());
+    }
 }
@@ -0,0 +1,363 @@
+use glam::{Vec3, Vec3Swizzles as _};
+
+use crate::{OffMeshLinkId, PolygonNavmesh};
+
+/// Where a straight path built by [`PolygonNavmesh::straight_path`] crosses the portals between the polygons of its corridor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum PortalCrossing {
+    /// Pulls the path taut around the corners of the corridor with the funnel algorithm,
+    /// which results in the shortest path. This is what Detour's `findStraightPath` does.
+    #[default]
+    Funnel,
+    /// Crosses every portal at its midpoint, keeping agents in the center of the corridor,
+    /// e.g. for movement that reads better than hugging corners.
+    Midpoint,
+    /// Crosses every portal at the point closest to the straight line from the previous crossing to the end.
+    /// Cuts corners less tightly than [`Self::Funnel`], while following the corridor less rigidly than [`Self::Midpoint`].
+    ClosestPoint,
+}
+
+/// A point of a straight path, see [`PolygonNavmesh::straight_path`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct StraightPathPoint {
+    /// The world space position of the point.
+    pub position: Vec3,
+    /// The polygon the path continues through after this point, or the last polygon of the corridor for the end point.
+    pub polygon: usize,
+    /// Whether an off-mesh connection starts or ends at this point.
+    pub kind: StraightPathPointKind,
+}
+
+/// Whether a [`StraightPathPoint`] starts or ends an off-mesh connection, like Detour's `DT_STRAIGHTPATH_OFFMESH_CONNECTION`,
+/// so animation systems know exactly where jumps start and end.
+///
+/// [`PolygonNavmesh::straight_path`] only walks over polygons, the off-mesh kinds are set by
+/// [`NavmeshQuery::find_straight_path`](crate::NavmeshQuery::find_straight_path) and its variants.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum StraightPathPointKind {
+    /// The agent walks on to the next point over the polygons of the corridor.
+    #[default]
+    Walk,
+    /// The agent traverses an off-mesh connection to the next point, which is its [`Self::OffMeshEnd`].
+    OffMeshStart {
+        /// The link being traversed.
+        link: OffMeshLinkId,
+        /// The flags of the connection, see [`OffMeshConnection::flags`](crate::OffMeshConnection::flags).
+        flags: u16,
+    },
+    /// The agent arrived at this point over an off-mesh connection and walks on from here.
+    /// If another connection starts at the same point, the point is an [`Self::OffMeshStart`] instead.
+    OffMeshEnd {
+        /// The link that was traversed.
+        link: OffMeshLinkId,
+        /// The flags of the connection, see [`OffMeshConnection::flags`](crate::OffMeshConnection::flags).
+        flags: u16,
+    },
+}
+
+/// Marks the first point of a segment of a straight path as the end of the off-mesh connection it `arrival`es over,
+/// and the last point as the start of the connection it leaves over in `departure`.
+pub(crate) fn mark_off_mesh_segment(
+    segment: &mut [StraightPathPoint],
+    arrival: Option<StraightPathPointKind>,
+    departure: Option<StraightPathPointKind>,
+) {
+    if let (Some(first), Some(kind)) = (segment.first_mut(), arrival) {
+        first.kind = kind;
+    }
+    if let (Some(last), Some(kind)) = (segment.last_mut(), departure) {
+        last.kind = kind;
+    }
+}
+
+impl PolygonNavmesh {
+    /// Turns a corridor of adjacent polygons into the points an agent walks along from `start` to `end`.
+    ///
+    /// `corridor` starts with the polygon containing `start` and ends with the polygon containing `end`.
+    /// `crossing` decides where the path crosses the portals between the polygons, see [`PortalCrossing`].
+    /// If two consecutive polygons of the corridor are not adjacent, the path ends at the last polygon that could be reached.
+    ///
+    /// The first point is always `start`. Returns an empty path if `corridor` is empty.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn straight_path(
+        &self,
+        corridor: &[usize],
+        start: Vec3,
+        end: Vec3,
+        crossing: PortalCrossing,
+    ) -> Vec<StraightPathPoint> {
+        let mut path = Vec::new();
+        self.straight_path_reusing(
+            corridor,
+            start,
+            end,
+            crossing,
+            &mut Vec::with_capacity(corridor.len()),
+            &mut path,
+        );
+        path
+    }
+
+    /// Like [`Self::straight_path`], but writes the path into `path` and uses `portals` as scratch space,
+    /// so that repeated queries do not allocate once the buffers are large enough. Both buffers are cleared first.
+    pub(crate) fn straight_path_reusing(
+        &self,
+        corridor: &[usize],
+        start: Vec3,
+        end: Vec3,
+        crossing: PortalCrossing,
+        portals: &mut Vec<(Vec3, Vec3)>,
+        path: &mut Vec<StraightPathPoint>,
+    ) {
+        portals.clear();
+        path.clear();
+        let Some(&first) = corridor.first() else {
+            return;
+        };
+        for window in corridor.windows(2) {
+            let Some(portal) = self.portal_points(window[0], window[1]) else {
+                break;
+            };
+            portals.push(portal);
+        }
+        let reached = portals.len();
+        // Without a complete corridor, head for the closest point on the last portal instead.
+        let end = if reached + 1 < corridor.len() {
+            let (left, right) = portals.last().copied().unwrap_or((start, start));
+            closest_point_on_segment(end, left, right)
+        } else {
+            end
+        };
+        portals.push((end, end));
+
+        path.push(StraightPathPoint {
+            position: start,
+            polygon: first,
+            kind: StraightPathPointKind::Walk,
+        });
+        match crossing {
+            PortalCrossing::Funnel => funnel(path, corridor, portals),
+            PortalCrossing::Midpoint => {
+                for (index, (left, right)) in portals.iter().enumerate() {
+                    let polygon = polygon_after(corridor, portals, index);
+                    push_point(path, left.lerp(*right, 0.5), polygon);
+                }
+            }
+            PortalCrossing::ClosestPoint => {
+                for (index, (left, right)) in portals.iter().enumerate() {
+                    let previous = path[path.len() - 1].position;
+                    let point = crossing_towards(previous, end, *left, *right);
+                    push_point(path, point, polygon_after(corridor, portals, index));
+                }
+            }
+        }
+        // The end point belongs to the last polygon that was reached.
+        if let Some(last) = path.last_mut() {
+            last.polygon = corridor[reached];
+        }
+    }
+
+    /// Returns the left and right end of the edge shared by the polygons `from` and `to`, as seen when walking from `from` to `to`,
+    /// or `None` if the polygons are not adjacent.
+    pub fn portal_points(&self, from: usize, to: usize) -> Option<(Vec3, Vec3)> {
+        let vertices = self.polygon_vertices(from);
+        let edge =
+            (0..vertices.len()).find(|edge| self.internal_neighbor(from, *edge) == Some(to))?;
+        Some((
+            self.world_vertex(vertices[edge]),
+            self.world_vertex(vertices[(edge + 1) % vertices.len()]),
+        ))
+    }
+}
+
+/// Two points closer than this on the xz-plane are considered equal. `[Units: wu]`
+const EQUAL_EPSILON: f32 = 1.0 / 16384.0;
+
+/// Appends the corners of the funnel through `portals` to `path`, like Detour's `findStraightPath`.
+/// The last portal is the end point.
+fn funnel(path: &mut Vec<StraightPathPoint>, corridor: &[usize], portals: &[(Vec3, Vec3)]) {
+    let mut apex = path[0].position;
+    let (mut left, mut right) = (apex, apex);
+    let (mut left_index, mut right_index) = (0, 0);
+    let mut i = 0;
+    while i < portals.len() {
+        let (portal_left, portal_right) = portals[i];
+
+        // Right vertex
+        if tri_area_2d(apex, right, portal_right) <= 0.0 {
+            if equal_2d(apex, right) || tri_area_2d(apex, left, portal_right) > 0.0 {
+                // Tighten the funnel
+                right = portal_right;
+                right_index = i;
+            } else {
+                // Right over left, the left point becomes a corner. Restart the scan from it.
+                apex = left;
+                let apex_index = left_index;
+                push_point(path, apex, polygon_after(corridor, portals, apex_index));
+                right = apex;
+                right_index = apex_index;
+                i = apex_index + 1;
+                continue;
+            }
+        }
+
+        // Left vertex
+        if tri_area_2d(apex, left, portal_left) >= 0.0 {
+            if equal_2d(apex, left) || tri_area_2d(apex, right, portal_left) < 0.0 {
+                // Tighten the funnel
+                left = portal_left;
+                left_index = i;
+            } else {
+                // Left over right, the right point becomes a corner. Restart the scan from it.
+                apex = right;
+                let apex_index = right_index;
+                push_point(path, apex, polygon_after(corridor, portals, apex_index));
+                left = apex;
+                left_index = apex_index;
+                i = apex_index + 1;
+                continue;
+            }
+        }
+
+        i += 1;
+    }
+    let (end, _) = portals[portals.len() - 1];
+    push_point(path, end, corridor[portals.len() - 1]);
+}
+
+/// The polygon a path continues through after crossing the portal at index `portal`.
+/// The last portal is the end point, which lies in the polygon before it.
+fn polygon_after(corridor: &[usize], portals: &[(Vec3, Vec3)], portal: usize) -> usize {
+    corridor[(portal + 1).min(portals.len() - 1)]
+}
+
+/// Appends `position` to `path`, unless it is equal to the last point.
+fn push_point(path: &mut Vec<StraightPathPoint>, position: Vec3, polygon: usize) {
+    if path
+        .last()
+        .is_some_and(|last| equal_2d(last.position, position))
+    {
+        return;
+    }
+    path.push(StraightPathPoint {
+        position,
+        polygon,
+        kind: StraightPathPointKind::Walk,
+    });
+}
+
+/// Twice the signed area of the triangle on the xz-plane, positive if `c` lies to the right of `a` to `b`.
+/// Same as Detour's `dtTriArea2D`.
+fn tri_area_2d(a: Vec3, b: Vec3, c: Vec3) -> f32 {
+    let ab = b - a;
+    let ac = c - a;
+    ac.x * ab.z - ab.x * ac.z
+}
+
+pub(crate) fn equal_2d(a: Vec3, b: Vec3) -> bool {
+    a.xz().distance_squared(b.xz()) < EQUAL_EPSILON * EQUAL_EPSILON
+}
+
+/// Returns the point on the portal from `left` to `right` closest to the line from `previous` to `end` on the xz-plane.
+pub(crate) fn crossing_towards(previous: Vec3, end: Vec3, left: Vec3, right: Vec3) -> Vec3 {
+    let direction = (end - previous).xz();
+    let portal = (right - left).xz();
+    let denominator = direction.perp_dot(portal);
+    let t = if denominator.abs() < EQUAL_EPSILON {
+        // The line runs parallel to the portal
+        if previous.distance_squared(left) <= previous.distance_squared(right) {
+            0.0
+        } else {
+            1.0
+        }
+    } else {
+        direction.perp_dot((previous - left).xz()) / denominator
+    };
+    left.lerp(right, t.clamp(0.0, 1.0))
+}
+
+pub(crate) fn closest_point_on_segment(point: Vec3, a: Vec3, b: Vec3) -> Vec3 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared == 0.0 {
+        return a;
+    }
+    a + ab * ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GridNavmesh;
+
+    fn corner() -> (GridNavmesh, Vec<usize>) {
+        let grid = GridNavmesh::parse(
+            "
+            acd
+            ##e
+            ##b
+            ",
+        );
+        let corridor = "acdeb".chars().map(|cell| grid.polygon(cell)).collect();
+        (grid, corridor)
+    }
+
+    fn positions(path: &[StraightPathPoint]) -> Vec<Vec3> {
+        path.iter().map(|point| point.position).collect()
+    }
+
+    #[test]
+    fn pulls_path_around_corners() {
+        let (grid, corridor) = corner();
+        let path = grid.navmesh.straight_path(
+            &corridor,
+            grid.position('a'),
+            grid.position('b'),
+            PortalCrossing::Funnel,
+        );
+        assert_eq!(
+            positions(&path),
+            vec![
+                grid.position('a'),
+                Vec3::new(2.0, 0.0, 1.0),
+                grid.position('b')
+            ]
+        );
+        assert_eq!(path[1].polygon, grid.polygon('e'));
+        assert_eq!(path[2].polygon, grid.polygon('b'));
+    }
+
+    #[test]
+    fn crosses_portals_at_midpoints() {
+        let (grid, corridor) = corner();
+        let path = grid.navmesh.straight_path(
+            &corridor,
+            grid.position('a'),
+            grid.position('b'),
+            PortalCrossing::Midpoint,
+        );
+        assert_eq!(
+            positions(&path),
+            vec![
+                grid.position('a'),
+                Vec3::new(1.0, 0.0, 0.5),
+                Vec3::new(2.0, 0.0, 0.5),
+                Vec3::new(2.5, 0.0, 1.0),
+                Vec3::new(2.5, 0.0, 2.0),
+                grid.position('b'),
+            ]
+        );
+
+        let closest = grid.navmesh.straight_path(
+            &corridor,
+            grid.position('a'),
+            grid.position('b'),
+            PortalCrossing::ClosestPoint,
+        );
+        assert!(closest.len() < path.len());
+        assert_eq!(closest[closest.len() - 1].position, grid.position('b'));
+    }
+}
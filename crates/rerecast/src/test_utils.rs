@@ -0,0 +1,387 @@
+//! Utilities for writing readable navmesh tests.
+//!
+//! [`GridNavmesh::parse`] builds a tiny [`PolygonNavmesh`] from an ASCII map with one quad per floor cell,
+//! and its assertions check the connectivity, paths and line of sight between named markers on the map.
+//!
+//! Only available with the `test_utils` feature.
+
+use std::collections::HashMap;
+
+use glam::{U16Vec3, Vec3};
+
+use crate::{
+    Aabb3d, AreaType, BvTree, NavmeshPath, NavmeshQuery, NearestPolygon, PolygonNavmesh,
+    QueryFilter, RegionId,
+};
+
+/// A [`PolygonNavmesh`] parsed from an ASCII map, together with the named markers placed on it.
+///
+/// Each character of the map is one cell of size 1x1 on the xz-plane at height 0,
+/// where columns run along the x-axis and rows along the z-axis. The legend is:
+/// - `#` and spaces inside a row are walls and produce no polygon.
+/// - `.` is a floor with [`AreaType::DEFAULT_WALKABLE`].
+/// - A digit `n` is a floor with the area `AreaType::new(n)`.
+/// - Any other character is a floor with [`AreaType::DEFAULT_WALKABLE`] and a marker of that name at its center.
+///
+/// Blank lines before the first and after the last row are ignored, and the indentation shared by all rows is removed,
+/// so maps can be written as indented raw strings. Any further spaces are walls, so use `#` for walls in the first column.
+#[derive(Debug, Clone)]
+pub struct GridNavmesh {
+    /// The navmesh containing one polygon per floor cell, connected to the floors of the four adjacent cells.
+    pub navmesh: PolygonNavmesh,
+    markers: HashMap<char, (usize, Vec3)>,
+}
+
+impl GridNavmesh {
+    /// Parses the ASCII map. See [`GridNavmesh`] for the legend.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a marker is used more than once.
+    pub fn parse(map: &str) -> Self {
+        let is_blank = |line: &&str| line.trim().is_empty();
+        let mut lines: Vec<&str> = map.lines().skip_while(is_blank).collect();
+        while lines.last().is_some_and(is_blank) {
+            lines.pop();
+        }
+        let indentation = lines
+            .iter()
+            .filter(|line| !is_blank(line))
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let rows: Vec<Vec<char>> = lines
+            .iter()
+            .map(|line| line.get(indentation..).unwrap_or("").chars().collect())
+            .collect();
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let depth = rows.len();
+        let is_floor = |x: usize, z: usize| {
+            rows.get(z)
+                .and_then(|row| row.get(x))
+                .is_some_and(|&cell| cell != '#' && cell != ' ')
+        };
+
+        let vertex_index = |x: usize, z: usize| (z * (width + 1) + x) as u16;
+        let mut vertices = Vec::with_capacity((width + 1) * (depth + 1));
+        for z in 0..=depth {
+            for x in 0..=width {
+                vertices.push(U16Vec3::new(x as u16, 0, z as u16));
+            }
+        }
+
+        let mut polygon_indices = HashMap::new();
+        for (z, row) in rows.iter().enumerate() {
+            for x in 0..row.len() {
+                if is_floor(x, z) {
+                    polygon_indices.insert((x, z), polygon_indices.len());
+                }
+            }
+        }
+
+        let polygon_count = polygon_indices.len();
+        let mut polygons = vec![0; polygon_count * 4];
+        let mut polygon_neighbors = vec![PolygonNavmesh::NO_CONNECTION; polygon_count * 4];
+        let mut areas = vec![AreaType::DEFAULT_WALKABLE; polygon_count];
+        let mut markers = HashMap::new();
+        for (&(x, z), &polygon) in &polygon_indices {
+            polygons[polygon * 4..polygon * 4 + 4].copy_from_slice(&[
+                vertex_index(x, z),
+                vertex_index(x, z + 1),
+                vertex_index(x + 1, z + 1),
+                vertex_index(x + 1, z),
+            ]);
+            // The edges in the same order as the vertices: -x, +z, +x, -z
+            let neighbors = [
+                x.checked_sub(1).map(|x| (x, z)),
+                Some((x, z + 1)),
+                Some((x + 1, z)),
+                z.checked_sub(1).map(|z| (x, z)),
+            ];
+            for (edge, neighbor) in neighbors.into_iter().enumerate() {
+                if let Some(&neighbor) = neighbor.and_then(|cell| polygon_indices.get(&cell)) {
+                    polygon_neighbors[polygon * 4 + edge] = neighbor as u16;
+                }
+            }
+            match rows[z][x] {
+                '.' => {}
+                cell @ '0'..='9' => areas[polygon] = AreaType::new(cell as u8 - b'0'),
+                marker => {
+                    let center = Vec3::new(x as f32 + 0.5, 0.0, z as f32 + 0.5);
+                    let previous = markers.insert(marker, (polygon, center));
+                    assert!(
+                        previous.is_none(),
+                        "Marker {marker:?} is used more than once"
+                    );
+                }
+            }
+        }
+
+        let navmesh = PolygonNavmesh {
+            vertices,
+            polygons,
+            polygon_neighbors,
+            flags: vec![0; polygon_count],
+            regions: vec![RegionId::from(1); polygon_count],
+            areas,
+            edge_flags: Vec::new(),
+            max_vertices_per_polygon: 4,
+            aabb: Aabb3d {
+                min: Vec3::ZERO,
+                max: Vec3::new(width as f32, 1.0, depth as f32),
+            },
+            cell_size: 1.0,
+            cell_height: 1.0,
+            border_size: 0,
+            max_edge_error: 1.3,
+        };
+        Self { navmesh, markers }
+    }
+
+    /// Returns the world space center of the cell with the given marker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the map contains no such marker.
+    pub fn position(&self, marker: char) -> Vec3 {
+        self.marker(marker).1
+    }
+
+    /// Returns the index of the polygon of the cell with the given marker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the map contains no such marker.
+    pub fn polygon(&self, marker: char) -> usize {
+        self.marker(marker).0
+    }
+
+    /// Asserts that the two markers can be reached from each other by walking over the navmesh.
+    #[track_caller]
+    pub fn assert_connected(&self, from: char, to: char) {
+        let islands = self.navmesh.islands();
+        assert_eq!(
+            islands[self.polygon(from)],
+            islands[self.polygon(to)],
+            "Expected {from:?} and {to:?} to be connected"
+        );
+    }
+
+    /// Asserts that the two markers can not be reached from each other by walking over the navmesh.
+    #[track_caller]
+    pub fn assert_disconnected(&self, from: char, to: char) {
+        let islands = self.navmesh.islands();
+        assert_ne!(
+            islands[self.polygon(from)],
+            islands[self.polygon(to)],
+            "Expected {from:?} and {to:?} to be disconnected"
+        );
+    }
+
+    /// Asserts that a [`PolygonNavmesh::raycast`] from one marker to the other reaches its end without hitting a wall.
+    #[track_caller]
+    pub fn assert_line_of_sight(&self, from: char, to: char) {
+        let raycast =
+            self.navmesh
+                .raycast(self.polygon(from), self.position(from), self.position(to));
+        assert_eq!(
+            raycast.hit, None,
+            "Expected a line of sight between {from:?} and {to:?}"
+        );
+        assert_eq!(raycast.path.last(), Some(&self.polygon(to)));
+    }
+
+    /// Asserts that a [`PolygonNavmesh::raycast`] from one marker to the other hits a wall.
+    #[track_caller]
+    pub fn assert_blocked(&self, from: char, to: char) {
+        let raycast =
+            self.navmesh
+                .raycast(self.polygon(from), self.position(from), self.position(to));
+        assert!(
+            raycast.hit.is_some(),
+            "Expected the line of sight between {from:?} and {to:?} to be blocked"
+        );
+    }
+
+    /// Finds the path from one marker to the other with [`NavmeshQuery::find_path`].
+    pub fn find_path(&self, from: char, to: char, filter: &QueryFilter) -> NavmeshPath {
+        let tree = BvTree::new(&self.navmesh);
+        NavmeshQuery::new(&self.navmesh, &tree).find_path(
+            self.nearest(from),
+            self.nearest(to),
+            filter,
+        )
+    }
+
+    /// Asserts that the path found by [`Self::find_path`] reaches `to`, crossing the cells of the markers in `via` in order.
+    #[track_caller]
+    pub fn assert_path(&self, from: char, to: char, via: &[char], filter: &QueryFilter) {
+        let path = self.find_path(from, to, filter);
+        assert!(
+            path.complete,
+            "Expected a path from {from:?} to {to:?}, but it stopped at polygon {:?}",
+            path.polygons.last()
+        );
+        let mut polygons = path.polygons.iter();
+        for marker in via {
+            let polygon = self.polygon(*marker);
+            assert!(
+                polygons.any(|other| *other == polygon),
+                "Expected the path from {from:?} to {to:?} to pass {via:?} in order, but it is {:?}",
+                path.polygons
+            );
+        }
+    }
+
+    /// Asserts that the path found by [`Self::find_path`] does not reach `to`.
+    #[track_caller]
+    pub fn assert_no_path(&self, from: char, to: char, filter: &QueryFilter) {
+        let path = self.find_path(from, to, filter);
+        assert!(
+            !path.complete,
+            "Expected no path from {from:?} to {to:?}, but found {:?}",
+            path.polygons
+        );
+    }
+
+    fn nearest(&self, marker: char) -> NearestPolygon {
+        NearestPolygon {
+            polygon: self.polygon(marker),
+            point: self.position(marker),
+        }
+    }
+
+    #[track_caller]
+    fn marker(&self, marker: char) -> (usize, Vec3) {
+        *self
+            .markers
+            .get(&marker)
+            .unwrap_or_else(|| panic!("The map contains no marker {marker:?}"))
+    }
+}
+
+/// Builds a [`PolygonNavmesh`] with a cell size and height of 1 from `quads`, given as indices into `vertices`,
+/// for tests that need larger or uneven polygons than the cells of a [`GridNavmesh`].
+///
+/// Quads sharing an edge are linked, all quads lie in the same region and have [`AreaType::DEFAULT_WALKABLE`].
+#[cfg(test)]
+pub(crate) fn quad_navmesh(vertices: Vec<U16Vec3>, quads: &[[u16; 4]]) -> PolygonNavmesh {
+    let edge = |quad: &[u16; 4], index: usize| (quad[index], quad[(index + 1) % 4]);
+    let polygon_neighbors = quads
+        .iter()
+        .flat_map(|quad| {
+            (0..4).map(move |index| {
+                let (a, b) = edge(quad, index);
+                quads
+                    .iter()
+                    .position(|other| (0..4).any(|index| edge(other, index) == (b, a)))
+                    .map_or(PolygonNavmesh::NO_CONNECTION, |neighbor| neighbor as u16)
+            })
+        })
+        .collect();
+    let max = vertices
+        .iter()
+        .fold(U16Vec3::ZERO, |max, vertex| max.max(*vertex))
+        .as_vec3();
+    PolygonNavmesh {
+        polygons: quads.concat(),
+        polygon_neighbors,
+        flags: vec![0; quads.len()],
+        regions: vec![RegionId::from(1); quads.len()],
+        areas: vec![AreaType::DEFAULT_WALKABLE; quads.len()],
+        edge_flags: Vec::new(),
+        max_vertices_per_polygon: 4,
+        aabb: Aabb3d {
+            min: Vec3::ZERO,
+            max: max + Vec3::Y,
+        },
+        cell_size: 1.0,
+        cell_height: 1.0,
+        border_size: 0,
+        max_edge_error: 1.3,
+        vertices,
+    }
+}
+
+/// A single 4x4 quad at the origin.
+#[cfg(test)]
+pub(crate) fn unit_quad() -> PolygonNavmesh {
+    quad_navmesh(
+        vec![
+            U16Vec3::new(0, 0, 0),
+            U16Vec3::new(0, 0, 4),
+            U16Vec3::new(4, 0, 4),
+            U16Vec3::new(4, 0, 0),
+        ],
+        &[[0, 1, 2, 3]],
+    )
+}
+
+/// Two 4x4 quads sharing the edge at x = 4.
+#[cfg(test)]
+pub(crate) fn two_quads() -> PolygonNavmesh {
+    quad_navmesh(
+        vec![
+            U16Vec3::new(0, 0, 0),
+            U16Vec3::new(0, 0, 4),
+            U16Vec3::new(4, 0, 4),
+            U16Vec3::new(4, 0, 0),
+            U16Vec3::new(8, 0, 4),
+            U16Vec3::new(8, 0, 0),
+        ],
+        &[[0, 1, 2, 3], [3, 2, 4, 5]],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ascii_map() {
+        let grid = GridNavmesh::parse(
+            "
+            A.#C
+            .3#.
+            .B#D
+            ",
+        );
+        assert_eq!(grid.navmesh.polygon_count(), 9);
+        assert_eq!(grid.position('B'), Vec3::new(1.5, 0.0, 2.5));
+        assert!(
+            grid.navmesh
+                .areas
+                .iter()
+                .any(|&area| area == AreaType::new(3))
+        );
+
+        grid.assert_connected('A', 'B');
+        grid.assert_line_of_sight('A', 'B');
+        grid.assert_connected('C', 'D');
+        grid.assert_disconnected('A', 'C');
+        grid.assert_blocked('A', 'C');
+    }
+
+    #[test]
+    fn parses_indented_maps_with_leading_walls() {
+        let grid = GridNavmesh::parse(
+            "
+
+            a.1.b
+              # .
+            c...d
+
+            ",
+        );
+        assert_eq!(grid.navmesh.polygon_count(), 11);
+        assert_eq!(grid.position('a'), Vec3::new(0.5, 0.0, 0.5));
+        assert_eq!(grid.position('c'), Vec3::new(0.5, 0.0, 2.5));
+        assert_eq!(grid.navmesh.areas[2], AreaType::new(1));
+
+        let mut filter = QueryFilter::default();
+        grid.assert_path('a', 'b', &[], &filter);
+        grid.assert_path('c', 'b', &['d'], &filter);
+        filter.set_area_cost(AreaType::new(1), f32::INFINITY);
+        grid.assert_no_path('a', 'b', &filter);
+    }
+}
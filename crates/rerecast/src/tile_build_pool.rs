@@ -0,0 +1,149 @@
+use crate::{ContourSet, DetailNavmesh, NavmeshTile, PolygonNavmesh};
+
+/// A pool of the outputs of earlier tile builds, whose buffers are reused instead of allocated again
+/// when tiles are rebuilt frequently, e.g. around dynamic obstacles.
+///
+/// [`TiledNavmeshBuilder`](crate::TiledNavmeshBuilder) keeps a pool of its own: tiles replaced by
+/// [`TiledNavmeshBuilder::insert_tile`](crate::TiledNavmeshBuilder::insert_tile) are recycled into it,
+/// and [`TiledNavmeshBuilder::rebuild_dirty`](crate::TiledNavmeshBuilder::rebuild_dirty) builds into their buffers.
+/// When building tiles on several threads, give every thread its own pool and pass it to
+/// [`TiledNavmeshBuilder::build_tile_pooled`](crate::TiledNavmeshBuilder::build_tile_pooled).
+#[derive(Debug, Clone)]
+pub struct TileBuildPool {
+    polygons: Vec<PolygonNavmesh>,
+    details: Vec<DetailNavmesh>,
+    contours: Vec<ContourSet>,
+    /// The maximum number of recycled [`PolygonNavmesh`]es, [`DetailNavmesh`]es and [`ContourSet`]s kept each.
+    /// Recycling more than that drops the recycled value instead. `[Limit: >= 0]`
+    pub capacity: usize,
+}
+
+impl Default for TileBuildPool {
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+impl TileBuildPool {
+    /// Creates an empty pool that keeps up to `capacity` recycled values of each kind.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            polygons: Vec::new(),
+            details: Vec::new(),
+            contours: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Recycles the polygon and detail meshes of a tile that is no longer needed.
+    pub fn recycle_tile(&mut self, tile: NavmeshTile) {
+        self.recycle_polygon(tile.polygon);
+        self.recycle_detail(tile.detail);
+    }
+
+    /// Recycles a polygon mesh that is no longer needed.
+    pub fn recycle_polygon(&mut self, polygon: PolygonNavmesh) {
+        if self.polygons.len() < self.capacity {
+            self.polygons.push(polygon);
+        }
+    }
+
+    /// Recycles a detail mesh that is no longer needed.
+    pub fn recycle_detail(&mut self, detail: DetailNavmesh) {
+        if self.details.len() < self.capacity {
+            self.details.push(detail);
+        }
+    }
+
+    /// Recycles a contour set that is no longer needed.
+    pub fn recycle_contours(&mut self, contours: ContourSet) {
+        if self.contours.len() < self.capacity {
+            self.contours.push(contours);
+        }
+    }
+
+    /// Returns the number of recycled values of all kinds in the pool.
+    pub fn len(&self) -> usize {
+        self.polygons.len() + self.details.len() + self.contours.len()
+    }
+
+    /// Returns whether the pool holds no recycled values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops all recycled values, e.g. to free their memory once no more tiles are rebuilt for a while.
+    pub fn clear(&mut self) {
+        self.polygons.clear();
+        self.details.clear();
+        self.contours.clear();
+    }
+
+    /// Takes a recycled polygon mesh, or an empty one if the pool holds none.
+    pub(crate) fn take_polygon(&mut self) -> PolygonNavmesh {
+        self.polygons.pop().unwrap_or_default()
+    }
+
+    /// Takes a recycled detail mesh, or an empty one if the pool holds none.
+    pub(crate) fn take_detail(&mut self) -> DetailNavmesh {
+        self.details.pop().unwrap_or_default()
+    }
+
+    /// Takes a recycled contour set, or an empty one if the pool holds none.
+    pub(crate) fn take_contours(&mut self) -> ContourSet {
+        self.contours.pop().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec2, UVec3, Vec3, Vec3A};
+
+    use super::*;
+    use crate::{Aabb3d, AreaType, NavmeshConfig, TiledNavmeshBuilder, TriMesh};
+
+    #[test]
+    fn rebuilds_tiles_from_recycled_buffers() {
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 10.0),
+                Vec3A::new(10.0, 0.0, 0.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2), UVec3::new(0, 2, 3)],
+            area_types: vec![AreaType::NOT_WALKABLE; 2],
+            materials: Vec::new(),
+        };
+        let mut builder = TiledNavmeshBuilder::new(NavmeshConfig {
+            tile_size: 16,
+            aabb: Aabb3d {
+                min: Vec3::new(0.0, -1.0, 0.0),
+                max: Vec3::new(10.0, 1.0, 10.0),
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        builder.rebuild_dirty(&trimesh).unwrap();
+        let first: Vec<_> = builder.tiles().cloned().collect();
+        assert!(!first.is_empty());
+
+        // Rebuilding recycles the replaced tiles and produces the same result from their buffers.
+        builder.mark_all_dirty();
+        builder.rebuild_dirty(&trimesh).unwrap();
+        for tile in &first {
+            assert_eq!(builder.tile(tile.coordinates), Some(tile));
+        }
+
+        let mut pool = TileBuildPool::new(1);
+        pool.recycle_tile(first[0].clone());
+        pool.recycle_tile(first[0].clone());
+        assert_eq!(pool.len(), 2);
+        let tile = builder
+            .build_tile_pooled(UVec2::ZERO, &trimesh, &mut pool)
+            .unwrap();
+        assert_eq!(tile.as_ref(), builder.tile(UVec2::ZERO));
+        // The contours were recycled into the pool after building the polygon mesh.
+        assert_eq!(pool.len(), 1);
+    }
+}
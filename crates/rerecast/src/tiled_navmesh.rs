@@ -0,0 +1,676 @@
+use std::collections::{HashMap, HashSet};
+
+use glam::{U16Vec3, UVec2, UVec3, Vec2, Vec3, Vec3Swizzles as _};
+use thiserror::Error;
+
+use crate::{
+    Aabb3d, DetailNavmesh, GeometryProvider, NavmeshConfig, PolygonEdgeFlags, PolygonNavmesh,
+    RegionId, SoloNavmeshError, SubMesh, TileBuildPool, solo_navmesh::build_masked_navmesh_in,
+};
+
+/// A tile of a [`TiledNavmeshBuilder`], built independently from its neighbors.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct NavmeshTile {
+    /// The position of the tile in the grid of tiles along the x- and z-axes.
+    pub coordinates: UVec2,
+    /// The polygons of the tile.
+    /// Edges on the tile border that continue in a neighboring tile are marked as portals.
+    pub polygon: PolygonNavmesh,
+    /// The detail mesh of the tile. Empty if [`NavmeshConfig::build_detail_mesh`] is `false`.
+    pub detail: DetailNavmesh,
+}
+
+/// Builds a navmesh as a grid of tiles that can be rebuilt individually, like Recast's `Sample_TileMesh`.
+///
+/// [`NavmeshConfig::aabb`] is split into square tiles of [`NavmeshConfig::tile_size`] cells on the xz-plane.
+/// Each tile is built from the geometry inside its bounds grown by [`NavmeshConfig::border_size`],
+/// so only the heightfield of a single tile is held in memory at a time and neighboring tiles line up at their seams.
+/// The border size must be greater than 0 for the seams to be marked as portals, Recast recommends `walkable_radius + 3`.
+///
+/// When geometry changes, pass its old and new bounds to [`Self::mark_dirty`] and call [`Self::rebuild_dirty`]
+/// to only regenerate the affected tiles. [`Self::build_tile`] only borrows the builder,
+/// so dirty tiles can also be built on several threads and stored with [`Self::insert_tile`] afterwards.
+/// [`Self::merge`] stitches the tiles into a single navmesh.
+///
+/// Replaced tiles are recycled into a [`TileBuildPool`], so that [`Self::rebuild_dirty`] reuses their buffers.
+#[derive(Debug, Clone)]
+pub struct TiledNavmeshBuilder {
+    config: NavmeshConfig,
+    tile_counts: UVec2,
+    tiles: HashMap<UVec2, NavmeshTile>,
+    dirty: HashSet<UVec2>,
+    pool: TileBuildPool,
+}
+
+impl TiledNavmeshBuilder {
+    /// Creates a builder for the tiles covering [`NavmeshConfig::aabb`], with all tiles marked dirty.
+    pub fn new(config: NavmeshConfig) -> Result<Self, TiledNavmeshError> {
+        if config.tile_size == 0 {
+            return Err(TiledNavmeshError::ZeroTileSize);
+        }
+        let extent = config.aabb.max - config.aabb.min;
+        if extent.x <= 0.0 || extent.z <= 0.0 || extent.y < 0.0 {
+            return Err(TiledNavmeshError::EmptyBounds);
+        }
+        let tile_width = config.tile_size as f32 * config.cell_size;
+        let tile_counts = (extent.xz() / tile_width).ceil().as_uvec2();
+        let mut builder = Self {
+            config,
+            tile_counts,
+            tiles: HashMap::new(),
+            dirty: HashSet::new(),
+            pool: TileBuildPool::default(),
+        };
+        builder.mark_all_dirty();
+        Ok(builder)
+    }
+
+    /// The config the tiles are built with.
+    #[inline]
+    pub fn config(&self) -> &NavmeshConfig {
+        &self.config
+    }
+
+    /// The number of tiles along the x- and z-axes.
+    #[inline]
+    pub fn tile_counts(&self) -> UVec2 {
+        self.tile_counts
+    }
+
+    /// The number of cells along the x- and z-axes covered by all tiles together.
+    ///
+    /// May exceed the cells of [`NavmeshConfig::aabb`], as the tiles at its far edges are not cut off.
+    #[inline]
+    pub fn cell_counts(&self) -> UVec2 {
+        self.tile_counts * self.config.tile_size as u32
+    }
+
+    /// Returns the world space bounds covered by all tiles together, see [`Self::cell_counts`].
+    /// These are the bounds of the navmesh returned by [`Self::merge`].
+    pub fn bounds(&self) -> Aabb3d {
+        let size = self.cell_counts().as_vec2() * self.config.cell_size;
+        Aabb3d {
+            min: self.config.aabb.min,
+            max: Vec3::new(
+                self.config.aabb.min.x + size.x,
+                self.config.aabb.max.y,
+                self.config.aabb.min.z + size.y,
+            ),
+        }
+    }
+
+    /// Returns the coordinates of the tile containing the world space `position`, ignoring its height,
+    /// or `None` if it lies outside all tiles.
+    pub fn tile_coords_for(&self, position: Vec3) -> Option<UVec2> {
+        let tile = ((position.xz() - self.config.aabb.min.xz()) / self.tile_width()).floor();
+        (tile.cmpge(Vec2::ZERO).all() && tile.cmplt(self.tile_counts.as_vec2()).all())
+            .then(|| tile.as_uvec2())
+    }
+
+    /// Iterates over the coordinates of all tiles in the grid, built or not, row by row.
+    pub fn tile_coords(&self) -> impl Iterator<Item = UVec2> + use<> {
+        let counts = self.tile_counts;
+        (0..counts.y).flat_map(move |z| (0..counts.x).map(move |x| UVec2::new(x, z)))
+    }
+
+    /// Iterates over the coordinates of all built tiles, in no particular order.
+    ///
+    /// Tiles that were never built or contain no polygons are skipped, see [`Self::tile`].
+    pub fn built_tile_coords(&self) -> impl Iterator<Item = UVec2> + '_ {
+        self.tiles.keys().copied()
+    }
+
+    /// Returns the world space bounds of the tile at `coordinates`, without its border.
+    pub fn tile_aabb(&self, coordinates: UVec2) -> Aabb3d {
+        let tile_width = self.tile_width();
+        let min = self.config.aabb.min
+            + Vec3::new(
+                coordinates.x as f32 * tile_width,
+                0.0,
+                coordinates.y as f32 * tile_width,
+            );
+        Aabb3d {
+            min,
+            max: Vec3::new(
+                min.x + tile_width,
+                self.config.aabb.max.y,
+                min.z + tile_width,
+            ),
+        }
+    }
+
+    /// Iterates over the coordinates of all tiles whose geometry, including their border, intersects `aabb`.
+    pub fn tiles_overlapping(&self, aabb: &Aabb3d) -> impl Iterator<Item = UVec2> {
+        let origin = self.config.aabb.min.xz();
+        let border = self.border_width();
+        let tile_width = self.tile_width();
+        let min = ((aabb.min.xz() - origin - border) / tile_width)
+            .floor()
+            .max(Vec2::ZERO);
+        let max = ((aabb.max.xz() - origin + border) / tile_width)
+            .floor()
+            .min(self.tile_counts.as_vec2() - 1.0);
+        let overlaps_y =
+            aabb.min.y <= self.config.aabb.max.y && aabb.max.y >= self.config.aabb.min.y;
+        let ranges = (overlaps_y && min.cmple(max).all()).then(|| {
+            let (min, max) = (min.as_uvec2(), max.as_uvec2());
+            (min.x..=max.x, min.y..=max.y)
+        });
+        ranges.into_iter().flat_map(|(x_range, z_range)| {
+            z_range.flat_map(move |z| x_range.clone().map(move |x| UVec2::new(x, z)))
+        })
+    }
+
+    /// Marks all tiles affected by geometry inside `aabb` as dirty, so the next [`Self::rebuild_dirty`] regenerates them.
+    pub fn mark_dirty(&mut self, aabb: &Aabb3d) {
+        let tiles: Vec<UVec2> = self.tiles_overlapping(aabb).collect();
+        self.dirty.extend(tiles);
+    }
+
+    /// Marks all tiles as dirty, e.g. after changing the config.
+    pub fn mark_all_dirty(&mut self) {
+        let tiles = self.tile_coords();
+        self.dirty.extend(tiles);
+    }
+
+    /// Returns whether the tile at `coordinates` needs to be rebuilt.
+    #[inline]
+    pub fn is_dirty(&self, coordinates: UVec2) -> bool {
+        self.dirty.contains(&coordinates)
+    }
+
+    /// Iterates over the coordinates of all tiles that need to be rebuilt, in no particular order.
+    pub fn dirty_tiles(&self) -> impl Iterator<Item = UVec2> + '_ {
+        self.dirty.iter().copied()
+    }
+
+    /// Builds the tile at `coordinates` from the geometry `geometry` provides for its bounds including the border.
+    ///
+    /// Triangles are marked walkable like in [`build_solo_navmesh`](crate::build_solo_navmesh).
+    /// Returns `None` if the tile contains no polygons. The result is not stored, see [`Self::insert_tile`].
+    pub fn build_tile(
+        &self,
+        coordinates: UVec2,
+        geometry: &impl GeometryProvider,
+    ) -> Result<Option<NavmeshTile>, TiledNavmeshError> {
+        self.build_tile_pooled(coordinates, geometry, &mut TileBuildPool::new(0))
+    }
+
+    /// Like [`Self::build_tile`], but builds the tile into the buffers of values recycled into `pool`.
+    ///
+    /// Intermediate results, and the meshes of tiles that turn out to contain no polygons, are recycled into `pool` again.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn build_tile_pooled(
+        &self,
+        coordinates: UVec2,
+        geometry: &impl GeometryProvider,
+        pool: &mut TileBuildPool,
+    ) -> Result<Option<NavmeshTile>, TiledNavmeshError> {
+        let mut aabb = self.tile_aabb(coordinates);
+        let border = Vec3::new(self.border_width(), 0.0, self.border_width());
+        aabb.min -= border;
+        aabb.max += border;
+
+        let trimesh = GeometryProvider::triangles(geometry, &aabb);
+        if trimesh.indices.is_empty() {
+            return Ok(None);
+        }
+        let (polygon, detail) =
+            build_masked_navmesh_in(aabb, &trimesh, &self.config, |_| true, pool)
+                .map_err(|error| TiledNavmeshError::Tile { coordinates, error })?;
+        if polygon.polygon_count() == 0 {
+            pool.recycle_polygon(polygon);
+            pool.recycle_detail(detail);
+            return Ok(None);
+        }
+        Ok(Some(NavmeshTile {
+            coordinates,
+            polygon,
+            detail,
+        }))
+    }
+
+    /// Stores the result of [`Self::build_tile`] for the tile at `coordinates` and clears its dirty mark.
+    ///
+    /// `None` removes the tile. The replaced tile is recycled into [`Self::pool_mut`].
+    pub fn insert_tile(&mut self, coordinates: UVec2, tile: Option<NavmeshTile>) {
+        self.dirty.remove(&coordinates);
+        let replaced = match tile {
+            Some(tile) => self.tiles.insert(coordinates, tile),
+            None => self.tiles.remove(&coordinates),
+        };
+        if let Some(replaced) = replaced {
+            self.pool.recycle_tile(replaced);
+        }
+    }
+
+    /// The pool replaced tiles are recycled into, e.g. to change its [`TileBuildPool::capacity`]
+    /// or to [clear](TileBuildPool::clear) it once no more tiles are rebuilt for a while.
+    #[inline]
+    pub fn pool_mut(&mut self) -> &mut TileBuildPool {
+        &mut self.pool
+    }
+
+    /// Rebuilds all dirty tiles and returns their coordinates.
+    ///
+    /// If a tile fails to build, it stays dirty and the error is returned. Tiles rebuilt before it are kept.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn rebuild_dirty(
+        &mut self,
+        geometry: &impl GeometryProvider,
+    ) -> Result<Vec<UVec2>, TiledNavmeshError> {
+        let mut dirty: Vec<UVec2> = self.dirty.iter().copied().collect();
+        dirty.sort_by_key(|coordinates| (coordinates.y, coordinates.x));
+        for coordinates in &dirty {
+            let mut pool = std::mem::take(&mut self.pool);
+            let tile = self.build_tile_pooled(*coordinates, geometry, &mut pool);
+            self.pool = pool;
+            self.insert_tile(*coordinates, tile?);
+        }
+        Ok(dirty)
+    }
+
+    /// Returns the tile at `coordinates`, or `None` if it was not built or contains no polygons.
+    #[inline]
+    pub fn tile(&self, coordinates: UVec2) -> Option<&NavmeshTile> {
+        self.tiles.get(&coordinates)
+    }
+
+    /// Iterates over all built tiles, in no particular order.
+    pub fn tiles(&self) -> impl Iterator<Item = &NavmeshTile> {
+        self.tiles.values()
+    }
+
+    /// Stitches all built tiles into a single navmesh, e.g. for queries that work on one [`PolygonNavmesh`].
+    ///
+    /// Vertices shared by neighboring tiles are welded. Every portal is connected to the polygon on the other side of the seam
+    /// whose portal overlaps it the most, as long as their heights differ by at most [`NavmeshConfig::walkable_climb`].
+    /// Portals without such a polygon, e.g. towards tiles without geometry, become solid borders.
+    /// Region ids are offset per tile so they stay unique in the merged navmesh.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn merge(&self) -> Result<(PolygonNavmesh, DetailNavmesh), TiledNavmeshError> {
+        let mut coordinates: Vec<UVec2> = self.tiles.keys().copied().collect();
+        coordinates.sort_by_key(|coordinates| (coordinates.y, coordinates.x));
+
+        let nvp = self.config.max_vertices_per_polygon as usize;
+        let mut polygon = PolygonNavmesh {
+            max_vertices_per_polygon: self.config.max_vertices_per_polygon,
+            aabb: self.bounds(),
+            cell_size: self.config.cell_size,
+            cell_height: self.config.cell_height,
+            border_size: self.config.border_size,
+            ..Default::default()
+        };
+        let mut detail = DetailNavmesh::default();
+        let mut welded: HashMap<U16Vec3, u16> = HashMap::new();
+        let mut portals = Vec::new();
+        let mut region_base = 0_u32;
+
+        for coordinates in &coordinates {
+            let tile = &self.tiles[coordinates];
+            let offset = *coordinates * self.config.tile_size as u32;
+            let offset = UVec3::new(offset.x, 0, offset.y);
+
+            let mut vertex_map = Vec::with_capacity(tile.polygon.vertices.len());
+            for vertex in &tile.polygon.vertices {
+                let global = vertex.as_uvec3() + offset;
+                if global.max_element() >= u16::MAX as u32 {
+                    return Err(TiledNavmeshError::TooLargeToMerge);
+                }
+                let global = global.as_u16vec3();
+                let next_index = welded.len();
+                let index = match welded.get(&global) {
+                    Some(index) => *index,
+                    None if next_index < PolygonNavmesh::NO_INDEX as usize => {
+                        welded.insert(global, next_index as u16);
+                        polygon.vertices.push(global);
+                        next_index as u16
+                    }
+                    None => return Err(TiledNavmeshError::TooLargeToMerge),
+                };
+                vertex_map.push(index);
+            }
+
+            let polygon_base = polygon.polygon_count();
+            if polygon_base + tile.polygon.polygon_count()
+                >= RegionId::BORDER_REGION.bits() as usize
+            {
+                return Err(TiledNavmeshError::TooLargeToMerge);
+            }
+            for tile_polygon in 0..tile.polygon.polygon_count() {
+                let merged_polygon = polygon_base + tile_polygon;
+                for edge in 0..nvp {
+                    let vertex = tile.polygon.polygons[tile_polygon * nvp + edge];
+                    polygon
+                        .polygons
+                        .push(if vertex == PolygonNavmesh::NO_INDEX {
+                            vertex
+                        } else {
+                            vertex_map[vertex as usize]
+                        });
+
+                    polygon
+                        .edge_flags
+                        .push(tile.polygon.polygon_edge_flags(tile_polygon, edge));
+                    let neighbor = tile.polygon.polygon_neighbors[tile_polygon * nvp + edge];
+                    polygon
+                        .polygon_neighbors
+                        .push(if neighbor == PolygonNavmesh::NO_CONNECTION {
+                            neighbor
+                        } else if neighbor & RegionId::BORDER_REGION.bits() != 0 {
+                            // Connected once all tiles are merged
+                            portals.push(Portal {
+                                polygon: merged_polygon,
+                                edge,
+                                direction: neighbor & !RegionId::BORDER_REGION.bits(),
+                            });
+                            PolygonNavmesh::NO_CONNECTION
+                        } else {
+                            neighbor + polygon_base as u16
+                        });
+                }
+
+                let region = tile.polygon.regions[tile_polygon];
+                let region = if region == RegionId::NONE {
+                    region
+                } else {
+                    let region = region_base + region.bits() as u32;
+                    if region >= RegionId::BORDER_REGION.bits() as u32 {
+                        return Err(TiledNavmeshError::TooLargeToMerge);
+                    }
+                    RegionId::from(region as u16)
+                };
+                polygon.regions.push(region);
+                polygon.flags.push(tile.polygon.flags[tile_polygon]);
+                polygon.areas.push(tile.polygon.areas[tile_polygon]);
+            }
+            region_base += tile
+                .polygon
+                .regions
+                .iter()
+                .map(|region| region.bits() as u32)
+                .max()
+                .unwrap_or(0);
+            polygon.max_edge_error = polygon.max_edge_error.max(tile.polygon.max_edge_error);
+
+            let base_vertex_index = detail.vertices.len() as u32;
+            let base_triangle_index = detail.triangles.len() as u32;
+            detail
+                .meshes
+                .extend(tile.detail.meshes.iter().map(|mesh| SubMesh {
+                    base_vertex_index: mesh.base_vertex_index + base_vertex_index,
+                    base_triangle_index: mesh.base_triangle_index + base_triangle_index,
+                    ..mesh.clone()
+                }));
+            detail.vertices.extend_from_slice(&tile.detail.vertices);
+            detail.triangles.extend_from_slice(&tile.detail.triangles);
+            detail
+                .triangle_flags
+                .extend_from_slice(&tile.detail.triangle_flags);
+        }
+
+        self.connect_portals(&mut polygon, &portals);
+        Ok((polygon, detail))
+    }
+
+    /// Connects every portal of the merged `navmesh` to the portal on the other side of its seam that overlaps it the most.
+    ///
+    /// Updates the [`PolygonNavmesh::edge_flags`] of the portals to match, i.e. connected portals are no longer
+    /// [`PolygonEdgeFlags::TILE_PORTAL`]s and unconnected ones become [`PolygonEdgeFlags::SOLID_BORDER`]s.
+    fn connect_portals(&self, navmesh: &mut PolygonNavmesh, portals: &[Portal]) {
+        let nvp = navmesh.max_vertices_per_polygon as usize;
+        let endpoints = |portal: &Portal| {
+            let vertices = navmesh.polygon_vertices(portal.polygon);
+            (
+                navmesh.vertices[vertices[portal.edge] as usize],
+                navmesh.vertices[vertices[(portal.edge + 1) % vertices.len()] as usize],
+            )
+        };
+
+        // Portals facing -x and +x lie on seams of constant x, portals facing -z and +z on seams of constant z.
+        let mut seams: HashMap<(bool, u16), Vec<usize>> = HashMap::new();
+        for (index, portal) in portals.iter().enumerate() {
+            let (a, _) = endpoints(portal);
+            let along_z = portal.direction % 2 == 0;
+            let coordinate = if along_z { a.x } else { a.z };
+            seams.entry((along_z, coordinate)).or_default().push(index);
+        }
+
+        let mut connections = Vec::new();
+        for portal in portals {
+            let (a, b) = endpoints(portal);
+            let along_z = portal.direction % 2 == 0;
+            let coordinate = if along_z { a.x } else { a.z };
+            let opposite = (portal.direction + 2) % 4;
+            let best = seams[&(along_z, coordinate)]
+                .iter()
+                .map(|index| &portals[*index])
+                .filter(|other| other.direction == opposite)
+                .filter_map(|other| {
+                    let (c, d) = endpoints(other);
+                    let (min, max) = overlap(along_z, (a, b), (c, d))?;
+                    let middle = (min + max) * 0.5;
+                    let height = height_along(along_z, (a, b), middle);
+                    let other_height = height_along(along_z, (c, d), middle);
+                    ((height - other_height).abs() <= self.config.walkable_climb as f32)
+                        .then_some((other.polygon, max - min))
+                })
+                .max_by(|(_, first), (_, second)| first.total_cmp(second));
+            connections.push((
+                portal.polygon,
+                portal.polygon * nvp + portal.edge,
+                best.map(|(neighbor, _)| neighbor),
+            ));
+        }
+        for (polygon, edge, neighbor) in connections {
+            let flags = &mut navmesh.edge_flags[edge];
+            flags.remove(PolygonEdgeFlags::TILE_PORTAL);
+            match neighbor {
+                Some(neighbor) => {
+                    navmesh.polygon_neighbors[edge] = neighbor as u16;
+                    if navmesh.areas[neighbor] != navmesh.areas[polygon] {
+                        flags.insert(PolygonEdgeFlags::AREA_BORDER);
+                    }
+                }
+                None => flags.insert(PolygonEdgeFlags::SOLID_BORDER),
+            }
+        }
+    }
+
+    #[inline]
+    fn tile_width(&self) -> f32 {
+        self.config.tile_size as f32 * self.config.cell_size
+    }
+
+    #[inline]
+    fn border_width(&self) -> f32 {
+        self.config.border_size as f32 * self.config.cell_size
+    }
+}
+
+/// A portal edge of a tile, remembered while merging until all tiles are present.
+struct Portal {
+    polygon: usize,
+    edge: usize,
+    /// The side of the tile the portal lies on: 0 is -x, 1 is +z, 2 is +x and 3 is -z, as marked by Recast.
+    direction: u16,
+}
+
+/// Returns the interval along the seam that two portal edges share, if it is not empty.
+fn overlap(
+    along_z: bool,
+    (a, b): (U16Vec3, U16Vec3),
+    (c, d): (U16Vec3, U16Vec3),
+) -> Option<(f32, f32)> {
+    let axis = |vertex: U16Vec3| (if along_z { vertex.z } else { vertex.x }) as f32;
+    let min = axis(a).min(axis(b)).max(axis(c).min(axis(d)));
+    let max = axis(a).max(axis(b)).min(axis(c).max(axis(d)));
+    (min < max).then_some((min, max))
+}
+
+/// Interpolates the height of the edge from `a` to `b` at `position` along the seam.
+fn height_along(along_z: bool, (a, b): (U16Vec3, U16Vec3), position: f32) -> f32 {
+    let axis = |vertex: U16Vec3| (if along_z { vertex.z } else { vertex.x }) as f32;
+    let length = axis(b) - axis(a);
+    let t = if length == 0.0 {
+        0.5
+    } else {
+        (position - axis(a)) / length
+    };
+    a.y as f32 + (b.y as f32 - a.y as f32) * t
+}
+
+/// Errors that can occur when building or merging the tiles of a [`TiledNavmeshBuilder`].
+#[derive(Error, Debug)]
+pub enum TiledNavmeshError {
+    /// [`NavmeshConfig::tile_size`] is 0.
+    #[error("Tile size must be greater than 0")]
+    ZeroTileSize,
+    /// [`NavmeshConfig::aabb`] has no area on the xz-plane.
+    #[error("Cannot split empty bounds into tiles")]
+    EmptyBounds,
+    /// A tile could not be built.
+    #[error("Failed to build tile {coordinates}: {error}")]
+    Tile {
+        /// The coordinates of the tile.
+        coordinates: UVec2,
+        /// The reason the tile could not be built.
+        #[source]
+        error: SoloNavmeshError,
+    },
+    /// The merged navmesh has more vertices, polygons or regions than a [`PolygonNavmesh`] can index,
+    /// or the tile grid is too large for its vertex coordinates.
+    #[error("Tiles are too large to be merged into a single navmesh")]
+    TooLargeToMerge,
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3A;
+
+    use super::*;
+    use crate::{AreaType, TriMesh};
+
+    fn plane() -> TriMesh {
+        TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 12.0),
+                Vec3A::new(24.0, 0.0, 12.0),
+                Vec3A::new(24.0, 0.0, 0.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2), UVec3::new(0, 2, 3)],
+            area_types: vec![AreaType::NOT_WALKABLE; 2],
+            materials: Vec::new(),
+        }
+    }
+
+    fn config() -> NavmeshConfig {
+        NavmeshConfig {
+            cell_size: 0.5,
+            tile_size: 24,
+            aabb: Aabb3d {
+                min: Vec3::new(0.0, -1.0, 0.0),
+                max: Vec3::new(24.0, 1.0, 12.0),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn builds_and_stitches_tiles() {
+        let trimesh = plane();
+        let mut builder = TiledNavmeshBuilder::new(config()).unwrap();
+        assert_eq!(builder.tile_counts(), UVec2::new(2, 1));
+
+        let rebuilt = builder.rebuild_dirty(&trimesh).unwrap();
+        assert_eq!(rebuilt, vec![UVec2::new(0, 0), UVec2::new(1, 0)]);
+        assert_eq!(builder.dirty_tiles().count(), 0);
+        let left = builder
+            .tile(UVec2::new(0, 0))
+            .unwrap()
+            .polygon
+            .polygon_count();
+        let right = builder
+            .tile(UVec2::new(1, 0))
+            .unwrap()
+            .polygon
+            .polygon_count();
+
+        let (navmesh, detail) = builder.merge().unwrap();
+        assert_eq!(navmesh.polygon_count(), left + right);
+        assert_eq!(detail.meshes.len(), left + right);
+        // All portals are resolved, and at least one of them connects the two tiles
+        let nvp = navmesh.max_vertices_per_polygon as usize;
+        assert!(navmesh.polygon_neighbors.iter().all(|neighbor| {
+            *neighbor == PolygonNavmesh::NO_CONNECTION
+                || neighbor & RegionId::BORDER_REGION.bits() == 0
+        }));
+        assert!((0..left).any(|polygon| {
+            (0..nvp).any(|edge| {
+                navmesh
+                    .internal_neighbor(polygon, edge)
+                    .is_some_and(|neighbor| neighbor >= left)
+            })
+        }));
+        // The edge flags agree: linked seams are no portals anymore, and every unlinked edge is solid
+        for polygon in 0..navmesh.polygon_count() {
+            for edge in 0..navmesh.polygon_vertices(polygon).len() {
+                let flags = navmesh.polygon_edge_flags(polygon, edge);
+                assert!(!flags.contains(PolygonEdgeFlags::TILE_PORTAL));
+                assert_eq!(
+                    navmesh.is_solid_edge(polygon, edge),
+                    navmesh.internal_neighbor(polygon, edge).is_none()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn marks_only_affected_tiles_dirty() {
+        let mut builder = TiledNavmeshBuilder::new(config()).unwrap();
+        builder.rebuild_dirty(&plane()).unwrap();
+
+        builder.mark_dirty(&Aabb3d::new([3.0, 0.0, 6.0], [0.5, 0.5, 0.5]));
+        assert_eq!(
+            builder.dirty_tiles().collect::<Vec<_>>(),
+            vec![UVec2::new(0, 0)]
+        );
+
+        // Geometry near the seam lies in the border of both tiles
+        builder.mark_dirty(&Aabb3d::new([11.0, 0.0, 6.0], [0.5, 0.5, 0.5]));
+        assert!(builder.is_dirty(UVec2::new(1, 0)));
+
+        // Geometry outside the bounds affects no tile
+        builder.mark_dirty(&Aabb3d::new([3.0, 10.0, 6.0], [0.5, 0.5, 0.5]));
+        assert_eq!(builder.dirty_tiles().count(), 2);
+    }
+
+    #[test]
+    fn reports_tile_coordinates_and_bounds() {
+        let mut config = config();
+        config.aabb.max.x = 20.0;
+        let mut builder = TiledNavmeshBuilder::new(config).unwrap();
+        // The last tile sticks out of the configured bounds
+        assert_eq!(builder.cell_counts(), UVec2::new(48, 24));
+        assert_eq!(builder.bounds().max, Vec3::new(24.0, 1.0, 12.0));
+
+        assert_eq!(
+            builder.tile_coords_for(Vec3::new(13.0, 5.0, 1.0)),
+            Some(UVec2::new(1, 0))
+        );
+        assert_eq!(builder.tile_coords_for(Vec3::new(-0.1, 0.0, 1.0)), None);
+        assert_eq!(builder.tile_coords_for(Vec3::new(1.0, 0.0, 12.0)), None);
+        assert_eq!(
+            builder.tile_coords().collect::<Vec<_>>(),
+            vec![UVec2::new(0, 0), UVec2::new(1, 0)]
+        );
+
+        assert_eq!(builder.built_tile_coords().count(), 0);
+        builder.rebuild_dirty(&plane()).unwrap();
+        let mut built: Vec<UVec2> = builder.built_tile_coords().collect();
+        built.sort_by_key(|coordinates| coordinates.x);
+        assert_eq!(built, vec![UVec2::new(0, 0), UVec2::new(1, 0)]);
+    }
+}
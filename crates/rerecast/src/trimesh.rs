@@ -1,6 +1,6 @@
 //! Contains traits and methods for converting [`Collider`]s into trimeshes, expressed as [`TrimeshedCollider`]s.
 
-use glam::{UVec3, Vec3A};
+use glam::{Affine3A, UVec3, Vec3A};
 
 use crate::{
     math::{Aabb3d, TriangleIndices as _},
@@ -21,6 +21,13 @@ pub struct TriMesh {
 
     /// The area types of the trimesh. Each index corresponds 1:1 to the [`TriMesh::indices`].
     pub area_types: Vec<AreaType>,
+
+    /// The material ids of the trimesh, used to look up per-material walkable slopes in
+    /// [`TriMesh::mark_walkable_triangles_by_material`]. Each index corresponds 1:1 to the [`TriMesh::indices`].
+    ///
+    /// May be empty, in which case all triangles have the material `0`.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub materials: Vec<u8>,
 }
 
 impl TriMesh {
@@ -35,6 +42,36 @@ impl TriMesh {
         self.indices
             .extend(other.indices.iter().map(|i| i + next_vertex_index));
         self.area_types.extend(other.area_types);
+        if !self.materials.is_empty() || !other.materials.is_empty() {
+            // Keep the materials aligned with the triangles if only one side has them.
+            let triangle_count = self.indices.len() - other.indices.len();
+            self.materials.resize(triangle_count, 0);
+            self.materials.extend(other.materials);
+            self.materials.resize(self.indices.len(), 0);
+        }
+    }
+
+    /// Returns the material of the triangle at index `triangle`, see [`TriMesh::materials`].
+    #[inline]
+    pub fn material(&self, triangle: usize) -> u8 {
+        self.materials.get(triangle).copied().unwrap_or(0)
+    }
+
+    /// Transforms all vertices of the trimesh by the given affine transform,
+    /// e.g. the global transform of the entity the trimesh belongs to.
+    ///
+    /// Non-uniform scale and shear, as produced by nested transform hierarchies, are applied as-is.
+    /// If the transform mirrors the mesh, i.e. its determinant is negative, the winding of all triangles is flipped
+    /// so that their normals keep pointing outwards and walkable surfaces stay walkable.
+    pub fn transform(&mut self, transform: &Affine3A) {
+        for vertex in &mut self.vertices {
+            *vertex = transform.transform_point3a(*vertex);
+        }
+        if transform.matrix3.determinant() < 0.0 {
+            for indices in &mut self.indices {
+                *indices = UVec3::new(indices.x, indices.z, indices.y);
+            }
+        }
     }
 
     /// Computes the AABB of the trimesh.
@@ -51,14 +88,140 @@ impl TriMesh {
     ///
     /// * `threshold_rad` - The threshold angle in radians.
     ///
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn mark_walkable_triangles(&mut self, threshold_rad: f32) {
+        self.mark_walkable_triangles_by_material(threshold_rad, &[]);
+    }
+
+    /// Like [`TriMesh::mark_walkable_triangles`], but triangles whose [material](TriMesh::materials) is listed in
+    /// `material_thresholds_rad` use the threshold angle listed for it instead of `threshold_rad`,
+    /// e.g. to let agents walk up steeper rock than ice.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold_rad` - The threshold angle in radians for materials that are not listed.
+    /// * `material_thresholds_rad` - Pairs of materials and their threshold angle in radians.
+    ///
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn mark_walkable_triangles_by_material(
+        &mut self,
+        threshold_rad: f32,
+        material_thresholds_rad: &[(u8, f32)],
+    ) {
+        self.area_types = self
+            .walkable_area_types(threshold_rad, material_thresholds_rad)
+            .collect();
+    }
+
+    /// Returns the area types [`TriMesh::mark_walkable_triangles_by_material`] would assign, without modifying the trimesh.
+    ///
+    /// This allows rasterizing the same trimesh with different slope thresholds, e.g. for several agent types,
+    /// through [`Heightfield::rasterize_triangle_stream`](crate::Heightfield::rasterize_triangle_stream) without cloning it.
+    pub fn walkable_area_types<'a>(
+        &'a self,
+        threshold_rad: f32,
+        material_thresholds_rad: &[(u8, f32)],
+    ) -> impl Iterator<Item = AreaType> + 'a {
         let threshold_cos = threshold_rad.cos();
-        for (i, indices) in self.indices.iter().enumerate() {
+        let material_thresholds_cos: Vec<(u8, f32)> = material_thresholds_rad
+            .iter()
+            .map(|(material, threshold)| (*material, threshold.cos()))
+            .collect();
+        self.indices.iter().enumerate().map(move |(i, indices)| {
             let normal = indices.normal(&self.vertices);
+            let material = self.material(i);
+            let threshold_cos = material_thresholds_cos
+                .iter()
+                .find(|(threshold_material, _)| *threshold_material == material)
+                .map_or(threshold_cos, |(_, threshold)| *threshold);
 
             if normal.y > threshold_cos {
-                self.area_types[i] = AreaType::DEFAULT_WALKABLE;
+                AreaType::DEFAULT_WALKABLE
+            } else {
+                self.area_types[i]
             }
-        }
+        })
+    }
+
+    /// Iterates over the vertices of all triangles, in the order of [`TriMesh::indices`].
+    pub fn triangles(&self) -> impl Iterator<Item = [Vec3A; 3]> + '_ {
+        self.indices.iter().map(|indices| {
+            [
+                self.vertices[indices[0] as usize],
+                self.vertices[indices[1] as usize],
+                self.vertices[indices[2] as usize],
+            ]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+
+    #[test]
+    fn mirroring_keeps_triangles_walkable() {
+        let mut trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 1.0),
+                Vec3A::new(1.0, 0.0, 0.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2)],
+            area_types: vec![AreaType::NOT_WALKABLE],
+            materials: Vec::new(),
+        };
+        trimesh.transform(&Affine3A::from_scale(Vec3::new(-2.0, 1.0, 1.0)));
+        assert_eq!(trimesh.vertices[2], Vec3A::new(-2.0, 0.0, 0.0));
+        assert_eq!(trimesh.indices[0], UVec3::new(0, 2, 1));
+
+        trimesh.mark_walkable_triangles(45_f32.to_radians());
+        assert_eq!(trimesh.area_types[0], AreaType::DEFAULT_WALKABLE);
+    }
+
+    #[test]
+    fn uses_per_material_slopes() {
+        // A 30° slope, once as rock and once as ice
+        let height = 30_f32.to_radians().tan();
+        let mut trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, height, 1.0),
+                Vec3A::new(1.0, 0.0, 0.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2)],
+            area_types: vec![AreaType::NOT_WALKABLE],
+            materials: vec![1],
+        };
+        let mut ice = trimesh.clone();
+        ice.materials = vec![2];
+        trimesh.extend(ice);
+
+        let thresholds = [(1, 35_f32.to_radians()), (2, 15_f32.to_radians())];
+        trimesh.mark_walkable_triangles_by_material(45_f32.to_radians(), &thresholds);
+        assert_eq!(
+            trimesh.area_types,
+            vec![AreaType::DEFAULT_WALKABLE, AreaType::NOT_WALKABLE]
+        );
+    }
+
+    #[test]
+    fn extending_keeps_materials_aligned() {
+        let triangle = TriMesh {
+            vertices: vec![Vec3A::ZERO, Vec3A::Z, Vec3A::X],
+            indices: vec![UVec3::new(0, 1, 2)],
+            area_types: vec![AreaType::NOT_WALKABLE],
+            materials: Vec::new(),
+        };
+        let mut trimesh = triangle.clone();
+        trimesh.extend(TriMesh {
+            materials: vec![3],
+            ..triangle.clone()
+        });
+        trimesh.extend(triangle);
+        assert_eq!(trimesh.materials, vec![0, 3, 0]);
+        assert_eq!(trimesh.material(2), 0);
     }
 }
@@ -0,0 +1,151 @@
+use std::ops::RangeInclusive;
+
+use glam::{Vec2, Vec3, Vec3Swizzles as _};
+
+use crate::{AreaType, CompactHeightfield};
+
+/// A 2D grid of walkable cells, flattened from one height band of a [`CompactHeightfield`].
+///
+/// Meant for games that want classic grid pathfinding or influence maps alongside the navmesh,
+/// built from the same voxelization so that both agree on what is walkable.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct WalkabilityGrid {
+    /// The width of the grid. (Along the x-axis in cell units.)
+    pub width: u16,
+    /// The height of the grid. (Along the z-axis in cell units.)
+    pub height: u16,
+    /// The world space position of the minimum corner of cell `(0, 0)` on the xz-plane.
+    pub origin: Vec2,
+    /// The size of each cell on the xz-plane. `[Units: wu]`
+    pub cell_size: f32,
+    /// The area of each cell, or `None` if the cell is not walkable. `[Size: width * height]`
+    pub cells: Vec<Option<AreaType>>,
+}
+
+impl WalkabilityGrid {
+    /// Returns the area of the cell at `(x, z)`, or `None` if it is not walkable or out of bounds.
+    #[inline]
+    pub fn area_at(&self, x: u16, z: u16) -> Option<AreaType> {
+        if x >= self.width || z >= self.height {
+            return None;
+        }
+        self.cells[x as usize + z as usize * self.width as usize]
+    }
+
+    /// Returns whether the cell at `(x, z)` is walkable.
+    #[inline]
+    pub fn is_walkable(&self, x: u16, z: u16) -> bool {
+        self.area_at(x, z).is_some()
+    }
+
+    /// Returns the cell containing the world space `position`, ignoring its height,
+    /// or `None` if it lies outside the grid.
+    pub fn cell_containing(&self, position: Vec3) -> Option<(u16, u16)> {
+        let cell = ((position.xz() - self.origin) / self.cell_size).floor();
+        if cell.x < 0.0 || cell.y < 0.0 {
+            return None;
+        }
+        let (x, z) = (cell.x as u16, cell.y as u16);
+        (x < self.width && z < self.height).then_some((x, z))
+    }
+
+    /// Returns the world space center of the cell at `(x, z)` on the xz-plane.
+    #[inline]
+    pub fn cell_center(&self, x: u16, z: u16) -> Vec2 {
+        self.origin + (Vec2::new(x as f32, z as f32) + 0.5) * self.cell_size
+    }
+
+    /// Maps every cell to a traversal cost, e.g. for A* or as the base layer of an influence map.
+    ///
+    /// Unwalkable cells get a cost of [`f32::INFINITY`], walkable ones whatever `cost` returns for their area.
+    pub fn costs(&self, cost: impl Fn(AreaType) -> f32) -> Vec<f32> {
+        self.cells
+            .iter()
+            .map(|area| area.map_or(f32::INFINITY, &cost))
+            .collect()
+    }
+}
+
+impl CompactHeightfield {
+    /// Flattens the spans whose floor lies within `y_band` into a [`WalkabilityGrid`]. `[Units: wu]`
+    ///
+    /// If several walkable spans of a column fall into the band, the highest one decides the area of the cell.
+    pub fn to_walkability_grid(&self, y_band: RangeInclusive<f32>) -> WalkabilityGrid {
+        let mut cells = vec![None; self.width as usize * self.height as usize];
+        for z in 0..self.height {
+            for x in 0..self.width {
+                let cell = self.cell_at(x, z);
+                cells[x as usize + z as usize * self.width as usize] = cell
+                    .index_range()
+                    .rev()
+                    .find(|&i| {
+                        let floor = self.aabb.min.y + self.spans[i].y as f32 * self.cell_height;
+                        self.areas[i].is_walkable() && y_band.contains(&floor)
+                    })
+                    .map(|i| self.areas[i]);
+            }
+        }
+        WalkabilityGrid {
+            width: self.width,
+            height: self.height,
+            origin: self.aabb.min.xz(),
+            cell_size: self.cell_size,
+            cells,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3A;
+
+    use super::*;
+    use crate::{Aabb3d, HeightfieldBuilder, SpanBuilder, SpanInsertion, SpanOverlap};
+
+    #[test]
+    fn flattens_height_band() {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 5.0, 5.0]),
+            cell_size: 1.0,
+            cell_height: 1.0,
+        }
+        .build()
+        .unwrap();
+        // A ground floor in columns (1, 1) and (2, 1), and an upper floor above (1, 1).
+        for (x, min, max) in [(1, 0, 1), (1, 5, 6), (2, 0, 1)] {
+            heightfield
+                .add_span(SpanInsertion {
+                    x,
+                    z: 1,
+                    flag_merge_threshold: 0,
+                    overlap: SpanOverlap::Merge,
+                    span: SpanBuilder {
+                        min,
+                        max,
+                        area: AreaType::DEFAULT_WALKABLE,
+                        next: None,
+                    }
+                    .build(),
+                })
+                .unwrap();
+        }
+        let compact_heightfield = heightfield.into_compact(2, 1).unwrap();
+
+        let ground = compact_heightfield.to_walkability_grid(-5.0..=-3.0);
+        assert!(ground.is_walkable(1, 1));
+        assert!(ground.is_walkable(2, 1));
+        assert!(!ground.is_walkable(3, 1));
+        assert!(!ground.is_walkable(1, 10));
+        assert_eq!(
+            ground.cell_containing(Vec3::new(-3.5, 2.0, -3.5)),
+            Some((1, 1))
+        );
+
+        let upper = compact_heightfield.to_walkability_grid(0.5..=1.5);
+        assert!(upper.is_walkable(1, 1));
+        assert!(!upper.is_walkable(2, 1));
+        let costs = upper.costs(|_| 1.0);
+        assert_eq!(costs.iter().filter(|cost| cost.is_finite()).count(), 1);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    AreaType, CompactHeightfield, RegionId,
+    AreaType, BuildWarning, CompactHeightfield, RegionId, RegionProgress,
     math::{dir_offset_x, dir_offset_z},
 };
 
@@ -19,11 +19,49 @@ impl CompactHeightfield {
     /// and [`CompactSpan::region`](crate::CompactSpan::region) fields.
     ///
     /// Warning: The distance field must be created using [`CompactHeightfield::build_distance_field`] before attempting to build regions.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn build_regions(
         &mut self,
         border_size: u16,
         min_region_area: u16,
         merge_region_area: u16,
+    ) -> Result<(), BuildRegionsError> {
+        self.build_regions_with_progress(border_size, min_region_area, merge_region_area, |_| {})
+    }
+
+    /// Same as [`Self::build_regions`], but calls `on_progress` with the regions assigned so far
+    /// every time the watershed has flooded another band of distance levels, see [`RegionProgress`].
+    ///
+    /// Lets editors show the regions growing during long bakes.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn build_regions_with_progress(
+        &mut self,
+        border_size: u16,
+        min_region_area: u16,
+        merge_region_area: u16,
+        on_progress: impl FnMut(RegionProgress),
+    ) -> Result<(), BuildRegionsError> {
+        self.build_regions_with_settings(
+            border_size,
+            min_region_area,
+            merge_region_area,
+            &WatershedSettings::default(),
+            on_progress,
+        )
+    }
+
+    /// Same as [`Self::build_regions_with_progress`], but floods the watershed as configured by `settings`,
+    /// trading the quality of the regions for build time.
+    ///
+    /// Emits [`BuildWarning::RegionExpansionCapped`] if regions stopped expanding at one of the iteration caps.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn build_regions_with_settings(
+        &mut self,
+        border_size: u16,
+        min_region_area: u16,
+        merge_region_area: u16,
+        settings: &WatershedSettings,
+        mut on_progress: impl FnMut(RegionProgress),
     ) -> Result<(), BuildRegionsError> {
         const LOG_NB_STACKS: usize = 3;
         const NB_STACKS: usize = 1 << LOG_NB_STACKS;
@@ -38,14 +76,21 @@ impl CompactHeightfield {
         let mut src_dist = vec![0_u16; self.spans.len()];
 
         let mut region_id = RegionId::from(1);
-        let mut level = (self.max_distance + 1) & !1;
-
-        // Jan: The following comment is taken from the original implementation.
-        // TODO: Figure better formula, expandIters defines how much the
-        // watershed "overflows" and simplifies the regions. Tying it to
-        // agent radius was usually good indication how greedy it could be.
-        //	const int expandIters = 4 + walkableRadius * 2;
-        let expand_iters = 8;
+        let level_step = settings.level_step.clamp(2, 1 << 15).next_power_of_two();
+        let log_levels_per_stack = level_step.trailing_zeros() as u16;
+        // Round up to a multiple of the step, without exceeding the largest multiple that fits into a `u16`.
+        let mut level = (self.max_distance as u32)
+            .next_multiple_of(level_step as u32)
+            .min(!(level_step - 1) as u32) as u16;
+        let total_levels = level;
+        let expand_iterations = |level: u16| {
+            if level > 0 {
+                Some(settings.expand_iterations)
+            } else {
+                settings.final_expand_iterations
+            }
+        };
+        let mut capped_expansions = 0;
 
         if border_size > 0 {
             // Make sure border will not overflow.
@@ -94,25 +139,33 @@ impl CompactHeightfield {
 
         let mut s_id = -1_i32;
         while level > 0 {
-            level = level.saturating_sub(2);
+            level = level.saturating_sub(level_step);
             s_id = (s_id + 1) & (NB_STACKS as i32 - 1);
 
             if s_id == 0 {
-                self.sort_cells_by_level(level, &mut src_reg, NB_STACKS, &mut level_stacks, 1);
+                self.sort_cells_by_level(
+                    level,
+                    &mut src_reg,
+                    NB_STACKS,
+                    &mut level_stacks,
+                    log_levels_per_stack,
+                );
             } else {
                 // copy left overs from last level
                 let (src, dst) = level_stacks.split_at_mut(s_id as usize);
                 append_stacks(&src[s_id as usize - 1], &mut dst[0], &src_reg);
             }
 
-            self.expand_regions(
-                expand_iters,
+            if self.expand_regions(
+                expand_iterations(level),
                 level,
                 &mut src_reg,
                 &mut src_dist,
                 &mut level_stacks[s_id as usize],
                 false,
-            );
+            ) {
+                capped_expansions += 1;
+            }
 
             // Mark new regions with IDs.
             for current in level_stacks[s_id as usize].iter() {
@@ -130,22 +183,40 @@ impl CompactHeightfield {
                     )
                 {
                     if region_id == RegionId::MAX {
+                        BuildWarning::TooManyRegions {
+                            max: RegionId::MAX.bits(),
+                        }
+                        .emit();
                         return Err(BuildRegionsError::RegionIdOverflow);
                     }
                     region_id += 1;
                 }
             }
+
+            on_progress(RegionProgress {
+                regions: &src_reg,
+                completed_levels: total_levels - level,
+                total_levels,
+            });
         }
 
         // Expand current regions until no empty connected cells found.
-        self.expand_regions(
-            expand_iters * 8,
+        if self.expand_regions(
+            expand_iterations(0),
             0,
             &mut src_reg,
             &mut src_dist,
             &mut stack,
             true,
-        );
+        ) {
+            capped_expansions += 1;
+        }
+        if capped_expansions > 0 {
+            BuildWarning::RegionExpansionCapped {
+                expansions: capped_expansions,
+            }
+            .emit();
+        }
 
         // Merge regions and filter out small regions.
         self.max_region = region_id;
@@ -683,15 +754,17 @@ impl CompactHeightfield {
         }
     }
 
+    /// Grows the regions into the unassigned spans of `stack` for at most `max_iter` iterations, or until they stop growing if `None`.
+    /// Returns whether the regions were still growing when the iterations ran out.
     fn expand_regions(
         &self,
-        max_iter: u16,
+        max_iter: Option<u16>,
         level: u16,
         src_reg: &mut [RegionId],
         src_dist: &mut [u16],
         stack: &mut Vec<LevelStackEntry>,
         fill_stack: bool,
-    ) {
+    ) -> bool {
         if fill_stack {
             // Find cells revealed by the raised level.
             stack.clear();
@@ -789,13 +862,14 @@ impl CompactHeightfield {
                 break;
             }
 
-            if level > 0 {
+            if let Some(max_iter) = max_iter {
                 iter += 1;
                 if iter >= max_iter {
-                    break;
+                    return true;
                 }
             }
         }
+        false
     }
 }
 
@@ -815,6 +889,41 @@ fn append_stacks(
     }
 }
 
+/// Settings for flooding the watershed in [`CompactHeightfield::build_regions_with_settings`].
+///
+/// The watershed floods the distance field from the highest distance level down to the boundaries,
+/// growing the existing regions into every newly flooded band of levels before seeding new regions in it.
+/// The defaults match Recast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct WatershedSettings {
+    /// The number of distance levels flooded at once. Larger steps flood the watershed in fewer bands,
+    /// which is faster but results in fewer, less regularly shaped regions. Rounded up to a power of two. `[Limit: >= 2]`
+    pub level_step: u16,
+    /// The maximum number of times the regions grow by one span into every band of levels before new regions are seeded in it.
+    /// More iterations result in fewer, larger regions at the cost of build time. `[Limit: > 0]`
+    pub expand_iterations: u16,
+    /// The maximum number of times the regions grow by one span into the spans left over after flooding all levels,
+    /// or `None` to grow them until no unassigned span is left next to a region.
+    /// Spans the regions did not reach are left without a region and do not become part of the navmesh. `[Limit: > 0]`
+    pub final_expand_iterations: Option<u16>,
+}
+
+impl Default for WatershedSettings {
+    fn default() -> Self {
+        Self {
+            level_step: 2,
+            // Jan: The following comment is taken from the original implementation.
+            // TODO: Figure better formula, expandIters defines how much the
+            // watershed "overflows" and simplifies the regions. Tying it to
+            // agent radius was usually good indication how greedy it could be.
+            //	const int expandIters = 4 + walkableRadius * 2;
+            expand_iterations: 8,
+            final_expand_iterations: None,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 struct LevelStackEntry {
     x: u16,
@@ -989,3 +1098,68 @@ pub enum BuildRegionsError {
     #[error("Region ID overflow")]
     RegionIdOverflow,
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec3, Vec3A};
+
+    use crate::{Aabb3d, HeightfieldBuilder, TriMesh};
+
+    use super::*;
+
+    fn floor() -> CompactHeightfield {
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d::new(Vec3A::ZERO, [5.0, 1.0, 5.0]),
+            cell_size: 0.25,
+            cell_height: 0.25,
+        }
+        .build()
+        .unwrap();
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(-4.0, 0.0, -4.0),
+                Vec3A::new(-4.0, 0.0, 4.0),
+                Vec3A::new(4.0, 0.0, 4.0),
+                Vec3A::new(4.0, 0.0, -4.0),
+            ],
+            indices: vec![UVec3::new(0, 1, 2), UVec3::new(0, 2, 3)],
+            area_types: vec![AreaType::DEFAULT_WALKABLE; 2],
+            materials: Vec::new(),
+        };
+        heightfield.rasterize_triangles(&trimesh, 1).unwrap();
+        let mut compact = heightfield.into_compact(2, 1).unwrap();
+        compact.build_distance_field();
+        compact
+    }
+
+    #[test]
+    fn floods_with_configured_level_step() {
+        let regions = |compact: &CompactHeightfield| -> Vec<RegionId> {
+            compact.spans.iter().map(|span| span.region).collect()
+        };
+        let mut recast = floor();
+        recast.build_regions(0, 0, 0).unwrap();
+
+        let bands = |settings: WatershedSettings| {
+            let mut compact = floor();
+            let mut bands = 0;
+            compact
+                .build_regions_with_settings(0, 0, 0, &settings, |_| bands += 1)
+                .unwrap();
+            (compact, bands)
+        };
+        let (default, default_bands) = bands(WatershedSettings::default());
+        assert_eq!(regions(&default), regions(&recast));
+
+        let (coarse, coarse_bands) = bands(WatershedSettings {
+            level_step: 8,
+            ..Default::default()
+        });
+        assert!(coarse_bands < default_bands);
+        assert!(
+            regions(&coarse)
+                .iter()
+                .all(|region| *region != RegionId::NONE)
+        );
+    }
+}
@@ -15,6 +15,7 @@ use crate::{
 
 impl CompactHeightfield {
     /// Prepare for region partitioning, by calculating distance field along the walkable surface.
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub fn build_distance_field(&mut self) {
         let distance_field = self.calculate_distance_field();
         // Safety: Unwrap is fine as long as `spans` is not empty, as `distance_field` has the same length
@@ -24,40 +25,16 @@ impl CompactHeightfield {
     }
 
     fn calculate_distance_field(&self) -> Vec<u16> {
-        // Init distance and points.
-        let mut distance_field = vec![u16::MAX; self.spans.len()];
-
         // Mark boundary cells.
-        for z in 0..self.height {
-            for x in 0..self.width {
-                let cell = self.cell_at(x, z);
-                let max_index = cell.index() as usize + cell.count() as usize;
-                #[expect(
-                    clippy::needless_range_loop,
-                    reason = "lol the alternative suggestion is really unreadable"
-                )]
-                for i in cell.index() as usize..max_index {
-                    let span = &self.spans[i];
-                    let area = self.areas[i];
-
-                    let mut connection_count = 0;
-                    for dir in 0..4 {
-                        if let Some(con) = span.con(dir) {
-                            let a_x = (x as i32 + dir_offset_x(dir) as i32) as u16;
-                            let a_z = (z as i32 + dir_offset_z(dir) as i32) as u16;
-                            let a_index = self.cell_at(a_x, a_z).index() as usize + con as usize;
-                            if area == self.areas[a_index] {
-                                connection_count += 1;
-                            }
-                        }
-                    }
-                    if connection_count < 4 {
-                        distance_field[i] = 0;
-                    }
-                }
-            }
-        }
+        let mut distance_field: Vec<u16> = (0..self.height)
+            .flat_map(|z| self.boundary_row(z))
+            .collect();
+        self.propagate_distances(&mut distance_field);
+        distance_field
+    }
 
+    /// Propagates the distances from the boundary through the distance field in two chamfer passes.
+    fn propagate_distances(&self, distance_field: &mut [u16]) {
         // Pass 1
         for z in 0..self.height {
             for x in 0..self.width {
@@ -145,52 +122,157 @@ impl CompactHeightfield {
                 }
             }
         }
+    }
 
-        distance_field
+    /// Like [`CompactHeightfield::build_distance_field`], but marks the boundary and blurs the distance field
+    /// on all threads of the rayon thread pool. The result is identical to [`CompactHeightfield::build_distance_field`].
+    ///
+    /// Both stages only read the spans around each span, so the rows are processed independently.
+    /// The two chamfer passes in between carry the distance from every row into the next one, so they stay sequential.
+    /// They are not vectorized either, as every span reaches its neighbors through its connections instead of at fixed offsets.
+    ///
+    /// Used by [`build_solo_navmesh`](crate::build_solo_navmesh) and the tiled builds in place of [`CompactHeightfield::build_distance_field`].
+    /// Only available with the `rayon` feature.
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub fn build_distance_field_parallel(&mut self) {
+        use rayon::prelude::*;
+
+        let mut distance_field: Vec<u16> = (0..self.height)
+            .into_par_iter()
+            .flat_map_iter(|z| self.boundary_row(z))
+            .collect();
+        self.propagate_distances(&mut distance_field);
+        // Safety: Unwrap is fine as long as `spans` is not empty, as `distance_field` has the same length
+        self.max_distance = *distance_field.iter().max().unwrap();
+        self.dist = (0..self.height)
+            .into_par_iter()
+            .flat_map_iter(|z| self.box_blur_row(1, &distance_field, z))
+            .collect();
+    }
+
+    /// The initial distance of every span in row `z`, in the order of [`Self::spans`]:
+    /// 0 for spans at the boundary of their area, [`u16::MAX`] otherwise.
+    fn boundary_row(&self, z: u16) -> impl Iterator<Item = u16> + '_ {
+        (0..self.width).flat_map(move |x| {
+            self.column_spans(x, z).map(move |i| {
+                let span = &self.spans[i];
+                let area = self.areas[i];
+
+                let mut connection_count = 0;
+                for dir in 0..4 {
+                    if let Some(con) = span.con(dir) {
+                        let a_x = (x as i32 + dir_offset_x(dir) as i32) as u16;
+                        let a_z = (z as i32 + dir_offset_z(dir) as i32) as u16;
+                        let a_index = self.cell_at(a_x, a_z).index() as usize + con as usize;
+                        if area == self.areas[a_index] {
+                            connection_count += 1;
+                        }
+                    }
+                }
+                if connection_count < 4 { 0 } else { u16::MAX }
+            })
+        })
     }
 
     fn box_blur(&self, threshold: u16, distance_field: &[u16]) -> Vec<u16> {
+        (0..self.height)
+            .flat_map(|z| self.box_blur_row(threshold, distance_field, z))
+            .collect()
+    }
+
+    /// The blurred distance of every span in row `z`, in the order of [`Self::spans`].
+    fn box_blur_row<'a>(
+        &'a self,
+        threshold: u16,
+        distance_field: &'a [u16],
+        z: u16,
+    ) -> impl Iterator<Item = u16> + 'a {
         let threshold = threshold.saturating_mul(2);
-        let mut result = vec![0; distance_field.len()];
+        (0..self.width).flat_map(move |x| {
+            self.column_spans(x, z).map(move |i| {
+                let span = &self.spans[i];
+                let cd = distance_field[i];
+                if cd <= threshold {
+                    return cd;
+                }
+                let mut d = cd as u32;
+                for dir in 0..4 {
+                    if let Some(con) = span.con(dir) {
+                        let a_x = (x as i32 + dir_offset_x(dir) as i32) as u16;
+                        let a_z = (z as i32 + dir_offset_z(dir) as i32) as u16;
+                        let a_index = self.cell_at(a_x, a_z).index() as usize + con as usize;
+                        d += distance_field[a_index] as u32;
 
-        for z in 0..self.height {
-            for x in 0..self.width {
-                let cell = self.cell_at(x, z);
-                let max_index = cell.index() as usize + cell.count() as usize;
-                for i in cell.index() as usize..max_index {
-                    let span = &self.spans[i];
-                    let cd = distance_field[i];
-                    if cd <= threshold {
-                        result[i] = cd;
-                        continue;
-                    }
-                    let mut d = cd as u32;
-                    for dir in 0..4 {
-                        if let Some(con) = span.con(dir) {
-                            let a_x = (x as i32 + dir_offset_x(dir) as i32) as u16;
-                            let a_z = (z as i32 + dir_offset_z(dir) as i32) as u16;
-                            let a_index = self.cell_at(a_x, a_z).index() as usize + con as usize;
-                            d += distance_field[a_index] as u32;
-
-                            let a_span = &self.spans[a_index];
-                            let dir2 = (dir + 1) & 0x3;
-                            if let Some(con) = a_span.con(dir2) {
-                                let b_x = (a_x as i32 + dir_offset_x(dir2) as i32) as u16;
-                                let b_z = (a_z as i32 + dir_offset_z(dir2) as i32) as u16;
-                                let b_index =
-                                    self.cell_at(b_x, b_z).index() as usize + con as usize;
-                                d += distance_field[b_index] as u32;
-                            } else {
-                                d += cd as u32;
-                            }
+                        let a_span = &self.spans[a_index];
+                        let dir2 = (dir + 1) & 0x3;
+                        if let Some(con) = a_span.con(dir2) {
+                            let b_x = (a_x as i32 + dir_offset_x(dir2) as i32) as u16;
+                            let b_z = (a_z as i32 + dir_offset_z(dir2) as i32) as u16;
+                            let b_index = self.cell_at(b_x, b_z).index() as usize + con as usize;
+                            d += distance_field[b_index] as u32;
                         } else {
-                            d += cd as u32 * 2;
+                            d += cd as u32;
                         }
+                    } else {
+                        d += cd as u32 * 2;
                     }
-                    result[i] = ((d + 5) / 9) as u16;
                 }
-            }
+                ((d + 5) / 9) as u16
+            })
+        })
+    }
+}
+
+#[cfg(all(test, feature = "rayon"))]
+mod tests {
+    use glam::{UVec3, Vec3, Vec3A};
+
+    use crate::{Aabb3d, AreaType, HeightfieldBuilder, TriMesh};
+
+    #[test]
+    fn parallel_matches_serial_distance_field() {
+        // A floor with a step of a different area in the middle.
+        let trimesh = TriMesh {
+            vertices: vec![
+                Vec3A::new(0.0, 0.0, 0.0),
+                Vec3A::new(0.0, 0.0, 8.0),
+                Vec3A::new(8.0, 0.0, 8.0),
+                Vec3A::new(8.0, 0.0, 0.0),
+                Vec3A::new(2.0, 0.2, 3.0),
+                Vec3A::new(3.0, 0.2, 6.5),
+                Vec3A::new(6.0, 0.2, 2.0),
+            ],
+            indices: vec![
+                UVec3::new(0, 1, 2),
+                UVec3::new(0, 2, 3),
+                UVec3::new(4, 5, 6),
+            ],
+            area_types: vec![
+                AreaType::DEFAULT_WALKABLE,
+                AreaType::DEFAULT_WALKABLE,
+                AreaType::new(3),
+            ],
+            materials: Vec::new(),
+        };
+        let mut heightfield = HeightfieldBuilder {
+            aabb: Aabb3d {
+                min: Vec3::new(-1.0, -1.0, -1.0),
+                max: Vec3::new(9.0, 1.0, 9.0),
+            },
+            cell_size: 0.25,
+            cell_height: 0.1,
         }
-        result
+        .build()
+        .unwrap();
+        heightfield.populate_from_trimesh(&trimesh, 10, 2).unwrap();
+        let mut serial = heightfield.into_compact(10, 2).unwrap();
+        let mut parallel = serial.clone();
+
+        serial.build_distance_field();
+        parallel.build_distance_field_parallel();
+        assert!(serial.max_distance > 0);
+        assert_eq!(serial.max_distance, parallel.max_distance);
+        assert_eq!(serial.dist, parallel.dist);
     }
 }
@@ -168,7 +168,7 @@ fn assert_eq_heightfield(heightfield: &Heightfield, reference_name: &str) {
                     assert_eq!(span.min, cpp_span.min, "[{x}, {z}, {layer}] span min");
                     assert_eq!(span.max, cpp_span.max, "[{x}, {z}, {layer}] span max");
                     let cpp_area = if cpp_span.area == 63 {
-                        // We use u8::MAX currently, though this may change in the future.
+                        // Recast's RC_WALKABLE_AREA, which is the same id as our default walkable area.
                         AreaType::DEFAULT_WALKABLE
                     } else {
                         AreaType::from(cpp_span.area)
@@ -510,7 +510,7 @@ fn assert_eq_poly_mesh(poly_mesh: &PolygonNavmesh, reference_name: &str) {
         .enumerate()
     {
         let cpp_area = if *cpp_area == 63 {
-            // We use u8::MAX currently, though this may change in the future.
+            // Recast's RC_WALKABLE_AREA, which is the same id as our default walkable area.
             AreaType::DEFAULT_WALKABLE
         } else {
             AreaType::from(*cpp_area)
@@ -704,6 +704,7 @@ impl CppGeometry {
             vertices: self.verts.iter().map(|v| Vec3A::from(*v)).collect(),
             indices: self.tris.iter().map(|i| UVec3::from(*i)).collect(),
             area_types: vec![AreaType::NOT_WALKABLE; self.tris.len()],
+            materials: Vec::new(),
         }
     }
 }
@@ -0,0 +1,121 @@
+//! End-to-end tests for walkable layers that overlap vertically, like a bridge crossing over a road.
+
+use glam::{Affine3A, UVec3, Vec3, Vec3A};
+use rerecast::{
+    AreaType, BuildContoursFlags, CompactHeightfield, DynamicSurface, HeightfieldBuilder,
+    NavmeshConfig, PolygonNavmesh, RegionId, TriMesh,
+};
+
+/// A 20x20 ground plane with a 4 wide bridge deck crossing over it at a height of 3.
+fn bridge_over_road() -> TriMesh {
+    let mut trimesh = TriMesh::default();
+    trimesh.extend(quad(Vec3::new(0.0, 0.0, 0.0), Vec3::new(20.0, 0.0, 20.0)));
+    trimesh.extend(quad(Vec3::new(0.0, 3.0, 8.0), Vec3::new(20.0, 3.0, 12.0)));
+    trimesh
+}
+
+/// An upwards facing quad on the xz-plane.
+fn quad(min: Vec3, max: Vec3) -> TriMesh {
+    TriMesh {
+        vertices: vec![
+            Vec3A::new(min.x, min.y, min.z),
+            Vec3A::new(min.x, min.y, max.z),
+            Vec3A::new(max.x, min.y, max.z),
+            Vec3A::new(max.x, min.y, min.z),
+        ],
+        indices: vec![UVec3::new(0, 1, 2), UVec3::new(0, 2, 3)],
+        area_types: vec![AreaType::NOT_WALKABLE; 2],
+        materials: Vec::new(),
+    }
+}
+
+fn build(min_layer_separation: u16) -> (CompactHeightfield, PolygonNavmesh) {
+    let config = NavmeshConfig {
+        border_size: 0,
+        min_region_area: 8,
+        ..Default::default()
+    };
+    let mut trimesh = bridge_over_road();
+    trimesh.mark_walkable_triangles(config.walkable_slope_angle);
+    let mut heightfield = HeightfieldBuilder {
+        aabb: trimesh.compute_aabb().unwrap(),
+        cell_size: config.cell_size,
+        cell_height: config.cell_height,
+    }
+    .build()
+    .unwrap();
+    heightfield
+        .rasterize_triangles(&trimesh, config.walkable_climb)
+        .unwrap();
+    heightfield.filter_low_hanging_walkable_obstacles(config.walkable_climb);
+    heightfield.filter_ledge_spans(config.walkable_height, config.walkable_climb);
+    heightfield.filter_walkable_low_height_spans(min_layer_separation);
+
+    let mut compact_heightfield = heightfield
+        .into_compact(config.walkable_height, config.walkable_climb)
+        .unwrap();
+    compact_heightfield.erode_walkable_area(config.walkable_radius);
+    compact_heightfield.build_distance_field();
+    compact_heightfield
+        .build_regions(
+            config.border_size,
+            config.min_region_area,
+            config.merge_region_area,
+        )
+        .unwrap();
+    let contours = compact_heightfield.build_contours(
+        config.max_simplification_error,
+        config.max_edge_len,
+        BuildContoursFlags::TESSELLATE_SOLID_WALL_EDGES,
+    );
+    let poly_mesh = contours
+        .into_polygon_mesh(config.max_vertices_per_polygon)
+        .unwrap();
+    (compact_heightfield, poly_mesh)
+}
+
+/// Returns the walkable spans in the column below the center of the bridge.
+fn spans_under_bridge(compact_heightfield: &CompactHeightfield) -> Vec<usize> {
+    let cell = compact_heightfield.cell_at(33, 33);
+    cell.index_range()
+        .filter(|i| compact_heightfield.areas[*i].is_walkable())
+        .collect()
+}
+
+#[test]
+fn keeps_both_layers_separate() {
+    let (compact_heightfield, poly_mesh) = build(NavmeshConfig::default().walkable_height);
+
+    let spans = spans_under_bridge(&compact_heightfield);
+    assert_eq!(
+        spans.len(),
+        2,
+        "both the road and the bridge should be walkable"
+    );
+    let road = &compact_heightfield.spans[spans[0]];
+    let bridge = &compact_heightfield.spans[spans[1]];
+    assert!(road.y < bridge.y);
+    assert_ne!(road.region, RegionId::NONE);
+    assert_ne!(bridge.region, RegionId::NONE);
+    assert_ne!(road.region, bridge.region, "layers must not share a region");
+
+    let surface = DynamicSurface::new(poly_mesh, Affine3A::IDENTITY);
+    let (road_polygon, road_point) = surface.locate(Vec3::new(10.0, 0.0, 10.0), 0.5).unwrap();
+    let (bridge_polygon, bridge_point) = surface.locate(Vec3::new(10.0, 3.0, 10.0), 0.5).unwrap();
+    assert_ne!(road_polygon, bridge_polygon);
+    assert!(road_point.y < 0.5);
+    assert!(bridge_point.y > 2.5);
+}
+
+#[test]
+fn discards_lower_layer_below_min_layer_separation() {
+    // Require more clearance than there is under the bridge
+    let (compact_heightfield, poly_mesh) = build(16);
+
+    let spans = spans_under_bridge(&compact_heightfield);
+    assert_eq!(spans.len(), 1, "only the bridge should be walkable");
+
+    let surface = DynamicSurface::new(poly_mesh, Affine3A::IDENTITY);
+    assert!(surface.locate(Vec3::new(10.0, 0.0, 10.0), 0.5).is_none());
+    assert!(surface.locate(Vec3::new(10.0, 3.0, 10.0), 0.5).is_some());
+}